@@ -1,12 +1,158 @@
-extern crate gcc;
+extern crate cc;
+#[cfg(feature = "system-libntru")]
+extern crate pkg_config;
 
+use std::env;
 use std::fs::File;
-use std::path::Path;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::env;
+
+/// The C sources of libntru
+const SOURCES: [&str; 14] = ["bitstring.c",
+                             "encparams.c",
+                             "hash.c",
+                             "idxgen.c",
+                             "key.c",
+                             "mgf.c",
+                             "ntru.c",
+                             "poly.c",
+                             "rand.c",
+                             "arith.c",
+                             "sha1.c",
+                             "sha2.c",
+                             "nist_ctr_drbg.c",
+                             "rijndael.c"];
+
+/// The perlasm scripts of the multi-buffer SHA implementations for x86_64
+const ASM_SCRIPTS: [&str; 2] = ["sha1-mb-x86_64", "sha256-mb-x86_64"];
+
+/// The versions of a system libntru with the same ABI as the bundled sources, from the first one
+/// to the first incompatible one
+#[cfg(feature = "system-libntru")]
+const SYSTEM_LIBNTRU_VERSIONS: (&str, &str) = ("0.5", "0.6");
+
+/// The target being compiled for, from the variables that Cargo gives to build scripts
+///
+/// The build script runs on the host, so `cfg!()` describes the host, not the target, and they
+/// differ when cross-compiling.
+struct Target {
+    arch: String,
+    os: String,
+    features: Vec<String>,
+    is_host: bool,
+}
+
+impl Target {
+    fn from_env() -> Target {
+        let var = |name: &str| env::var(name).unwrap_or_default();
+        Target {
+            arch: var("CARGO_CFG_TARGET_ARCH"),
+            os: var("CARGO_CFG_TARGET_OS"),
+            features: var("CARGO_CFG_TARGET_FEATURE").split(',').map(str::to_owned).collect(),
+            is_host: env::var("HOST").ok() == env::var("TARGET").ok(),
+        }
+    }
+
+    fn is_x86(&self) -> bool {
+        self.arch == "x86" || self.arch == "x86_64"
+    }
+
+    fn is_apple(&self) -> bool {
+        self.os == "macos" || self.os == "ios"
+    }
+
+    /// Whether the target supports a CPU feature
+    ///
+    /// The features enabled with `-C target-feature` or `-C target-cpu` are always supported.
+    /// Otherwise, only when compiling for the host, the CPU of the host is checked, as the
+    /// binary will usually run on it.
+    fn has_feature(&self, feature: &str) -> bool {
+        self.is_x86() && (self.features.iter().any(|f| f == feature) ||
+                          self.is_host && host_has_feature(feature))
+    }
+
+    /// The perlasm flavour of the target, if its assembler can build the perlasm output
+    fn asm_flavour(&self) -> Option<&'static str> {
+        if self.arch != "x86_64" {
+            None
+        } else if self.is_apple() {
+            Some("macosx")
+        } else if self.os == "windows" {
+            Some("mingw64")
+        } else {
+            Some("elf")
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn host_has_feature(feature: &str) -> bool {
+    match feature {
+        "ssse3" => is_x86_feature_detected!("ssse3"),
+        "avx2" => is_x86_feature_detected!("avx2"),
+        _ => false,
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn host_has_feature(_feature: &str) -> bool {
+    false
+}
+
+/// Generates the assembly of a perlasm script into the output directory
+fn perlasm(script: &str, flavour: &str, out_dir: &Path) -> PathBuf {
+    let perl = env::var("PERL").unwrap_or_else(|_| "perl".to_owned());
+    let output = Command::new(&perl)
+        .arg(format!("src/c/src/{}.pl", script))
+        .arg(flavour)
+        .output()
+        .unwrap_or_else(|e| panic!("Could not run {} to generate the SSE assembly: {}", perl, e));
+    if !output.status.success() {
+        panic!("{} failed to generate {}.s: {}",
+               perl,
+               script,
+               String::from_utf8_lossy(&output.stderr));
+    }
+
+    let path = out_dir.join(format!("{}.s", script));
+    File::create(&path).and_then(|mut f| f.write_all(&output.stdout)).unwrap();
+    path
+}
+
+/// Links the system libntru found by pkg-config, instead of compiling the bundled sources
+///
+/// Whether the system library was compiled with SSE is not known, so the SSE functions are not
+/// available. With the `avoid-hamming-wt-patent` feature, it must have been compiled with
+/// `NTRU_AVOID_HAMMING_WT_PATENT`, as that changes the layout of the private keys.
+#[cfg(feature = "system-libntru")]
+fn link_system_libntru() {
+    let (min, max) = SYSTEM_LIBNTRU_VERSIONS;
+    let lib = pkg_config::Config::new()
+        .range_version(min..max)
+        .probe("libntru")
+        .unwrap_or_else(|e| {
+            panic!("The system-libntru feature needs libntru >= {} and < {}: {}", min, max, e)
+        });
+    println!("cargo:rustc-env=NTRU_SYSTEM_LIBNTRU_VERSION={}", lib.version);
+}
 
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // Declared even when they are not set, e.g. with `system-libntru` or without SSE, so that
+    // `#[cfg(SSE3)]` and `#[cfg(AVX2)]` never trigger the `unexpected_cfgs` lint
+    println!("cargo:rustc-check-cfg=cfg(SSE3)");
+    println!("cargo:rustc-check-cfg=cfg(AVX2)");
+
+    #[cfg(feature = "system-libntru")]
+    link_system_libntru();
+    #[cfg(not(feature = "system-libntru"))]
+    compile_bundled();
+}
+
+/// Compiles the bundled libntru sources for the target
+#[cfg_attr(feature = "system-libntru", allow(dead_code))]
+fn compile_bundled() {
     if cfg!(feature = "no-sse") && cfg!(feature = "sse") {
         panic!("You need to decide if you want SSE support or not. If you have doubts, simply disable both options and let the build script autodetect it.");
     }
@@ -17,200 +163,61 @@ fn main() {
         panic!("SSE is needed for AVX2 support.");
     }
 
-    if cfg!(target_os = "linux") || cfg!(target_os = "macos") || cfg!(target_os = "windows") {
-        env::set_var("CC", "gcc");
-        env::set_var("AS", "gcc -c");
-        if cfg!(target_os = "linux") {
-            env::set_var("AR", "ar");
-        }
-    } else if cfg!(target_os = "freebsd") || cfg!(target_os = "openbsd") {
-        env::set_var("CC", "cc");
-        env::set_var("AS", "cc -c");
-        env::set_var("AR", "ar");
+    let target = Target::from_env();
+    if (cfg!(feature = "sse") || cfg!(feature = "avx2")) && !target.is_x86() {
+        panic!("SSE and AVX2 are only available on x86 targets, not on {}.", target.arch);
     }
 
-    let mut avx2 = if cfg!(feature = "no-avx2") { false } else if cfg!(target_os = "windows") {
-        cfg!(feature = "avx2")
+    let mut config = cc::Build::new();
+    for source in SOURCES.iter() {
+        config.file(format!("src/c/src/{}", source));
+    }
+    config.include("src/c/src").opt_level(2);
+    if cfg!(feature = "avoid-hamming-wt-patent") {
+        config.define("NTRU_AVOID_HAMMING_WT_PATENT", None);
+    }
+
+    // The SSE code of libntru is selected with `__SSSE3__`, that MSVC never defines
+    let msvc = config.get_compiler().is_like_msvc();
+    if msvc && (cfg!(feature = "sse") || cfg!(feature = "avx2")) {
+        panic!("The SSE code of libntru cannot be compiled with MSVC, use the GNU toolchain.");
+    }
+
+    let avx2 = if cfg!(feature = "no-avx2") || cfg!(feature = "no-sse") || msvc {
+        false
     } else {
-        let output = if cfg!(target_os = "freebsd") || cfg!(target_os = "openbsd") {
-            // /usr/bin/grep -o AVX2 /var/run/dmesg.boot | /usr/bin/head -1
-            Command::new("/usr/bin/grep")
-                .arg("-o")
-                .arg("AVX2")
-                .arg("/var/run/dmesg.boot")
-                .output()
-                .unwrap()
-        } else if cfg!(target_os = "macos") {
-            // /usr/sbin/sysctl machdep.cpu.features | grep -m 1 -ow AVX2
-            Command::new("/usr/sbin/sysctl")
-                .arg("machdep.cpu.features")
-                .output()
-                .unwrap()
-        } else {
-            // /bin/grep -m 1 -o avx2 /proc/cpuinfo
-            Command::new("/bin/grep")
-                .arg("-m")
-                .arg("1")
-                .arg("-o")
-                .arg("avx2")
-                .arg("/proc/cpuinfo")
-                .output()
-                .unwrap()
-        };
-
-        let output = std::str::from_utf8(&output.stdout[..]).unwrap().trim();
-
-        if cfg!(target_os = "freebsd") || cfg!(target_os = "openbsd") || cfg!(target_os = "macos") {
-            output.contains("AVX2")
-        } else {
-            output == "avx2"
-        }
+        cfg!(feature = "avx2") || target.has_feature("avx2")
     };
-
-    let sse3 = if cfg!(feature = "no-sse3") { false } else if avx2 { true } else if cfg!(target_os = "windows") {
-        cfg!(feature = "sse")
+    let sse3 = if cfg!(feature = "no-sse") || msvc {
+        false
     } else {
-        let output = if cfg!(target_os = "freebsd") || cfg!(target_os = "openbsd") {
-            // /usr/bin/grep -o SSSE3 /var/run/dmesg.boot | /usr/bin/head -1
-            Command::new("/usr/bin/grep")
-                .arg("-o")
-                .arg("SSE3")
-                .arg("/var/run/dmesg.boot")
-                .output()
-                .unwrap()
-        } else if cfg!(target_os = "macos") {
-            // /usr/sbin/sysctl machdep.cpu.features | grep -m 1 -ow SSSE3
-            Command::new("/usr/sbin/sysctl")
-                .arg("machdep.cpu.features")
-                .output()
-                .unwrap()
-        } else {
-            // /bin/grep -m 1 -o ssse3 /proc/cpuinfo
-            Command::new("/bin/grep")
-                .arg("-m")
-                .arg("1")
-                .arg("-o")
-                .arg("ssse3")
-                .arg("/proc/cpuinfo")
-                .output()
-                .unwrap()
-        };
-        let output = std::str::from_utf8(&output.stdout[..]).unwrap().trim();
-
-        if cfg!(target_os = "freebsd") || cfg!(target_os = "openbsd") || cfg!(target_os = "macos") {
-            output.contains("SSSE3")
-        } else {
-            output == "ssse3"
-        }
+        avx2 || cfg!(feature = "sse") || target.has_feature("ssse3")
     };
 
-    if !sse3 {
-        avx2 = false;
+    if !msvc {
+        config.flag("-Wall").flag("-Wextra").flag("-Wno-unused-parameter");
     }
-
-    let mut cflags = "-g -Wall -Wextra -Wno-unused-parameter".to_owned();
     if avx2 {
-        cflags = cflags + " -mavx2";
+        config.flag("-mavx2");
     }
     if sse3 {
-        cflags = cflags + " -mssse3";
-    } else if cfg!(target_os = "macos") {
-        cflags = cflags + " -march=x86-64";
-    }
-    cflags = cflags + " -O2";
-
-    env::set_var("CFLAGS", cflags);
-
-    let mut config = gcc::Config::new();
-    config.file("src/c/src/bitstring.c")
-          .file("src/c/src/encparams.c")
-          .file("src/c/src/hash.c")
-          .file("src/c/src/idxgen.c")
-          .file("src/c/src/key.c")
-          .file("src/c/src/mgf.c")
-          .file("src/c/src/ntru.c")
-          .file("src/c/src/poly.c")
-          .file("src/c/src/rand.c")
-          .file("src/c/src/arith.c")
-          .file("src/c/src/sha1.c")
-          .file("src/c/src/sha2.c")
-          .file("src/c/src/nist_ctr_drbg.c")
-          .file("src/c/src/rijndael.c");
-
-    if sse3 &&
-       (cfg!(target_pointer_width = "64") || cfg!(target_os = "macos") ||
-        cfg!(target_os = "windows")) {
-        let out = if cfg!(target_os = "windows") {
-            Command::new("c:\\mingw\\msys\\1.0\\bin\\perl")
-                .arg("src/c/src/sha1-mb-x86_64.pl")
-                .arg("coff")
-                .output()
-                .unwrap()
-        } else if cfg!(target_os = "macos") {
-            Command::new("/usr/bin/perl")
-                .arg("src/c/src/sha1-mb-x86_64.pl")
-                .arg("macosx")
-                .output()
-                .unwrap()
-        } else  {
-            Command::new("/usr/bin/perl")
-                .arg("src/c/src/sha1-mb-x86_64.pl")
-                .arg("elf")
-                .output()
-                .unwrap()
-        };
-        let out = std::str::from_utf8(&out.stdout[..]).unwrap().trim();
-
-        let p = Path::new("src/c/src/sha1-mb-x86_64.s");
-        let mut f = File::create(&p).unwrap();
-        f.write(out.as_bytes()).unwrap();
-
-        Command::new(env::var("CC").unwrap())
-            .arg("-c")
-            .arg("src/c/src/sha1-mb-x86_64.s")
-            .arg("-o")
-            .arg("src/c/src/sha1-mb-x86_64.o")
-            .output()
-            .unwrap();
-
-        let out = if cfg!(target_os = "windows") {
-            Command::new("c:\\mingw\\msys\\1.0\\bin\\perl")
-                .arg("src/c/src/sha256-mb-x86_64.pl")
-                .arg("coff")
-                .output()
-                .unwrap()
-        } else if cfg!(target_os = "macos") {
-            Command::new("/usr/bin/perl")
-                .arg("src/c/src/sha256-mb-x86_64.pl")
-                .arg("macosx")
-                .output()
-                .unwrap()
-        } else {
-            Command::new("/usr/bin/perl")
-                .arg("src/c/src/sha256-mb-x86_64.pl")
-                .arg("elf")
-                .output()
-                .unwrap()
-        };
-        let out = std::str::from_utf8(&out.stdout[..]).unwrap().trim();
-
-        let p = Path::new("src/c/src/sha256-mb-x86_64.s");
-        let mut f = File::create(&p).unwrap();
-        f.write(out.as_bytes()).unwrap();
-
-        Command::new(env::var("CC").unwrap())
-            .arg("-c")
-            .arg("src/c/src/sha256-mb-x86_64.s")
-            .arg("-o")
-            .arg("src/c/src/sha256-mb-x86_64.o")
-            .output()
-            .unwrap();
+        config.flag("-mssse3");
+    } else if target.is_apple() && target.arch == "x86_64" {
+        config.flag("-march=x86-64");
+    }
 
-        config.object("src/c/src/sha1-mb-x86_64.o").object("src/c/src/sha256-mb-x86_64.o");
+    // The multi-buffer SHA assembly is only used by the 64 bit SSE code
+    if let Some(flavour) = target.asm_flavour().filter(|_| sse3) {
+        let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+        for script in ASM_SCRIPTS.iter() {
+            config.file(perlasm(script, flavour, &out_dir));
+        }
     }
 
-    config.include("src/c/src").compile("libntru.a");
+    config.compile("ntru");
 
+    println!("cargo:rerun-if-changed=src/c/src");
+    println!("cargo:rerun-if-env-changed=PERL");
     if sse3 {
         println!("cargo:rustc-cfg=SSE3")
     }
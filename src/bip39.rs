@@ -0,0 +1,149 @@
+//! BIP39 mnemonic backups
+//!
+//! This module derives key pairs from BIP39 mnemonic sentences, so a key pair can be backed up by
+//! writing down 12 to 24 words, and restored from them with the same passphrase. The mnemonic and
+//! the passphrase give the 64 byte BIP39 seed, with PBKDF2-HMAC-SHA512, as in BIP39, and the seed
+//! seeds a `CTR_DRBG` that generates the key pair, personalized with the OID of the parameter
+//! set, so each parameter set gets a different key pair from the same mnemonic. Only available
+//! with the `bip39` feature, with the English wordlist.
+//!
+//! BIP39 normalizes the mnemonic and the passphrase to Unicode NFKD. The English words are ASCII,
+//! and so unaffected, but passphrases with other characters must be given in NFKD to get the
+//! seeds of other BIP39 implementations.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let mnemonic = ntru::bip39::generate_mnemonic(24, &rand_ctx).unwrap();
+//! let kp = ntru::bip39::generate_key_pair(&mnemonic, "passphrase", &EES401EP1).unwrap();
+//!
+//! let restored = ntru::bip39::generate_key_pair(&mnemonic, "passphrase", &EES401EP1).unwrap();
+//! assert_eq!(restored, kp);
+//! ```
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha512;
+use types::{self, KeyPair, SecretVec, Error};
+use encparams::EncParams;
+use rand::{self, RandContext};
+use cca::sha256;
+
+/// The English wordlist of BIP39, one word per line, in alphabetical order
+const WORDLIST: &str = include_str!("bip39_english.txt");
+/// Length of the BIP39 seed
+pub const SEED_LEN: usize = 64;
+/// Iterations of PBKDF2
+const PBKDF2_ROUNDS: u32 = 2048;
+/// Personalization string of the `CTR_DRBG`, followed by the OID of the parameter set
+const PERSONALIZATION: &[u8] = b"ntru-rs bip39 key pair";
+
+/// Generates a random mnemonic sentence
+///
+/// The number of words must be 12, 15, 18, 21 or 24, for 128 to 256 bits of entropy, otherwise
+/// an `Error::InvalidParam` will be returned.
+pub fn generate_mnemonic(num_words: usize, rand_ctx: &RandContext) -> Result<String, Error> {
+    if !(12..=24).contains(&num_words) || !num_words.is_multiple_of(3) {
+        return Err(Error::InvalidParam);
+    }
+    let mut entropy = rand::generate((num_words / 3 * 4) as u16, rand_ctx)?;
+    let mnemonic = entropy_to_mnemonic(&entropy);
+    types::wipe(&mut entropy);
+    mnemonic
+}
+
+/// Encodes entropy as a mnemonic sentence
+///
+/// The entropy must be 16 to 32 bytes long, a multiple of 4, otherwise an `Error::InvalidParam`
+/// will be returned.
+pub fn entropy_to_mnemonic(entropy: &[u8]) -> Result<String, Error> {
+    if entropy.len() < 16 || entropy.len() > 32 || !entropy.len().is_multiple_of(4) {
+        return Err(Error::InvalidParam);
+    }
+    let checksum = sha256(&[entropy]);
+    let bit = |i: usize| {
+        let byte = if i < 8 * entropy.len() {
+            entropy[i / 8]
+        } else {
+            checksum[i / 8 - entropy.len()]
+        };
+        byte >> (7 - i % 8) & 1
+    };
+
+    // The entropy is followed by one bit of checksum for each 4 bytes, 11 bits for each word
+    let words = wordlist();
+    let num_words = (8 * entropy.len() + entropy.len() / 4) / 11;
+    let mnemonic: Vec<&str> = (0..num_words)
+        .map(|w| words[(0..11).fold(0, |index, b| index << 1 | bit(11 * w + b) as usize)])
+        .collect();
+    Ok(mnemonic.join(" "))
+}
+
+/// Decodes the entropy of a mnemonic sentence
+///
+/// The words may be separated by any whitespace. Returns `Error::InvalidEncoding` if a word is not
+/// in the wordlist, the number of words is not 12, 15, 18, 21 or 24, or the checksum does not
+/// match.
+pub fn mnemonic_to_entropy(mnemonic: &str) -> Result<SecretVec, Error> {
+    let words = wordlist();
+    let mut indices = Vec::new();
+    for word in mnemonic.split_whitespace() {
+        indices.push(words.binary_search(&word).map_err(|_| Error::InvalidEncoding)?);
+    }
+    if indices.len() < 12 || indices.len() > 24 || indices.len() % 3 != 0 {
+        return Err(Error::InvalidEncoding);
+    }
+    let bit = |i: usize| (indices[i / 11] >> (10 - i % 11) & 1) as u8;
+
+    let entropy_len = indices.len() / 3 * 4;
+    let entropy: Vec<u8> = (0..entropy_len)
+        .map(|i| (0..8).fold(0, |byte, b| byte << 1 | bit(8 * i + b)))
+        .collect();
+    let entropy = SecretVec::new(entropy);
+    let checksum = sha256(&[&entropy]);
+    let valid = (0..entropy_len / 4)
+        .all(|i| bit(8 * entropy_len + i) == checksum[i / 8] >> (7 - i % 8) & 1);
+    if valid {
+        Ok(entropy)
+    } else {
+        Err(Error::InvalidEncoding)
+    }
+}
+
+/// Derives the BIP39 seed of a mnemonic sentence and a passphrase
+///
+/// Returns `Error::InvalidEncoding` if the mnemonic is not valid, as `mnemonic_to_entropy()`.
+pub fn to_seed(mnemonic: &str, passphrase: &str) -> Result<SecretVec, Error> {
+    let _ = mnemonic_to_entropy(mnemonic)?;
+    let mut normalized = mnemonic.split_whitespace().collect::<Vec<&str>>().join(" ").into_bytes();
+    let mut salt = b"mnemonic".to_vec();
+    salt.extend_from_slice(passphrase.as_bytes());
+
+    let mut seed = vec![0u8; SEED_LEN];
+    pbkdf2_hmac::<Sha512>(&normalized, &salt, PBKDF2_ROUNDS, &mut seed);
+    types::wipe(&mut normalized);
+    types::wipe(&mut salt);
+    Ok(SecretVec::new(seed))
+}
+
+/// Generates the key pair of a mnemonic sentence and a passphrase
+///
+/// The same mnemonic, passphrase and parameter set always give the same key pair. Returns
+/// `Error::InvalidEncoding` if the mnemonic is not valid, as `mnemonic_to_entropy()`.
+pub fn generate_key_pair(mnemonic: &str,
+                         passphrase: &str,
+                         params: &EncParams)
+                         -> Result<KeyPair, Error> {
+    let seed = to_seed(mnemonic, passphrase)?;
+    let mut personalization = PERSONALIZATION.to_vec();
+    personalization.extend_from_slice(&params.get_oid());
+    let rand_ctx = rand::init_det_with(&rand::RNG_CTR_DRBG, &seed, &personalization)?;
+    ::generate_key_pair(params, &rand_ctx)
+}
+
+/// Gets the words of the wordlist
+fn wordlist() -> Vec<&'static str> {
+    WORDLIST.lines().collect()
+}
@@ -0,0 +1,187 @@
+//! Blinded decryption
+//!
+//! This module decrypts messages with the ciphertext polynomial blinded before it is multiplied
+//! by the private key, as a countermeasure against power analysis and timing attacks on devices
+//! such as smartcards. Each decryption multiplies the ciphertext polynomial `e` by a random
+//! monomial `x^k`, and splits it into a random polynomial `a` and `x^k * e - a`. Each part is
+//! multiplied by the private polynomial `t` separately, and the sum of the products is rotated
+//! back, so the private key is only ever multiplied by uniformly random polynomials. Only
+//! available with the `blinding` feature.
+//!
+//! libntru cannot decrypt a polynomial product computed outside of it, so the rest of the
+//! P1363.1 decryption, from the check of `dm0` to the re-encryption check, is done in Rust, with
+//! the IGF and the MGF of the `low_level` module. The results are the same as the ones of
+//! `ntru::decrypt()`, at the cost of two multiplications by `t` instead of one.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ntru::encrypt(msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+//! let decrypted = ntru::blinding::decrypt(&encrypted, &kp, &EES401EP1, &rand_ctx).unwrap();
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use types::{self, KeyPair, IntPoly, ProdPoly, PrivPoly, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use low_level::{IndexGenerator, mgf};
+
+/// Decrypts a message with the ciphertext polynomial blinded
+///
+/// Works like `ntru::decrypt()`, and also returns `Error::Prng` if the random number generator
+/// fails to generate the blinding values.
+pub fn decrypt(enc: &[u8],
+               kp: &KeyPair,
+               params: &EncParams,
+               rand_ctx: &RandContext)
+               -> Result<Box<[u8]>, Error> {
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 {
+        return Err(Error::InvalidParam);
+    }
+    if enc.len() != enc_len {
+        return Err(Error::InvalidCiphertextLength {
+            expected: enc_len,
+            actual: enc.len(),
+        });
+    }
+    let n = params.get_n();
+    let q = params.get_q();
+    let mod_mask = q - 1;
+    let blen = params.get_db() as usize / 8;
+    let max_msg_len = params.max_msg_len() as usize;
+
+    // The first error found is reported, but all the checks are done
+    let mut error = None;
+    let mut check = |valid: bool, e: Error| {
+        if !valid && error.is_none() {
+            error = Some(e);
+        }
+    };
+
+    let e = IntPoly::from_arr(enc, n, q);
+    let ci = decrypt_poly(&e, kp.get_private().get_t(), q, rand_ctx)?;
+    check(check_rep_weight(&ci, params.get_dm0()), Error::Md0Violation);
+
+    let mut c_r = &e - &ci;
+    c_r.mod_mask(mod_mask);
+    let mut cm_trin = &ci - &mgf(&to_arr4(&c_r), params)?;
+    cm_trin.mod3();
+    let (mut cm, valid) = to_sves(&cm_trin);
+    check(valid, Error::InvalidEncoding);
+
+    // cM = b || len || m || zero padding
+    let cl = cm[blen] as usize;
+    check(cl <= max_msg_len, Error::MessageTooLong);
+    let cl = if cl <= max_msg_len { cl } else { 0 };
+    let msg_start = blen + 1;
+    check(cm[msg_start + cl..].iter().all(|&b| b == 0), Error::NoZeroPad);
+
+    let mut s_data = params.get_oid().to_vec();
+    s_data.extend_from_slice(&cm[msg_start..msg_start + cl]);
+    s_data.extend_from_slice(&cm[..blen]);
+    let h_arr = kp.get_public().get_h().to_arr(params);
+    s_data.extend_from_slice(&h_arr[..params.get_pklen() as usize / 8]);
+    let r = blind_poly(&s_data, params)?;
+    let mut c_r_prime = kp.get_public().get_h().mult_priv(&r, mod_mask)?;
+    c_r_prime.mod_mask(mod_mask);
+    check(c_r_prime == c_r, Error::InvalidEncoding);
+
+    let result = match error {
+        None => Ok(cm[msg_start..msg_start + cl].to_vec().into_boxed_slice()),
+        Some(e) => Err(::decryption_error(e)),
+    };
+    // The encoded message and the seed hold the message
+    types::wipe(&mut cm);
+    types::wipe(&mut s_data);
+    result
+}
+
+/// Computes `e * f mod 3`, with `f = 1 + 3t`, the ciphertext polynomial blinded
+///
+/// The coefficients of the result are in `{0, 1, 2}`, as `ntru_mod3()` leaves them.
+fn decrypt_poly(e: &IntPoly,
+                t: &PrivPoly,
+                q: u16,
+                rand_ctx: &RandContext)
+                -> Result<IntPoly, Error> {
+    let mod_mask = q - 1;
+    let n = e.get_coeffs().len();
+    let rand = ::rand::generate(2, rand_ctx)?;
+    let k = ((rand[0] as usize) << 8 | rand[1] as usize) % n;
+
+    // x^k * e, undone by rotating the product back
+    let rotated: Vec<i16> = (0..n).map(|i| e.get_coeffs()[(i + n - k) % n]).collect();
+    let a = IntPoly::rand(n as u16, q.trailing_zeros() as u16, rand_ctx)?;
+    let mut rest = &IntPoly::new(&rotated) - &a;
+    rest.mod_mask(mod_mask);
+    let mut product = a.mult_priv(t, mod_mask)? + &rest.mult_priv(t, mod_mask)?;
+    product.mod_mask(mod_mask);
+
+    let coeffs: Vec<i16> = (0..n).map(|i| product.get_coeffs()[(i + k) % n]).collect();
+    let mut d = IntPoly::new(&coeffs);
+    d.mult_fac(3);
+    d += e;
+    d.mod_center(q);
+    d.mod3();
+    Ok(d)
+}
+
+/// Checks that the polynomial has at least `dm0` coefficients of each value modulo 3
+fn check_rep_weight(p: &IntPoly, dm0: u16) -> bool {
+    let mut weights = [0u16; 3];
+    for &c in p.get_coeffs() {
+        weights[((c % 3 + 3) % 3) as usize] += 1;
+    }
+    weights.iter().all(|&w| w >= dm0)
+}
+
+/// Packs the 2 least significant bits of each coefficient, the seed of the MGF
+fn to_arr4(p: &IntPoly) -> Vec<u8> {
+    let coeffs = p.get_coeffs();
+    let mut arr = vec![0u8; (coeffs.len() * 2).div_ceil(8)];
+    for (i, &c) in coeffs.iter().enumerate() {
+        arr[i / 4] |= ((c & 3) as u8) << (2 * (i % 4));
+    }
+    arr
+}
+
+/// Encodes the coefficients in `{0, 1, 2}` as bits, 3 bits for each 2 coefficients
+///
+/// The 3 bits of the coefficients `c1` and `c2` are `3 * c1 + c2`, least significant bit first.
+/// If the number of coefficients is odd, the last one is left out. Also returns whether the
+/// encoding is valid, that is, no two coefficients are both 2.
+fn to_sves(p: &IntPoly) -> (Vec<u8>, bool) {
+    let coeffs = p.get_coeffs();
+    let num_bits = (coeffs.len() * 3).div_ceil(2);
+    let mut arr = vec![0u8; num_bits.div_ceil(8)];
+    let mut valid = true;
+    for (j, pair) in coeffs.chunks(2).filter(|pair| pair.len() == 2).enumerate() {
+        let bits = 3 * ((pair[0] % 3 + 3) % 3) as usize + ((pair[1] % 3 + 3) % 3) as usize;
+        valid &= bits < 8;
+        for b in 0..3 {
+            let bit_index = 3 * j + b;
+            arr[bit_index / 8] |= ((bits >> b) as u8 & 1) << (bit_index % 8);
+        }
+    }
+    (arr, valid)
+}
+
+/// Generates the blinding polynomial `r` of the encryption from the seed
+fn blind_poly(s_data: &[u8], params: &EncParams) -> Result<PrivPoly, Error> {
+    let mut igf = IndexGenerator::new(s_data, params)?;
+    if params.is_product() {
+        let f1 = igf.tern_poly(params.get_df1())?;
+        let f2 = igf.tern_poly(params.get_df2())?;
+        let f3 = igf.tern_poly(params.get_df3())?;
+        PrivPoly::from_prod_poly(ProdPoly::new(params.get_n(), f1, f2, f3))
+    } else {
+        Ok(PrivPoly::new_with_tern_poly(igf.tern_poly(params.get_df1())?))
+    }
+}
@@ -0,0 +1,221 @@
+//! Bouncy Castle interoperability
+//!
+//! This module converts keys to and from the encodings of the NTRUEncrypt implementation of Bouncy
+//! Castle for Java (`NTRUEncryptionPublicKeyParameters` and `NTRUEncryptionPrivateKeyParameters`),
+//! so keys can be exchanged with JVM services. The encodings differ from the ones of libntru:
+//!
+//! * Public keys are only the coefficients of `h`, packed with `log2(q)` bits each, least
+//!   significant bit first, without the `N` and `q` header of libntru.
+//! * Private keys are the public key as above, followed by the private polynomial `t`. For
+//!   product-form parameter sets, the indices of the ones and then of the negative ones of `f1`,
+//!   `f2` and `f3`, packed with 11 bits each, with each list padded to a whole byte. Otherwise,
+//!   the coefficients of `t` as a base 3 big-endian integer, with the coefficient `i` as the digit
+//!   `i`, and `-1`, `0` and `1` as the digits `0`, `1` and `2`.
+//!
+//! Encrypted messages have the same layout in both libraries, so they can be exchanged as they
+//! are. The Bouncy Castle parameter set must be the one with the same name, and must use `f = 1 +
+//! 3t` (`fastFp`), as libntru always does.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP2;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP2, &rand_ctx).unwrap();
+//!
+//! let encoded = ntru::bouncycastle::export_key_pair(&kp, &EES401EP2);
+//! let imported = ntru::bouncycastle::import_key_pair(&encoded, &EES401EP2).unwrap();
+//! assert_eq!(imported, kp);
+//! ```
+use types::{KeyPair, PrivateKey, PublicKey, IntPoly, TernPoly, ProdPoly, PrivPoly, Error};
+use encparams::EncParams;
+
+/// Bits of each index of the product-form polynomials
+const BITS_PER_INDEX: u32 = 11;
+
+/// Exports a public key in the encoding of Bouncy Castle
+pub fn export_public(public: &PublicKey, params: &EncParams) -> Box<[u8]> {
+    let mask = params.get_q() - 1;
+    let h = public.get_h().get_coeffs().iter().map(|&c| c as u16 & mask);
+    encode_mod_q(h, params.get_q().trailing_zeros()).into_boxed_slice()
+}
+
+/// Imports a public key in the encoding of Bouncy Castle
+///
+/// Returns `Error::InvalidKey` if the array does not have the length of the public keys of the
+/// parameter set.
+pub fn import_public(arr: &[u8], params: &EncParams) -> Result<PublicKey, Error> {
+    if arr.len() != params.enc_len() as usize {
+        return Err(Error::InvalidKey);
+    }
+    let bits = params.get_q().trailing_zeros();
+    let h: Vec<i16> = decode_mod_q(arr, params.get_n() as usize, bits)
+        .into_iter()
+        .map(|c| c as i16)
+        .collect();
+    Ok(PublicKey::new(params.get_q(), IntPoly::new(&h)))
+}
+
+/// Exports a key pair as a private key in the encoding of Bouncy Castle
+pub fn export_key_pair(kp: &KeyPair, params: &EncParams) -> Box<[u8]> {
+    let mut arr = export_public(kp.get_public(), params).into_vec();
+    let t = kp.get_private().get_t();
+    if t.is_product() {
+        let prod = t.get_poly_prod();
+        for f in &[prod.get_f1(), prod.get_f2(), prod.get_f3()] {
+            arr.extend_from_slice(&encode_mod_q(f.get_ones().iter().cloned(), BITS_PER_INDEX));
+            arr.extend_from_slice(&encode_mod_q(f.get_neg_ones().iter().cloned(),
+                                                BITS_PER_INDEX));
+        }
+    } else {
+        arr.extend_from_slice(&to_base3(t.get_poly_tern().to_int_poly().get_coeffs()));
+    }
+    arr.into_boxed_slice()
+}
+
+/// Imports a key pair from a private key in the encoding of Bouncy Castle
+///
+/// Returns `Error::InvalidKey` if the array does not have the length of the private keys of the
+/// parameter set, or it is not a valid private key for it.
+pub fn import_key_pair(arr: &[u8], params: &EncParams) -> Result<KeyPair, Error> {
+    let n = params.get_n();
+    let public_len = params.enc_len() as usize;
+    if arr.len() < public_len {
+        return Err(Error::InvalidKey);
+    }
+    let (public, rest) = arr.split_at(public_len);
+    let public = import_public(public, params)?;
+
+    let t = if params.is_product() {
+        let mut rest = rest;
+        let mut polys = Vec::with_capacity(3);
+        for &df in &[params.get_df1(), params.get_df2(), params.get_df3()] {
+            let len = (df as usize * BITS_PER_INDEX as usize).div_ceil(8);
+            if rest.len() < 2 * len {
+                return Err(Error::InvalidKey);
+            }
+            let ones = decode_mod_q(&rest[..len], df as usize, BITS_PER_INDEX);
+            let neg_ones = decode_mod_q(&rest[len..2 * len], df as usize, BITS_PER_INDEX);
+            polys.push(TernPoly::new(n, &ones, &neg_ones));
+            rest = &rest[2 * len..];
+        }
+        if !rest.is_empty() {
+            return Err(Error::InvalidKey);
+        }
+        let mut polys = polys.into_iter();
+        match (polys.next(), polys.next(), polys.next()) {
+            (Some(f1), Some(f2), Some(f3)) => {
+                PrivPoly::from_prod_poly(ProdPoly::new(n, f1, f2, f3))?
+            }
+            _ => return Err(Error::InvalidKey),
+        }
+    } else {
+        let coeffs = from_base3(rest, n as usize)?;
+        let ones: Vec<u16> = (0..n).filter(|&i| coeffs[i as usize] == 1).collect();
+        let neg_ones: Vec<u16> = (0..n).filter(|&i| coeffs[i as usize] == -1).collect();
+        // Check the weights before building the polynomial, that has room for df1 indices
+        if ones.len() != params.get_df1() as usize || neg_ones.len() != params.get_df1() as usize {
+            return Err(Error::InvalidKey);
+        }
+        PrivPoly::new_with_tern_poly(TernPoly::new(n, &ones, &neg_ones))
+    };
+    t.check_weights(params).map_err(|_| Error::InvalidKey)?;
+
+    Ok(KeyPair::new(PrivateKey::new(params.get_q(), t), public))
+}
+
+/// Packs the values with the given number of bits each, least significant bit first
+fn encode_mod_q<I: Iterator<Item = u16>>(values: I, bits: u32) -> Vec<u8> {
+    let mut arr = Vec::new();
+    let mut bit_index = 0;
+    for value in values {
+        for j in 0..bits {
+            if bit_index % 8 == 0 {
+                arr.push(0);
+            }
+            let last = arr.len() - 1;
+            arr[last] |= ((value >> j) as u8 & 1) << (bit_index % 8);
+            bit_index += 1;
+        }
+    }
+    arr
+}
+
+/// Unpacks `count` values with the given number of bits each, least significant bit first
+fn decode_mod_q(arr: &[u8], count: usize, bits: u32) -> Vec<u16> {
+    (0..count)
+        .map(|i| {
+            (0..bits).fold(0u16, |value, j| {
+                let bit_index = i * bits as usize + j as usize;
+                value | ((arr[bit_index / 8] >> (bit_index % 8)) as u16 & 1) << j
+            })
+        })
+        .collect()
+}
+
+/// Multiplies a little-endian big integer by a small factor, and adds a small term
+fn mul_add(limbs: &mut Vec<u32>, factor: u32, term: u32) {
+    let mut carry = term as u64;
+    for limb in limbs.iter_mut() {
+        let product = *limb as u64 * factor as u64 + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+    if carry != 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+/// Divides a little-endian big integer by a small divisor, and returns the remainder
+fn div_rem(limbs: &mut [u32], divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let dividend = rem << 32 | *limb as u64;
+        *limb = (dividend / divisor as u64) as u32;
+        rem = dividend % divisor as u64;
+    }
+    rem as u32
+}
+
+/// Length in bytes of the base 3 encoding of `n` ternary coefficients, the length of `3^n`
+fn base3_len(n: usize) -> usize {
+    let mut pow = vec![1u32];
+    for _ in 0..n {
+        mul_add(&mut pow, 3, 0);
+    }
+    let last = pow[pow.len() - 1];
+    ((pow.len() - 1) * 32 + (32 - last.leading_zeros()) as usize).div_ceil(8)
+}
+
+/// Encodes ternary coefficients as a base 3 big-endian integer
+fn to_base3(coeffs: &[i16]) -> Vec<u8> {
+    let mut limbs = vec![0u32];
+    for &c in coeffs.iter().rev() {
+        mul_add(&mut limbs, 3, (c + 1) as u32);
+    }
+    let len = base3_len(coeffs.len());
+    (0..len)
+        .map(|i| {
+            let byte = len - 1 - i;
+            limbs.get(byte / 4).map_or(0, |limb| (limb >> (8 * (byte % 4))) as u8)
+        })
+        .collect()
+}
+
+/// Decodes `n` ternary coefficients from a base 3 big-endian integer
+fn from_base3(arr: &[u8], n: usize) -> Result<Vec<i16>, Error> {
+    if arr.len() != base3_len(n) {
+        return Err(Error::InvalidKey);
+    }
+    let mut limbs = vec![0u32];
+    for &b in arr {
+        mul_add(&mut limbs, 256, b as u32);
+    }
+    let coeffs = (0..n).map(|_| div_rem(&mut limbs, 3) as i16 - 1).collect();
+    if limbs.iter().any(|&limb| limb != 0) {
+        return Err(Error::InvalidKey);
+    }
+    Ok(coeffs)
+}
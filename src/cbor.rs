@@ -0,0 +1,205 @@
+//! CBOR and COSE encodings
+//!
+//! This module encodes NTRU keys as COSE keys (RFC 9052), and encrypted messages as compact CBOR
+//! arrays, so they can be carried in CBOR protocols without base64. Only available with the
+//! `cbor` feature. There is no registered COSE key type for NTRU, so the COSE keys are maps with
+//! the following labels, following the ones of the `OKP` key type:
+//!
+//! * `1` (`kty`): always `"NTRU"`.
+//! * `2` (`kid`): the key ID of the public key, as `keyring::key_id()`. It is ignored when
+//!   decoding.
+//! * `3` (`alg`): always `"NTRUEncrypt"`.
+//! * `-1`: the 3 byte OID of the parameter set.
+//! * `-2`: the exported public key.
+//! * `-4`: the exported private key, only for key pairs.
+//!
+//! Encrypted messages are encoded as an array with the OID of the parameter set and the
+//! encrypted message, both as byte strings.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let cose_key = ntru::cbor::public_to_cose_key(kp.get_public(), &EES401EP1);
+//! let (public, params) = ntru::cbor::public_from_cose_key(&cose_key).unwrap();
+//! assert_eq!(&public, kp.get_public());
+//!
+//! let encrypted = ntru::encrypt(b"Hello", &public, params, &rand_ctx).unwrap();
+//! let encoded = ntru::cbor::encode_ciphertext(&encrypted, params);
+//! let (decoded, params) = ntru::cbor::decode_ciphertext(&encoded).unwrap();
+//! assert_eq!(&ntru::decrypt(&decoded, &kp, params).unwrap()[..], b"Hello");
+//! ```
+use ciborium::value::Value;
+use ciborium::{de, ser};
+use types::{KeyPair, PrivateKey, PublicKey, Error};
+use encparams::{self, EncParams};
+use keyring;
+
+/// Key type of NTRU COSE keys
+pub const KTY: &str = "NTRU";
+/// Algorithm of NTRU COSE keys
+pub const ALG: &str = "NTRUEncrypt";
+
+/// Label of the key type
+const LABEL_KTY: i64 = 1;
+/// Label of the key ID
+const LABEL_KID: i64 = 2;
+/// Label of the algorithm
+const LABEL_ALG: i64 = 3;
+/// Label of the OID of the parameter set
+const LABEL_OID: i64 = -1;
+/// Label of the public key
+const LABEL_PUBLIC: i64 = -2;
+/// Label of the private key
+const LABEL_PRIVATE: i64 = -4;
+
+/// Encodes a public key as a COSE key
+pub fn public_to_cose_key(public: &PublicKey, params: &EncParams) -> Vec<u8> {
+    to_vec(&Value::Map(public_entries(public, params)))
+}
+
+/// Encodes a key pair as a COSE key, with both the public and the private key
+pub fn key_pair_to_cose_key(kp: &KeyPair, params: &EncParams) -> Vec<u8> {
+    let mut entries = public_entries(kp.get_public(), params);
+    entries.push((LABEL_PRIVATE.into(), Value::Bytes(kp.get_private().export(params).to_vec())));
+    to_vec(&Value::Map(entries))
+}
+
+/// Decodes a public key and its parameter set from a COSE key
+///
+/// Returns `Error::InvalidEncoding` if it is not a COSE key of an NTRU key,
+/// `Error::UnknownParamSet` if the parameter set is not known, and `Error::InvalidKey` if the
+/// public key is not valid for the parameter set.
+pub fn public_from_cose_key(cose_key: &[u8]) -> Result<(PublicKey, &'static EncParams), Error> {
+    let entries = map_entries(from_slice(cose_key)?)?;
+    public_from_entries(&entries)
+}
+
+/// Decodes a key pair and its parameter set from a COSE key
+///
+/// Returns the same errors as `public_from_cose_key()`, and `Error::InvalidEncoding` if the COSE
+/// key does not have the private key.
+pub fn key_pair_from_cose_key(cose_key: &[u8]) -> Result<(KeyPair, &'static EncParams), Error> {
+    let entries = map_entries(from_slice(cose_key)?)?;
+    let (public, params) = public_from_entries(&entries)?;
+    let private = PrivateKey::import_checked(bytes(&entries, LABEL_PRIVATE)?, params)?;
+    Ok((KeyPair::new(private, public), params))
+}
+
+/// Encodes an encrypted message with the OID of its parameter set
+pub fn encode_ciphertext(enc: &[u8], params: &EncParams) -> Vec<u8> {
+    let oid = params.get_oid().to_vec();
+    to_vec(&Value::Array(vec![Value::Bytes(oid), Value::Bytes(enc.to_vec())]))
+}
+
+/// Decodes an encrypted message and its parameter set
+///
+/// Returns `Error::InvalidEncoding` if it is not an encoded encrypted message,
+/// `Error::UnknownParamSet` if the parameter set is not known, and
+/// `Error::InvalidCiphertextLength` if the encrypted message does not have the length of the
+/// parameter set.
+pub fn decode_ciphertext(encoded: &[u8]) -> Result<(Box<[u8]>, &'static EncParams), Error> {
+    let mut items = match from_slice(encoded)? {
+        Value::Array(items) => items,
+        _ => return Err(Error::InvalidEncoding),
+    };
+    if items.len() != 2 {
+        return Err(Error::InvalidEncoding);
+    }
+    let (oid, enc) = match (items.remove(0), items.remove(0)) {
+        (Value::Bytes(oid), Value::Bytes(enc)) => (oid, enc),
+        _ => return Err(Error::InvalidEncoding),
+    };
+    let params = params_from_oid(&oid)?;
+    if enc.len() != params.enc_len() as usize {
+        return Err(Error::InvalidCiphertextLength {
+            expected: params.enc_len() as usize,
+            actual: enc.len(),
+        });
+    }
+    Ok((enc.into_boxed_slice(), params))
+}
+
+/// Gets the entries of the COSE key of a public key
+fn public_entries(public: &PublicKey, params: &EncParams) -> Vec<(Value, Value)> {
+    vec![(LABEL_KTY.into(), Value::Text(KTY.to_owned())),
+         (LABEL_KID.into(), Value::Bytes(keyring::key_id(public, params).to_vec())),
+         (LABEL_ALG.into(), Value::Text(ALG.to_owned())),
+         (LABEL_OID.into(), Value::Bytes(params.get_oid().to_vec())),
+         (LABEL_PUBLIC.into(), Value::Bytes(public.export(params).to_vec()))]
+}
+
+/// Checks the key type and the algorithm of the COSE key, and gets its public key
+fn public_from_entries(entries: &[(Value, Value)])
+                       -> Result<(PublicKey, &'static EncParams), Error> {
+    if text(entries, LABEL_KTY)? != KTY || text(entries, LABEL_ALG)? != ALG {
+        return Err(Error::InvalidEncoding);
+    }
+    let params = params_from_oid(bytes(entries, LABEL_OID)?)?;
+    let public = PublicKey::import_for(bytes(entries, LABEL_PUBLIC)?, params)?;
+    Ok((public, params))
+}
+
+/// Finds the parameter set of the OID
+fn params_from_oid(oid: &[u8]) -> Result<&'static EncParams, Error> {
+    if oid.len() != 3 {
+        return Err(Error::InvalidEncoding);
+    }
+    encparams::from_oid([oid[0], oid[1], oid[2]]).ok_or(Error::UnknownParamSet)
+}
+
+/// Gets the entries of a map
+fn map_entries(value: Value) -> Result<Vec<(Value, Value)>, Error> {
+    match value {
+        Value::Map(entries) => Ok(entries),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Finds the value of the label in the entries of a map
+fn find(entries: &[(Value, Value)], label: i64) -> Option<&Value> {
+    entries.iter()
+        .find(|&(key, _)| match *key {
+            Value::Integer(i) => i128::from(i) == label as i128,
+            _ => false,
+        })
+        .map(|(_, value)| value)
+}
+
+/// Gets the text string value of the label
+fn text(entries: &[(Value, Value)], label: i64) -> Result<&str, Error> {
+    match find(entries, label) {
+        Some(Value::Text(s)) => Ok(s),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Gets the byte string value of the label
+fn bytes(entries: &[(Value, Value)], label: i64) -> Result<&[u8], Error> {
+    match find(entries, label) {
+        Some(Value::Bytes(b)) => Ok(b),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Serializes a value
+#[cfg_attr(feature = "no-panic", allow(clippy::expect_used))]
+fn to_vec(value: &Value) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    ser::into_writer(value, &mut encoded).expect("serializing to a vector does not fail");
+    encoded
+}
+
+/// Deserializes a value, that must take the whole slice
+fn from_slice(mut encoded: &[u8]) -> Result<Value, Error> {
+    let value = de::from_reader(&mut encoded).map_err(|_| Error::InvalidEncoding)?;
+    if !encoded.is_empty() {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok(value)
+}
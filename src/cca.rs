@@ -0,0 +1,120 @@
+//! CCA secure KEM
+//!
+//! This module provides a key encapsulation mechanism built on top of NTRU encryption with a
+//! Fujisaki-Okamoto style transform, so that it can be exposed to adaptive chosen ciphertext
+//! attackers. The encapsulation encrypts a random secret with randomness derived from the secret
+//! itself, so the decapsulation can re-encrypt the decrypted secret and check that it gets the
+//! same ciphertext. Ciphertexts that fail that check are implicitly rejected: a pseudorandom key
+//! derived from the private key and the ciphertext is returned instead of an error, so an attacker
+//! learns nothing from them.
+//!
+//! All hashes are SHA-256, with a different prefix for each use.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::DEFAULT_PARAMS_128_BITS;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&DEFAULT_PARAMS_128_BITS, &rand_ctx).unwrap();
+//!
+//! let (ciphertext, shared1) = ntru::cca::encapsulate(kp.get_public(), &DEFAULT_PARAMS_128_BITS,
+//!                                                    &rand_ctx).unwrap();
+//! let shared2 = ntru::cca::decapsulate(&ciphertext, &kp, &DEFAULT_PARAMS_128_BITS).unwrap();
+//!
+//! assert_eq!(shared1, shared2);
+//! ```
+use types::{self, KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::{RandContext, RNG_CTR_DRBG};
+use ct::{self, Choice};
+use super::ffi;
+
+/// Length of the shared key
+pub const SHARED_KEY_LEN: usize = 32;
+/// Length of the encrypted secret
+const SECRET_LEN: usize = 32;
+
+/// Ciphertext and shared key returned by the encapsulation
+pub type Encapsulated = (Box<[u8]>, Box<[u8]>);
+
+/// Encapsulation
+///
+/// Generates a random shared key for the public key, and returns the ciphertext that encapsulates
+/// it and the shared key itself. The ciphertext is `params.enc_len()` bytes long.
+pub fn encapsulate(public: &PublicKey,
+                   params: &EncParams,
+                   rand_ctx: &RandContext)
+                   -> Result<Encapsulated, Error> {
+    let secret = ::rand::generate(SECRET_LEN as u16, rand_ctx)?;
+    let ciphertext = encrypt_derandomized(&secret, public, params)?;
+    let key = sha256(&[b"ntru-cca key", &secret, &ciphertext]);
+    Ok((ciphertext, key.to_vec().into_boxed_slice()))
+}
+
+/// Decapsulation
+///
+/// Recovers the shared key encapsulated in the ciphertext. Invalid ciphertexts are implicitly
+/// rejected, and a pseudorandom key is returned for them. Only ciphertexts with an invalid length
+/// return `Error::InvalidCiphertextLength`.
+///
+/// A secret is re-encrypted even if the decryption fails, a dummy one derived from the private key,
+/// so invalid ciphertexts are not rejected faster than valid ones.
+pub fn decapsulate(ciphertext: &[u8], kp: &KeyPair, params: &EncParams) -> Result<Box<[u8]>, Error> {
+    let enc_len = params.enc_len() as usize;
+    if ciphertext.len() != enc_len {
+        return Err(Error::InvalidCiphertextLength {
+            expected: enc_len,
+            actual: ciphertext.len(),
+        });
+    }
+
+    let mut private = kp.get_private().export(params);
+    let mut rejection_secret = sha256(&[b"ntru-cca rejection", &private]);
+    types::wipe(&mut private);
+    let mut rejection_key = sha256(&[b"ntru-cca reject", &rejection_secret, ciphertext]);
+
+    let mut secret = rejection_secret;
+    let decrypted_ok = match ::decrypt(ciphertext, kp, params) {
+        Ok(decrypted) => {
+            let mut decrypted = decrypted.into_vec();
+            let decrypted_ok = Choice::from((decrypted.len() == SECRET_LEN) as u8);
+            decrypted.resize(SECRET_LEN, 0);
+            ct::conditional_copy(&mut secret, &decrypted, decrypted_ok)?;
+            types::wipe(&mut decrypted);
+            decrypted_ok
+        }
+        Err(_) => Choice::from(0),
+    };
+    let valid = match encrypt_derandomized(&secret, kp.get_public(), params) {
+        Ok(reencrypted) => decrypted_ok & ct::bytes_eq(&reencrypted, ciphertext),
+        Err(_) => Choice::from(0),
+    };
+
+    let mut key = sha256(&[b"ntru-cca key", &secret, ciphertext]);
+    types::wipe(&mut secret);
+    types::wipe(&mut rejection_secret);
+    let shared = ct::select_bytes(&rejection_key, &key, valid)?;
+    types::wipe(&mut key);
+    types::wipe(&mut rejection_key);
+    Ok(shared.into_boxed_slice())
+}
+
+/// Encrypts the secret with randomness derived from the secret and the public key
+fn encrypt_derandomized(secret: &[u8],
+                        public: &PublicKey,
+                        params: &EncParams)
+                        -> Result<Box<[u8]>, Error> {
+    let coins = sha256(&[b"ntru-cca coins", secret, &public.export(params)]);
+    let rand_ctx = ::rand::init_det(&RNG_CTR_DRBG, &coins)?;
+    ::encrypt(secret, public, params, &rand_ctx)
+}
+
+/// SHA-256 of the concatenation of the inputs
+pub(crate) fn sha256(inputs: &[&[u8]]) -> [u8; 32] {
+    let input = inputs.concat();
+    let mut digest = [0u8; 32];
+    unsafe { ffi::ntru_sha256(&input[0], input.len() as u16, &mut digest[0]) };
+    digest
+}
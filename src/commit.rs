@@ -0,0 +1,95 @@
+//! Key-committing encryption
+//!
+//! This module binds encrypted messages to the public key they were encrypted for. A commitment,
+//! the SHA-256 hash of the OID of the parameter set, the public key and the encrypted message, is
+//! appended to the encrypted message. When decrypting, the commitment is checked against the
+//! public key of the key pair first, so a message tried with another key is rejected with
+//! `Error::KeyMismatch`, instead of going through the NTRU decryption. The commitment only depends
+//! on public data, so the right key can also be found with `is_for_key()`, without any private
+//! key.
+//!
+//! The envelope is the encrypted message followed by the commitment, so it is `params.enc_len() +
+//! COMMITMENT_LEN` bytes long. Since anyone can check the commitment against a public key, the
+//! envelope reveals which key it was encrypted for.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::DEFAULT_PARAMS_128_BITS;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&DEFAULT_PARAMS_128_BITS, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ntru::commit::encrypt(msg, kp.get_public(), &DEFAULT_PARAMS_128_BITS,
+//!                                       &rand_ctx).unwrap();
+//! assert!(ntru::commit::is_for_key(&encrypted, kp.get_public(), &DEFAULT_PARAMS_128_BITS));
+//! let decrypted = ntru::commit::decrypt(&encrypted, &kp, &DEFAULT_PARAMS_128_BITS).unwrap();
+//!
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use types::{KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use cca::sha256;
+use ct;
+
+/// Length of the commitment to the public key
+pub const COMMITMENT_LEN: usize = 32;
+
+/// Encrypts a message and commits to the public key
+///
+/// The message can be at most `params.max_msg_len()` bytes long, otherwise an
+/// `Error::MessageTooLong` will be returned.
+pub fn encrypt(msg: &[u8],
+               public: &PublicKey,
+               params: &EncParams,
+               rand_ctx: &RandContext)
+               -> Result<Box<[u8]>, Error> {
+    let mut envelope = ::encrypt(msg, public, params, rand_ctx)?.into_vec();
+    let commitment = commitment(&envelope, public, params);
+    envelope.extend_from_slice(&commitment);
+    Ok(envelope.into_boxed_slice())
+}
+
+/// Checks the commitment and decrypts a message
+///
+/// The envelope must be exactly `params.enc_len() + COMMITMENT_LEN` bytes long, otherwise an
+/// `Error::InvalidCiphertextLength` will be returned. If it was not encrypted for the public key
+/// of the key pair, an `Error::KeyMismatch` will be returned and the message will not be
+/// decrypted.
+pub fn decrypt(envelope: &[u8], kp: &KeyPair, params: &EncParams) -> Result<Box<[u8]>, Error> {
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 {
+        return Err(Error::InvalidParam);
+    }
+    if envelope.len() != enc_len + COMMITMENT_LEN {
+        return Err(Error::InvalidCiphertextLength {
+            expected: enc_len + COMMITMENT_LEN,
+            actual: envelope.len(),
+        });
+    }
+    if !is_for_key(envelope, kp.get_public(), params) {
+        return Err(Error::KeyMismatch);
+    }
+
+    ::decrypt(&envelope[..enc_len], kp, params)
+}
+
+/// Checks whether an envelope was encrypted for the public key
+///
+/// Envelopes that do not have the length of the parameter set are never for the key.
+pub fn is_for_key(envelope: &[u8], public: &PublicKey, params: &EncParams) -> bool {
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 || envelope.len() != enc_len + COMMITMENT_LEN {
+        return false;
+    }
+    let (encrypted, commitment) = envelope.split_at(enc_len);
+    bool::from(ct::bytes_eq(&self::commitment(encrypted, public, params), commitment))
+}
+
+/// Computes the commitment of an encrypted message to the public key
+fn commitment(encrypted: &[u8], public: &PublicKey, params: &EncParams) -> [u8; COMMITMENT_LEN] {
+    sha256(&[b"ntru-rs key commitment", &params.get_oid(), &public.export(params), encrypted])
+}
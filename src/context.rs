@@ -0,0 +1,109 @@
+//! Contexts bundling a parameter set and a random number generator
+//!
+//! Most functions of this crate take the parameter set and the random number generator as
+//! arguments. An `NtruContext` owns both, so they are given once, and all the key pairs and
+//! encrypted messages of the context use the same parameter set. Keys of other parameter sets are
+//! rejected with `Error::InvalidKey`, instead of failing later, or being used with the wrong
+//! parameters.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::context::NtruContext;
+//! use ntru::encparams::DEFAULT_PARAMS_128_BITS;
+//!
+//! let ctx = NtruContext::with_default_rng(&DEFAULT_PARAMS_128_BITS).unwrap();
+//! let kp = ctx.keygen().unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ctx.encrypt(msg, kp.get_public()).unwrap();
+//! let decrypted = ctx.decrypt(&encrypted, &kp).unwrap();
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use std::fmt;
+use types::{KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::{self, RandContext, RNG_DEFAULT};
+
+/// A parameter set and a random number generator
+pub struct NtruContext {
+    /// The parameter set of all the operations
+    params: EncParams,
+    /// The random number generator of the key generation and the encryption
+    rand_ctx: RandContext,
+}
+
+impl NtruContext {
+    /// Creates a context, that takes over the random number generator
+    ///
+    /// Returns `Error::InvalidParam` if the parameters are not valid, and
+    /// `Error::DeprecatedParamSet` if the parameter policy refuses them.
+    pub fn new(params: &EncParams, rand_ctx: RandContext) -> Result<NtruContext, Error> {
+        ::check_policy(params)?;
+        if params.enc_len() == 0 {
+            return Err(Error::InvalidParam);
+        }
+        Ok(NtruContext {
+            params: params.clone(),
+            rand_ctx,
+        })
+    }
+
+    /// Creates a context with the default random number generator, `RNG_DEFAULT`
+    ///
+    /// Returns the same errors as `new()`, and `Error::Prng` if the random number generator cannot
+    /// be initialized.
+    pub fn with_default_rng(params: &EncParams) -> Result<NtruContext, Error> {
+        NtruContext::new(params, rand::init(&RNG_DEFAULT)?)
+    }
+
+    /// Get the parameter set
+    pub fn get_params(&self) -> &EncParams {
+        &self.params
+    }
+
+    /// Get the random number generator
+    pub fn get_rand_ctx(&self) -> &RandContext {
+        &self.rand_ctx
+    }
+
+    /// Generates a key pair, as `ntru::generate_key_pair()`
+    pub fn keygen(&self) -> Result<KeyPair, Error> {
+        ::generate_key_pair(&self.params, &self.rand_ctx)
+    }
+
+    /// Encrypts a message, as `ntru::encrypt()`
+    ///
+    /// Returns `Error::InvalidKey` if the public key is not a key of the parameter set.
+    pub fn encrypt(&self, msg: &[u8], public: &PublicKey) -> Result<Box<[u8]>, Error> {
+        self.check_key(public)?;
+        ::encrypt(msg, public, &self.params, &self.rand_ctx)
+    }
+
+    /// Decrypts a message, as `ntru::decrypt()`
+    ///
+    /// Returns `Error::InvalidKey` if the key pair is not a key pair of the parameter set.
+    pub fn decrypt(&self, enc: &[u8], kp: &KeyPair) -> Result<Box<[u8]>, Error> {
+        self.check_key(kp.get_public())?;
+        if kp.get_private().get_q() != self.params.get_q() {
+            return Err(Error::InvalidKey);
+        }
+        ::decrypt(enc, kp, &self.params)
+    }
+
+    /// Checks that the public key has the `N` and `q` of the parameter set
+    fn check_key(&self, public: &PublicKey) -> Result<(), Error> {
+        if public.get_q() == self.params.get_q() &&
+           public.get_h().get_coeffs().len() == self.params.get_n() as usize {
+            Ok(())
+        } else {
+            Err(Error::InvalidKey)
+        }
+    }
+}
+
+impl fmt::Debug for NtruContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NtruContext").field("params", &self.params).finish()
+    }
+}
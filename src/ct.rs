@@ -0,0 +1,140 @@
+//! Constant-time helpers
+//!
+//! This module wraps the `subtle` crate for the comparisons and selections that must not depend
+//! on secret data: comparing tags and re-encrypted ciphertexts, checking for all-zero keys and
+//! padding, checking the weights of the decrypted message representatives, and choosing between a
+//! key and its rejection value, or between two polynomials. The results of
+//! the comparisons are `Choice`s, that can be combined without branching, and only converted to
+//! `bool` once the secret-dependent work is done. The lengths of the slices and the degrees of
+//! the polynomials, and the numbers of ones of the ternary polynomials, are treated as public, and
+//! the selections return an error if they do not match.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::ct;
+//!
+//! let tag = [0x2a; 16];
+//! let valid = ct::bytes_eq(&tag, &[0x2a; 16]) & !ct::is_zero(&tag);
+//! assert!(bool::from(valid));
+//!
+//! let mut key = [1u8; 4];
+//! ct::conditional_copy(&mut key, &[2u8; 4], !valid).unwrap();
+//! assert_eq!(key, [1u8; 4]);
+//! ```
+pub use subtle::{Choice, ConstantTimeEq, ConstantTimeGreater, ConditionallySelectable};
+use types::{IntPoly, TernPoly, ProdPoly, PrivPoly, Error};
+
+/// Compares two byte slices in constant time
+///
+/// Slices of different lengths are never equal.
+pub fn bytes_eq(a: &[u8], b: &[u8]) -> Choice {
+    a.ct_eq(b)
+}
+
+/// Checks in constant time that all the bytes are zero
+pub fn is_zero(arr: &[u8]) -> Choice {
+    arr.iter().fold(0u8, |acc, b| acc | b).ct_eq(&0)
+}
+
+/// Checks in constant time that there are at least `dm0` coefficients equal to each of 0, 1 and 2
+///
+/// This is the check of the number of each coefficient of the message representative modulo 3
+/// that NTRU decryption does.
+pub fn min_rep_weight(coeffs: &[i16], dm0: u16) -> Choice {
+    let mut counts = [0u16; 3];
+    for c in coeffs {
+        for (v, count) in counts.iter_mut().enumerate() {
+            *count += c.ct_eq(&(v as i16)).unwrap_u8() as u16;
+        }
+    }
+    counts.iter().fold(Choice::from(1), |ok, count| ok & !dm0.ct_gt(count))
+}
+
+/// Copies `src` into `dst` if `choice` is set, in constant time
+///
+/// Returns `Error::InvalidParam` if the slices do not have the same length.
+pub fn conditional_copy(dst: &mut [u8], src: &[u8], choice: Choice) -> Result<(), Error> {
+    if dst.len() != src.len() {
+        return Err(Error::InvalidParam);
+    }
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        d.conditional_assign(s, choice);
+    }
+    Ok(())
+}
+
+/// Selects `a` if `choice` is not set and `b` if it is, in constant time
+///
+/// Returns `Error::InvalidParam` if the slices do not have the same length.
+pub fn select_bytes(a: &[u8], b: &[u8], choice: Choice) -> Result<Vec<u8>, Error> {
+    let mut selected = a.to_vec();
+    conditional_copy(&mut selected, b, choice)?;
+    Ok(selected)
+}
+
+/// Selects the polynomial `a` if `choice` is not set and `b` if it is, in constant time
+///
+/// Returns `Error::InvalidDegree` if the polynomials do not have the same number of coefficients.
+pub fn select_poly(a: &IntPoly, b: &IntPoly, choice: Choice) -> Result<IntPoly, Error> {
+    let mut selected = a.clone();
+    conditional_copy_poly(&mut selected, b, choice)?;
+    Ok(selected)
+}
+
+/// Copies the coefficients of `src` into `dst` if `choice` is set, in constant time
+///
+/// Returns `Error::InvalidDegree` if the polynomials do not have the same number of coefficients.
+pub fn conditional_copy_poly(dst: &mut IntPoly,
+                             src: &IntPoly,
+                             choice: Choice)
+                             -> Result<(), Error> {
+    if dst.get_coeffs().len() != src.get_coeffs().len() {
+        return Err(Error::InvalidDegree);
+    }
+    for (i, s) in src.get_coeffs().iter().enumerate() {
+        let c = i16::conditional_select(&dst.get_coeffs()[i], s, choice);
+        dst.set_coeff(i, c);
+    }
+    Ok(())
+}
+
+/// Selects the ternary polynomial `a` if `choice` is not set and `b` if it is, in constant time
+///
+/// Returns `Error::InvalidDegree` if the polynomials do not have the same number of coefficients,
+/// and `Error::InvalidWeight` if they do not have the same numbers of ones and negative ones.
+pub fn select_tern_poly(a: &TernPoly, b: &TernPoly, choice: Choice) -> Result<TernPoly, Error> {
+    if a.get_n() != b.get_n() {
+        return Err(Error::InvalidDegree);
+    }
+    if a.get_ones().len() != b.get_ones().len() ||
+       a.get_neg_ones().len() != b.get_neg_ones().len() {
+        return Err(Error::InvalidWeight);
+    }
+    let select = |x: &[u16], y: &[u16]| -> Vec<u16> {
+        x.iter().zip(y.iter()).map(|(x, y)| u16::conditional_select(x, y, choice)).collect()
+    };
+    Ok(TernPoly::new(a.get_n(),
+                     &select(a.get_ones(), b.get_ones()),
+                     &select(a.get_neg_ones(), b.get_neg_ones())))
+}
+
+/// Selects the private polynomial `a` if `choice` is not set and `b` if it is, in constant time
+///
+/// Returns `Error::InvalidWeight` if they are not both ternary or both product-form polynomials,
+/// or the error of `select_tern_poly()` if their ternary polynomials cannot be selected.
+pub fn select_priv_poly(a: &PrivPoly, b: &PrivPoly, choice: Choice) -> Result<PrivPoly, Error> {
+    if a.is_product() != b.is_product() {
+        return Err(Error::InvalidWeight);
+    }
+    if a.is_product() {
+        let (a, b) = (a.get_poly_prod(), b.get_poly_prod());
+        let f1 = select_tern_poly(a.get_f1(), b.get_f1(), choice)?;
+        let f2 = select_tern_poly(a.get_f2(), b.get_f2(), choice)?;
+        let f3 = select_tern_poly(a.get_f3(), b.get_f3(), choice)?;
+        PrivPoly::from_prod_poly(ProdPoly::new(f1.get_n(), f1, f2, f3))
+    } else {
+        let t = select_tern_poly(a.get_poly_tern(), b.get_poly_tern(), choice)?;
+        Ok(PrivPoly::new_with_tern_poly(t))
+    }
+}
@@ -0,0 +1,90 @@
+//! Hardware RNGs of microcontrollers
+//!
+//! Microcontrollers have no operating system RNG, such as `/dev/urandom`, but many of them have a
+//! true random number generator peripheral. This module adapts the RNG traits that their drivers
+//! implement into `RandomSource`s, so a `RandContext` for key generation and encryption can be
+//! created from the peripheral:
+//!
+//! * `HalRng` adapts the `embedded_hal::blocking::rng::Read` trait of `embedded-hal` 0.2.
+//! * `RngCoreRng` adapts the `rand_core::RngCore` trait, that must also be a `CryptoRng`.
+//!
+//! The module is only available with the `embedded-rng` feature.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use ntru::embedded_rng;
+//! use ntru::encparams::DEFAULT_PARAMS_128_BITS;
+//!
+//! // `trng` is the driver of the TRNG peripheral of the board.
+//! let rand_ctx = embedded_rng::init_hal(trng).unwrap();
+//! let kp = ntru::generate_key_pair(&DEFAULT_PARAMS_128_BITS, &rand_ctx).unwrap();
+//! ```
+use embedded_hal::blocking::rng::Read;
+use rand_core::{CryptoRng, RngCore};
+use rand::{self, RandContext, RandomSource};
+use types::Error;
+
+/// A `RandomSource` reading from an `embedded-hal` RNG
+#[derive(Debug)]
+pub struct HalRng<R: Read> {
+    /// The RNG driver
+    rng: R,
+}
+
+impl<R: Read> HalRng<R> {
+    /// Creates the source, that takes over the RNG driver
+    pub fn new(rng: R) -> HalRng<R> {
+        HalRng { rng }
+    }
+
+    /// Gets the RNG driver back
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+impl<R: Read> RandomSource for HalRng<R> {
+    fn fill(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.rng.read(buf).map_err(|_| Error::Prng)
+    }
+}
+
+/// A `RandomSource` reading from a `rand_core` cryptographically secure RNG
+#[derive(Debug)]
+pub struct RngCoreRng<R: RngCore + CryptoRng> {
+    /// The RNG
+    rng: R,
+}
+
+impl<R: RngCore + CryptoRng> RngCoreRng<R> {
+    /// Creates the source, that takes over the RNG
+    pub fn new(rng: R) -> RngCoreRng<R> {
+        RngCoreRng { rng }
+    }
+
+    /// Gets the RNG back
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+impl<R: RngCore + CryptoRng> RandomSource for RngCoreRng<R> {
+    fn fill(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(buf).map_err(|_| Error::Prng)
+    }
+}
+
+/// Initializes a rand context that takes all its random data from an `embedded-hal` RNG
+///
+/// If the RNG fails, the operations using the context return `Error::Prng`.
+pub fn init_hal<R: Read + 'static>(rng: R) -> Result<RandContext, Error> {
+    rand::init_source(HalRng::new(rng))
+}
+
+/// Initializes a rand context that takes all its random data from a `rand_core` RNG
+///
+/// If the RNG fails, the operations using the context return `Error::Prng`.
+pub fn init_rng_core<R: RngCore + CryptoRng + 'static>(rng: R) -> Result<RandContext, Error> {
+    rand::init_source(RngCoreRng::new(rng))
+}
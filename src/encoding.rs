@@ -0,0 +1,368 @@
+//! Text encodings
+//!
+//! Hexadecimal and base64 encodings of byte strings, used to convert keys to and from text, and
+//! the Bech32m and Base58Check encodings, that add a checksum to detect transcription errors. PEM
+//! wraps DER structures in base64 between labeled lines.
+use types::Error;
+use cca::sha256;
+
+/// Digits of the hexadecimal encoding
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+/// Alphabet of the standard base64 encoding
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// Alphabet of the URL-safe base64 encoding
+#[cfg(feature = "jose")]
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+/// Padding character of the base64 encoding
+const BASE64_PAD: u8 = b'=';
+/// Alphabet of the Bech32m encoding
+const BECH32_ALPHABET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Separator between the human-readable part and the data of the Bech32m encoding
+const BECH32_SEPARATOR: char = '1';
+/// Constant of the Bech32m checksum
+const BECH32M_CONST: u32 = 0x2bc830a3;
+/// Length of the Bech32m checksum, in characters
+const BECH32_CHECKSUM_LEN: usize = 6;
+/// Alphabet of the Base58 encoding
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// Length of the Base58Check checksum, in bytes
+const BASE58_CHECKSUM_LEN: usize = 4;
+/// Characters of base64 in each line of PEM
+const PEM_LINE_LEN: usize = 64;
+
+/// Encodes the bytes in lowercase hexadecimal
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 * bytes.len());
+    for b in bytes {
+        s.push(HEX_DIGITS[(b >> 4) as usize] as char);
+        s.push(HEX_DIGITS[(b & 0xF) as usize] as char);
+    }
+    s
+}
+
+/// Decodes hexadecimal, in lowercase or uppercase
+///
+/// Returns `Error::InvalidEncoding` if the string is not valid hexadecimal.
+pub(crate) fn from_hex(s: &str) -> Result<Vec<u8>, Error> {
+    let s = s.as_bytes();
+    if !s.len().is_multiple_of(2) {
+        return Err(Error::InvalidEncoding);
+    }
+    s.chunks(2)
+        .map(|pair| Ok(hex_value(pair[0])? << 4 | hex_value(pair[1])?))
+        .collect()
+}
+
+/// Gets the value of a hexadecimal digit
+fn hex_value(c: u8) -> Result<u8, Error> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Encodes the bytes in standard base64, with padding
+pub(crate) fn to_base64(bytes: &[u8]) -> String {
+    encode_base64(bytes, BASE64_ALPHABET, true)
+}
+
+/// Decodes standard base64, with padding
+///
+/// Returns `Error::InvalidEncoding` if the string is not valid base64.
+pub(crate) fn from_base64(s: &str) -> Result<Vec<u8>, Error> {
+    decode_base64(s.as_bytes(), BASE64_ALPHABET)
+}
+
+/// Encodes the bytes in URL-safe base64, without padding, as used by JOSE
+#[cfg(feature = "jose")]
+pub(crate) fn to_base64url(bytes: &[u8]) -> String {
+    encode_base64(bytes, BASE64URL_ALPHABET, false)
+}
+
+/// Decodes URL-safe base64, without padding
+///
+/// Returns `Error::InvalidEncoding` if the string is not valid URL-safe base64.
+#[cfg(feature = "jose")]
+pub(crate) fn from_base64url(s: &str) -> Result<Vec<u8>, Error> {
+    if s.len() % 4 == 1 || s.bytes().any(|c| c == BASE64_PAD) {
+        return Err(Error::InvalidEncoding);
+    }
+    let mut padded = s.as_bytes().to_vec();
+    while !padded.len().is_multiple_of(4) {
+        padded.push(BASE64_PAD);
+    }
+    decode_base64(&padded, BASE64URL_ALPHABET)
+}
+
+/// Encodes the DER structure in PEM, with the given label
+pub(crate) fn to_pem(label: &str, der: &[u8]) -> String {
+    let base64 = to_base64(der);
+    let mut s = format!("-----BEGIN {}-----\n", label);
+    for line in base64.as_bytes().chunks(PEM_LINE_LEN) {
+        s.push_str(&String::from_utf8_lossy(line));
+        s.push('\n');
+    }
+    s.push_str(&format!("-----END {}-----\n", label));
+    s
+}
+
+/// Decodes PEM, and returns its label and the DER structure
+///
+/// Whitespace around the lines is ignored. Returns `Error::InvalidEncoding` if the string is not
+/// valid PEM, with the same label at the beginning and the end.
+pub(crate) fn from_pem(s: &str) -> Result<(String, Vec<u8>), Error> {
+    let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+    let label = match lines.next() {
+        Some(line) if line.starts_with("-----BEGIN ") && line.ends_with("-----") &&
+                      line.len() >= 16 => &line[11..line.len() - 5],
+        _ => return Err(Error::InvalidEncoding),
+    };
+    let end = format!("-----END {}-----", label);
+    let mut base64 = String::new();
+    loop {
+        match lines.next() {
+            Some(line) if line == end => break,
+            Some(line) => base64.push_str(line),
+            None => return Err(Error::InvalidEncoding),
+        }
+    }
+    if lines.next().is_some() {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok((label.to_owned(), from_base64(&base64)?))
+}
+
+/// Encodes the bytes in base64 with the given alphabet, with or without padding
+fn encode_base64(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0],
+                 if chunk.len() > 1 { chunk[1] } else { 0 },
+                 if chunk.len() > 2 { chunk[2] } else { 0 }];
+        let sextets = [b[0] >> 2,
+                       (b[0] & 0x3) << 4 | b[1] >> 4,
+                       (b[1] & 0xF) << 2 | b[2] >> 6,
+                       b[2] & 0x3F];
+        for (i, sextet) in sextets.iter().enumerate() {
+            if i <= chunk.len() {
+                s.push(alphabet[*sextet as usize] as char);
+            } else if pad {
+                s.push(BASE64_PAD as char);
+            }
+        }
+    }
+    s
+}
+
+/// Decodes padded base64 with the given alphabet
+fn decode_base64(s: &[u8], alphabet: &[u8; 64]) -> Result<Vec<u8>, Error> {
+    if !s.len().is_multiple_of(4) {
+        return Err(Error::InvalidEncoding);
+    }
+    let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+    for (i, quad) in s.chunks(4).enumerate() {
+        let last = i == s.len() / 4 - 1;
+        let pad = quad.iter().rev().take_while(|&&c| c == BASE64_PAD).count();
+        if pad > 2 || (pad > 0 && !last) {
+            return Err(Error::InvalidEncoding);
+        }
+        let mut sextets = [0u8; 4];
+        for (sextet, &c) in sextets.iter_mut().zip(quad[..4 - pad].iter()) {
+            *sextet = match alphabet.iter().position(|&a| a == c) {
+                Some(v) => v as u8,
+                None => return Err(Error::InvalidEncoding),
+            };
+        }
+        let b = [sextets[0] << 2 | sextets[1] >> 4,
+                 sextets[1] << 4 | sextets[2] >> 2,
+                 sextets[2] << 6 | sextets[3]];
+        // The unused bits of the last sextet must be 0
+        if (pad == 1 && sextets[2] & 0x3 != 0) || (pad == 2 && sextets[1] & 0xF != 0) {
+            return Err(Error::InvalidEncoding);
+        }
+        bytes.extend_from_slice(&b[..3 - pad]);
+    }
+    Ok(bytes)
+}
+
+/// Encodes the bytes in Bech32m, with the given human-readable part
+///
+/// The human-readable part must be lowercase. Unlike BIP-350, the length of the result is not
+/// limited to 90 characters. The checksum detects any error in up to 4 characters only for
+/// strings of up to 89 characters, for longer ones it still detects almost all errors.
+pub(crate) fn to_bech32(hrp: &str, bytes: &[u8]) -> String {
+    // Converting with padding does not fail
+    let mut data = convert_bits(bytes, 8, 5, true).unwrap_or_default();
+    let checksum = bech32_polymod(hrp, &data, &[0; BECH32_CHECKSUM_LEN]) ^ BECH32M_CONST;
+    for i in 0..BECH32_CHECKSUM_LEN {
+        data.push((checksum >> (5 * (BECH32_CHECKSUM_LEN - 1 - i)) & 0x1F) as u8);
+    }
+
+    let mut s = String::with_capacity(hrp.len() + 1 + data.len());
+    s.push_str(hrp);
+    s.push(BECH32_SEPARATOR);
+    for d in data {
+        s.push(BECH32_ALPHABET[d as usize] as char);
+    }
+    s
+}
+
+/// Decodes Bech32m with the given human-readable part
+///
+/// The string can be lowercase or uppercase, but not mixed. Returns `Error::InvalidEncoding` if
+/// the string is not valid Bech32m, the checksum does not match, or the human-readable part is
+/// not the given one.
+pub(crate) fn from_bech32(hrp: &str, s: &str) -> Result<Vec<u8>, Error> {
+    if s.bytes().any(|c| c.is_ascii_lowercase()) && s.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Err(Error::InvalidEncoding);
+    }
+    let s = s.to_ascii_lowercase();
+    let sep = match s.rfind(BECH32_SEPARATOR) {
+        Some(sep) => sep,
+        None => return Err(Error::InvalidEncoding),
+    };
+    if &s[..sep] != hrp || s.len() - sep - 1 < BECH32_CHECKSUM_LEN {
+        return Err(Error::InvalidEncoding);
+    }
+
+    let data = s[sep + 1..]
+        .bytes()
+        .map(|c| match BECH32_ALPHABET.iter().position(|&a| a == c) {
+            Some(d) => Ok(d as u8),
+            None => Err(Error::InvalidEncoding),
+        })
+        .collect::<Result<Vec<u8>, Error>>()?;
+    let (data, checksum) = data.split_at(data.len() - BECH32_CHECKSUM_LEN);
+    if bech32_polymod(hrp, data, checksum) != BECH32M_CONST {
+        return Err(Error::InvalidEncoding);
+    }
+    convert_bits(data, 5, 8, false)
+}
+
+/// Computes the BCH checksum polynomial of Bech32 over the expanded human-readable part, the data
+/// and the checksum
+fn bech32_polymod(hrp: &str, data: &[u8], checksum: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    let expanded_hrp = hrp.bytes()
+        .map(|c| c >> 5)
+        .chain(Some(0))
+        .chain(hrp.bytes().map(|c| c & 0x1F));
+    expanded_hrp.chain(data.iter().cloned()).chain(checksum.iter().cloned()).fold(1, |chk, v| {
+        let top = chk >> 25;
+        let chk = (chk & 0x1FFFFFF) << 5 ^ v as u32;
+        GENERATOR.iter()
+            .enumerate()
+            .filter(|&(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+/// Regroups the bits of the values from `from` bits to `to` bits each
+///
+/// If `pad` is true, the last value is padded with zeros, otherwise the leftover bits must be
+/// fewer than `from` and zero, or an `Error::InvalidEncoding` is returned.
+fn convert_bits(values: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Error> {
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let max = (1u32 << to) - 1;
+    let mut result = Vec::with_capacity(values.len() * from as usize / to as usize + 1);
+    for &v in values {
+        acc = acc << from | v as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push((acc >> bits & max) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if pad {
+        if bits > 0 {
+            result.push((acc << (to - bits) & max) as u8);
+        }
+    } else if bits >= from || acc != 0 {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok(result)
+}
+
+/// Encodes the bytes in Base58Check
+///
+/// The bytes are followed by the first 4 bytes of their double SHA-256, and encoded in Base58
+/// with the alphabet of Bitcoin.
+pub(crate) fn to_base58check(bytes: &[u8]) -> String {
+    let mut payload = bytes.to_vec();
+    payload.extend_from_slice(&base58_checksum(bytes));
+
+    // Each leading zero byte is encoded as a leading '1'
+    let zeros = payload.iter().take_while(|&&b| b == 0).count();
+    let mut digits: Vec<u8> = Vec::with_capacity(payload.len() * 138 / 100 + 1);
+    for &b in &payload[zeros..] {
+        let mut carry = b as u32;
+        for d in digits.iter_mut() {
+            carry += (*d as u32) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut s = String::with_capacity(zeros + digits.len());
+    for _ in 0..zeros {
+        s.push(BASE58_ALPHABET[0] as char);
+    }
+    for &d in digits.iter().rev() {
+        s.push(BASE58_ALPHABET[d as usize] as char);
+    }
+    s
+}
+
+/// Decodes Base58Check
+///
+/// Returns `Error::InvalidEncoding` if the string is not valid Base58 or the checksum does not
+/// match.
+pub(crate) fn from_base58check(s: &str) -> Result<Vec<u8>, Error> {
+    let zeros = s.bytes().take_while(|&c| c == BASE58_ALPHABET[0]).count();
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 733 / 1000 + 1);
+    for c in s[zeros..].bytes() {
+        let mut carry = match BASE58_ALPHABET.iter().position(|&a| a == c) {
+            Some(d) => d as u32,
+            None => return Err(Error::InvalidEncoding),
+        };
+        for b in bytes.iter_mut() {
+            carry += *b as u32 * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(vec![0u8; zeros]);
+    bytes.reverse();
+
+    if bytes.len() < BASE58_CHECKSUM_LEN {
+        return Err(Error::InvalidEncoding);
+    }
+    let (payload, checksum) = bytes.split_at(bytes.len() - BASE58_CHECKSUM_LEN);
+    if checksum != base58_checksum(payload) {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok(payload.to_vec())
+}
+
+/// Computes the Base58Check checksum, the first bytes of the double SHA-256
+fn base58_checksum(bytes: &[u8]) -> [u8; BASE58_CHECKSUM_LEN] {
+    let hash = sha256(&[&sha256(&[bytes])]);
+    let mut checksum = [0u8; BASE58_CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash[..BASE58_CHECKSUM_LEN]);
+    checksum
+}
@@ -10,11 +10,161 @@
 //! * `DEFAULT_PARAMS_192_BITS` for 192 bits of security.
 //! * `DEFAULT_PARAMS_256_BITS` for 256 bits of security.
 //!
+//! Parameter sets optimized for key size or for speed can be selected by security level with
+//! `EncParams::select()`.
+//!
 use libc::{c_char, uint16_t, uint8_t};
 use std::fmt;
+use std::f64::consts::{E, PI};
+use std::hash::{Hash, Hasher};
+use std::slice;
+#[cfg(feature = "config")]
+use serde_json;
+#[cfg(feature = "config")]
+use toml;
 use super::ffi;
+use hash;
+use std::sync::RwLock;
+use types::{Error, MAX_DEGREE, MAX_ONES};
+
+/// A hash algorithm of the IGF and the MGF of a parameter set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u8)]
+pub enum HashAlgorithm {
+    /// SHA-1
+    Sha1,
+    /// SHA-256
+    Sha256,
+    /// SHA3-256, only available with the `extra-hashes` feature
+    #[cfg(feature = "extra-hashes")]
+    Sha3_256,
+    /// BLAKE2b with a 256 bit output, only available with the `extra-hashes` feature
+    #[cfg(feature = "extra-hashes")]
+    Blake2b256,
+    /// A hash function implemented in Rust, set with `EncParams::with_hash_function()`
+    Custom,
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HashAlgorithm::Sha1 => write!(f, "SHA-1"),
+            HashAlgorithm::Sha256 => write!(f, "SHA-256"),
+            #[cfg(feature = "extra-hashes")]
+            HashAlgorithm::Sha3_256 => write!(f, "SHA3-256"),
+            #[cfg(feature = "extra-hashes")]
+            HashAlgorithm::Blake2b256 => write!(f, "BLAKE2b-256"),
+            HashAlgorithm::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+/// Whether the crate was built with the `avoid-hamming-wt-patent` feature
+///
+/// libntru is then compiled with `NTRU_AVOID_HAMMING_WT_PATENT`: private keys are always sampled
+/// as ternary polynomials, and the product-form parameter sets cannot be used.
+pub const AVOID_HAMMING_WT_PATENT: bool = cfg!(feature = "avoid-hamming-wt-patent");
+
+/// How the private keys of a parameter set are sampled, from `EncParams::sampler()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sampler {
+    /// A ternary polynomial with `df` ones and `df` negative ones
+    Ternary,
+    /// A product-form polynomial `f1*f2+f3` of ternary polynomials
+    ProductForm,
+}
+
+/// A security level, to select a parameter set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecurityLevel {
+    /// 112 bits of security
+    Bits112,
+    /// 128 bits of security
+    Bits128,
+    /// 192 bits of security
+    Bits192,
+    /// 256 bits of security
+    Bits256,
+}
+
+/// What a parameter set is optimized for, to select a parameter set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Small keys and encrypted messages, such as `EES401EP1`
+    KeySize,
+    /// A tradeoff between key size and encryption/decryption speed, such as `EES541EP1`. These are
+    /// the default parameter sets.
+    Balanced,
+    /// Fast encryption and decryption, such as `EES659EP1`
+    Speed,
+}
+
+/// A coarse estimate of the security of a parameter set, from `EncParams::estimate_security()`
+///
+/// It is an estimate only, from a simple cost model, and not a security guarantee.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct SecurityEstimate {
+    /// The BKZ block size needed to recover the private key
+    pub block_size: u16,
+    /// The estimated cost of the attack on a classical computer, in bits
+    pub classical_bits: f64,
+    /// The estimated cost of the attack on a quantum computer, in bits
+    pub quantum_bits: f64,
+}
+
+/// A hash function of the IGF and the MGF implemented in Rust
+///
+/// It is the counterpart of the `Digest` trait of the RustCrypto crates for libntru, that calls
+/// its hash functions without any state, with the whole input at once, so the functions of the
+/// trait take no `self`. A parameter set uses it with `EncParams::with_hash_function()`, through
+/// an adapter with the signature of the hash functions of libntru. Since libntru calls it, a
+/// panic in `digest()` aborts the process.
+///
+/// # Examples
+///
+/// ```
+/// extern crate ntru;
+/// extern crate sha2;
+///
+/// use sha2::{Digest, Sha512};
+/// use ntru::encparams::{HashFunction, EES401EP1};
+///
+/// struct Sha512Hash;
+///
+/// impl HashFunction for Sha512Hash {
+///     const OUTPUT_LEN: usize = 64;
+///
+///     fn digest(input: &[u8], output: &mut [u8]) {
+///         output.copy_from_slice(&Sha512::digest(input));
+///     }
+/// }
+///
+/// # fn main() {
+/// let params = EES401EP1.with_hash_function::<Sha512Hash>("EES401H512", [255, 3, 0]).unwrap();
+/// assert_eq!(params.get_hlen(), 64);
+/// # }
+/// ```
+pub trait HashFunction {
+    /// The length of the output in bytes, from 20 to 64
+    const OUTPUT_LEN: usize;
+
+    /// Hashes the input, and writes the `OUTPUT_LEN` bytes of the output
+    fn digest(input: &[u8], output: &mut [u8]);
+}
+
+/// A hash function of the IGF and the MGF, as libntru calls it
+type HashFn = unsafe extern "C" fn(input: *const uint8_t,
+                                   input_len: uint16_t,
+                                   digest: *mut uint8_t);
+/// A hash function of several inputs of the IGF and the MGF, as libntru calls it
+type MultiHashFn = unsafe extern "C" fn(input: *const *const uint8_t,
+                                        input_len: uint16_t,
+                                        digest: *mut *mut uint8_t);
 
 /// A set of parameters for NTRU encryption
+#[derive(Clone)]
 #[repr(C)]
 pub struct EncParams {
     /// Name of the parameter set
@@ -49,21 +199,18 @@ pub struct EncParams {
     /// Three bytes that uniquely identify the parameter set
     oid: [uint8_t; 3],
     /// Hash function, e.g. ntru_sha256
-    hash: unsafe extern "C" fn(input: *const uint8_t,
-                                   input_len: uint16_t,
-                                   digest: *mut uint8_t),
+    hash: HashFn,
     /// Hash function for 4 inputs, e.g. ntru_sha256_4way
-    hash_4way: unsafe extern "C" fn(input: *const *const uint8_t,
-                                        input_len: uint16_t,
-                                        digest: *mut *mut uint8_t),
+    hash_4way: MultiHashFn,
     /// Hash function for 8 inputs, e.g. ntru_sha256_8way
-    hash_8way: unsafe extern "C" fn(input: *const *const uint8_t,
-                                        input_len: uint16_t,
-                                        digest: *mut *mut uint8_t),
+    hash_8way: MultiHashFn,
     /// output length of the hash function
     hlen: uint16_t,
     /// number of bits of the public key to hash
     pklen: uint16_t,
+    /// Hash algorithm of `hash`, `hash_4way` and `hash_8way`, not read by libntru, that only
+    /// knows the fields above
+    hash_algorithm: HashAlgorithm,
 }
 
 impl Default for EncParams {
@@ -89,6 +236,7 @@ impl Default for EncParams {
             hash_8way: ffi::ntru_sha1_8way,
             hlen: 0,
             pklen: 0,
+            hash_algorithm: HashAlgorithm::Sha1,
         }
     }
 }
@@ -98,30 +246,68 @@ impl PartialEq for EncParams {
         self.name == other.name && self.n == other.n && self.q == other.q &&
         self.prod_flag == other.prod_flag && self.df1 == other.df1 &&
         (self.prod_flag == 0 || (self.df2 == other.df2 && self.df3 == other.df3)) &&
-        self.dm0 == other.dm0 && self.db == other.db && self.c == other.c &&
-        self.min_calls_r == other.min_calls_r &&
-        self.min_calls_mask == other.min_calls_mask &&
-        self.hash_seed == other.hash_seed && self.oid == other.oid &&
-        {
-            let input = [0u8; 100];
-            let mut hash1 = [0u8; 256];
-            let mut hash2 = [0u8; 256];
-            unsafe { (self.hash)(&input[0], 100, &mut hash1[0]) };
-            unsafe { (other.hash)(&input[0], 100, &mut hash2[0]) };
-
-            for (i, b) in hash1.iter().enumerate() {
-                if *b != hash2[i] {
-                    return false;
-                }
-            }
-            true
-        } && self.hlen == other.hlen && self.pklen == other.pklen
+        self.dg == other.dg && self.dm0 == other.dm0 && self.db == other.db &&
+        self.c == other.c && self.min_calls_r == other.min_calls_r &&
+        self.min_calls_mask == other.min_calls_mask && self.hash_seed == other.hash_seed &&
+        self.oid == other.oid && self.hash_algorithm == other.hash_algorithm &&
+        self.hlen == other.hlen && self.pklen == other.pklen
+    }
+}
+
+impl Eq for EncParams {}
+
+impl Hash for EncParams {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.n.hash(state);
+        self.q.hash(state);
+        self.prod_flag.hash(state);
+        self.df1.hash(state);
+        // Like in the equality, f2 and f3 only matter for product-form private keys
+        if self.prod_flag == 1 {
+            self.df2.hash(state);
+            self.df3.hash(state);
+        }
+        self.dg.hash(state);
+        self.dm0.hash(state);
+        self.db.hash(state);
+        self.c.hash(state);
+        self.min_calls_r.hash(state);
+        self.min_calls_mask.hash(state);
+        self.hash_seed.hash(state);
+        self.oid.hash(state);
+        self.hash_algorithm.hash(state);
+        self.hlen.hash(state);
+        self.pklen.hash(state);
     }
 }
 
 
 impl fmt::Debug for EncParams {
+    /// Prints the name of the parameter set, or the full parameter table with `{:#?}`
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return f.debug_struct("EncParams")
+                .field("name", &self.get_name().trim_end_matches('\0'))
+                .field("n", &self.n)
+                .field("q", &self.q)
+                .field("prod_flag", &self.prod_flag)
+                .field("df1", &self.df1)
+                .field("df2", &self.df2)
+                .field("df3", &self.df3)
+                .field("dg", &self.dg)
+                .field("dm0", &self.dm0)
+                .field("db", &self.db)
+                .field("c", &self.c)
+                .field("min_calls_r", &self.min_calls_r)
+                .field("min_calls_mask", &self.min_calls_mask)
+                .field("hash_seed", &self.hash_seed)
+                .field("oid", &self.oid)
+                .field("hash", &self.get_hash_algorithm())
+                .field("hlen", &self.hlen)
+                .field("pklen", &self.pklen)
+                .finish();
+        }
         let mut name = String::with_capacity(10);
         for c in &self.name {
             name.push(*c as u8 as char);
@@ -130,7 +316,72 @@ impl fmt::Debug for EncParams {
     }
 }
 
+impl fmt::Display for EncParams {
+    /// Prints the name, the main parameters, the hash function and the security level, such as
+    /// `EES401EP1 (n = 401, q = 2048, df = 113, dg = 133, dm0 = 113, SHA-1, 112 bits)`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (n = {}, q = {}, ", self.get_name().trim_end_matches('\0'), self.n, self.q)?;
+        if self.prod_flag == 1 {
+            write!(f, "df1 = {}, df2 = {}, df3 = {}", self.df1, self.df2, self.df3)?;
+        } else {
+            write!(f, "df = {}", self.df1)?;
+        }
+        write!(f,
+               ", dg = {}, dm0 = {}, {}, {} bits)",
+               self.dg,
+               self.dm0,
+               self.get_hash_algorithm(),
+               self.security_bits())
+    }
+}
+
 impl EncParams {
+    /// Selects the IEEE 1363.1 parameter set of the security level and the profile
+    ///
+    /// The product-form parameter sets, such as `EES401EP2`, are not selected, and can be used
+    /// directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::encparams::{EncParams, SecurityLevel, Profile, EES1171EP1, EES677EP1};
+    ///
+    /// assert!(EncParams::select(SecurityLevel::Bits256, Profile::Balanced) == &EES1171EP1);
+    /// assert!(EncParams::select(SecurityLevel::Bits192, Profile::KeySize) == &EES677EP1);
+    /// ```
+    pub fn select(level: SecurityLevel, profile: Profile) -> &'static EncParams {
+        // The IEEE 1363.1 parameter sets are the first ones, by profile and then by level
+        &PARAM_SETS[profile as usize * 4 + level as usize]
+    }
+
+    /// Iterates over all the built-in parameter sets
+    pub fn all() -> ParamSets {
+        ParamSets::new(&PARAM_SETS)
+    }
+
+    /// Iterates over the recommended parameter sets, the default ones, by security level
+    pub fn recommended() -> ParamSets {
+        ParamSets::new(&RECOMMENDED_PARAM_SETS)
+    }
+
+    /// Whether the parameter set is deprecated
+    ///
+    /// `EES439EP1` and `EES593EP1` are deprecated, in favor of `EES443EP1` and `EES587EP1`.
+    pub fn is_deprecated(&self) -> bool {
+        *self == EES439EP1 || *self == EES593EP1
+    }
+
+    /// Whether the parameter policy allows generating keys and encrypting with the parameter set
+    ///
+    /// Always `true`, unless the `deny-deprecated-params` feature is enabled: then the deprecated
+    /// parameter sets, and the ones with less than 128 bits of security, are refused with
+    /// `Error::DeprecatedParamSet`. Decrypting is always allowed, to be able to read existing
+    /// messages.
+    pub fn is_allowed(&self) -> bool {
+        !cfg!(feature = "deny-deprecated-params") ||
+        (!self.is_deprecated() && self.security_bits() >= 128)
+    }
+
     /// Get the name of the parameter set
     pub fn get_name(&self) -> String {
         let slice: [u8; 11] = [self.name[0] as u8,
@@ -162,41 +413,425 @@ impl EncParams {
         self.db
     }
 
+    /// Get the security level in bits
+    ///
+    /// As in IEEE 1363.1, it is the number of random bits prepended to the message.
+    pub const fn security_bits(&self) -> u16 {
+        self.db
+    }
+
+    /// Get the number of bytes that an encrypted message takes beyond the message
+    ///
+    /// The encrypted messages always have `enc_len()` bytes, so this is the overhead of a message
+    /// of `max_msg_len()` bytes, and shorter messages have a larger overhead.
+    pub const fn ciphertext_overhead(&self) -> u16 {
+        self.enc_len() - self.max_msg_len() as u16
+    }
+
+    /// Get the ratio between the length of the encrypted message and the length of the message
+    ///
+    /// Returns `None` if the message is empty or longer than `max_msg_len()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::encparams::EES401EP1;
+    ///
+    /// assert_eq!(EES401EP1.enc_len(), 552);
+    /// assert_eq!(EES401EP1.expansion_ratio(46), Some(12.0));
+    /// assert_eq!(EES401EP1.expansion_ratio(0), None);
+    /// ```
+    pub fn expansion_ratio(&self, msg_len: usize) -> Option<f64> {
+        if msg_len == 0 || msg_len > self.max_msg_len() as usize {
+            None
+        } else {
+            Some(self.enc_len() as f64 / msg_len as f64)
+        }
+    }
+
     /// Maximum message length
-    pub fn max_msg_len(&self) -> u8 {
+    pub const fn max_msg_len(&self) -> u8 {
         (self.n / 2 * 3 / 8 - 1 - self.db / 8) as u8
     }
 
     /// Encryption length
-    pub fn enc_len(&self) -> u16 {
+    ///
+    /// Like the other lengths, it can be used in constant contexts, such as array lengths:
+    /// `[u8; EES613EP1.enc_len() as usize]`.
+    pub const fn enc_len(&self) -> u16 {
         if self.q & (self.q - 1) != 0 {
             0
         } else {
             let len_bits = self.n * EncParams::log2(self.q) as u16;
-            (len_bits + 7) / 8
+            len_bits.div_ceil(8)
         }
     }
 
     /// Public key length
-    pub fn public_len(&self) -> u16 {
+    pub const fn public_len(&self) -> u16 {
         4 + self.enc_len()
     }
 
     /// Private key length
-    pub fn private_len(&self) -> u16 {
+    pub const fn private_len(&self) -> u16 {
         let bits_per_idx = EncParams::log2(self.n - 1) as u16 + 1;
         if self.prod_flag == 1 {
-            let poly1_len = 4 + (bits_per_idx * 2 * self.df1 + 7) / 8;
-            let poly2_len = 4 + (bits_per_idx * 2 * self.df2 + 7) / 8;
-            let poly3_len = 4 + (bits_per_idx * 2 * self.df3 + 7) / 8;
+            let poly1_len = 4 + (bits_per_idx * 2 * self.df1).div_ceil(8);
+            let poly2_len = 4 + (bits_per_idx * 2 * self.df2).div_ceil(8);
+            let poly3_len = 4 + (bits_per_idx * 2 * self.df3).div_ceil(8);
 
             5 + poly1_len + poly2_len + poly3_len
         } else {
-            5 + 4 + (bits_per_idx * 2 * self.df1 + 7) / 8
+            5 + 4 + (bits_per_idx * 2 * self.df1).div_ceil(8)
         }
     }
 
-    fn log2(n: u16) -> u8 {
+    /// Get the object identifier of the parameter set
+    pub fn get_oid(&self) -> [u8; 3] {
+        self.oid
+    }
+
+    /// Whether private keys are product-form polynomials
+    pub fn is_product(&self) -> bool {
+        self.prod_flag == 1
+    }
+
+    /// Get the sampler of the private keys of the parameter set
+    ///
+    /// Returns `None` for product-form parameter sets with the `avoid-hamming-wt-patent` feature,
+    /// as they cannot be used then. See `AVOID_HAMMING_WT_PATENT`.
+    pub fn sampler(&self) -> Option<Sampler> {
+        if !self.is_product() {
+            Some(Sampler::Ternary)
+        } else if AVOID_HAMMING_WT_PATENT {
+            None
+        } else {
+            Some(Sampler::ProductForm)
+        }
+    }
+
+    /// Get the number of ones of f, or of f1 for product-form private keys
+    pub fn get_df1(&self) -> u16 {
+        self.df1
+    }
+
+    /// Get the number of ones of f2 for product-form private keys
+    pub fn get_df2(&self) -> u16 {
+        self.df2
+    }
+
+    /// Get the number of ones of f3 for product-form private keys
+    pub fn get_df3(&self) -> u16 {
+        self.df3
+    }
+
+    /// Get the number of ones of g
+    pub fn get_dg(&self) -> u16 {
+        self.dg
+    }
+
+    /// Get the minimum number of -1's, 0's and 1's of the polynomial m' in the last encryption
+    /// step
+    pub fn get_dm0(&self) -> u16 {
+        self.dm0
+    }
+
+    /// Get the IGF parameter `c`, the number of bits of each candidate index
+    pub fn get_c(&self) -> u16 {
+        self.c
+    }
+
+    /// Get the minimum number of hash calls for the IGF
+    pub fn get_min_calls_r(&self) -> u16 {
+        self.min_calls_r
+    }
+
+    /// Get the minimum number of hash calls for the MGF
+    pub fn get_min_calls_mask(&self) -> u16 {
+        self.min_calls_mask
+    }
+
+    /// Whether the MGF hashes its seed first
+    pub fn hashes_seed(&self) -> bool {
+        self.hash_seed == 1
+    }
+
+    /// Get the hash algorithm of the IGF and the MGF
+    ///
+    /// The hash functions of `with_hash_function()` are `HashAlgorithm::Custom`.
+    pub fn get_hash_algorithm(&self) -> HashAlgorithm {
+        self.hash_algorithm
+    }
+
+    /// Get the output length of the hash function
+    pub fn get_hlen(&self) -> u16 {
+        self.hlen
+    }
+
+    /// Get the number of bits of the public key to hash
+    pub fn get_pklen(&self) -> u16 {
+        self.pklen
+    }
+
+    /// Derives a parameter set that uses another hash algorithm in the IGF and the MGF
+    ///
+    /// The other parameters are kept. The derived parameter set is not compatible with the
+    /// original one, so it gets the given name and OID, and can then be registered with
+    /// `register()`. Returns `Error::InvalidParam` if the name is empty, longer than 10 bytes or
+    /// not printable ASCII, or if the parameters are not safe, with the same checks as
+    /// `register()`, or if the hash algorithm is `HashAlgorithm::Custom`, that is set with
+    /// `with_hash_function()` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::encparams::{HashAlgorithm, EES401EP1};
+    ///
+    /// let params = EES401EP1.with_hash_algorithm(HashAlgorithm::Sha256, "EES401H256", [255, 2, 0])
+    ///     .unwrap();
+    /// assert_eq!(params.get_hash_algorithm(), HashAlgorithm::Sha256);
+    /// assert_eq!(params.get_n(), EES401EP1.get_n());
+    /// ```
+    pub fn with_hash_algorithm(&self,
+                               hash: HashAlgorithm,
+                               name: &str,
+                               oid: [u8; 3])
+                               -> Result<EncParams, Error> {
+        let mut params = self.clone();
+        params.set_name(name)?;
+        params.oid = oid;
+        params.set_hash_algorithm(hash)?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Derives a parameter set that uses a hash function implemented in Rust in the IGF and the MGF
+    ///
+    /// Works like `with_hash_algorithm()`, with the hash function `H`, and its hash algorithm is
+    /// `HashAlgorithm::Custom`. Parameter sets with custom hash functions are compared by their
+    /// name, OID and output length, not by their hash functions. Also returns
+    /// `Error::InvalidParam` if the output length of `H` is not between 20 and 64 bytes.
+    pub fn with_hash_function<H: HashFunction>(&self,
+                                               name: &str,
+                                               oid: [u8; 3])
+                                               -> Result<EncParams, Error> {
+        if H::OUTPUT_LEN < 20 || H::OUTPUT_LEN > 64 {
+            return Err(Error::InvalidParam);
+        }
+        let mut params = self.clone();
+        params.set_name(name)?;
+        params.oid = oid;
+        params.hash = hash::rust_hash::<H>;
+        params.hash_4way = hash::rust_hash_4way::<H>;
+        params.hash_8way = hash::rust_hash_8way::<H>;
+        params.hlen = H::OUTPUT_LEN as u16;
+        params.hash_algorithm = HashAlgorithm::Custom;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Estimates the probability that the decryption of a message fails
+    ///
+    /// The decryption fails if a coefficient of `3*r*g + f*m'`, with `f = 1+3t`, is not in
+    /// `[-q/2, q/2)`. As usual in the NTRU literature, the coefficients are taken to be normally
+    /// distributed, a coefficient of the product of two random polynomials `a` and `b` having the
+    /// variance `|a|^2 |b|^2 / N`, and `m'` having `2N/3` non-zero coefficients. The probability
+    /// of each coefficient is multiplied by `N`, as a union bound, and limited to 1.
+    ///
+    /// It is only an estimate, meant to catch custom parameter sets with unacceptable failure
+    /// rates: the built-in parameter sets are all below `2^-100`. Probabilities below about
+    /// `2^-1074` are rounded to 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::encparams::EES401EP1;
+    ///
+    /// assert!(EES401EP1.decryption_failure_rate_estimate() < 2f64.powi(-112));
+    /// ```
+    pub fn decryption_failure_rate_estimate(&self) -> f64 {
+        let n = self.n as f64;
+        // The squared norms of t, of r, that has the same weights, of g and of m'
+        let t_norm2 = self.t_norm2();
+        let g_norm2 = (2 * self.dg) as f64 - 1.0;
+        let m_norm2 = 2.0 * n / 3.0;
+
+        let variance = (9.0 * (t_norm2 * g_norm2 + t_norm2 * m_norm2) + m_norm2) / n;
+        let bound = self.q as f64 / 2.0;
+        (n * erfc(bound / (2.0 * variance).sqrt())).min(1.0)
+    }
+
+    /// Estimates the security of the parameter set against lattice reduction
+    ///
+    /// This is a coarse heuristic, meant as a sanity check when tweaking `n`, `q` and the weights
+    /// of custom parameter sets, not a replacement for a proper analysis. It finds the smallest
+    /// BKZ block size `b` that recovers the private key `(f, g)` from the NTRU lattice, of
+    /// dimension `d = 2N` and volume `q^N`, with the primal attack of the "2016 estimate":
+    /// `sigma * sqrt(b) <= delta^(2b - d) * sqrt(q)`, where `sigma` is the standard deviation of
+    /// the coefficients of `(f, g)` and `delta` the root Hermite factor of BKZ with block size
+    /// `b`. The cost of the attack is the cost of one call to the SVP oracle of BKZ, as in the
+    /// "core-SVP" model: `2^(0.292b)` classically, and `2^(0.265b)` on a quantum computer.
+    ///
+    /// Hybrid and meet-in-the-middle attacks, that do better against sparse private keys, are not
+    /// taken into account, so the estimates of the product-form parameter sets and of the ones
+    /// with few ones may be too high.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::encparams::EES401EP1;
+    ///
+    /// let estimate = EES401EP1.estimate_security();
+    /// assert!(estimate.classical_bits > estimate.quantum_bits);
+    /// ```
+    pub fn estimate_security(&self) -> SecurityEstimate {
+        let d = 2 * self.n;
+        // f = 1+3t, and h can be divided by 3 so that the lattice has (f, g)
+        let norm2 = 1.0 + 9.0 * self.t_norm2() + (2 * self.dg) as f64 - 1.0;
+        let sigma = (norm2 / d as f64).sqrt();
+        let sqrt_q = (self.q as f64).sqrt();
+
+        let block_size = (50..d)
+            .find(|&b| {
+                let b = b as f64;
+                let delta = ((PI * b).powf(1.0 / b) * b / (2.0 * PI * E))
+                    .powf(1.0 / (2.0 * (b - 1.0)));
+                sigma * b.sqrt() <= delta.powf(2.0 * b - d as f64) * sqrt_q
+            })
+            .unwrap_or(d);
+        SecurityEstimate {
+            block_size,
+            classical_bits: 0.292 * block_size as f64,
+            quantum_bits: 0.265 * block_size as f64,
+        }
+    }
+
+    /// The squared norm of the private polynomial `t`
+    fn t_norm2(&self) -> f64 {
+        if self.prod_flag == 1 {
+            4.0 * self.df1 as f64 * self.df2 as f64 + 2.0 * self.df3 as f64
+        } else {
+            2.0 * self.df1 as f64
+        }
+    }
+
+    /// Sets the name, that must be printable ASCII of at most 10 bytes
+    fn set_name(&mut self, name: &str) -> Result<(), Error> {
+        if name.is_empty() || name.len() > 10 || !name.bytes().all(|b| b.is_ascii_graphic()) {
+            return Err(Error::InvalidParam);
+        }
+        self.name = [0; 11];
+        for (c, b) in self.name.iter_mut().zip(name.bytes()) {
+            *c = b as c_char;
+        }
+        Ok(())
+    }
+
+    /// Sets the hash functions and the output length of the hash algorithm
+    ///
+    /// Returns `Error::InvalidParam` for `HashAlgorithm::Custom`, that has no hash functions.
+    fn set_hash_algorithm(&mut self, algorithm: HashAlgorithm) -> Result<(), Error> {
+        let (hash, hash_4way, hash_8way, hlen): (HashFn, MultiHashFn, MultiHashFn, u16) =
+            match algorithm {
+                HashAlgorithm::Sha1 => {
+                    (ffi::ntru_sha1, ffi::ntru_sha1_4way, ffi::ntru_sha1_8way, 20)
+                }
+                HashAlgorithm::Sha256 => {
+                    (ffi::ntru_sha256, ffi::ntru_sha256_4way, ffi::ntru_sha256_8way, 32)
+                }
+                #[cfg(feature = "extra-hashes")]
+                HashAlgorithm::Sha3_256 => {
+                    (hash::ntru_sha3_256,
+                     hash::ntru_sha3_256_4way,
+                     hash::ntru_sha3_256_8way,
+                     hash::HLEN as u16)
+                }
+                #[cfg(feature = "extra-hashes")]
+                HashAlgorithm::Blake2b256 => {
+                    (hash::ntru_blake2b_256,
+                     hash::ntru_blake2b_256_4way,
+                     hash::ntru_blake2b_256_8way,
+                     hash::HLEN as u16)
+                }
+                HashAlgorithm::Custom => return Err(Error::InvalidParam),
+            };
+        self.hash = hash;
+        self.hash_4way = hash_4way;
+        self.hash_8way = hash_8way;
+        self.hlen = hlen;
+        self.hash_algorithm = algorithm;
+        Ok(())
+    }
+
+    /// Checks that the parameters are consistent and safe
+    fn validate(&self) -> Result<(), Error> {
+        let n = self.n as u32;
+        let weights_valid = if self.prod_flag == 1 {
+            [self.df1, self.df2, self.df3].iter().all(|&df| df > 0 && df as usize <= MAX_ONES)
+        } else {
+            self.df1 > 0 && self.df1 as usize <= MAX_ONES && 2 * self.df1 as u32 <= n
+        };
+        // The message, the random bits and the public key hash must fit in the message polynomial
+        let msg_len = (n / 2 * 3 / 8) as i32 - 1 - self.db as i32 / 8;
+        let valid = n >= 401 && (n as usize) < MAX_DEGREE && is_prime(n) &&
+                    self.q.is_power_of_two() && self.q >= 256 && self.q <= 1 << 15 &&
+                    weights_valid && self.dg > 0 && 2 * self.dg as u32 <= n &&
+                    self.dm0 as u32 <= n / 3 && self.db >= 112 && self.db.is_multiple_of(8) &&
+                    msg_len > 0 && msg_len <= u8::MAX as i32 && self.c <= 16 &&
+                    1 << self.c >= n && self.min_calls_r > 0 &&
+                    self.min_calls_mask > 0 && self.pklen > 0 &&
+                    self.pklen as u32 <= 8 * self.enc_len() as u32;
+        if !valid {
+            return Err(Error::InvalidParam);
+        }
+        match from_oid(self.oid) {
+            Some(known) if known != self => Err(Error::InvalidParam),
+            _ => Ok(()),
+        }
+    }
+
+    /// Hashes the input with the hash function of the parameter set
+    pub(crate) fn hash(&self, input: &[u8]) -> Vec<u8> {
+        let mut digest = [0u8; 64];
+        unsafe { (self.hash)(input.as_ptr(), input.len() as u16, &mut digest[0]) };
+        digest[..self.hlen as usize].to_vec()
+    }
+
+    /// Hashes each input with the hash function of the parameter set
+    ///
+    /// Each 8 or 4 consecutive inputs of the same length are hashed at once with the 8-way or
+    /// 4-way hash function, and the rest one by one.
+    pub(crate) fn hash_many(&self, inputs: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        // Whether the first `ways` inputs have the same length
+        let same_len = |inputs: &[Vec<u8>], ways: usize| {
+            inputs.len() >= ways &&
+            inputs[..ways].iter().all(|input| input.len() == inputs[0].len())
+        };
+        let mut digests = Vec::with_capacity(inputs.len());
+        let mut rest = inputs;
+        while !rest.is_empty() {
+            let (hash_ways, ways): (MultiHashFn, usize) = if same_len(rest, 8) {
+                (self.hash_8way, 8)
+            } else if same_len(rest, 4) {
+                (self.hash_4way, 4)
+            } else {
+                digests.push(self.hash(&rest[0]));
+                rest = &rest[1..];
+                continue;
+            };
+
+            let (chunk, tail) = rest.split_at(ways);
+            let input: Vec<*const uint8_t> = chunk.iter().map(|input| input.as_ptr()).collect();
+            let mut out = vec![[0u8; 64]; ways];
+            let mut digest: Vec<*mut uint8_t> = out.iter_mut().map(|d| d.as_mut_ptr()).collect();
+            unsafe { hash_ways(input.as_ptr(), chunk[0].len() as u16, digest.as_mut_ptr()) };
+            digests.extend(out.iter().map(|d| d[..self.hlen as usize].to_vec()));
+            rest = tail;
+        }
+        digests
+    }
+
+    const fn log2(n: u16) -> u8 {
         let mut n = n;
         let mut log = 0;
         while n > 1 {
@@ -229,6 +864,7 @@ pub const EES401EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 114,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// An IEEE 1361.1 parameter set that gives 128 bits of security and is optimized for key size.
@@ -253,6 +889,7 @@ pub const EES449EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 128,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// An IEEE 1361.1 parameter set that gives 192 bits of security and is optimized for key size.
@@ -277,6 +914,7 @@ pub const EES677EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 192,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// An IEEE 1361.1 parameter set that gives 256 bits of security and is optimized for key size.
@@ -301,6 +939,7 @@ pub const EES1087EP2: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 256,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// An IEEE 1361.1 parameter set that gives 112 bits of security and is a tradeoff between key size
@@ -326,6 +965,7 @@ pub const EES541EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 112,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// An IEEE 1361.1 parameter set that gives 128 bits of security and is a tradeoff between key
@@ -351,6 +991,7 @@ pub const EES613EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 128,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// An IEEE 1361.1 parameter set that gives 192 bits of security and is a tradeoff between key size
@@ -376,6 +1017,7 @@ pub const EES887EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 192,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// An IEEE 1361.1 parameter set that gives 256 bits of security and is a tradeoff between key size
@@ -401,6 +1043,7 @@ pub const EES1171EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 256,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// An IEEE 1361.1 parameter set that gives 112 bits of security and is optimized for
@@ -426,6 +1069,7 @@ pub const EES659EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 112,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// An IEEE 1361.1 parameter set that gives 128 bits of security and is optimized for
@@ -451,6 +1095,7 @@ pub const EES761EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 128,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// An IEEE 1361.1 parameter set that gives 192 bits of security and is optimized for
@@ -476,6 +1121,7 @@ pub const EES1087EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 192,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// An IEEE 1361.1 parameter set that gives 256 bits of security and is optimized for
@@ -501,6 +1147,7 @@ pub const EES1499EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 256,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// A product-form parameter set that gives 112 bits of security.
@@ -525,6 +1172,7 @@ pub const EES401EP2: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 112,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// **DEPRECATED** A product-form parameter set that gives 128 bits of security.
@@ -551,6 +1199,7 @@ pub const EES439EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha1_8way,
     hlen: 20,
     pklen: 128,
+    hash_algorithm: HashAlgorithm::Sha1,
 };
 
 /// A product-form parameter set that gives 128 bits of security.
@@ -575,6 +1224,7 @@ pub const EES443EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 128,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// **DEPRECATED** A product-form parameter set that gives 192 bits of security.
@@ -601,6 +1251,7 @@ pub const EES593EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 192,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// A product-form parameter set that gives 192 bits of security.
@@ -625,6 +1276,7 @@ pub const EES587EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 192,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// A product-form parameter set that gives 256 bits of security.
@@ -649,6 +1301,7 @@ pub const EES743EP1: EncParams = EncParams {
     hash_8way: ffi::ntru_sha256_8way,
     hlen: 32,
     pklen: 256,
+    hash_algorithm: HashAlgorithm::Sha256,
 };
 
 /// The default parameter set for 112 bits of security.
@@ -664,7 +1317,409 @@ pub const DEFAULT_PARAMS_192_BITS: EncParams = EES887EP1;
 pub const DEFAULT_PARAMS_256_BITS: EncParams = EES1171EP1;
 
 /// All parameter sets, in an array
+///
+/// `EncParams::all()` iterates over them by reference, and can filter them.
 pub const ALL_PARAM_SETS: [EncParams; 18] =
     [EES401EP1, EES449EP1, EES677EP1, EES1087EP2, EES541EP1, EES613EP1, EES887EP1, EES1171EP1,
      EES659EP1, EES761EP1, EES1087EP1, EES1499EP1, EES401EP2, EES439EP1, EES443EP1, EES593EP1,
      EES587EP1, EES743EP1];
+
+/// Length of the exported public keys of `EES401EP1`
+pub const EES401EP1_PUBLIC_LEN: usize = EES401EP1.public_len() as usize;
+/// Length of the exported private keys of `EES401EP1`
+pub const EES401EP1_PRIVATE_LEN: usize = EES401EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES449EP1`
+pub const EES449EP1_PUBLIC_LEN: usize = EES449EP1.public_len() as usize;
+/// Length of the exported private keys of `EES449EP1`
+pub const EES449EP1_PRIVATE_LEN: usize = EES449EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES677EP1`
+pub const EES677EP1_PUBLIC_LEN: usize = EES677EP1.public_len() as usize;
+/// Length of the exported private keys of `EES677EP1`
+pub const EES677EP1_PRIVATE_LEN: usize = EES677EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES1087EP2`
+pub const EES1087EP2_PUBLIC_LEN: usize = EES1087EP2.public_len() as usize;
+/// Length of the exported private keys of `EES1087EP2`
+pub const EES1087EP2_PRIVATE_LEN: usize = EES1087EP2.private_len() as usize;
+
+/// Length of the exported public keys of `EES541EP1`
+pub const EES541EP1_PUBLIC_LEN: usize = EES541EP1.public_len() as usize;
+/// Length of the exported private keys of `EES541EP1`
+pub const EES541EP1_PRIVATE_LEN: usize = EES541EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES613EP1`
+pub const EES613EP1_PUBLIC_LEN: usize = EES613EP1.public_len() as usize;
+/// Length of the exported private keys of `EES613EP1`
+pub const EES613EP1_PRIVATE_LEN: usize = EES613EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES887EP1`
+pub const EES887EP1_PUBLIC_LEN: usize = EES887EP1.public_len() as usize;
+/// Length of the exported private keys of `EES887EP1`
+pub const EES887EP1_PRIVATE_LEN: usize = EES887EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES1171EP1`
+pub const EES1171EP1_PUBLIC_LEN: usize = EES1171EP1.public_len() as usize;
+/// Length of the exported private keys of `EES1171EP1`
+pub const EES1171EP1_PRIVATE_LEN: usize = EES1171EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES659EP1`
+pub const EES659EP1_PUBLIC_LEN: usize = EES659EP1.public_len() as usize;
+/// Length of the exported private keys of `EES659EP1`
+pub const EES659EP1_PRIVATE_LEN: usize = EES659EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES761EP1`
+pub const EES761EP1_PUBLIC_LEN: usize = EES761EP1.public_len() as usize;
+/// Length of the exported private keys of `EES761EP1`
+pub const EES761EP1_PRIVATE_LEN: usize = EES761EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES1087EP1`
+pub const EES1087EP1_PUBLIC_LEN: usize = EES1087EP1.public_len() as usize;
+/// Length of the exported private keys of `EES1087EP1`
+pub const EES1087EP1_PRIVATE_LEN: usize = EES1087EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES1499EP1`
+pub const EES1499EP1_PUBLIC_LEN: usize = EES1499EP1.public_len() as usize;
+/// Length of the exported private keys of `EES1499EP1`
+pub const EES1499EP1_PRIVATE_LEN: usize = EES1499EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES401EP2`
+pub const EES401EP2_PUBLIC_LEN: usize = EES401EP2.public_len() as usize;
+/// Length of the exported private keys of `EES401EP2`
+pub const EES401EP2_PRIVATE_LEN: usize = EES401EP2.private_len() as usize;
+
+/// Length of the exported public keys of `EES439EP1`
+pub const EES439EP1_PUBLIC_LEN: usize = EES439EP1.public_len() as usize;
+/// Length of the exported private keys of `EES439EP1`
+pub const EES439EP1_PRIVATE_LEN: usize = EES439EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES443EP1`
+pub const EES443EP1_PUBLIC_LEN: usize = EES443EP1.public_len() as usize;
+/// Length of the exported private keys of `EES443EP1`
+pub const EES443EP1_PRIVATE_LEN: usize = EES443EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES593EP1`
+pub const EES593EP1_PUBLIC_LEN: usize = EES593EP1.public_len() as usize;
+/// Length of the exported private keys of `EES593EP1`
+pub const EES593EP1_PRIVATE_LEN: usize = EES593EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES587EP1`
+pub const EES587EP1_PUBLIC_LEN: usize = EES587EP1.public_len() as usize;
+/// Length of the exported private keys of `EES587EP1`
+pub const EES587EP1_PRIVATE_LEN: usize = EES587EP1.private_len() as usize;
+
+/// Length of the exported public keys of `EES743EP1`
+pub const EES743EP1_PUBLIC_LEN: usize = EES743EP1.public_len() as usize;
+/// Length of the exported private keys of `EES743EP1`
+pub const EES743EP1_PRIVATE_LEN: usize = EES743EP1.private_len() as usize;
+
+/// All parameter sets, to be able to borrow them
+pub(crate) static PARAM_SETS: [EncParams; 18] = ALL_PARAM_SETS;
+
+/// The recommended parameter sets
+static RECOMMENDED_PARAM_SETS: [EncParams; 4] =
+    [DEFAULT_PARAMS_112_BITS, DEFAULT_PARAMS_128_BITS, DEFAULT_PARAMS_192_BITS,
+     DEFAULT_PARAMS_256_BITS];
+
+/// An iterator over parameter sets, that can be filtered
+///
+/// It is returned by `EncParams::all()` and `EncParams::recommended()`.
+///
+/// # Examples
+///
+/// ```
+/// use ntru::encparams::EncParams;
+///
+/// let names: Vec<String> = EncParams::all()
+///     .product_form(true)
+///     .deprecated(false)
+///     .security_bits(128)
+///     .map(|params| params.get_name())
+///     .collect();
+/// assert_eq!(names, ["EES443EP1\0\0"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParamSets {
+    /// The remaining parameter sets
+    iter: slice::Iter<'static, EncParams>,
+    /// The security level in bits to keep, if any
+    security_bits: Option<u16>,
+    /// Whether to keep product-form or ternary parameter sets, if any
+    product: Option<bool>,
+    /// Whether to keep deprecated or not deprecated parameter sets, if any
+    deprecated: Option<bool>,
+}
+
+impl ParamSets {
+    /// Iterates over the parameter sets of the slice
+    fn new(params: &'static [EncParams]) -> ParamSets {
+        ParamSets {
+            iter: params.iter(),
+            security_bits: None,
+            product: None,
+            deprecated: None,
+        }
+    }
+
+    /// Keeps only the parameter sets with the given security level in bits
+    pub fn security_bits(mut self, bits: u16) -> ParamSets {
+        self.security_bits = Some(bits);
+        self
+    }
+
+    /// Keeps only the product-form parameter sets if `true`, or the other ones if `false`
+    pub fn product_form(mut self, product: bool) -> ParamSets {
+        self.product = Some(product);
+        self
+    }
+
+    /// Keeps only the deprecated parameter sets if `true`, or the other ones if `false`
+    pub fn deprecated(mut self, deprecated: bool) -> ParamSets {
+        self.deprecated = Some(deprecated);
+        self
+    }
+}
+
+impl Iterator for ParamSets {
+    type Item = &'static EncParams;
+
+    fn next(&mut self) -> Option<&'static EncParams> {
+        let (bits, product, deprecated) = (self.security_bits, self.product, self.deprecated);
+        self.iter.find(|params| {
+            bits.is_none_or(|bits| params.security_bits() == bits) &&
+            product.is_none_or(|product| params.is_product() == product) &&
+            deprecated.is_none_or(|deprecated| params.is_deprecated() == deprecated)
+        })
+    }
+}
+
+/// The parameter sets registered with `register()`
+static REGISTERED_PARAM_SETS: RwLock<Vec<&'static EncParams>> = RwLock::new(Vec::new());
+
+/// Registers a custom parameter set, such as one loaded with `EncParams::from_toml()`
+///
+/// Once registered, the parameter set is found by its OID and its name like the built-in ones, so
+/// keys, encrypted messages and containers of it can be imported and decoded, and
+/// `PrivateKey::get_params()` finds it. Registered parameter sets cannot be unregistered, and live
+/// until the end of the program. Registering a parameter set again returns the registered one.
+///
+/// Returns `Error::InvalidParam` if the parameters are not safe, with the same checks as
+/// `EncParams::from_json()`, or if a different parameter set has the same OID or name.
+pub fn register(params: EncParams) -> Result<&'static EncParams, Error> {
+    params.validate()?;
+    let name = params.get_name();
+    let mut registered = REGISTERED_PARAM_SETS.write().unwrap_or_else(|e| e.into_inner());
+    let existing = PARAM_SETS.iter()
+        .chain(registered.iter().cloned())
+        .find(|known| known.oid == params.oid || known.get_name() == name);
+    match existing {
+        Some(known) if *known == params => Ok(known),
+        Some(_) => Err(Error::InvalidParam),
+        None => {
+            let params: &'static EncParams = Box::leak(Box::new(params));
+            registered.push(params);
+            Ok(params)
+        }
+    }
+}
+
+/// Gets the parameter sets registered with `register()`, in registration order
+pub fn registered() -> Vec<&'static EncParams> {
+    REGISTERED_PARAM_SETS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Gets the built-in and the registered parameter sets
+pub(crate) fn known() -> Vec<&'static EncParams> {
+    PARAM_SETS.iter().chain(registered()).collect()
+}
+
+/// Finds the parameter set with the given name, such as `"EES401EP1"`
+#[cfg(any(feature = "jose", feature = "config"))]
+pub(crate) fn from_name(name: &str) -> Option<&'static EncParams> {
+    known().into_iter().find(|params| params.get_name().trim_end_matches('\0') == name)
+}
+
+/// Finds the parameter set with the given OID
+pub(crate) fn from_oid(oid: [u8; 3]) -> Option<&'static EncParams> {
+    known().into_iter().find(|params| params.oid == oid)
+}
+
+/// Keys of the parameter set configurations
+#[cfg(feature = "config")]
+const CONFIG_KEYS: [&str; 18] = ["base", "name", "n", "q", "prod_flag", "df1", "df2", "df3", "dg",
+                                 "dm0", "db", "c", "min_calls_r", "min_calls_mask", "hash_seed",
+                                 "oid", "hash", "pklen"];
+
+#[cfg(feature = "config")]
+impl EncParams {
+    /// Loads a parameter set from a TOML configuration
+    ///
+    /// The configuration has the keys of `from_json()`, such as `n = 613` or `hash = "SHA-256"`.
+    /// Returns the same errors as `from_json()`.
+    ///
+    /// Only available with the `config` feature.
+    pub fn from_toml(config: &str) -> Result<EncParams, Error> {
+        let table: toml::Table = config.parse().map_err(|_| Error::InvalidEncoding)?;
+        let config = serde_json::to_value(table).map_err(|_| Error::InvalidEncoding)?;
+        EncParams::from_config(&config)
+    }
+
+    /// Loads a parameter set from a JSON configuration
+    ///
+    /// The configuration is an object with the fields of the parameter set, as printed by
+    /// `{:#?}`: `name`, `n`, `q`, `prod_flag`, `df1`, `df2`, `df3`, `dg`, `dm0`, `db`, `c`,
+    /// `min_calls_r`, `min_calls_mask`, `hash_seed`, `oid` (an array of 3 bytes), `hash`
+    /// (`"SHA-1"` or `"SHA-256"`, or with the `extra-hashes` feature `"SHA3-256"` or
+    /// `"BLAKE2b-256"`) and `pklen`. `df2` and `df3` are only needed for product-form
+    /// parameter sets, and `pklen` defaults to `db`. With `base`, the name of a built-in parameter
+    /// set, the other keys are optional and override the fields of that parameter set.
+    ///
+    /// Returns `Error::InvalidEncoding` if the configuration cannot be parsed, has unknown keys,
+    /// or misses a key, `Error::UnknownParamSet` if the base parameter set is not known, and
+    /// `Error::InvalidParam` if the parameters are not safe: `n` must be a prime of at least 401,
+    /// `q` a power of 2, `db` (the security level in bits) at least 112, the weights must fit `n`,
+    /// and the OID must not be the one of a different built-in parameter set.
+    ///
+    /// Only available with the `config` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::encparams::{EncParams, EES613EP1};
+    ///
+    /// let params = EncParams::from_json(r#"{"base": "EES613EP1"}"#).unwrap();
+    /// assert!(params == EES613EP1);
+    ///
+    /// let params = EncParams::from_json(r#"{"base": "EES613EP1", "db": 96}"#);
+    /// assert_eq!(params, Err(ntru::types::Error::InvalidParam));
+    /// ```
+    pub fn from_json(config: &str) -> Result<EncParams, Error> {
+        let config = serde_json::from_str(config).map_err(|_| Error::InvalidEncoding)?;
+        EncParams::from_config(&config)
+    }
+
+    /// Loads a parameter set from a parsed configuration
+    fn from_config(config: &serde_json::Value) -> Result<EncParams, Error> {
+        let config = config.as_object().ok_or(Error::InvalidEncoding)?;
+        if config.keys().any(|key| !CONFIG_KEYS.contains(&key.as_str())) {
+            return Err(Error::InvalidEncoding);
+        }
+        let base = match config.get("base") {
+            Some(name) => {
+                let name = name.as_str().ok_or(Error::InvalidEncoding)?;
+                Some(from_name(name).ok_or(Error::UnknownParamSet)?)
+            }
+            None => None,
+        };
+        let mut params = match base {
+            Some(base) => base.clone(),
+            None => EncParams::default(),
+        };
+        // Without a base, the fields have no default
+        let default = |value| base.map(|_| value);
+
+        if let Some(name) = config.get("name") {
+            params.set_name(name.as_str().ok_or(Error::InvalidEncoding)?)?;
+        } else if base.is_none() {
+            return Err(Error::InvalidEncoding);
+        }
+        params.n = config_int(config, "n", default(params.n))?;
+        params.q = config_int(config, "q", default(params.q))?;
+        params.prod_flag = config_flag(config, "prod_flag", default(params.prod_flag as u16))?;
+        params.df1 = config_int(config, "df1", default(params.df1))?;
+        let product_default = if params.prod_flag == 1 { None } else { Some(0) };
+        params.df2 = config_int(config, "df2", default(params.df2).or(product_default))?;
+        params.df3 = config_int(config, "df3", default(params.df3).or(product_default))?;
+        params.dg = config_int(config, "dg", default(params.dg))?;
+        params.dm0 = config_int(config, "dm0", default(params.dm0))?;
+        params.db = config_int(config, "db", default(params.db))?;
+        params.c = config_int(config, "c", default(params.c))?;
+        params.min_calls_r = config_int(config, "min_calls_r", default(params.min_calls_r))?;
+        params.min_calls_mask = config_int(config,
+                                           "min_calls_mask",
+                                           default(params.min_calls_mask))?;
+        params.hash_seed = config_flag(config, "hash_seed", default(params.hash_seed as u16))?;
+        let pklen = default(params.pklen).unwrap_or(params.db);
+        params.pklen = config_int(config, "pklen", Some(pklen))?;
+
+        if let Some(oid) = config.get("oid") {
+            let oid = oid.as_array().ok_or(Error::InvalidEncoding)?;
+            if oid.len() != 3 {
+                return Err(Error::InvalidEncoding);
+            }
+            for (byte, value) in params.oid.iter_mut().zip(oid) {
+                *byte = value.as_u64()
+                    .filter(|&value| value <= u8::MAX as u64)
+                    .ok_or(Error::InvalidEncoding)? as u8;
+            }
+        } else if base.is_none() {
+            return Err(Error::InvalidEncoding);
+        }
+        let hash = match config.get("hash").map(|hash| hash.as_str()) {
+            Some(Some("SHA-1")) => Some(HashAlgorithm::Sha1),
+            Some(Some("SHA-256")) => Some(HashAlgorithm::Sha256),
+            #[cfg(feature = "extra-hashes")]
+            Some(Some("SHA3-256")) => Some(HashAlgorithm::Sha3_256),
+            #[cfg(feature = "extra-hashes")]
+            Some(Some("BLAKE2b-256")) => Some(HashAlgorithm::Blake2b256),
+            Some(_) => return Err(Error::InvalidEncoding),
+            None if base.is_none() => return Err(Error::InvalidEncoding),
+            None => None,
+        };
+        if let Some(hash) = hash {
+            params.set_hash_algorithm(hash)?;
+        }
+
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+/// Gets an integer field of a parameter set configuration, or its default value
+#[cfg(feature = "config")]
+fn config_int(config: &serde_json::Map<String, serde_json::Value>,
+              key: &str,
+              default: Option<u16>)
+              -> Result<u16, Error> {
+    match config.get(key) {
+        Some(value) => {
+            value.as_u64()
+                .filter(|&value| value <= u16::MAX as u64)
+                .map(|value| value as u16)
+                .ok_or(Error::InvalidEncoding)
+        }
+        None => default.ok_or(Error::InvalidEncoding),
+    }
+}
+
+/// Gets a field of a parameter set configuration that is 0 or 1, or its default value
+#[cfg(feature = "config")]
+fn config_flag(config: &serde_json::Map<String, serde_json::Value>,
+               key: &str,
+               default: Option<u16>)
+               -> Result<u8, Error> {
+    match config_int(config, key, default)? {
+        flag @ 0..=1 => Ok(flag as u8),
+        _ => Err(Error::InvalidParam),
+    }
+}
+
+/// Complementary error function
+///
+/// Uses the Chebyshev approximation of Numerical Recipes, with a relative error below `1.2e-7`
+/// everywhere, also in the tail.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [-1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806, 0.27886807,
+                -1.13520398, 1.48851587, -0.82215223, 0.17087277];
+    let sum = poly.iter().rev().fold(0.0, |acc, &c| acc * t + c);
+    let r = t * (-z * z + sum).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Whether the number is a prime
+fn is_prime(n: u32) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+}
@@ -0,0 +1,269 @@
+//! Multi-recipient envelopes
+//!
+//! This module defines a container format for messages of any length encrypted to one or more
+//! recipients, in the spirit of age. The message is encrypted with ChaCha20-Poly1305 under a
+//! random file key, and the file key is wrapped for each recipient with a shared key encapsulated
+//! with the CCA secure KEM of the `cca` module. Only available with the `envelope` feature.
+//!
+//! The encoded envelope has the following format, with integers in big endian:
+//!
+//! * The 4 byte `MAGIC`, the 1 byte `VERSION` and the 3 byte OID of the parameter set.
+//! * The number of recipients, in 2 bytes.
+//! * For each recipient, its key ID, the KEM ciphertext, `params.enc_len()` bytes long, and the
+//!   wrapped file key, followed by its `TAG_LEN` byte tag.
+//! * The encrypted message, followed by its `TAG_LEN` byte tag. Everything before it is
+//!   authenticated as associated data.
+//!
+//! All the recipients must use the same parameter set.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//! use ntru::envelope::Envelope;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let alice = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//! let bob = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let envelope = Envelope::seal(msg, &[alice.get_public(), bob.get_public()], &EES401EP1,
+//!                               &rand_ctx).unwrap();
+//! let encoded = envelope.to_bytes();
+//!
+//! let envelope = Envelope::from_bytes(&encoded).unwrap();
+//! assert_eq!(&envelope.open(&bob).unwrap()[..], &msg[..]);
+//! ```
+use chacha20::ChaCha20Legacy;
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use poly1305::Poly1305;
+use poly1305::universal_hash::KeyInit;
+use types::{self, KeyPair, PublicKey, Error};
+use encparams::{self, EncParams};
+use rand::RandContext;
+use keyring::{self, KeyId, KEY_ID_LEN};
+use cca;
+use ct;
+
+/// Magic bytes at the start of envelopes
+pub const MAGIC: [u8; 4] = *b"NTRE";
+/// Version of the envelope format
+pub const VERSION: u8 = 1;
+/// Length of the authentication tags
+pub const TAG_LEN: usize = 16;
+/// Length of the file key
+const FILE_KEY_LEN: usize = 32;
+/// Length of the header before the recipients
+const HEADER_LEN: usize = 10;
+/// Nonce of ChaCha20-Poly1305, since every key is used only once
+const NONCE: [u8; 8] = [0u8; 8];
+
+/// The file key wrapped for a recipient
+#[derive(Debug, Clone, PartialEq)]
+struct Recipient {
+    /// The key ID of the recipient
+    key_id: KeyId,
+    /// The KEM ciphertext
+    kem_ciphertext: Box<[u8]>,
+    /// The wrapped file key, followed by its tag
+    wrapped_key: [u8; FILE_KEY_LEN + TAG_LEN],
+}
+
+/// An encrypted message for one or more recipients
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    /// The parameter set of the recipients
+    params: &'static EncParams,
+    /// The recipients
+    recipients: Vec<Recipient>,
+    /// The encrypted message, followed by its tag
+    payload: Box<[u8]>,
+}
+
+impl Envelope {
+    /// Encrypts a message for the recipients
+    ///
+    /// All the public keys must be of the parameter set. If there are no recipients, or more than
+    /// 65535, an `Error::InvalidParam` will be returned.
+    pub fn seal(msg: &[u8],
+                recipients: &[&PublicKey],
+                params: &'static EncParams,
+                rand_ctx: &RandContext)
+                -> Result<Envelope, Error> {
+        if recipients.is_empty() || recipients.len() > u16::MAX as usize {
+            return Err(Error::InvalidParam);
+        }
+        let file_key = ::rand::generate(FILE_KEY_LEN as u16, rand_ctx)?;
+
+        let mut wrapped = Vec::with_capacity(recipients.len());
+        for public in recipients {
+            let (kem_ciphertext, shared) = cca::encapsulate(public, params, rand_ctx)?;
+            let mut wrapped_key = [0u8; FILE_KEY_LEN + TAG_LEN];
+            wrapped_key[..FILE_KEY_LEN].copy_from_slice(&file_key);
+            let tag = seal(&shared, &kem_ciphertext, &mut wrapped_key[..FILE_KEY_LEN])?;
+            wrapped_key[FILE_KEY_LEN..].copy_from_slice(&tag);
+            wrapped.push(Recipient {
+                key_id: keyring::key_id(public, params),
+                kem_ciphertext,
+                wrapped_key,
+            });
+        }
+
+        let mut envelope = Envelope {
+            params,
+            recipients: wrapped,
+            payload: Box::new([]),
+        };
+        let header = envelope.header();
+        let mut payload = msg.to_vec();
+        let tag = seal(&file_key, &header, &mut payload)?;
+        payload.extend_from_slice(&tag);
+        envelope.payload = payload.into_boxed_slice();
+        Ok(envelope)
+    }
+
+    /// Decrypts the message with the key pair of one of the recipients
+    ///
+    /// Returns `Error::UnknownKey` if the key pair is not one of the recipients, and
+    /// `Error::InvalidMac` if the envelope has been modified.
+    pub fn open(&self, kp: &KeyPair) -> Result<Box<[u8]>, Error> {
+        let key_id = keyring::key_id(kp.get_public(), self.params);
+        let recipient = match self.recipients.iter().find(|r| r.key_id == key_id) {
+            Some(recipient) => recipient,
+            None => return Err(Error::UnknownKey),
+        };
+
+        let shared = cca::decapsulate(&recipient.kem_ciphertext, kp, self.params)?;
+        let (key, tag) = recipient.wrapped_key.split_at(FILE_KEY_LEN);
+        let mut file_key = [0u8; FILE_KEY_LEN];
+        file_key.copy_from_slice(key);
+        if let Err(e) = open(&shared, &recipient.kem_ciphertext, &mut file_key, tag) {
+            types::wipe(&mut file_key);
+            return Err(e);
+        }
+
+        let (enc, tag) = self.payload.split_at(self.payload.len() - TAG_LEN);
+        let mut msg = enc.to_vec();
+        let opened = open(&file_key, &self.header(), &mut msg, tag);
+        types::wipe(&mut file_key);
+        opened?;
+        Ok(msg.into_boxed_slice())
+    }
+
+    /// Get the parameter set of the recipients
+    pub fn get_params(&self) -> &'static EncParams {
+        self.params
+    }
+
+    /// Get the key IDs of the recipients
+    pub fn get_recipients(&self) -> Vec<KeyId> {
+        self.recipients.iter().map(|r| r.key_id).collect()
+    }
+
+    /// Encodes the envelope
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoded = self.header();
+        encoded.extend_from_slice(&self.payload);
+        encoded
+    }
+
+    /// Decodes an envelope
+    ///
+    /// Returns `Error::InvalidEncoding` if it is not an envelope of a known version, or it is
+    /// truncated, and `Error::UnknownParamSet` if the parameter set is not known. The envelope is
+    /// only authenticated when opening it.
+    pub fn from_bytes(encoded: &[u8]) -> Result<Envelope, Error> {
+        if encoded.len() < HEADER_LEN || encoded[..4] != MAGIC || encoded[4] != VERSION {
+            return Err(Error::InvalidEncoding);
+        }
+        let params = encparams::from_oid([encoded[5], encoded[6], encoded[7]])
+            .ok_or(Error::UnknownParamSet)?;
+        let count = (encoded[8] as usize) << 8 | encoded[9] as usize;
+        let enc_len = params.enc_len() as usize;
+        let recipient_len = KEY_ID_LEN + enc_len + FILE_KEY_LEN + TAG_LEN;
+        if count == 0 || encoded.len() < HEADER_LEN + count * recipient_len + TAG_LEN {
+            return Err(Error::InvalidEncoding);
+        }
+
+        let (recipients, payload) = encoded[HEADER_LEN..].split_at(count * recipient_len);
+        let recipients = recipients.chunks(recipient_len)
+            .map(|recipient| {
+                let (key_id, rest) = recipient.split_at(KEY_ID_LEN);
+                let (kem_ciphertext, wrapped) = rest.split_at(enc_len);
+                let mut r = Recipient {
+                    key_id: [0u8; KEY_ID_LEN],
+                    kem_ciphertext: kem_ciphertext.to_vec().into_boxed_slice(),
+                    wrapped_key: [0u8; FILE_KEY_LEN + TAG_LEN],
+                };
+                r.key_id.copy_from_slice(key_id);
+                r.wrapped_key.copy_from_slice(wrapped);
+                r
+            })
+            .collect();
+        Ok(Envelope {
+            params,
+            recipients,
+            payload: payload.to_vec().into_boxed_slice(),
+        })
+    }
+
+    /// Encodes everything but the payload, that is authenticated with it
+    fn header(&self) -> Vec<u8> {
+        let oid = self.params.get_oid();
+        let count = self.recipients.len() as u16;
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&[VERSION, oid[0], oid[1], oid[2], (count >> 8) as u8,
+                                   count as u8]);
+        for recipient in &self.recipients {
+            header.extend_from_slice(&recipient.key_id);
+            header.extend_from_slice(&recipient.kem_ciphertext);
+            header.extend_from_slice(&recipient.wrapped_key);
+        }
+        header
+    }
+}
+
+/// Encrypts the data in place with ChaCha20-Poly1305 and returns the tag
+///
+/// This is the original ChaCha20-Poly1305 construction, with a 64 bit nonce and without padding
+/// in the authenticated data, that the version 1 format uses.
+fn seal(key: &[u8], aad: &[u8], data: &mut [u8]) -> Result<[u8; TAG_LEN], Error> {
+    let (mut cipher, mac) = aead_init(key)?;
+    cipher.apply_keystream(data);
+    Ok(aead_tag(mac, aad, data))
+}
+
+/// Verifies the tag and decrypts the data in place with ChaCha20-Poly1305
+///
+/// Returns `Error::InvalidMac` without decrypting if the tag does not match.
+fn open(key: &[u8], aad: &[u8], data: &mut [u8], tag: &[u8]) -> Result<(), Error> {
+    let (mut cipher, mac) = aead_init(key)?;
+    if !bool::from(ct::bytes_eq(&aead_tag(mac, aad, data), tag)) {
+        return Err(Error::InvalidMac);
+    }
+    cipher.apply_keystream(data);
+    Ok(())
+}
+
+/// Creates the cipher and the Poly1305 instance, keyed with the first keystream block
+fn aead_init(key: &[u8]) -> Result<(ChaCha20Legacy, Poly1305), Error> {
+    let mut cipher = ChaCha20Legacy::new_from_slices(key, &NONCE)
+        .map_err(|_| Error::InvalidParam)?;
+    let mut block = [0u8; 64];
+    cipher.apply_keystream(&mut block);
+    let mac = Poly1305::new_from_slice(&block[..32]).map_err(|_| Error::InvalidParam)?;
+    types::wipe(&mut block);
+    Ok((cipher, mac))
+}
+
+/// Computes the tag of the associated data and the ciphertext, followed by their lengths
+fn aead_tag(mac: Poly1305, aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+    let mut data = aad.to_vec();
+    data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    data.extend_from_slice(ciphertext);
+    data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    let mut tag = [0u8; TAG_LEN];
+    tag.copy_from_slice(&mac.compute_unpadded(&data));
+    tag
+}
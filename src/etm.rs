@@ -0,0 +1,131 @@
+//! Encrypt-then-MAC
+//!
+//! This module adds ciphertext integrity to NTRU encryption. A shared key is encapsulated with the
+//! CCA secure KEM of the `cca` module, and an encryption key and a MAC key are derived from it
+//! with HKDF-SHA256. The message is encrypted with ChaCha20 under the encryption key, and an
+//! HMAC-SHA256 tag is computed with the MAC key over the KEM ciphertext and the encrypted message.
+//! Only the holder of the private key can recover the shared key, so a tag can not be forged for a
+//! chosen ciphertext, and modified envelopes are rejected before decrypting the message. Only
+//! available with the `etm` feature.
+//!
+//! The KEM only carries the key, so the message can have any length. The envelope is the KEM
+//! ciphertext, followed by the encrypted message and the tag, so it is `envelope_len(params,
+//! msg.len())` bytes long.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::DEFAULT_PARAMS_128_BITS;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&DEFAULT_PARAMS_128_BITS, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ntru::etm::encrypt(msg, kp.get_public(), &DEFAULT_PARAMS_128_BITS,
+//!                                    &rand_ctx).unwrap();
+//! let decrypted = ntru::etm::decrypt(&encrypted, &kp, &DEFAULT_PARAMS_128_BITS).unwrap();
+//!
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use chacha20::ChaCha20Legacy;
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use types::{self, KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use cca;
+use ct;
+
+/// Length of the authentication tag
+pub const TAG_LEN: usize = 32;
+/// Length of the encryption key and of the MAC key
+const KEY_LEN: usize = 32;
+/// Nonce of ChaCha20, since every key is used only once
+const NONCE: [u8; 8] = [0u8; 8];
+/// HKDF info label
+const LABEL: &[u8] = b"ntru-rs etm";
+
+/// Length of the envelope of a message of `msg_len` bytes for the parameter set
+pub fn envelope_len(params: &EncParams, msg_len: usize) -> usize {
+    params.enc_len() as usize + msg_len + TAG_LEN
+}
+
+/// Encrypts a message of any length and authenticates the ciphertext
+pub fn encrypt(msg: &[u8],
+               public: &PublicKey,
+               params: &EncParams,
+               rand_ctx: &RandContext)
+               -> Result<Box<[u8]>, Error> {
+    let (kem_ciphertext, shared) = cca::encapsulate(public, params, rand_ctx)?;
+
+    let mut envelope = kem_ciphertext.to_vec();
+    envelope.extend_from_slice(msg);
+    let (mut enc_key, mut mac_key) = derive_keys(&shared, params)?;
+    ChaCha20Legacy::new(&enc_key.into(), &NONCE.into())
+        .apply_keystream(&mut envelope[kem_ciphertext.len()..]);
+    types::wipe(&mut enc_key);
+    let tag = hmac_sha256(&mac_key, &envelope);
+    types::wipe(&mut mac_key);
+    envelope.extend_from_slice(&tag?);
+    Ok(envelope.into_boxed_slice())
+}
+
+/// Verifies and decrypts a message
+///
+/// The envelope must be at least `envelope_len(params, 0)` bytes long, otherwise an
+/// `Error::InvalidEncoding` will be returned. If the tag does not match, an `Error::InvalidMac`
+/// will be returned and the message will not be decrypted.
+pub fn decrypt(envelope: &[u8], kp: &KeyPair, params: &EncParams) -> Result<Box<[u8]>, Error> {
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 {
+        return Err(Error::InvalidParam);
+    }
+    if envelope.len() < envelope_len(params, 0) {
+        return Err(Error::InvalidEncoding);
+    }
+
+    let (authenticated, tag) = envelope.split_at(envelope.len() - TAG_LEN);
+    let shared = cca::decapsulate(&authenticated[..enc_len], kp, params)?;
+    let (mut enc_key, mut mac_key) = derive_keys(&shared, params)?;
+    let expected = hmac_sha256(&mac_key, authenticated);
+    types::wipe(&mut mac_key);
+    if !bool::from(ct::bytes_eq(&expected?, tag)) {
+        types::wipe(&mut enc_key);
+        return Err(Error::InvalidMac);
+    }
+
+    let mut msg = authenticated[enc_len..].to_vec();
+    ChaCha20Legacy::new(&enc_key.into(), &NONCE.into()).apply_keystream(&mut msg);
+    types::wipe(&mut enc_key);
+    Ok(msg.into_boxed_slice())
+}
+
+/// Derives the encryption key and the MAC key from the KEM shared key with HKDF-SHA256, bound
+/// to the parameter set
+fn derive_keys(shared: &[u8], params: &EncParams) -> Result<([u8; KEY_LEN], [u8; KEY_LEN]), Error> {
+    let hkdf = Hkdf::<Sha256>::new(None, shared);
+
+    let mut info = LABEL.to_vec();
+    info.extend_from_slice(&params.get_oid());
+    let mut okm = [0u8; 2 * KEY_LEN];
+    hkdf.expand(&info, &mut okm).map_err(|_| Error::InvalidParam)?;
+
+    let mut enc_key = [0u8; KEY_LEN];
+    let mut mac_key = [0u8; KEY_LEN];
+    enc_key.copy_from_slice(&okm[..KEY_LEN]);
+    mac_key.copy_from_slice(&okm[KEY_LEN..]);
+    types::wipe(&mut okm);
+    Ok((enc_key, mac_key))
+}
+
+/// HMAC-SHA256
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<[u8; TAG_LEN], Error> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| Error::InvalidParam)?;
+    hmac.update(data);
+    let mut tag = [0u8; TAG_LEN];
+    tag.copy_from_slice(&hmac.finalize().into_bytes());
+    Ok(tag)
+}
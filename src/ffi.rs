@@ -1,39 +1,40 @@
-use libc::{uint16_t, int16_t, uint8_t};
+use libc::{uint16_t, uint8_t, c_int, c_void};
 
 use encparams::EncParams;
-use types::{IntPoly, ProdPoly, TernPoly, KeyPair, PrivPoly, PublicKey, PrivateKey};
+use types::{CIntPoly, ProdPoly, TernPoly, CKeyPair, PrivPoly, CPublicKey, PrivateKey};
 use rand::{RandContext, RandGen};
 
 extern "C" {
     // ntru.h
     pub fn ntru_gen_key_pair(params: *const EncParams,
-                             kp: *mut KeyPair,
+                             kp: *mut CKeyPair,
                              rand_ctx: *const RandContext)
                              -> uint8_t;
     pub fn ntru_gen_key_pair_multi(params: *const EncParams,
                                    private: *mut PrivateKey,
-                                   public: *mut PublicKey,
+                                   public: *mut CPublicKey,
                                    rand_ctx: *const RandContext,
                                    num_pub: u32)
                                    -> uint8_t;
     pub fn ntru_gen_pub(params: *const EncParams,
                         private: *const PrivateKey,
-                        public: *mut PublicKey,
+                        public: *mut CPublicKey,
                         rand_ctx: *const RandContext)
                         -> uint8_t;
     pub fn ntru_encrypt(msg: *const uint8_t,
                         msg_len: uint16_t,
-                        public: *const PublicKey,
+                        public: *const CPublicKey,
                         params: *const EncParams,
                         rand_ctx: *const RandContext,
                         enc: *mut uint8_t)
                         -> uint8_t;
     pub fn ntru_decrypt(enc: *const uint8_t,
-                        kp: *const KeyPair,
+                        kp: *const CKeyPair,
                         params: *const EncParams,
                         dec: *mut uint8_t,
                         dec_len: *mut uint16_t)
                         -> uint8_t;
+    pub fn ntru_enc_len(params: *const EncParams) -> uint16_t;
 
     // hash.h
     pub fn ntru_sha1(input: *const uint8_t, input_len: uint16_t, digest: *mut uint8_t);
@@ -100,13 +101,6 @@ extern "C" {
     #[cfg(not(target_os = "windows"))]
     pub fn ntru_rand_devurandom_release(rand_ctx: *mut RandContext) -> uint8_t;
 
-    pub fn ntru_rand_default_init(rand_ctx: *mut RandContext, rand_gen: *const RandGen) -> uint8_t;
-    pub fn ntru_rand_default_generate(rand_data: *mut uint8_t,
-                                      len: uint16_t,
-                                      rand_ctx: *const RandContext)
-                                      -> uint8_t;
-    pub fn ntru_rand_default_release(rand_ctx: *mut RandContext) -> uint8_t;
-
     pub fn ntru_rand_ctr_drbg_init(rand_ctx: *mut RandContext,
                                    rand_gen: *const RandGen)
                                    -> uint8_t;
@@ -116,6 +110,28 @@ extern "C" {
                                        -> uint8_t;
     pub fn ntru_rand_ctr_drbg_release(rand_ctx: *mut RandContext) -> uint8_t;
 
+    // nist_ctr_drbg.h
+    pub fn nist_ctr_drbg_instantiate(drbg: *mut c_void,
+                                     entropy_input: *const c_void,
+                                     entropy_input_length: c_int,
+                                     nonce: *const c_void,
+                                     nonce_length: c_int,
+                                     personalization_string: *const c_void,
+                                     personalization_string_length: c_int)
+                                     -> c_int;
+    pub fn nist_ctr_drbg_generate(drbg: *mut c_void,
+                                  output_string: *mut c_void,
+                                  output_string_length: c_int,
+                                  additional_input: *const c_void,
+                                  additional_input_length: c_int)
+                                  -> c_int;
+    pub fn nist_ctr_drbg_reseed(drbg: *mut c_void,
+                                entropy_input: *const c_void,
+                                entropy_input_length: c_int,
+                                additional_input: *const c_void,
+                                additional_input_length: c_int)
+                                -> c_int;
+
     // poly.h
     pub fn ntru_rand_tern(n: uint16_t,
                           num_ones: uint16_t,
@@ -123,39 +139,59 @@ extern "C" {
                           poly: *mut TernPoly,
                           rand_ctx: *const RandContext)
                           -> uint8_t;
-    pub fn ntru_mult_tern(a: *const IntPoly,
+    pub fn ntru_mult_tern(a: *const CIntPoly,
                           b: *const TernPoly,
-                          c: *mut IntPoly,
+                          c: *mut CIntPoly,
                           mod_mask: uint16_t)
                           -> uint8_t;
-    pub fn ntru_mult_prod(a: *const IntPoly,
+    pub fn ntru_mult_tern_32(a: *const CIntPoly,
+                             b: *const TernPoly,
+                             c: *mut CIntPoly,
+                             mod_mask: uint16_t)
+                             -> uint8_t;
+    pub fn ntru_mult_tern_64(a: *const CIntPoly,
+                             b: *const TernPoly,
+                             c: *mut CIntPoly,
+                             mod_mask: uint16_t)
+                             -> uint8_t;
+    #[cfg(SSE3)]
+    pub fn ntru_mult_tern_sse(a: *const CIntPoly,
+                              b: *const TernPoly,
+                              c: *mut CIntPoly,
+                              mod_mask: uint16_t)
+                              -> uint8_t;
+    pub fn ntru_mult_prod(a: *const CIntPoly,
                           b: *const ProdPoly,
-                          c: *mut IntPoly,
+                          c: *mut CIntPoly,
                           mod_mask: uint16_t)
                           -> uint8_t;
     pub fn ntru_mult_priv(a: *const PrivPoly,
-                          b: *const IntPoly,
-                          c: *mut IntPoly,
+                          b: *const CIntPoly,
+                          c: *mut CIntPoly,
                           mod_mask: uint16_t)
                           -> uint8_t;
-    pub fn ntru_mult_int(a: *const IntPoly,
-                         b: *const IntPoly,
-                         c: *mut IntPoly,
+    pub fn ntru_mult_int(a: *const CIntPoly,
+                         b: *const CIntPoly,
+                         c: *mut CIntPoly,
                          mod_mask: uint16_t)
                          -> uint8_t;
-    pub fn ntru_add(a: *mut IntPoly, b: *const IntPoly);
-    pub fn ntru_sub(a: *mut IntPoly, b: *const IntPoly);
-    pub fn ntru_mod_mask(p: *mut IntPoly, mod_mask: uint16_t);
-    pub fn ntru_mult_fac(a: *mut IntPoly, factor: int16_t);
-    pub fn ntru_mod_center(p: *mut IntPoly, modulus: uint16_t);
-    pub fn ntru_mod3(p: *mut IntPoly);
-    pub fn ntru_to_arr(p: *const IntPoly, q: uint16_t, a: *mut uint8_t);
-    pub fn ntru_from_arr(arr: *const uint8_t, n: uint16_t, q: uint16_t, p: *mut IntPoly);
-    pub fn ntru_invert(a: *const PrivPoly, mod_mask: uint16_t, fq: *mut IntPoly) -> uint8_t;
+    pub fn ntru_mult_int_16(a: *const CIntPoly,
+                            b: *const CIntPoly,
+                            c: *mut CIntPoly,
+                            mod_mask: uint16_t)
+                            -> uint8_t;
+    pub fn ntru_mult_int_64(a: *const CIntPoly,
+                            b: *const CIntPoly,
+                            c: *mut CIntPoly,
+                            mod_mask: uint16_t)
+                            -> uint8_t;
+    pub fn ntru_invert(a: *const PrivPoly, mod_mask: uint16_t, fq: *mut CIntPoly) -> uint8_t;
+    pub fn ntru_invert_32(a: *const PrivPoly, mod_mask: uint16_t, fq: *mut CIntPoly) -> uint8_t;
+    pub fn ntru_invert_64(a: *const PrivPoly, mod_mask: uint16_t, fq: *mut CIntPoly) -> uint8_t;
 
     // key.h
-    pub fn ntru_export_pub(key: *const PublicKey, arr: *mut uint8_t);
-    pub fn ntru_import_pub(arr: *const uint8_t, key: *mut PublicKey) -> uint16_t;
+    pub fn ntru_export_pub(key: *const CPublicKey, arr: *mut uint8_t);
+    pub fn ntru_import_pub(arr: *const uint8_t, key: *mut CPublicKey) -> uint16_t;
 
     pub fn ntru_export_priv(key: *const PrivateKey, arr: *mut uint8_t) -> uint16_t;
     pub fn ntru_import_priv(arr: *const uint8_t, key: *mut PrivateKey);
@@ -0,0 +1,291 @@
+//! File encryption
+//!
+//! This module encrypts streams and files of any length with NTRU. The input is split in chunks
+//! of `params.max_msg_len()` bytes, that are encrypted one by one, so the memory used does not
+//! depend on the size of the input. The encrypted stream has the following format:
+//!
+//! * The 4 byte `MAGIC` and the 3 byte OID of the parameter set.
+//! * The encrypted chunks, each one `params.enc_len()` bytes long.
+//!
+//! All the chunks are full but the last one, that is shorter, and can be empty, so a truncated
+//! stream is detected when decrypting. `EncryptingWriter` and `DecryptingReader` encrypt and
+//! decrypt the data as it flows through them, so they can be composed with other adapters, such
+//! as compression. Note that the chunks are not authenticated: they could be reordered or
+//! replaced by anyone with the public key.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let data = vec![42u8; 1000];
+//! let mut encrypted = Vec::new();
+//! ntru::file::encrypt_stream(&mut &data[..], &mut encrypted, kp.get_public(), &EES401EP1,
+//!                            &rand_ctx).unwrap();
+//!
+//! let mut decrypted = Vec::new();
+//! ntru::file::decrypt_stream(&mut &encrypted[..], &mut decrypted, &kp, &EES401EP1).unwrap();
+//! assert_eq!(data, decrypted);
+//! ```
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::cmp;
+use std::fs::File;
+use std::path::Path;
+use types::{KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::{self, RandContext, RNG_DEFAULT};
+use session::{EncryptSession, DecryptSession};
+
+/// Magic bytes at the start of encrypted streams
+pub const MAGIC: [u8; 4] = *b"NTRF";
+/// Length of the header of encrypted streams
+pub const HEADER_LEN: usize = 7;
+
+/// Writer that encrypts the data written to it
+///
+/// Writes the encrypted stream to the inner writer as the chunks fill up. `finish()` must be
+/// called after the last write, to write the last chunk; if the writer is dropped without calling
+/// it, the encrypted stream is left truncated, and decrypting it will fail.
+pub struct EncryptingWriter<'a, W: Write> {
+    /// The inner writer
+    inner: W,
+    /// The encryption session
+    session: EncryptSession<'a>,
+    /// The data of the current chunk
+    chunk: Vec<u8>,
+    /// Buffer for the encrypted chunks
+    enc: Vec<u8>,
+}
+
+impl<'a, W: Write> EncryptingWriter<'a, W> {
+    /// Creates an encrypting writer, and writes the header to the inner writer
+    ///
+    /// Errors of the NTRU operations are returned as `io::Error`s of kind `Other`.
+    pub fn new(mut inner: W,
+               public: &'a PublicKey,
+               params: &'a EncParams,
+               rand_ctx: &'a RandContext)
+               -> io::Result<EncryptingWriter<'a, W>> {
+        let session = EncryptSession::new(public, params, rand_ctx).map_err(other)?;
+        inner.write_all(&header(params))?;
+        let max_msg_len = session.max_msg_len();
+        let enc_len = session.enc_len();
+        Ok(EncryptingWriter {
+            inner,
+            session,
+            chunk: Vec::with_capacity(max_msg_len),
+            enc: vec![0u8; enc_len],
+        })
+    }
+
+    /// Writes the last chunk, and returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encrypts the current chunk and writes it to the inner writer
+    fn write_chunk(&mut self) -> io::Result<()> {
+        self.session.encrypt_into(&self.chunk, &mut self.enc).map_err(other)?;
+        self.chunk.clear();
+        self.inner.write_all(&self.enc)
+    }
+}
+
+impl<'a, W: Write> Write for EncryptingWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = cmp::min(buf.len(), self.session.max_msg_len() - self.chunk.len());
+        self.chunk.extend_from_slice(&buf[..len]);
+        if self.chunk.len() == self.session.max_msg_len() {
+            self.write_chunk()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader that decrypts the data read from it
+///
+/// Reads the encrypted stream from the inner reader one chunk at a time. If the stream is
+/// truncated or has trailing data, an `io::Error` of kind `InvalidData` will be returned after
+/// the data decrypted until then. Errors of the NTRU operations are returned as `io::Error`s of
+/// kind `Other`.
+pub struct DecryptingReader<'a, R: Read> {
+    /// The inner reader
+    inner: R,
+    /// The decryption session
+    session: DecryptSession<'a>,
+    /// Maximum message length
+    max_msg_len: usize,
+    /// Buffer for the encrypted chunks
+    enc: Vec<u8>,
+    /// The decrypted current chunk
+    chunk: Vec<u8>,
+    /// Position of the next byte to read in the current chunk
+    pos: usize,
+    /// Whether the last chunk has been decrypted
+    done: bool,
+}
+
+impl<'a, R: Read> DecryptingReader<'a, R> {
+    /// Creates a decrypting reader, and reads the header from the inner reader
+    ///
+    /// If the stream is not encrypted with the parameter set, an `io::Error` of kind
+    /// `InvalidData` will be returned.
+    pub fn new(mut inner: R,
+               kp: &'a KeyPair,
+               params: &'a EncParams)
+               -> io::Result<DecryptingReader<'a, R>> {
+        let session = DecryptSession::new(kp, params).map_err(other)?;
+        let mut head = [0u8; HEADER_LEN];
+        if read_full(&mut inner, &mut head)? != HEADER_LEN || head != header(params) {
+            return Err(invalid_data("not an encrypted stream for the parameter set"));
+        }
+        let enc_len = session.enc_len();
+        Ok(DecryptingReader {
+            inner,
+            session,
+            max_msg_len: params.max_msg_len() as usize,
+            enc: vec![0u8; enc_len],
+            chunk: Vec::with_capacity(params.max_msg_len() as usize),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    /// Returns the inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decrypts the next chunk
+    fn read_chunk(&mut self) -> io::Result<()> {
+        if read_full(&mut self.inner, &mut self.enc)? != self.enc.len() {
+            return Err(invalid_data("truncated encrypted stream"));
+        }
+        self.chunk.clear();
+        self.chunk.extend_from_slice(self.session.decrypt(&self.enc).map_err(other)?);
+        self.pos = 0;
+        self.done = self.chunk.len() < self.max_msg_len;
+        Ok(())
+    }
+}
+
+impl<'a, R: Read> Read for DecryptingReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            if self.done {
+                if read_full(&mut self.inner, &mut [0u8])? != 0 {
+                    return Err(invalid_data("trailing data after the encrypted stream"));
+                }
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+        let len = cmp::min(buf.len(), self.chunk.len() - self.pos);
+        buf[..len].copy_from_slice(&self.chunk[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Encrypts a stream
+///
+/// Reads `src` until its end and writes the encrypted stream to `dst`. Errors of the NTRU
+/// operations are returned as `io::Error`s of kind `Other`.
+pub fn encrypt_stream<R: Read, W: Write>(src: &mut R,
+                                         dst: &mut W,
+                                         public: &PublicKey,
+                                         params: &EncParams,
+                                         rand_ctx: &RandContext)
+                                         -> io::Result<()> {
+    let mut writer = EncryptingWriter::new(dst, public, params, rand_ctx)?;
+    let _ = io::copy(src, &mut writer)?;
+    let _ = writer.finish()?;
+    Ok(())
+}
+
+/// Decrypts a stream
+///
+/// Reads the encrypted stream from `src` and writes the decrypted data to `dst`. If the stream
+/// is not encrypted with the parameter set, or it is truncated, an `io::Error` of kind
+/// `InvalidData` will be returned. Errors of the NTRU operations are returned as `io::Error`s of
+/// kind `Other`, and the data decrypted until then will have been written to `dst`.
+pub fn decrypt_stream<R: Read, W: Write>(src: &mut R,
+                                         dst: &mut W,
+                                         kp: &KeyPair,
+                                         params: &EncParams)
+                                         -> io::Result<()> {
+    let mut reader = DecryptingReader::new(src, kp, params)?;
+    let _ = io::copy(&mut reader, dst)?;
+    Ok(())
+}
+
+/// Encrypts a file
+///
+/// Encrypts the file at `src` to a new file at `dst`, that is replaced if it exists, using
+/// `RNG_DEFAULT` as random number generator.
+pub fn encrypt_path<P: AsRef<Path>, Q: AsRef<Path>>(src: P,
+                                                    dst: Q,
+                                                    public: &PublicKey,
+                                                    params: &EncParams)
+                                                    -> io::Result<()> {
+    let rand_ctx = rand::init(&RNG_DEFAULT).map_err(other)?;
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut writer = BufWriter::new(File::create(dst)?);
+    encrypt_stream(&mut reader, &mut writer, public, params, &rand_ctx)?;
+    writer.flush()
+}
+
+/// Decrypts a file
+///
+/// Decrypts the file at `src` to a new file at `dst`, that is replaced if it exists. On error,
+/// `dst` may have been partially written.
+pub fn decrypt_path<P: AsRef<Path>, Q: AsRef<Path>>(src: P,
+                                                    dst: Q,
+                                                    kp: &KeyPair,
+                                                    params: &EncParams)
+                                                    -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut writer = BufWriter::new(File::create(dst)?);
+    decrypt_stream(&mut reader, &mut writer, kp, params)?;
+    writer.flush()
+}
+
+/// Gets the header of the streams encrypted with the parameter set
+fn header(params: &EncParams) -> [u8; HEADER_LEN] {
+    let oid = params.get_oid();
+    [MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3], oid[0], oid[1], oid[2]]
+}
+
+/// Reads until the buffer is full or the end of the reader, and returns the bytes read
+fn read_full<R: Read>(src: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match src.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+/// Converts an NTRU error to an `io::Error`
+fn other(e: Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Creates an `io::Error` for a malformed encrypted stream
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,222 @@
+//! Encryption with fixed buffers
+//!
+//! The functions of the crate root return keys and messages in boxed slices. The functions of
+//! this module write them into buffers given by the caller instead, that can be arrays on the
+//! stack or in static storage, so they can be used on bare-metal targets without an allocator.
+//! Public keys are passed in their exported form, since `PublicKey` is allocated on the heap,
+//! while `PrivateKey` has a fixed size and is returned by value.
+//!
+//! The buffer lengths are constant functions of the parameter set, so they can be array lengths:
+//! `public_len()` for the public keys, `enc_len()` for the encrypted messages and `max_msg_len()`
+//! for the decrypted ones. With the `heapless` feature, the `_vec` variants return
+//! `heapless::Vec`s of the right length instead.
+//!
+//! These functions do not allocate, but the random number generator is created by the caller:
+//! some RNGs, such as `RNG_CTR_DRBG` or the ones of `rand::init_source()`, allocate their state
+//! once when the rand context is created. `RNG_GETRANDOM` does not. On big-endian targets, the
+//! encryption and the decryption are done with the `low_level` module, that allocates.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::encparams::EES439EP1;
+//! use ntru::fixed;
+//! use ntru::rand::{self, RNG_GETRANDOM};
+//!
+//! const PARAMS: ntru::encparams::EncParams = EES439EP1;
+//! let rand_ctx = rand::init(&RNG_GETRANDOM).unwrap();
+//!
+//! let mut public = [0u8; PARAMS.public_len() as usize];
+//! let private = fixed::generate_key_pair(&PARAMS, &rand_ctx, &mut public).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let mut encrypted = [0u8; PARAMS.enc_len() as usize];
+//! let _ = fixed::encrypt(msg, &public, &PARAMS, &rand_ctx, &mut encrypted).unwrap();
+//!
+//! let mut decrypted = [0u8; PARAMS.max_msg_len() as usize];
+//! let len = fixed::decrypt(&encrypted, &private, &public, &PARAMS, &mut decrypted).unwrap();
+//! assert_eq!(&decrypted[..len], &msg[..]);
+//! ```
+#[cfg(feature = "heapless")]
+use heapless::Vec;
+use types::{CKeyPair, CPublicKey, PrivateKey, PublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use low_level;
+use ffi;
+
+/// Generates a key pair, writing the exported public key into `public`
+///
+/// Works like `ntru::generate_key_pair()`. The public key is exported as `PublicKey::export()`
+/// does, and `public` must be exactly `params.public_len()` bytes long, otherwise an
+/// `Error::InvalidParam` will be returned.
+pub fn generate_key_pair(params: &EncParams,
+                         rand_ctx: &RandContext,
+                         public: &mut [u8])
+                         -> Result<PrivateKey, Error> {
+    ::check_policy(params)?;
+    if params.enc_len() == 0 || public.len() != params.public_len() as usize {
+        return Err(Error::InvalidParam);
+    }
+    let mut kp: CKeyPair = Default::default();
+    let result = unsafe { ffi::ntru_gen_key_pair(params, &mut kp, rand_ctx) };
+    if result == 0 {
+        kp.get_public().export(public);
+        Ok(kp.get_private().clone())
+    } else {
+        Err(Error::from(result))
+    }
+}
+
+/// Encrypts a message into `enc`, with an exported public key
+///
+/// Works like `ntru::encrypt()`, and returns the length of the encrypted message, that is always
+/// `params.enc_len()`. `enc` must be at least that long, otherwise an `Error::InvalidParam` will
+/// be returned. If the public key is not a key of the parameter set, an `Error::InvalidKey` will
+/// be returned.
+pub fn encrypt(msg: &[u8],
+               public: &[u8],
+               params: &EncParams,
+               rand_ctx: &RandContext,
+               enc: &mut [u8])
+               -> Result<usize, Error> {
+    ::check_policy(params)?;
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 || enc.len() < enc_len {
+        return Err(Error::InvalidParam);
+    }
+    if msg.len() > params.max_msg_len() as usize {
+        return Err(Error::MessageTooLong);
+    }
+    let public = import_public(public, params)?;
+    if cfg!(target_endian = "big") {
+        let h = PublicKey::from_c(&public);
+        low_level::encrypt_into(msg, h.get_h(), params, rand_ctx, &mut enc[..enc_len])?;
+        return Ok(enc_len);
+    }
+    let result = unsafe {
+        ffi::ntru_encrypt(msg.as_ptr(),
+                          msg.len() as u16,
+                          &public,
+                          params,
+                          rand_ctx,
+                          &mut enc[0])
+    };
+    if result == 0 {
+        Ok(enc_len)
+    } else {
+        Err(Error::from(result))
+    }
+}
+
+/// Decrypts a message into `dec`, with a private key and its exported public key
+///
+/// Works like `ntru::decrypt()`, and returns the length of the decrypted message. `dec` must be at
+/// least `params.max_msg_len()` bytes long, otherwise an `Error::InvalidParam` will be returned.
+/// If the keys are not keys of the parameter set, an `Error::InvalidKey` will be returned. On
+/// failure, `dec` is wiped.
+pub fn decrypt(enc: &[u8],
+               private: &PrivateKey,
+               public: &[u8],
+               params: &EncParams,
+               dec: &mut [u8])
+               -> Result<usize, Error> {
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 || dec.len() < params.max_msg_len() as usize {
+        return Err(Error::InvalidParam);
+    }
+    if enc.len() != enc_len {
+        return Err(Error::InvalidCiphertextLength {
+            expected: enc_len,
+            actual: enc.len(),
+        });
+    }
+    if !is_private_of(private, params) {
+        return Err(Error::InvalidKey);
+    }
+    let kp = CKeyPair::new(private.clone(), import_public(public, params)?);
+    let result = if cfg!(target_endian = "big") {
+        let public = PublicKey::from_c(kp.get_public());
+        low_level::decrypt_into(enc, private, public.get_h(), params, dec)
+    } else {
+        let mut dec_len = 0u16;
+        match unsafe { ffi::ntru_decrypt(&enc[0], &kp, params, &mut dec[0], &mut dec_len) } {
+            0 => Ok(dec_len as usize),
+            result => Err(Error::from(result)),
+        }
+    };
+    result.map_err(|e| {
+        ::types::wipe(dec);
+        ::decryption_error(e)
+    })
+}
+
+/// Generates a key pair, returning the exported public key in a `heapless::Vec`
+///
+/// Works like `generate_key_pair()`. `N` must be at least `params.public_len()`, otherwise an
+/// `Error::InvalidParam` will be returned.
+#[cfg(feature = "heapless")]
+pub fn generate_key_pair_vec<const N: usize>(params: &EncParams,
+                                             rand_ctx: &RandContext)
+                                             -> Result<(PrivateKey, Vec<u8, N>), Error> {
+    let mut public = Vec::new();
+    public.resize(params.public_len() as usize, 0).map_err(|_| Error::InvalidParam)?;
+    let private = generate_key_pair(params, rand_ctx, &mut public)?;
+    Ok((private, public))
+}
+
+/// Encrypts a message into a `heapless::Vec`
+///
+/// Works like `encrypt()`. `N` must be at least `params.enc_len()`, otherwise an
+/// `Error::InvalidParam` will be returned.
+#[cfg(feature = "heapless")]
+pub fn encrypt_vec<const N: usize>(msg: &[u8],
+                                   public: &[u8],
+                                   params: &EncParams,
+                                   rand_ctx: &RandContext)
+                                   -> Result<Vec<u8, N>, Error> {
+    let mut enc = Vec::new();
+    enc.resize(params.enc_len() as usize, 0).map_err(|_| Error::InvalidParam)?;
+    let _ = encrypt(msg, public, params, rand_ctx, &mut enc)?;
+    Ok(enc)
+}
+
+/// Decrypts a message into a `heapless::Vec`
+///
+/// Works like `decrypt()`. `N` must be at least `params.max_msg_len()`, otherwise an
+/// `Error::InvalidParam` will be returned.
+#[cfg(feature = "heapless")]
+pub fn decrypt_vec<const N: usize>(enc: &[u8],
+                                   private: &PrivateKey,
+                                   public: &[u8],
+                                   params: &EncParams)
+                                   -> Result<Vec<u8, N>, Error> {
+    let mut dec = Vec::new();
+    dec.resize(params.max_msg_len() as usize, 0).map_err(|_| Error::InvalidParam)?;
+    let len = decrypt(enc, private, public, params, &mut dec)?;
+    dec.truncate(len);
+    Ok(dec)
+}
+
+/// Imports an exported public key, checking that it is a key of the parameter set
+fn import_public(public: &[u8], params: &EncParams) -> Result<CPublicKey, Error> {
+    if public.len() != params.public_len() as usize ||
+       (public[0] as u16) << 8 | public[1] as u16 != params.get_n() ||
+       (public[2] as u16) << 8 | public[3] as u16 != params.get_q() {
+        return Err(Error::InvalidKey);
+    }
+    Ok(CPublicKey::import(public))
+}
+
+/// Whether the private key has the `q`, the form and the `N` of the parameter set
+fn is_private_of(private: &PrivateKey, params: &EncParams) -> bool {
+    let t = private.get_t();
+    let n = params.get_n();
+    private.get_q() == params.get_q() && t.is_product() == params.is_product() &&
+    if t.is_product() {
+        let prod = t.get_poly_prod();
+        prod.get_f1().get_n() == n && prod.get_f2().get_n() == n && prod.get_f3().get_n() == n
+    } else {
+        t.get_poly_tern().get_n() == n
+    }
+}
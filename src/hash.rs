@@ -0,0 +1,113 @@
+//! Hash functions of the IGF and the MGF implemented in Rust
+//!
+//! libntru only has SHA-1 and SHA-256. These functions have the signatures of its hash functions,
+//! so parameter sets can point to them and libntru calls them back, like its own. The 4 and 8
+//! way variants hash the inputs one after the other. SHA3-256 and BLAKE2b-256 are only available
+//! with the `extra-hashes` feature, and the adapters of the `HashFunction` trait always are.
+#[cfg(feature = "extra-hashes")]
+use std::ptr;
+use std::slice;
+use libc::{uint8_t, uint16_t};
+#[cfg(feature = "extra-hashes")]
+use sha3::{Digest, Sha3_256};
+#[cfg(feature = "extra-hashes")]
+use blake2::Blake2b;
+#[cfg(feature = "extra-hashes")]
+use blake2::digest::consts::U32;
+use encparams::HashFunction;
+
+/// Output length of the hash functions
+#[cfg(feature = "extra-hashes")]
+pub const HLEN: usize = 32;
+
+/// The hash function `H`, with the signature of the hash functions of libntru
+pub unsafe extern "C" fn rust_hash<H: HashFunction>(input: *const uint8_t,
+                                                    input_len: uint16_t,
+                                                    digest: *mut uint8_t) {
+    let digest = slice::from_raw_parts_mut(digest, H::OUTPUT_LEN);
+    H::digest(as_slice(input, input_len), digest);
+}
+
+/// The hash function `H` of 4 inputs
+pub unsafe extern "C" fn rust_hash_4way<H: HashFunction>(input: *const *const uint8_t,
+                                                         input_len: uint16_t,
+                                                         digest: *mut *mut uint8_t) {
+    multi_way(rust_hash::<H>, 4, input, input_len, digest)
+}
+
+/// The hash function `H` of 8 inputs
+pub unsafe extern "C" fn rust_hash_8way<H: HashFunction>(input: *const *const uint8_t,
+                                                         input_len: uint16_t,
+                                                         digest: *mut *mut uint8_t) {
+    multi_way(rust_hash::<H>, 8, input, input_len, digest)
+}
+
+/// SHA3-256
+#[cfg(feature = "extra-hashes")]
+pub unsafe extern "C" fn ntru_sha3_256(input: *const uint8_t,
+                                       input_len: uint16_t,
+                                       digest: *mut uint8_t) {
+    let out = Sha3_256::digest(as_slice(input, input_len));
+    ptr::copy_nonoverlapping(out.as_ptr(), digest, HLEN);
+}
+
+/// SHA3-256 of 4 inputs
+#[cfg(feature = "extra-hashes")]
+pub unsafe extern "C" fn ntru_sha3_256_4way(input: *const *const uint8_t,
+                                            input_len: uint16_t,
+                                            digest: *mut *mut uint8_t) {
+    multi_way(ntru_sha3_256, 4, input, input_len, digest)
+}
+
+/// SHA3-256 of 8 inputs
+#[cfg(feature = "extra-hashes")]
+pub unsafe extern "C" fn ntru_sha3_256_8way(input: *const *const uint8_t,
+                                            input_len: uint16_t,
+                                            digest: *mut *mut uint8_t) {
+    multi_way(ntru_sha3_256, 8, input, input_len, digest)
+}
+
+/// BLAKE2b with a 256 bit output and no key
+#[cfg(feature = "extra-hashes")]
+pub unsafe extern "C" fn ntru_blake2b_256(input: *const uint8_t,
+                                          input_len: uint16_t,
+                                          digest: *mut uint8_t) {
+    let out = Blake2b::<U32>::digest(as_slice(input, input_len));
+    ptr::copy_nonoverlapping(out.as_ptr(), digest, HLEN);
+}
+
+/// BLAKE2b-256 of 4 inputs
+#[cfg(feature = "extra-hashes")]
+pub unsafe extern "C" fn ntru_blake2b_256_4way(input: *const *const uint8_t,
+                                               input_len: uint16_t,
+                                               digest: *mut *mut uint8_t) {
+    multi_way(ntru_blake2b_256, 4, input, input_len, digest)
+}
+
+/// BLAKE2b-256 of 8 inputs
+#[cfg(feature = "extra-hashes")]
+pub unsafe extern "C" fn ntru_blake2b_256_8way(input: *const *const uint8_t,
+                                               input_len: uint16_t,
+                                               digest: *mut *mut uint8_t) {
+    multi_way(ntru_blake2b_256, 8, input, input_len, digest)
+}
+
+/// Hashes `count` inputs of the same length
+unsafe fn multi_way(hash: unsafe extern "C" fn(*const uint8_t, uint16_t, *mut uint8_t),
+                    count: usize,
+                    input: *const *const uint8_t,
+                    input_len: uint16_t,
+                    digest: *mut *mut uint8_t) {
+    for i in 0..count {
+        hash(*input.add(i), input_len, *digest.add(i));
+    }
+}
+
+/// Gets the input of libntru as a slice, that may be a null pointer if it is empty
+unsafe fn as_slice<'a>(input: *const uint8_t, input_len: uint16_t) -> &'a [u8] {
+    if input_len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(input, input_len as usize)
+    }
+}
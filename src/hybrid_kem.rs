@@ -0,0 +1,250 @@
+//! Hybrid X25519 + NTRU KEM
+//!
+//! This module combines an X25519 key exchange with an NTRU encapsulation, in the same way as the
+//! TLS hybrid key exchange drafts: the shared secret stays secure as long as either of the two
+//! schemes is not broken. This gives a classical fallback while migrating to post-quantum
+//! cryptography.
+//!
+//! The encapsulation generates an ephemeral X25519 key and a random 32 byte secret, encrypted with
+//! the NTRU public key. The ciphertext is the ephemeral X25519 public key followed by the NTRU
+//! encrypted message, and the shared key is derived with HKDF-SHA256 from both secrets, bound to
+//! the recipient X25519 key and the ciphertext.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES443EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::hybrid_kem::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+//!
+//! let (ciphertext, shared1) = ntru::hybrid_kem::encapsulate(&kp.get_public(), &EES443EP1,
+//!                                                          &rand_ctx).unwrap();
+//! let shared2 = ntru::hybrid_kem::decapsulate(&ciphertext, &kp, &EES443EP1).unwrap();
+//!
+//! assert_eq!(shared1, shared2);
+//! ```
+use std::fmt;
+
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use types::{self, KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use ct;
+
+/// Length of the X25519 keys
+pub const X25519_LEN: usize = 32;
+/// Length of the shared key
+pub const SHARED_KEY_LEN: usize = 32;
+/// Length of the secret encrypted with NTRU
+const NTRU_SECRET_LEN: usize = 32;
+/// HKDF info label
+const LABEL: &[u8] = b"ntru-rs hybrid x25519 kem";
+
+/// Ciphertext and shared key returned by the encapsulation
+pub type Encapsulated = (Box<[u8]>, Box<[u8]>);
+
+/// Hybrid public key
+#[derive(Debug, PartialEq, Clone)]
+pub struct HybridPublicKey {
+    /// X25519 public key
+    x25519: [u8; X25519_LEN],
+    /// NTRU public key
+    ntru: PublicKey,
+}
+
+impl HybridPublicKey {
+    /// Get the X25519 public key
+    pub fn get_x25519(&self) -> &[u8; X25519_LEN] {
+        &self.x25519
+    }
+
+    /// Get the NTRU public key
+    pub fn get_ntru(&self) -> &PublicKey {
+        &self.ntru
+    }
+
+    /// Import a hybrid public key
+    pub fn import(arr: &[u8], params: &EncParams) -> Result<HybridPublicKey, Error> {
+        if arr.len() != X25519_LEN + params.public_len() as usize {
+            return Err(Error::InvalidKey);
+        }
+        let mut x25519 = [0u8; X25519_LEN];
+        x25519.copy_from_slice(&arr[..X25519_LEN]);
+        Ok(HybridPublicKey {
+            x25519,
+            ntru: PublicKey::import_for(&arr[X25519_LEN..], params)?,
+        })
+    }
+
+    /// Export the hybrid public key
+    ///
+    /// It is exported as the X25519 public key followed by the exported NTRU public key.
+    pub fn export(&self, params: &EncParams) -> Box<[u8]> {
+        let mut arr = self.x25519.to_vec();
+        arr.extend_from_slice(&self.ntru.export(params));
+        arr.into_boxed_slice()
+    }
+}
+
+/// Hybrid key pair
+///
+/// The X25519 private key is wiped when the key pair is dropped.
+#[derive(Clone)]
+pub struct HybridKeyPair {
+    /// X25519 private key
+    x25519_private: [u8; X25519_LEN],
+    /// X25519 public key
+    x25519_public: [u8; X25519_LEN],
+    /// NTRU key pair
+    ntru: KeyPair,
+}
+
+impl HybridKeyPair {
+    /// Get the public key of the key pair
+    pub fn get_public(&self) -> HybridPublicKey {
+        HybridPublicKey {
+            x25519: self.x25519_public,
+            ntru: self.ntru.get_public().clone(),
+        }
+    }
+
+    /// Get the NTRU key pair
+    pub fn get_ntru(&self) -> &KeyPair {
+        &self.ntru
+    }
+}
+
+impl Drop for HybridKeyPair {
+    fn drop(&mut self) {
+        types::wipe(&mut self.x25519_private);
+    }
+}
+
+impl fmt::Debug for HybridKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HybridKeyPair")
+            .field("x25519_public", &self.x25519_public)
+            .field("ntru_public", self.ntru.get_public())
+            .finish()
+    }
+}
+
+/// Key generation
+///
+/// Generates an X25519 key and an NTRU key pair. If a deterministic RNG is used, the key pair will
+/// be deterministic for a given random seed.
+pub fn generate_key_pair(params: &EncParams,
+                         rand_ctx: &RandContext)
+                         -> Result<HybridKeyPair, Error> {
+    let mut x25519_private = x25519_generate(rand_ctx)?;
+    let ntru = ::generate_key_pair(params, rand_ctx);
+    let kp = ntru.map(|ntru| {
+        HybridKeyPair {
+            x25519_private,
+            x25519_public: x25519(x25519_private, X25519_BASEPOINT_BYTES),
+            ntru,
+        }
+    });
+    types::wipe(&mut x25519_private);
+    kp
+}
+
+/// Encapsulation
+///
+/// Generates a shared key for the hybrid public key, and returns the ciphertext and the shared
+/// key.
+pub fn encapsulate(public: &HybridPublicKey,
+                   params: &EncParams,
+                   rand_ctx: &RandContext)
+                   -> Result<Encapsulated, Error> {
+    let mut ephemeral = x25519_generate(rand_ctx)?;
+    let mut ciphertext = x25519(ephemeral, X25519_BASEPOINT_BYTES).to_vec();
+    let x25519_shared = x25519_agree(&ephemeral, &public.x25519);
+    types::wipe(&mut ephemeral);
+    let mut x25519_shared = x25519_shared?;
+    let ntru_secret = ::rand::generate(NTRU_SECRET_LEN as u16, rand_ctx);
+    let shared = ntru_secret.and_then(|mut ntru_secret| {
+        let shared = ::encrypt(&ntru_secret, &public.ntru, params, rand_ctx).and_then(|enc| {
+            ciphertext.extend_from_slice(&enc);
+            derive_key(&x25519_shared, &ntru_secret, &public.x25519, &ciphertext)
+        });
+        types::wipe(&mut ntru_secret);
+        shared
+    });
+    types::wipe(&mut x25519_shared);
+    Ok((ciphertext.into_boxed_slice(), shared?))
+}
+
+/// Decapsulation
+///
+/// Recovers the shared key from the ciphertext. The ciphertext must be exactly `X25519_LEN +
+/// params.enc_len()` bytes long, otherwise an `Error::InvalidCiphertextLength` will be returned.
+pub fn decapsulate(ciphertext: &[u8],
+                   kp: &HybridKeyPair,
+                   params: &EncParams)
+                   -> Result<Box<[u8]>, Error> {
+    let expected = X25519_LEN + params.enc_len() as usize;
+    if ciphertext.len() != expected {
+        return Err(Error::InvalidCiphertextLength {
+            expected,
+            actual: ciphertext.len(),
+        });
+    }
+
+    let mut x25519_shared = x25519_agree(&kp.x25519_private, &ciphertext[..X25519_LEN])?;
+    let shared = ::decrypt(&ciphertext[X25519_LEN..], &kp.ntru, params).and_then(|mut secret| {
+        let shared = if secret.len() == NTRU_SECRET_LEN {
+            derive_key(&x25519_shared, &secret, &kp.x25519_public, ciphertext)
+        } else {
+            Err(Error::InvalidEncoding)
+        };
+        types::wipe(&mut secret);
+        shared
+    });
+    types::wipe(&mut x25519_shared);
+    shared
+}
+
+/// Generates a random X25519 private key
+fn x25519_generate(rand_ctx: &RandContext) -> Result<[u8; X25519_LEN], Error> {
+    let mut random = ::rand::generate(X25519_LEN as u16, rand_ctx)?;
+    let mut private = [0u8; X25519_LEN];
+    private.copy_from_slice(&random);
+    types::wipe(&mut random);
+    Ok(private)
+}
+
+/// X25519 key agreement, rejecting low order public keys
+fn x25519_agree(private: &[u8; X25519_LEN], public: &[u8]) -> Result<[u8; X25519_LEN], Error> {
+    let mut point = [0u8; X25519_LEN];
+    point.copy_from_slice(public);
+    let shared = x25519(*private, point);
+    if bool::from(ct::is_zero(&shared)) {
+        Err(Error::InvalidKey)
+    } else {
+        Ok(shared)
+    }
+}
+
+/// Derives the shared key with HKDF-SHA256
+fn derive_key(x25519_shared: &[u8],
+              ntru_secret: &[u8],
+              x25519_public: &[u8],
+              ciphertext: &[u8])
+              -> Result<Box<[u8]>, Error> {
+    let mut ikm = x25519_shared.to_vec();
+    ikm.extend_from_slice(ntru_secret);
+    let hkdf = Hkdf::<Sha256>::new(None, &ikm);
+    types::wipe(&mut ikm);
+
+    let mut info = LABEL.to_vec();
+    info.extend_from_slice(x25519_public);
+    info.extend_from_slice(ciphertext);
+    let mut key = vec![0u8; SHARED_KEY_LEN];
+    hkdf.expand(&info, &mut key).map_err(|_| Error::InvalidParam)?;
+    Ok(key.into_boxed_slice())
+}
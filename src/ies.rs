@@ -0,0 +1,154 @@
+//! Integrated encryption scheme
+//!
+//! This module encrypts messages of any length in the style of ECIES, with NTRU in place of the
+//! Diffie-Hellman key agreement. Each message gets a fresh ephemeral key pair and a fresh shared
+//! key, encapsulated to the recipient with the CCA secure KEM of the `cca` module. An encryption
+//! key and a MAC key are derived from the shared key with HKDF-SHA256, bound to the parameter
+//! set, the recipient public key, the ephemeral public key and the KEM ciphertext. The message is
+//! encrypted with ChaCha20, and the package is authenticated with HMAC-SHA256. Only available
+//! with the `ies` feature.
+//!
+//! NTRU has no key agreement, so the ephemeral key pair does not take part in the shared key: it
+//! is bound to it, and gives the recipient a fresh public key to answer the sender with. The
+//! sender keeps the ephemeral key pair to decrypt the answer, and the recipient gets the ephemeral
+//! public key back from the decryption.
+//!
+//! The package is the ephemeral public key, `params.public_len()` bytes long, the KEM ciphertext,
+//! `params.enc_len()` bytes long, the encrypted message, that has the length of the message, and
+//! the `TAG_LEN` byte tag.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let (package, ephemeral) = ntru::ies::encrypt(msg, kp.get_public(), &EES401EP1,
+//!                                               &rand_ctx).unwrap();
+//! let (decrypted, sender) = ntru::ies::decrypt(&package, &kp, &EES401EP1).unwrap();
+//!
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! assert_eq!(&sender, ephemeral.get_public());
+//! ```
+use chacha20::ChaCha20Legacy;
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use types::{self, KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use cca;
+use ct;
+
+/// Length of the authentication tag
+pub const TAG_LEN: usize = 32;
+/// Length of the encryption key and of the MAC key
+const KEY_LEN: usize = 32;
+/// Nonce of ChaCha20, since every key is used only once
+const NONCE: [u8; 8] = [0u8; 8];
+/// HKDF info label
+const LABEL: &[u8] = b"ntru-rs ies";
+
+/// Encrypts a message of any length
+///
+/// Returns the package and the ephemeral key pair, that can decrypt an answer of the recipient.
+pub fn encrypt(msg: &[u8],
+               public: &PublicKey,
+               params: &EncParams,
+               rand_ctx: &RandContext)
+               -> Result<(Box<[u8]>, KeyPair), Error> {
+    let ephemeral = ::generate_key_pair(params, rand_ctx)?;
+    let (kem_ciphertext, shared) = cca::encapsulate(public, params, rand_ctx)?;
+
+    let mut package = ephemeral.get_public().export(params).into_vec();
+    package.extend_from_slice(&kem_ciphertext);
+    let (mut enc_key, mut mac_key) = derive_keys(&shared, public, params, &package)?;
+
+    let header_len = package.len();
+    package.extend_from_slice(msg);
+    ChaCha20Legacy::new(&enc_key.into(), &NONCE.into())
+        .apply_keystream(&mut package[header_len..]);
+    types::wipe(&mut enc_key);
+    let tag = hmac_sha256(&mac_key, &package);
+    types::wipe(&mut mac_key);
+    package.extend_from_slice(&tag?);
+    Ok((package.into_boxed_slice(), ephemeral))
+}
+
+/// Verifies and decrypts a message
+///
+/// Returns the message and the ephemeral public key of the sender, to answer it. Returns
+/// `Error::InvalidEncoding` if the package is too short or the ephemeral public key is not valid
+/// for the parameter set. If the tag does not match, an `Error::InvalidMac` will be returned and
+/// the message will not be decrypted.
+pub fn decrypt(package: &[u8],
+               kp: &KeyPair,
+               params: &EncParams)
+               -> Result<(Box<[u8]>, PublicKey), Error> {
+    let public_len = params.public_len() as usize;
+    let header_len = public_len + params.enc_len() as usize;
+    if params.enc_len() == 0 {
+        return Err(Error::InvalidParam);
+    }
+    if package.len() < header_len + TAG_LEN {
+        return Err(Error::InvalidEncoding);
+    }
+
+    let (authenticated, tag) = package.split_at(package.len() - TAG_LEN);
+    let sender = PublicKey::import_for(&authenticated[..public_len], params)
+        .map_err(|_| Error::InvalidEncoding)?;
+    let shared = cca::decapsulate(&authenticated[public_len..header_len], kp, params)?;
+    let (mut enc_key, mut mac_key) =
+        derive_keys(&shared, kp.get_public(), params, &authenticated[..header_len])?;
+    let expected = hmac_sha256(&mac_key, authenticated);
+    types::wipe(&mut mac_key);
+    if !bool::from(ct::bytes_eq(&expected?, tag)) {
+        types::wipe(&mut enc_key);
+        return Err(Error::InvalidMac);
+    }
+
+    let encrypted = &authenticated[header_len..];
+    let mut msg = encrypted.to_vec();
+    ChaCha20Legacy::new(&enc_key.into(), &NONCE.into()).apply_keystream(&mut msg);
+    types::wipe(&mut enc_key);
+    Ok((msg.into_boxed_slice(), sender))
+}
+
+/// Derives the encryption key and the MAC key with HKDF-SHA256
+///
+/// The header is the ephemeral public key followed by the KEM ciphertext.
+fn derive_keys(shared: &[u8],
+               public: &PublicKey,
+               params: &EncParams,
+               header: &[u8])
+               -> Result<([u8; KEY_LEN], [u8; KEY_LEN]), Error> {
+    let hkdf = Hkdf::<Sha256>::new(None, shared);
+
+    let mut info = LABEL.to_vec();
+    info.extend_from_slice(&params.get_oid());
+    info.extend_from_slice(&public.export(params));
+    info.extend_from_slice(header);
+    let mut okm = [0u8; 2 * KEY_LEN];
+    hkdf.expand(&info, &mut okm).map_err(|_| Error::InvalidParam)?;
+
+    let mut enc_key = [0u8; KEY_LEN];
+    let mut mac_key = [0u8; KEY_LEN];
+    enc_key.copy_from_slice(&okm[..KEY_LEN]);
+    mac_key.copy_from_slice(&okm[KEY_LEN..]);
+    types::wipe(&mut okm);
+    Ok((enc_key, mac_key))
+}
+
+/// HMAC-SHA256
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<[u8; TAG_LEN], Error> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| Error::InvalidParam)?;
+    hmac.update(data);
+    let mut tag = [0u8; TAG_LEN];
+    tag.copy_from_slice(&hmac.finalize().into_bytes());
+    Ok(tag)
+}
@@ -0,0 +1,103 @@
+//! JSON Web Keys
+//!
+//! This module converts NTRU keys to and from JWK-style JSON objects, so they can be stored and
+//! distributed along with other JOSE keys. Only available with the `jose` feature. There is no
+//! registered JWK key type for NTRU, so the objects have the following members:
+//!
+//! * `kty`: always `"NTRU"`.
+//! * `alg`: always `"NTRUEncrypt"`.
+//! * `ps`: the name of the parameter set, such as `"EES401EP1"`.
+//! * `kid`: the key ID of the public key, in hexadecimal, as `keyring::key_id()`. It is ignored
+//!   when importing.
+//! * `x`: the exported public key, in URL-safe base64 without padding.
+//! * `d`: the exported private key, in URL-safe base64 without padding, only for key pairs.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let jwk = ntru::jwk::public_to_jwk(kp.get_public(), &EES401EP1);
+//! assert_eq!(jwk["kty"], "NTRU");
+//! assert_eq!(jwk["ps"], "EES401EP1");
+//!
+//! let (public, params) = ntru::jwk::public_from_jwk(&jwk).unwrap();
+//! assert_eq!(&public, kp.get_public());
+//! assert!(params == &EES401EP1);
+//! ```
+use serde_json::{Map, Value};
+use types::{KeyPair, PrivateKey, PublicKey, Error};
+use encparams::{self, EncParams};
+use keyring;
+use encoding;
+
+/// Key type of NTRU keys
+pub const KTY: &str = "NTRU";
+/// Algorithm of NTRU keys
+pub const ALG: &str = "NTRUEncrypt";
+
+/// Converts a public key to a JWK
+pub fn public_to_jwk(public: &PublicKey, params: &EncParams) -> Value {
+    Value::Object(public_members(public, params))
+}
+
+/// Converts a key pair to a JWK, with both the public and the private key
+pub fn key_pair_to_jwk(kp: &KeyPair, params: &EncParams) -> Value {
+    let mut jwk = public_members(kp.get_public(), params);
+    let _ = jwk.insert("d".to_owned(),
+                       Value::String(encoding::to_base64url(&kp.get_private().export(params))));
+    Value::Object(jwk)
+}
+
+/// Gets a public key and its parameter set from a JWK
+///
+/// Returns `Error::InvalidEncoding` if it is not a JWK of an NTRU key,
+/// `Error::UnknownParamSet` if the parameter set is not known, and `Error::InvalidKey` if the
+/// public key is not valid for the parameter set.
+pub fn public_from_jwk(jwk: &Value) -> Result<(PublicKey, &'static EncParams), Error> {
+    let params = params_from_jwk(jwk)?;
+    let public = PublicKey::import_for(&encoding::from_base64url(member(jwk, "x")?)?, params)?;
+    Ok((public, params))
+}
+
+/// Gets a key pair and its parameter set from a JWK
+///
+/// Returns the same errors as `public_from_jwk()`, and `Error::InvalidEncoding` if the JWK does
+/// not have the private key.
+pub fn key_pair_from_jwk(jwk: &Value) -> Result<(KeyPair, &'static EncParams), Error> {
+    let (public, params) = public_from_jwk(jwk)?;
+    let d = encoding::from_base64url(member(jwk, "d")?)?;
+    let private = PrivateKey::import_checked(&d, params)?;
+    Ok((KeyPair::new(private, public), params))
+}
+
+/// Gets the members of the JWK of a public key
+fn public_members(public: &PublicKey, params: &EncParams) -> Map<String, Value> {
+    let name = params.get_name();
+    let mut jwk = Map::new();
+    let _ = jwk.insert("kty".to_owned(), Value::String(KTY.to_owned()));
+    let _ = jwk.insert("alg".to_owned(), Value::String(ALG.to_owned()));
+    let _ = jwk.insert("ps".to_owned(), Value::String(name.trim_end_matches('\0').to_owned()));
+    let _ = jwk.insert("kid".to_owned(),
+                       Value::String(keyring::key_id_to_hex(&keyring::key_id(public, params))));
+    let _ = jwk.insert("x".to_owned(),
+                       Value::String(encoding::to_base64url(&public.export(params))));
+    jwk
+}
+
+/// Checks the key type and the algorithm of the JWK, and gets its parameter set
+fn params_from_jwk(jwk: &Value) -> Result<&'static EncParams, Error> {
+    if member(jwk, "kty")? != KTY || member(jwk, "alg")? != ALG {
+        return Err(Error::InvalidEncoding);
+    }
+    encparams::from_name(member(jwk, "ps")?).ok_or(Error::UnknownParamSet)
+}
+
+/// Gets a string member of the JWK
+fn member<'a>(jwk: &'a Value, name: &str) -> Result<&'a str, Error> {
+    jwk.get(name).and_then(Value::as_str).ok_or(Error::InvalidEncoding)
+}
@@ -0,0 +1,207 @@
+//! Known-answer test files
+//!
+//! This module generates deterministic test vectors in the request / response format of the NIST
+//! post-quantum KAT files for public key encryption, so they can be compared with other NTRU
+//! implementations. Each record has the following fields:
+//!
+//! * `count`: the number of the record, starting at 0.
+//! * `seed`: the 48 byte seed of the record.
+//! * `mlen` and `msg`: the message to encrypt.
+//! * `pk` and `sk`: the exported public and private keys.
+//! * `clen` and `c`: the encrypted message.
+//!
+//! The request file only has the seeds and the messages. The random data comes from the
+//! AES-256 `CTR_DRBG` of the `PQCgenKAT` programs of NIST, `NistDrbg`, as in their KAT files: a
+//! `NistDrbg` instantiated with the given entropy input, usually `NIST_ENTROPY_INPUT`, generates
+//! the seed and then the message of each record. Then, for each record, a `NistDrbg`
+//! instantiated with the seed of the record generates the key pair and encrypts the message,
+//! that is checked to decrypt correctly. The messages are `16 * (count + 1)` bytes long, as in
+//! the NIST files, but at most `params.max_msg_len()` bytes.
+//!
+//! libntru samples its keys in its own way, so the key pair is derived instead from a 48 byte
+//! key seed taken from the `NistDrbg` of the record, with the index generation function of the
+//! `low_level` module, in the same way as the known-answer tests of `self_test()`. A new key
+//! seed is taken while the key is not invertible. The blinding value of the encryption is then
+//! taken from the same `NistDrbg`, so the files only depend on the NTRUEncrypt specification.
+//!
+//! Requires the `kat` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::encparams::EES401EP1;
+//! use ntru::kat::NIST_ENTROPY_INPUT;
+//!
+//! let mut req = Vec::new();
+//! let mut rsp = Vec::new();
+//! ntru::kat::generate(&EES401EP1, &NIST_ENTROPY_INPUT, 2, &mut req, &mut rsp).unwrap();
+//!
+//! let rsp = String::from_utf8(rsp).unwrap();
+//! assert!(rsp.starts_with("# EES401EP1\n\ncount = 0\nseed = 061550234D158C5E"));
+//! ```
+use std::io::{self, Write};
+use std::cmp;
+use aes::Aes256;
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::cipher::generic_array::GenericArray;
+use types::{Error, KeyPair};
+use encparams::EncParams;
+use rand::{self, RandContext, RandomSource};
+
+/// Length of the seed of each record
+pub const SEED_LEN: usize = 48;
+
+/// Entropy input of the `PQCgenKAT` programs of NIST, the bytes 0 to 47
+pub const NIST_ENTROPY_INPUT: [u8; SEED_LEN] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+                                                15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+                                                27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38,
+                                                39, 40, 41, 42, 43, 44, 45, 46, 47];
+
+/// The AES-256 `CTR_DRBG` of the KAT files of NIST
+///
+/// This is the `randombytes()` of the `rng.c` file of the NIST submissions: a `CTR_DRBG` of SP
+/// 800-90A without derivation function, prediction resistance nor reseeding. It is only meant
+/// to reproduce known-answer tests, never to generate real keys.
+pub struct NistDrbg {
+    /// The AES-256 key of the state
+    key: [u8; 32],
+    /// The counter of the state
+    v: [u8; 16],
+}
+
+impl NistDrbg {
+    /// Instantiates the DRBG, as `randombytes_init()` without personalization string
+    pub fn new(entropy_input: &[u8; SEED_LEN]) -> NistDrbg {
+        let mut drbg = NistDrbg {
+            key: [0; 32],
+            v: [0; 16],
+        };
+        drbg.update(Some(entropy_input));
+        drbg
+    }
+
+    /// Fills the buffer with random data, as `randombytes()`
+    pub fn randombytes(&mut self, x: &mut [u8]) {
+        let cipher = Aes256::new(GenericArray::from_slice(&self.key));
+        for chunk in x.chunks_mut(16) {
+            let block = self.next_block(&cipher);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.update(None);
+    }
+
+    /// Increments the counter and encrypts it
+    fn next_block(&mut self, cipher: &Aes256) -> [u8; 16] {
+        for b in self.v.iter_mut().rev() {
+            *b = b.wrapping_add(1);
+            if *b != 0 {
+                break;
+            }
+        }
+        let mut block = GenericArray::clone_from_slice(&self.v);
+        cipher.encrypt_block(&mut block);
+        let mut out = [0u8; 16];
+        out.copy_from_slice(&block);
+        out
+    }
+
+    /// Updates the state with the provided data, as `AES256_CTR_DRBG_Update()`
+    fn update(&mut self, provided_data: Option<&[u8; SEED_LEN]>) {
+        let cipher = Aes256::new(GenericArray::from_slice(&self.key));
+        let mut temp = [0u8; SEED_LEN];
+        for chunk in temp.chunks_mut(16) {
+            chunk.copy_from_slice(&self.next_block(&cipher));
+        }
+        if let Some(data) = provided_data {
+            for (t, d) in temp.iter_mut().zip(data.iter()) {
+                *t ^= *d;
+            }
+        }
+        self.key.copy_from_slice(&temp[..32]);
+        self.v.copy_from_slice(&temp[32..]);
+    }
+}
+
+impl RandomSource for NistDrbg {
+    fn fill(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.randombytes(buf);
+        Ok(())
+    }
+}
+
+/// Generates the request and response files
+///
+/// Writes `count` records generated from the entropy input to `req` and `rsp`. Errors of the
+/// NTRU operations are returned as `io::Error`s of kind `Other`.
+pub fn generate<R: Write, S: Write>(params: &EncParams,
+                                    entropy_input: &[u8; SEED_LEN],
+                                    count: usize,
+                                    req: &mut R,
+                                    rsp: &mut S)
+                                    -> io::Result<()> {
+    if params.enc_len() == 0 {
+        return Err(other(Error::InvalidParam));
+    }
+    let name = params.get_name();
+    let name = name.trim_end_matches('\0');
+    write!(req, "# {}\n\n", name)?;
+    write!(rsp, "# {}\n\n", name)?;
+
+    let mut master = NistDrbg::new(entropy_input);
+    for i in 0..count {
+        let mut record_seed = [0u8; SEED_LEN];
+        master.randombytes(&mut record_seed);
+        let mlen = cmp::min(16 * (i + 1), params.max_msg_len() as usize);
+        let mut msg = vec![0u8; mlen];
+        master.randombytes(&mut msg);
+
+        writeln!(req, "count = {}", i)?;
+        write_hex(req, "seed", &record_seed)?;
+        writeln!(req, "mlen = {}", mlen)?;
+        write_hex(req, "msg", &msg)?;
+        write!(req, "pk =\nsk =\nclen =\nc =\n\n")?;
+
+        let rand_ctx = rand::init_source(NistDrbg::new(&record_seed)).map_err(other)?;
+        let kp = key_pair(params, &rand_ctx).map_err(other)?;
+        let c = ::encrypt(&msg, kp.get_public(), params, &rand_ctx).map_err(other)?;
+        if ::decrypt(&c, &kp, params).map_err(other)?[..] != msg[..] {
+            return Err(io::Error::other("decrypted message does not match"));
+        }
+
+        writeln!(rsp, "count = {}", i)?;
+        write_hex(rsp, "seed", &record_seed)?;
+        writeln!(rsp, "mlen = {}", mlen)?;
+        write_hex(rsp, "msg", &msg)?;
+        write_hex(rsp, "pk", &kp.get_public().export(params))?;
+        write_hex(rsp, "sk", &kp.get_private().export(params))?;
+        writeln!(rsp, "clen = {}", c.len())?;
+        write_hex(rsp, "c", &c)?;
+        writeln!(rsp)?;
+    }
+    Ok(())
+}
+
+/// Derives the key pair of a record from key seeds of its random context
+fn key_pair(params: &EncParams, rand_ctx: &RandContext) -> Result<KeyPair, Error> {
+    loop {
+        let key_seed = rand::generate(SEED_LEN as u16, rand_ctx)?;
+        match ::self_test_key_pair(&key_seed, params) {
+            Err(Error::InvalidKey) => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Writes a field with the bytes in uppercase hexadecimal, as the NIST files
+fn write_hex<W: Write>(w: &mut W, field: &str, bytes: &[u8]) -> io::Result<()> {
+    write!(w, "{} = ", field)?;
+    for b in bytes {
+        write!(w, "{:02X}", b)?;
+    }
+    writeln!(w)
+}
+
+/// Converts an NTRU error to an `io::Error`
+fn other(e: Error) -> io::Error {
+    io::Error::other(e)
+}
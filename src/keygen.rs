@@ -0,0 +1,122 @@
+//! Key pair generator
+//!
+//! This module gathers the options of the key generation in one builder: the parameter set, the
+//! random number generator, an optional seed for deterministic keys, and the number of public
+//! keys. The options are checked together when the keys are generated, so contradictory ones,
+//! such as a seed for an RNG that cannot be seeded, are rejected instead of being ignored.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::encparams::DEFAULT_PARAMS_256_BITS;
+//! use ntru::keygen::KeyPairGenerator;
+//! use ntru::rand::Rng;
+//!
+//! let kp = KeyPairGenerator::new()
+//!     .params(DEFAULT_PARAMS_256_BITS)
+//!     .rng(Rng::Default)
+//!     .deterministic_seed(b"my seed")
+//!     .num_public_keys(4)
+//!     .generate()
+//!     .unwrap();
+//! assert_eq!(kp.get_publics().len(), 4);
+//! ```
+use types::{KeyPair, MultiKeyPair, Error};
+use encparams::{EncParams, DEFAULT_PARAMS_128_BITS};
+use rand::{self, Rng, RandContext, RNG_CTR_DRBG};
+
+/// A builder of key pairs
+#[derive(Debug, Clone)]
+pub struct KeyPairGenerator {
+    /// The parameter set
+    params: EncParams,
+    /// The random number generator
+    rng: Rng,
+    /// The seed of the random number generator, for deterministic keys
+    seed: Option<Vec<u8>>,
+    /// The number of public keys
+    num_public_keys: usize,
+}
+
+impl Default for KeyPairGenerator {
+    fn default() -> KeyPairGenerator {
+        KeyPairGenerator::new()
+    }
+}
+
+impl KeyPairGenerator {
+    /// Creates a generator of one key pair of `DEFAULT_PARAMS_128_BITS`, with `Rng::Default`
+    pub fn new() -> KeyPairGenerator {
+        KeyPairGenerator {
+            params: DEFAULT_PARAMS_128_BITS,
+            rng: Rng::Default,
+            seed: None,
+            num_public_keys: 1,
+        }
+    }
+
+    /// Sets the parameter set
+    pub fn params(mut self, params: EncParams) -> KeyPairGenerator {
+        self.params = params;
+        self
+    }
+
+    /// Sets the random number generator
+    pub fn rng(mut self, rng: Rng) -> KeyPairGenerator {
+        self.rng = rng;
+        self
+    }
+
+    /// Sets the seed of the random number generator, so the same seed gives the same keys
+    ///
+    /// The seed is used by a `CTR_DRBG`, so the random number generator must be `Rng::Default` or
+    /// `Rng::CtrDrbg`. With one public key, the key pair is the one of `ntru::generate_key_pair()`
+    /// with a context from `ntru::rand::init_det()` with `RNG_CTR_DRBG` and the seed.
+    pub fn deterministic_seed(mut self, seed: &[u8]) -> KeyPairGenerator {
+        self.seed = Some(seed.to_vec());
+        self
+    }
+
+    /// Sets the number of public keys of the private key
+    pub fn num_public_keys(mut self, num_public_keys: usize) -> KeyPairGenerator {
+        self.num_public_keys = num_public_keys;
+        self
+    }
+
+    /// Generates the private key and its public keys
+    ///
+    /// Returns `Error::InvalidParam` if the number of public keys is 0, the seed is empty or the
+    /// random number generator cannot be seeded, and the errors of `ntru::generate_key_pair()`.
+    pub fn generate(&self) -> Result<MultiKeyPair, Error> {
+        if self.num_public_keys == 0 {
+            return Err(Error::InvalidParam);
+        }
+        let rand_ctx = self.rand_ctx()?;
+        if self.num_public_keys == 1 {
+            let kp = ::generate_key_pair(&self.params, &rand_ctx)?;
+            MultiKeyPair::new(kp.get_private().clone(), vec![kp.get_public().clone()].into())
+        } else {
+            ::generate_multiple_key_pairs(&self.params, &rand_ctx, self.num_public_keys)
+        }
+    }
+
+    /// Generates a key pair
+    ///
+    /// Works like `generate()`, and also returns `Error::InvalidParam` if the number of public
+    /// keys is not 1.
+    pub fn generate_key_pair(&self) -> Result<KeyPair, Error> {
+        if self.num_public_keys != 1 {
+            return Err(Error::InvalidParam);
+        }
+        self.generate()?.get_key_pair(0).ok_or(Error::InvalidParam)
+    }
+
+    /// Initializes the random number generator, with the seed if there is one
+    fn rand_ctx(&self) -> Result<RandContext, Error> {
+        match self.seed {
+            Some(_) if !self.rng.is_ctr_drbg() => Err(Error::InvalidParam),
+            Some(ref seed) => rand::init_det(&RNG_CTR_DRBG, seed),
+            None => rand::init(self.rng.get_rand_gen()),
+        }
+    }
+}
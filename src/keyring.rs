@@ -0,0 +1,230 @@
+//! Key IDs and key rings
+//!
+//! This module identifies public keys with a short key ID, the first 8 bytes of the SHA-256 hash
+//! of the exported public key. Messages encrypted with `encrypt_with_key_id()` are prefixed with
+//! the key ID of the recipient, so that a `KeyRing` holding many key pairs can pick the right one
+//! to decrypt them, instead of trying each of them. Key IDs are also used to look up the public
+//! keys of a `PublicKeySet`, that groups the public keys of a shared private key. They can be
+//! written as text with `key_id_to_hex()`, and read back with `key_id_from_hex()`.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::{DEFAULT_PARAMS_128_BITS, DEFAULT_PARAMS_256_BITS};
+//! use ntru::keyring::KeyRing;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp1 = ntru::generate_key_pair(&DEFAULT_PARAMS_128_BITS, &rand_ctx).unwrap();
+//! let kp2 = ntru::generate_key_pair(&DEFAULT_PARAMS_256_BITS, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ntru::keyring::encrypt_with_key_id(msg, kp2.get_public(),
+//!                                                    &DEFAULT_PARAMS_256_BITS,
+//!                                                    &rand_ctx).unwrap();
+//!
+//! let mut key_ring = KeyRing::new();
+//! let _ = key_ring.add(kp1).unwrap();
+//! let _ = key_ring.add(kp2).unwrap();
+//! let decrypted = key_ring.decrypt(&encrypted).unwrap();
+//!
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use std::collections::HashMap;
+use types::{KeyPair, PrivateKey, PublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use cca::sha256;
+use encoding;
+
+/// Length of the key IDs
+pub const KEY_ID_LEN: usize = 8;
+
+/// Short identifier of a public key
+pub type KeyId = [u8; KEY_ID_LEN];
+
+/// Computes the key ID of a public key
+pub fn key_id(public: &PublicKey, params: &EncParams) -> KeyId {
+    let hash = sha256(&[&public.export(params)]);
+    let mut id = [0u8; KEY_ID_LEN];
+    id.copy_from_slice(&hash[..KEY_ID_LEN]);
+    id
+}
+
+/// Encodes a key ID in lowercase hexadecimal
+pub fn key_id_to_hex(id: &KeyId) -> String {
+    encoding::to_hex(id)
+}
+
+/// Decodes a key ID in hexadecimal
+///
+/// Returns `Error::InvalidEncoding` if the string is not valid hexadecimal, or if it does not
+/// have `2 * KEY_ID_LEN` digits.
+pub fn key_id_from_hex(s: &str) -> Result<KeyId, Error> {
+    let bytes = encoding::from_hex(s)?;
+    if bytes.len() != KEY_ID_LEN {
+        return Err(Error::InvalidEncoding);
+    }
+    let mut id = [0u8; KEY_ID_LEN];
+    id.copy_from_slice(&bytes);
+    Ok(id)
+}
+
+/// Encrypts a message and prepends the key ID of the public key
+///
+/// The result is `KEY_ID_LEN + params.enc_len()` bytes long, and can be decrypted with
+/// `KeyRing::decrypt()`.
+pub fn encrypt_with_key_id(msg: &[u8],
+                           public: &PublicKey,
+                           params: &EncParams,
+                           rand_ctx: &RandContext)
+                           -> Result<Box<[u8]>, Error> {
+    let encrypted = ::encrypt(msg, public, params, rand_ctx)?;
+    let mut result = key_id(public, params).to_vec();
+    result.extend_from_slice(&encrypted);
+    Ok(result.into_boxed_slice())
+}
+
+/// A set of key pairs, indexed by key ID
+#[derive(Debug, Default)]
+pub struct KeyRing {
+    /// Key pairs and their parameters
+    keys: HashMap<KeyId, (KeyPair, EncParams)>,
+}
+
+impl KeyRing {
+    /// Creates an empty key ring
+    pub fn new() -> KeyRing {
+        KeyRing { keys: HashMap::new() }
+    }
+
+    /// Adds a key pair to the key ring, and returns its key ID
+    ///
+    /// The parameters of the key pair are obtained from its private key. If a key pair with the
+    /// same key ID was in the key ring, it gets replaced.
+    pub fn add(&mut self, kp: KeyPair) -> Result<KeyId, Error> {
+        let params = kp.get_params()?;
+        let id = key_id(kp.get_public(), &params);
+        let _ = self.keys.insert(id, (kp, params));
+        Ok(id)
+    }
+
+    /// Removes a key pair from the key ring
+    pub fn remove(&mut self, id: &KeyId) -> Option<KeyPair> {
+        self.keys.remove(id).map(|(kp, _)| kp)
+    }
+
+    /// Gets the key pair with the given key ID
+    pub fn get(&self, id: &KeyId) -> Option<&KeyPair> {
+        self.keys.get(id).map(|(kp, _)| kp)
+    }
+
+    /// Number of key pairs in the key ring
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether the key ring is empty
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Decrypts a message encrypted with `encrypt_with_key_id()`
+    ///
+    /// The key pair is selected by the key ID prefix of the message. If no key pair in the key
+    /// ring matches it, an `Error::UnknownKey` will be returned.
+    pub fn decrypt(&self, enc: &[u8]) -> Result<Box<[u8]>, Error> {
+        if enc.len() < KEY_ID_LEN {
+            return Err(Error::InvalidEncoding);
+        }
+        let mut id = [0u8; KEY_ID_LEN];
+        id.copy_from_slice(&enc[..KEY_ID_LEN]);
+
+        match self.keys.get(&id) {
+            Some((kp, params)) => ::decrypt(&enc[KEY_ID_LEN..], kp, params),
+            None => Err(Error::UnknownKey),
+        }
+    }
+}
+
+/// A private key shared by several public keys
+///
+/// This owns the private key and the public keys generated for it with
+/// `ntru::generate_multiple_key_pairs()` or `ntru::generate_public()`. Public keys can be looked
+/// up by their key ID, and new public keys can be added at any time.
+#[derive(Debug)]
+pub struct PublicKeySet {
+    /// The shared private key
+    private: PrivateKey,
+    /// The parameters of the private key
+    params: EncParams,
+    /// The public keys, with their key IDs
+    public: Vec<(KeyId, PublicKey)>,
+}
+
+impl PublicKeySet {
+    /// Creates a public key set from a private key and its public keys
+    ///
+    /// The parameters are obtained from the private key.
+    pub fn new(private: PrivateKey, public: Vec<PublicKey>) -> Result<PublicKeySet, Error> {
+        let params = private.get_params()?;
+        let public = public.into_iter().map(|key| (key_id(&key, &params), key)).collect();
+        Ok(PublicKeySet {
+            private,
+            params,
+            public,
+        })
+    }
+
+    /// Generates a private key and `num_pub` public keys for it
+    pub fn generate(params: &EncParams,
+                    rand_ctx: &RandContext,
+                    num_pub: usize)
+                    -> Result<PublicKeySet, Error> {
+        let kp = ::generate_multiple_key_pairs(params, rand_ctx, num_pub)?;
+        let (private, public) = kp.into_parts();
+        PublicKeySet::new(private, public.into_vec())
+    }
+
+    /// Generates a new public key and adds it to the set
+    pub fn generate_public(&mut self, rand_ctx: &RandContext) -> Result<&PublicKey, Error> {
+        let public = ::generate_public(&self.params, &self.private, rand_ctx)?;
+        self.public.push((key_id(&public, &self.params), public));
+        Ok(&self.public[self.public.len() - 1].1)
+    }
+
+    /// The shared private key
+    pub fn get_private(&self) -> &PrivateKey {
+        &self.private
+    }
+
+    /// The parameters of the keys
+    pub fn get_params(&self) -> &EncParams {
+        &self.params
+    }
+
+    /// Iterates over the public keys, in the order they were added
+    pub fn public_keys<'a>(&'a self) -> impl Iterator<Item = &'a PublicKey> + 'a {
+        self.public.iter().map(|(_, key)| key)
+    }
+
+    /// Number of public keys in the set
+    pub fn len(&self) -> usize {
+        self.public.len()
+    }
+
+    /// Whether the set has no public keys
+    pub fn is_empty(&self) -> bool {
+        self.public.is_empty()
+    }
+
+    /// Gets the public key with the given key ID
+    pub fn get(&self, id: &KeyId) -> Option<&PublicKey> {
+        self.public.iter().find(|&(key_id, _)| key_id == id).map(|(_, key)| key)
+    }
+
+    /// Builds the key pair to decrypt messages encrypted for the public key with the given key ID
+    pub fn key_pair(&self, id: &KeyId) -> Option<KeyPair> {
+        self.get(id).map(|public| KeyPair::new(self.private.clone(), public.clone()))
+    }
+}
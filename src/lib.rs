@@ -40,6 +40,28 @@
 //!
 //! assert_eq!(&msg[..], &decrypted[..]);
 //! ```
+//!
+//! # Byte order
+//!
+//! Everything the crate encodes is canonical, so it can be exchanged between little-endian and
+//! big-endian targets, such as s390x or powerpc: integers in headers are big-endian, unless a
+//! format says otherwise, and polynomials are packed byte by byte with `log2(q)` bits per
+//! coefficient, least significant bit first, as libntru does on little-endian targets. The
+//! public keys and `IntPoly::to_arr()` are encoded in Rust, and so are the primitives of the
+//! `low_level` module. On little-endian targets, encrypted messages and private keys are encoded
+//! by libntru, and `check_library()` checks that it packs polynomials in the same layout. On
+//! big-endian targets, where libntru would use the native byte order, they are encoded with the
+//! `low_level` module instead, so they are the same as on little-endian targets.
+//!
+//! # Panics
+//!
+//! The functions that take untrusted input, such as the imports, `decrypt()` and the decoders of
+//! the other modules, check it and return errors for it. Some functions panic when called with
+//! invalid arguments, as documented, such as `IntPoly::set_coeff()`, `TernPoly::new()` or
+//! `PrivPoly::get_poly_prod()`, that have `try_` counterparts returning errors. With the
+//! `no-panic` feature, clippy denies `panic!()`, `unreachable!()`, `unwrap()` and `expect()` in
+//! the crate, and a `RandContext` that fails to be released is leaked instead of panicking. Slice
+//! indexing is not linted, so this is not a guarantee that nothing else panics.
 
 #![forbid(missing_docs, warnings)]
 #![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
@@ -48,15 +70,120 @@
     unused_comparisons, unused_features, unused_parens, while_true)]
 #![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
     unused_qualifications, unused_results, variant_size_differences)]
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
+#![cfg_attr(feature = "no-panic",
+            deny(clippy::panic, clippy::unreachable, clippy::unwrap_used, clippy::expect_used))]
 
 extern crate libc;
+extern crate getrandom;
+extern crate subtle;
+#[cfg(any(feature = "ntru-prime", feature = "hybrid-kem", feature = "ies", feature = "etm",
+          feature = "bip39"))]
+extern crate sha2;
+#[cfg(any(feature = "nist-ntru", feature = "extra-hashes"))]
+extern crate sha3;
+#[cfg(all(feature = "rustcrypto-kem", any(feature = "ntru-prime", feature = "nist-ntru")))]
+extern crate kem;
+#[cfg(any(all(feature = "rustcrypto-kem", any(feature = "ntru-prime", feature = "nist-ntru")),
+          feature = "embedded-rng"))]
+extern crate rand_core;
+#[cfg(feature = "embedded-rng")]
+extern crate embedded_hal;
+#[cfg(feature = "heapless")]
+extern crate heapless;
+#[cfg(feature = "extra-hashes")]
+extern crate blake2;
+#[cfg(feature = "bip39")]
+extern crate pbkdf2;
+#[cfg(feature = "hybrid-kem")]
+extern crate x25519_dalek;
+#[cfg(any(feature = "hybrid-kem", feature = "ies", feature = "etm"))]
+extern crate hkdf;
+#[cfg(any(feature = "ies", feature = "etm"))]
+extern crate hmac;
+#[cfg(any(feature = "ies", feature = "etm", feature = "envelope"))]
+extern crate chacha20;
+#[cfg(feature = "envelope")]
+extern crate poly1305;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(any(feature = "jose", feature = "config"))]
+extern crate serde_json;
+#[cfg(feature = "config")]
+extern crate toml;
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "kat")]
+extern crate aes;
 
 pub mod types;
 pub mod rand;
 pub mod encparams;
+pub mod cca;
+#[cfg(feature = "etm")]
+pub mod etm;
+pub mod commit;
+pub mod threshold;
+pub mod keyring;
+pub mod session;
+pub mod context;
+pub mod keygen;
+pub mod scheme;
+pub mod fixed;
+pub mod low_level;
+#[cfg(feature = "kat")]
+pub mod kat;
+pub mod file;
+pub mod x509;
+pub mod bouncycastle;
+pub mod ct;
+#[cfg(feature = "ntru-prime")]
+pub mod ntruprime;
+#[cfg(feature = "nist-ntru")]
+pub mod nistntru;
+#[cfg(all(feature = "rustcrypto-kem", any(feature = "ntru-prime", feature = "nist-ntru")))]
+pub mod rustcrypto;
+#[cfg(any(feature = "ntru-prime", feature = "nist-ntru"))]
+pub mod pqcrypto;
+#[cfg(feature = "hybrid-kem")]
+pub mod hybrid_kem;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "tokio")]
+pub mod nonblocking;
+#[cfg(feature = "jose")]
+pub mod jwk;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "envelope")]
+pub mod envelope;
+#[cfg(feature = "ies")]
+pub mod ies;
+#[cfg(feature = "bip39")]
+pub mod bip39;
+#[cfg(all(feature = "secret-box", unix))]
+pub mod secret_box;
+#[cfg(feature = "blinding")]
+pub mod blinding;
+#[cfg(feature = "embedded-rng")]
+pub mod embedded_rng;
 mod ffi;
+mod hash;
+mod poly;
+mod trace;
+mod encoding;
 
-use types::{KeyPair, PrivateKey, PublicKey, Error};
+use types::{KeyPair, CKeyPair, MultiKeyPair, PrivateKey, PublicKey, CPublicKey, PrivPoly, ProdPoly,
+            TernPoly, Error, ErrorCategory, SecretVec, SelfTestError};
 use encparams::EncParams;
 use rand::RandContext;
 
@@ -64,14 +191,102 @@ use rand::RandContext;
 ///
 /// Generates a NTRU encryption key pair. If a deterministic RNG is used, the key pair will be
 /// deterministic for a given random seed; otherwise, the key pair will be completely random.
+///
+/// The key pair is several kilobytes long. `KeyPair::generate_boxed()` generates it directly on
+/// the heap, for small stacks.
 pub fn generate_key_pair(params: &EncParams, rand_context: &RandContext) -> Result<KeyPair, Error> {
-    let mut kp: KeyPair = Default::default();
-    let result = unsafe { ffi::ntru_gen_key_pair(params, &mut kp, rand_context) };
-    if result == 0 {
-        Ok(kp)
-    } else {
-        Err(Error::from(result))
+    KeyPair::generate_boxed(params, rand_context).map(|kp| *kp)
+}
+
+/// Number of candidate polynomials of each key generation of `generate_key_pair_hardened()`
+pub const HARDENED_KEYGEN_CANDIDATES: usize = 8;
+
+/// Key generation hiding the number of rejected candidates
+///
+/// libntru samples `f` and `g` until they are invertible, so the time `generate_key_pair()` takes
+/// reveals how many candidates were rejected. This samples and inverts
+/// `HARDENED_KEYGEN_CANDIDATES` candidates every time, and selects the first invertible one in
+/// constant time, so the time does not depend on which one is kept. In the very unlikely case
+/// that none of them is invertible, it starts again.
+///
+/// It is slower than `generate_key_pair()`, and, with a deterministic RNG, generates other key
+/// pairs than it.
+pub fn generate_key_pair_hardened(params: &EncParams,
+                                  rand_ctx: &RandContext)
+                                  -> Result<KeyPair, Error> {
+    check_policy(params)?;
+    let n = params.get_n();
+    let q = params.get_q();
+    let dg = params.get_dg();
+    if n == 0 || q < 2 || q & (q - 1) != 0 || dg == 0 {
+        return Err(Error::InvalidParam);
     }
+
+    let candidate = || -> Result<(PrivPoly, TernPoly, ct::Choice), Error> {
+        let t = if params.is_product() {
+            let df3 = params.get_df3();
+            PrivPoly::from_prod_poly(ProdPoly::rand(n,
+                                                    params.get_df1(),
+                                                    params.get_df2(),
+                                                    df3,
+                                                    df3,
+                                                    rand_ctx)?)?
+        } else {
+            let df = params.get_df1();
+            PrivPoly::new_with_tern_poly(TernPoly::rand(n, df, df, rand_ctx)?)
+        };
+        let g = TernPoly::rand(n, dg, dg - 1, rand_ctx)?;
+        let f_invertible = t.invert(q - 1).1;
+        let g_invertible = poly::invert(g.to_int_poly().get_coeffs(), q - 1).is_some();
+        Ok((t, g, ct::Choice::from((f_invertible & g_invertible) as u8)))
+    };
+
+    trace::instrument("generate_key_pair_hardened", params, 0, || loop {
+        let (mut t, mut g, mut found) = candidate()?;
+        for _ in 1..HARDENED_KEYGEN_CANDIDATES {
+            let (t2, g2, valid) = candidate()?;
+            let take = !found & valid;
+            t = ct::select_priv_poly(&t, &t2, take)?;
+            g = ct::select_tern_poly(&g, &g2, take)?;
+            found |= valid;
+        }
+        if bool::from(found) {
+            return generate_key_pair_from(params, &t, &g);
+        }
+    })
+}
+
+/// Key generation from given polynomials
+///
+/// Generates the key pair for the given private polynomial `t` and polynomial `g`, instead of
+/// sampling them, as needed to reproduce key generation test vectors. As in libntru, the private
+/// key is `f = 1+3t` and the public key is `h = 3*g*f^-1 mod q`.
+///
+/// `t` must be a product-form polynomial if the parameter set uses them, or a ternary polynomial
+/// otherwise, with the numbers of ones and negative ones of the parameter set, and `g` must have
+/// `dg` ones and `dg-1` negative ones. Otherwise an `Error::InvalidDegree` or an
+/// `Error::InvalidWeight` will be returned. If `f` or `g` are not invertible modulo q, an
+/// `Error::InvalidKey` will be returned.
+pub fn generate_key_pair_from(params: &EncParams,
+                              t: &PrivPoly,
+                              g: &TernPoly)
+                              -> Result<KeyPair, Error> {
+    check_policy(params)?;
+    let q = params.get_q();
+    if params.get_n() == 0 || q < 2 || q & (q - 1) != 0 || params.get_dg() == 0 {
+        return Err(Error::InvalidParam);
+    }
+    t.check_weights(params)?;
+    g.check_weights(params.get_n(), params.get_dg(), params.get_dg() - 1)?;
+
+    let (fq, invertible) = t.invert(q - 1);
+    if !invertible || poly::invert(g.to_int_poly().get_coeffs(), q - 1).is_none() {
+        return Err(Error::InvalidKey);
+    }
+    let mut h = fq.mult_tern(g, q - 1)?;
+    h.mult_fac(3);
+    h.mod_mask(q - 1);
+    Ok(KeyPair::new(PrivateKey::new(q, t.clone()), PublicKey::new(q, h)))
 }
 
 /// Key generation with multiple public keys
@@ -79,29 +294,38 @@ pub fn generate_key_pair(params: &EncParams, rand_context: &RandContext) -> Resu
 /// Generates `num_pub` Ntru encryption key pairs. They all share a private key but their public
 /// keys differ. The private key decrypts messages encrypted for any of the public keys. Note that
 /// when decrypting, the public key of the key pair passed into `ntru_decrypt()` must match the
-/// public key used for encrypting the message. If a deterministic RNG is used, the key pair will
-/// be deterministic for a given random seed; otherwise, the key pair will be completely random.
+/// public key used for encrypting the message, or `decrypt_any()` can try all of them. If a
+/// deterministic RNG is used, the key pair will be deterministic for a given random seed;
+/// otherwise, the key pair will be completely random.
+///
+/// If `num_pub` is 0, an `Error::InvalidParam` will be returned.
 pub fn generate_multiple_key_pairs(params: &EncParams,
                                    rand_context: &RandContext,
                                    num_pub: usize)
-                                   -> Result<(PrivateKey, Box<[PublicKey]>), Error> {
-    let mut private: PrivateKey = Default::default();
-    let mut public: Vec<PublicKey> = Vec::with_capacity(num_pub);
-    for _ in 0..num_pub {
-        public.push(Default::default());
-    }
-    let result = unsafe {
-        ffi::ntru_gen_key_pair_multi(params,
-                                     &mut private,
-                                     &mut public[0],
-                                     rand_context,
-                                     num_pub as u32)
-    };
-    if result == 0 {
-        Ok((private, public.into_boxed_slice()))
-    } else {
-        Err(Error::from(result))
+                                   -> Result<MultiKeyPair, Error> {
+    check_policy(params)?;
+    if num_pub == 0 || num_pub > u32::MAX as usize {
+        return Err(Error::InvalidParam);
     }
+    trace::instrument("generate_multiple_key_pairs", params, num_pub, || {
+        let mut private: PrivateKey = Default::default();
+        let mut public: Vec<CPublicKey> = Vec::with_capacity(num_pub);
+        for _ in 0..num_pub {
+            public.push(Default::default());
+        }
+        let result = unsafe {
+            ffi::ntru_gen_key_pair_multi(params,
+                                         &mut private,
+                                         &mut public[0],
+                                         rand_context,
+                                         num_pub as u32)
+        };
+        if result == 0 {
+            MultiKeyPair::new(private, public.iter().map(PublicKey::from_c).collect())
+        } else {
+            Err(Error::from(result))
+        }
+    })
 }
 
 /// New public key
@@ -118,13 +342,16 @@ pub fn generate_public(params: &EncParams,
                        private: &PrivateKey,
                        rand_context: &RandContext)
                        -> Result<PublicKey, Error> {
-    let mut public: PublicKey = Default::default();
-    let result = unsafe { ffi::ntru_gen_pub(params, private, &mut public, rand_context) };
-    if result == 0 {
-        Ok(public)
-    } else {
-        Err(Error::from(result))
-    }
+    check_policy(params)?;
+    trace::instrument("generate_public", params, 0, || {
+        let mut public = types::boxed_zeroed::<CPublicKey>();
+        let result = unsafe { ffi::ntru_gen_pub(params, private, &mut *public, rand_context) };
+        if result == 0 {
+            Ok(PublicKey::from_c(&public))
+        } else {
+            Err(Error::from(result))
+        }
+    })
 }
 
 /// Encrypts a message
@@ -135,30 +362,149 @@ pub fn generate_public(params: &EncParams,
 /// * `msg`: The message to encrypt as an ```u8``` slice.
 /// * `public`: The public key to encrypt the message with.
 /// * `params`: The NTRU encryption parameters to use.
-/// * `and_ctx`: An initialized random number generator.
+/// * `rand_ctx`: An initialized random number generator.
+///
+/// The message can be at most `params.max_msg_len()` bytes long, otherwise an
+/// `Error::MessageTooLong` will be returned.
+///
+/// The public key is copied into the fixed layout of libntru on every call. To encrypt several
+/// messages for the same key, `session::EncryptSession` copies it once.
 pub fn encrypt(msg: &[u8],
                public: &PublicKey,
                params: &EncParams,
                rand_ctx: &RandContext)
                -> Result<Box<[u8]>, Error> {
-    let mut enc = vec![0u8; params.enc_len() as usize];
-    let result = unsafe {
-        ffi::ntru_encrypt(if msg.len() > 0 {
-                              &msg[0]
-                          } else {
-                              std::ptr::null()
-                          },
-                          msg.len() as u16,
-                          public,
-                          params,
-                          rand_ctx,
-                          &mut enc[0])
-    };
+    check_policy(params)?;
+    trace::instrument("encrypt", params, msg.len(), || {
+        if msg.len() > params.max_msg_len() as usize {
+            return Err(Error::MessageTooLong);
+        }
 
-    if result == 0 {
-        Ok(enc.into_boxed_slice())
+        let mut enc = vec![0u8; params.enc_len() as usize];
+        #[cfg(feature = "metrics")]
+        let calls = rand_ctx.generate_calls();
+        let result = if cfg!(target_endian = "big") {
+            // libntru would pack the encrypted message in the native byte order
+            low_level::encrypt_into(msg, public.get_h(), params, rand_ctx, &mut enc)
+        } else {
+            match unsafe {
+                ffi::ntru_encrypt(if !msg.is_empty() {
+                                      &msg[0]
+                                  } else {
+                                      std::ptr::null()
+                                  },
+                                  msg.len() as u16,
+                                  &*public.to_c(),
+                                  params,
+                                  rand_ctx,
+                                  &mut enc[0])
+            } {
+                0 => Ok(()),
+                result => Err(Error::from(result)),
+            }
+        };
+
+        result.map(|_| {
+            // The blinding value is generated once per round
+            #[cfg(feature = "metrics")]
+            metrics::record_encryption_rounds(rand_ctx.generate_calls() - calls);
+            enc.into_boxed_slice()
+        })
+    })
+}
+
+/// Encrypts a message with the given random bytes
+///
+/// Works like `encrypt()`, but instead of taking the random bytes `b` from a random number
+/// generator, it uses the `params.get_db() / 8` bytes given, so the encrypted message is exactly
+/// determined by them, as needed to reproduce known-answer tests. The blinding polynomial `r` is
+/// derived from `b`, the message and the public key, as P1363.1 requires for the decryption to
+/// verify it, so fixing `b` also fixes `r`.
+///
+/// If `b` does not have the right length an `Error::InvalidParam` will be returned. In the rare
+/// case that the bytes give a message representative with too few `-1`, `0` or `1` coefficients,
+/// libntru would need new random bytes, so an `Error::Prng` will be returned.
+pub fn encrypt_with_blinding(msg: &[u8],
+                             public: &PublicKey,
+                             params: &EncParams,
+                             b: &[u8])
+                             -> Result<Box<[u8]>, Error> {
+    if b.is_empty() || b.len() != params.get_db() as usize / 8 {
+        return Err(Error::InvalidParam);
+    }
+    let rand_ctx = rand::init_det(&rand::RNG_REPLAY, b)?;
+    encrypt(msg, public, params, &rand_ctx)
+}
+
+/// Personalization string of the deterministic encryption, that keeps its random bytes apart
+/// from the ones of other uses of the same seed
+const DETERMINISTIC_PERSONALIZATION: &[u8] = b"ntru-rs deterministic encryption";
+
+/// Encrypts a message deterministically
+///
+/// Works like `encrypt()`, but instead of taking the random bytes `b` from a random number
+/// generator, it derives them from the public key and the message, like a synthetic IV: the hash
+/// function of the parameter set hashes the OID, the public key and the message into the seed of
+/// a `CTR_DRBG`. Encrypting the same message with the same key and parameters always gives the
+/// same encrypted message, that decrypts with `decrypt()` as usual, so encrypted messages can be
+/// deduplicated or reproduced without fixing the seed of a random number generator.
+///
+/// Since the encryption is deterministic, anyone can tell whether two encrypted messages have the
+/// same plain text, and check a guess of the plain text by encrypting it. It must only be used for
+/// messages that have enough entropy, such as keys, or when revealing the equality is acceptable.
+pub fn encrypt_deterministic(msg: &[u8],
+                             public: &PublicKey,
+                             params: &EncParams)
+                             -> Result<Box<[u8]>, Error> {
+    if msg.len() > params.max_msg_len() as usize {
+        return Err(Error::MessageTooLong);
+    }
+    let mut input = params.get_oid().to_vec();
+    input.extend_from_slice(&public.export(params));
+    input.extend_from_slice(msg);
+    let mut seed = params.hash(&input);
+    types::wipe(&mut input);
+
+    let rand_ctx = rand::init_det_with(&rand::RNG_CTR_DRBG, &seed, DETERMINISTIC_PERSONALIZATION);
+    types::wipe(&mut seed);
+    encrypt(msg, public, params, &rand_ctx?)
+}
+
+/// Encrypts a batch of messages
+///
+/// Encrypts all the messages with the same public key, and returns the encrypted messages in the
+/// same order. If any message is longer than `params.max_msg_len()`, an `Error::MessageTooLong`
+/// will be returned and nothing will be encrypted.
+///
+/// The blinding polynomials and the masks of the messages are generated together, so the hashes
+/// of the IGF and the MGF of up to 8 messages are computed at once with the 4-way and 8-way hash
+/// functions of libntru. The hashes of the IGF can only be shared by messages of the same length.
+pub fn encrypt_batch(msgs: &[&[u8]],
+                     public: &PublicKey,
+                     params: &EncParams,
+                     rand_ctx: &RandContext)
+                     -> Result<Box<[Box<[u8]>]>, Error> {
+    check_policy(params)?;
+    let total_len = msgs.iter().map(|msg| msg.len()).sum();
+    trace::instrument("encrypt", params, total_len, || {
+        let mut encrypted = vec![vec![0u8; params.enc_len() as usize]; msgs.len()];
+        {
+            let mut encs: Vec<&mut [u8]> = encrypted.iter_mut().map(|enc| &mut enc[..]).collect();
+            low_level::encrypt_batch_into(msgs, public.get_h(), params, rand_ctx, &mut encs)?;
+        }
+        Ok(encrypted.into_iter().map(Vec::into_boxed_slice).collect())
+    })
+}
+
+/// Checks that the parameter policy allows the parameter set, as `EncParams::is_allowed()`, and
+/// that this build can use it, as `EncParams::sampler()`
+pub(crate) fn check_policy(params: &EncParams) -> Result<(), Error> {
+    if params.sampler().is_none() {
+        Err(Error::UnknownParamSet)
+    } else if params.is_allowed() {
+        Ok(())
     } else {
-        Err(Error::from(result))
+        Err(Error::DeprecatedParamSet)
     }
 }
 
@@ -167,18 +513,330 @@ pub fn encrypt(msg: &[u8],
 /// See P1363.1 section 9.2.3. The parameters needed are the following:
 /// * enc: The message to decrypt as an ```u8``` slice.
 /// * kp: A key pair that contains the public key the message was encrypted with, and the
-///       corresponding private key.
+///   corresponding private key.
 /// * params: Parameters the message was encrypted with
+///
+/// The encrypted message must be exactly `params.enc_len()` bytes long, otherwise an
+/// `Error::InvalidCiphertextLength` will be returned. If it cannot be decrypted, whether its
+/// padding is wrong, it violates the `dm0` check or it was not encrypted for the public key of the
+/// key pair, an `Error::DecryptionFailed` will be returned, so the error does not tell an attacker
+/// which check failed. The `verbose-errors` feature returns the detailed error of libntru
+/// instead, for debugging.
+///
+/// The key pair is copied into the fixed layout of libntru on every call. To decrypt several
+/// messages with the same key pair, `session::DecryptSession` copies it once.
 pub fn decrypt(enc: &[u8], kp: &KeyPair, params: &EncParams) -> Result<Box<[u8]>, Error> {
+    trace::instrument("decrypt", params, enc.len(), || decrypt_c(enc, &kp.to_c(), params))
+}
+
+/// Decrypts a message encrypted for any of the public keys of a private key
+///
+/// For keys made with `generate_multiple_key_pairs()` or `generate_public()`, that share a private
+/// key. The encrypted message does not tell which public key it was encrypted for, so each public
+/// key is tried in turn, in the order given, until one decrypts it. Works like `decrypt()`, and
+/// returns `Error::InvalidParam` if there are no public keys, and the error of the last public key
+/// if none of them decrypts the message.
+///
+/// # Examples
+///
+/// ```
+/// use ntru::rand::RNG_DEFAULT;
+/// use ntru::encparams::EES401EP1;
+///
+/// let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+/// let kp = ntru::generate_multiple_key_pairs(&EES401EP1, &rand_ctx, 3).unwrap();
+/// let (private, publics) = (kp.get_private(), kp.get_publics());
+///
+/// let encrypted = ntru::encrypt(b"Hello", &publics[2], &EES401EP1, &rand_ctx).unwrap();
+/// let decrypted = ntru::decrypt_any(&encrypted, private, publics, &EES401EP1).unwrap();
+/// assert_eq!(&decrypted[..], b"Hello");
+/// ```
+pub fn decrypt_any(enc: &[u8],
+                   private: &PrivateKey,
+                   publics: &[PublicKey],
+                   params: &EncParams)
+                   -> Result<Box<[u8]>, Error> {
+    let mut result = Err(Error::InvalidParam);
+    for public in publics {
+        let mut kp = KeyPair::new(private.clone(), public.clone());
+        result = decrypt(enc, &kp, params);
+        kp.wipe();
+        // Errors that are not about the key are the same with any other public key
+        match result {
+            Ok(_) |
+            Err(Error::InvalidParam) |
+            Err(Error::InvalidCiphertextLength { .. }) => break,
+            Err(_) => {}
+        }
+    }
+    result
+}
+
+/// Decrypts a message with a key pair in the fixed layout of libntru
+pub(crate) fn decrypt_c(enc: &[u8], kp: &CKeyPair, params: &EncParams) -> Result<Box<[u8]>, Error> {
+    let enc_len = params.enc_len() as usize;
+    if enc_len == 0 {
+        return Err(Error::InvalidParam);
+    }
+    if enc.len() != enc_len {
+        return Err(Error::InvalidCiphertextLength {
+            expected: enc_len,
+            actual: enc.len(),
+        });
+    }
+
     let mut dec = vec![0u8; params.max_msg_len() as usize];
-    let mut dec_len = 0u16;
-    let result = unsafe { ffi::ntru_decrypt(&enc[0], kp, params, &mut dec[0], &mut dec_len) };
+    let result = if cfg!(target_endian = "big") {
+        // libntru would unpack the encrypted message in the native byte order
+        let public = PublicKey::from_c(kp.get_public());
+        low_level::decrypt_into(enc, kp.get_private(), public.get_h(), params, &mut dec)
+    } else {
+        let mut dec_len = 0u16;
+        match unsafe { ffi::ntru_decrypt(&enc[0], kp, params, &mut dec[0], &mut dec_len) } {
+            0 => Ok(dec_len as usize),
+            result => Err(Error::from(result)),
+        }
+    };
+    let final_dec = result.map(|len| dec[..len].to_vec().into_boxed_slice())
+        .map_err(decryption_error);
+    // The buffer holds the message, and on failure, part of it
+    types::wipe(&mut dec);
+    final_dec
+}
+
+/// Converts an error of the decryption
+///
+/// The errors caused by the encrypted message are all reported as `Error::DecryptionFailed`,
+/// unless the `verbose-errors` feature is enabled.
+pub(crate) fn decryption_error(e: Error) -> Error {
+    if cfg!(feature = "verbose-errors") || e.category() != ErrorCategory::Input {
+        e
+    } else {
+        Error::DecryptionFailed
+    }
+}
+
+/// Decrypts a message, that will be wiped from memory when dropped
+///
+/// Works like `decrypt()`, but returns a `SecretVec`, for messages such as keys that must not
+/// be left in memory. The message is not copied: the returned memory is the only copy.
+///
+/// # Examples
+///
+/// ```
+/// use ntru::rand::RNG_DEFAULT;
+/// use ntru::encparams::EES401EP1;
+///
+/// let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+/// let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+///
+/// let key = [42u8; 32];
+/// let encrypted = ntru::encrypt(&key, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+/// let decrypted = ntru::decrypt_secret(&encrypted, &kp, &EES401EP1).unwrap();
+/// assert_eq!(&decrypted[..], &key[..]);
+/// ```
+pub fn decrypt_secret(enc: &[u8], kp: &KeyPair, params: &EncParams) -> Result<SecretVec, Error> {
+    decrypt(enc, kp, params).map(|dec| SecretVec::new(dec.into_vec()))
+}
+
+/// Decrypts a batch of messages in parallel
+///
+/// Decrypts each encrypted message with the key pair, spreading the work across the rayon thread
+/// pool, and returns a result for each of them in the same order. A message that fails to decrypt
+/// does not affect the others. Only available with the `rayon` feature.
+#[cfg(feature = "rayon")]
+pub fn decrypt_batch(encs: &[&[u8]],
+                     kp: &KeyPair,
+                     params: &EncParams)
+                     -> Box<[Result<Box<[u8]>, Error>]> {
+    use rayon::prelude::*;
+
+    let decrypted: Vec<_> = encs.par_iter().map(|enc| decrypt(enc, kp, params)).collect();
+    decrypted.into_boxed_slice()
+}
+
+/// SHA-1 of "abc", from FIPS 180-2 appendix A
+const SHA1_ABC: [u8; 20] = [0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71,
+                            0x78, 0x50, 0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d];
+/// SHA-256 of "abc", from FIPS 180-2 appendix B
+const SHA256_ABC: [u8; 32] = [0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40,
+                              0xde, 0x5d, 0xae, 0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17,
+                              0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad];
+
+/// SHA3-256 of "abc", from FIPS 202
+#[cfg(feature = "extra-hashes")]
+const SHA3_256_ABC: [u8; 32] = [0x3a, 0x98, 0x5d, 0xa7, 0x4f, 0xe2, 0x25, 0xb2, 0x04, 0x5c, 0x17,
+                                0x2c, 0x6b, 0xd3, 0x90, 0xbd, 0x85, 0x5f, 0x08, 0x6e, 0x3e, 0x9d,
+                                0x52, 0x5b, 0x46, 0xbf, 0xe2, 0x45, 0x11, 0x43, 0x15, 0x32];
+/// BLAKE2b-256 of "abc", unkeyed
+#[cfg(feature = "extra-hashes")]
+const BLAKE2B_256_ABC: [u8; 32] = [0xbd, 0xdd, 0x81, 0x3c, 0x63, 0x42, 0x39, 0x72, 0x31, 0x71,
+                                   0xef, 0x3f, 0xee, 0x98, 0x57, 0x9b, 0x94, 0x96, 0x4e, 0x3b,
+                                   0xb1, 0xcb, 0x3e, 0x42, 0x72, 0x62, 0xc8, 0xc0, 0x68, 0xd5,
+                                   0x23, 0x19];
+
+/// SHA-256 of the exported public key and of the encrypted message of `self_test()` for EES401EP1
+const SELF_TEST_EES401EP1: ([u8; 32], [u8; 32]) = ([0x77, 0xea, 0x09, 0xd6, 0xdf, 0x27, 0x8b, 0x2b,
+                                                   0xd7, 0xa1, 0x12, 0x27, 0x27, 0x1e, 0x93, 0xc2,
+                                                   0xe0, 0x16, 0x9c, 0xc7, 0x6e, 0x73, 0x43, 0xd6,
+                                                   0x89, 0x31, 0xd6, 0x2b, 0xe5, 0x18, 0x13, 0xb4],
+                                                  [0x3a, 0x24, 0x78, 0x0f, 0xdb, 0xe0, 0x30, 0xd3,
+                                                   0x57, 0x4a, 0xd4, 0xa0, 0x49, 0x15, 0x55, 0x47,
+                                                   0x8e, 0x24, 0x50, 0x4f, 0x9d, 0x38, 0x12, 0x6d,
+                                                   0x26, 0xa2, 0xa1, 0x5b, 0x5f, 0x01, 0x0b, 0x7d]);
+/// SHA-256 of the exported public key and of the encrypted message of `self_test()` for EES401EP2
+const SELF_TEST_EES401EP2: ([u8; 32], [u8; 32]) = ([0xd5, 0x8f, 0x2f, 0xbd, 0xf1, 0x82, 0x17, 0x89,
+                                                   0x56, 0x7d, 0x38, 0x6a, 0x34, 0x04, 0x9c, 0xe7,
+                                                   0x7b, 0x55, 0xa0, 0xf8, 0xdb, 0x43, 0xf0, 0x72,
+                                                   0x7b, 0x78, 0xee, 0xe0, 0x66, 0xb6, 0x3e, 0xa1],
+                                                  [0xc3, 0x6a, 0x49, 0x91, 0x51, 0xb2, 0xf2, 0x49,
+                                                   0x73, 0x29, 0x63, 0x65, 0x0a, 0x4a, 0x95, 0xef,
+                                                   0xc1, 0xfb, 0xc6, 0x9a, 0x5e, 0x1c, 0x1d, 0x12,
+                                                   0xea, 0xb8, 0xa3, 0x1e, 0x87, 0xfb, 0x72, 0x31]);
 
-    if result == 0 {
-        let mut final_dec = Vec::with_capacity(dec_len as usize);
-        final_dec.extend(dec.into_iter().take(dec_len as usize));
-        Ok(final_dec.into_boxed_slice())
+/// Power-on self test
+///
+/// Checks that the linked libntru is compatible with `check_library()`, and the SHA-1 and SHA-256
+/// implementations of libntru against known answers, as well as
+/// SHA3-256 and BLAKE2b-256 with the `extra-hashes` feature. Then, with a ternary and a
+/// product-form parameter set, it checks that the key generation is deterministic, and runs the
+/// known-answer tests: a key pair derived from a fixed seed, as `self_test_key_pair()` does, must
+/// give the known public key, the encryption of a fixed message with fixed random bytes must give
+/// the known encrypted message, and its decryption the message. The product-form one is skipped
+/// with the `avoid-hamming-wt-patent` feature. It returns the first check that failed.
+pub fn self_test() -> Result<(), SelfTestError> {
+    check_library()?;
+    let mut sha1 = [0u8; 20];
+    let mut sha256 = [0u8; 32];
+    unsafe {
+        ffi::ntru_sha1(&b"abc"[0], 3, &mut sha1[0]);
+        ffi::ntru_sha256(&b"abc"[0], 3, &mut sha256[0]);
+    }
+    if sha1 != SHA1_ABC || sha256 != SHA256_ABC {
+        return Err(SelfTestError::Hash);
+    }
+    #[cfg(feature = "extra-hashes")]
+    {
+        let mut sha3_256 = [0u8; 32];
+        let mut blake2b_256 = [0u8; 32];
+        unsafe {
+            hash::ntru_sha3_256(&b"abc"[0], 3, &mut sha3_256[0]);
+            hash::ntru_blake2b_256(&b"abc"[0], 3, &mut blake2b_256[0]);
+        }
+        if sha3_256 != SHA3_256_ABC || blake2b_256 != BLAKE2B_256_ABC {
+            return Err(SelfTestError::Hash);
+        }
+    }
+
+    let seed = b"ntru-rs self test";
+    let random = |seed: &[u8]| {
+        rand::init_det(&rand::RNG_CTR_DRBG, seed)
+            .and_then(|rand_ctx| rand::generate(64, &rand_ctx))
+            .map_err(|_| SelfTestError::Rng)
+    };
+    if random(seed)? != random(seed)? {
+        return Err(SelfTestError::Rng);
+    }
+
+    let param_sets = [(encparams::EES401EP1, SELF_TEST_EES401EP1),
+                      (encparams::EES401EP2, SELF_TEST_EES401EP2)];
+    for &(ref params, (public_digest, enc_digest)) in
+        param_sets.iter().filter(|&(params, _)| params.sampler().is_some()) {
+        let key_pair = || {
+            rand::init_det(&rand::RNG_CTR_DRBG, seed)
+                .and_then(|rand_ctx| generate_key_pair(params, &rand_ctx))
+                .map_err(|_| SelfTestError::KeyGeneration)
+        };
+        if key_pair()? != key_pair()? {
+            return Err(SelfTestError::KeyGeneration);
+        }
+
+        let kp = self_test_key_pair(seed, params).map_err(|_| SelfTestError::KeyGeneration)?;
+        if sha256_digest(&kp.get_public().export(params)) != public_digest {
+            return Err(SelfTestError::KeyGeneration);
+        }
+
+        let msg = b"ntru-rs self test message";
+        let b = vec![0x5a; params.get_db() as usize / 8];
+        let enc = encrypt_with_blinding(msg, kp.get_public(), params, &b)
+            .map_err(|_| SelfTestError::Encryption)?;
+        if sha256_digest(&enc) != enc_digest {
+            return Err(SelfTestError::Encryption);
+        }
+
+        match decrypt(&enc, &kp, params) {
+            Ok(ref dec) if dec[..] == msg[..] => {}
+            _ => return Err(SelfTestError::Decryption),
+        }
+    }
+    Ok(())
+}
+
+/// Derives the key pair of the known-answer tests of `self_test()` and `kat` from a seed
+///
+/// The ternary polynomials `t` of the private key and `g` are generated by the IGF of the
+/// `low_level` module from the seed, `g` with one negative one less than ones as in
+/// `generate_key_pair()`, and the public key is `h = 3 * g / (1 + 3 * t)` modulo `q`,
+/// so the key pair does not depend on the random number generators of libntru. Returns an
+/// `Error::InvalidKey` if `1 + 3 * t` is not invertible.
+pub(crate) fn self_test_key_pair(seed: &[u8], params: &EncParams) -> Result<KeyPair, Error> {
+    let q = params.get_q();
+    let mut igf = low_level::IndexGenerator::new(seed, params)?;
+    let t = if params.is_product() {
+        let f1 = igf.tern_poly(params.get_df1())?;
+        let f2 = igf.tern_poly(params.get_df2())?;
+        let f3 = igf.tern_poly(params.get_df3())?;
+        PrivPoly::from_prod_poly(ProdPoly::new(params.get_n(), f1, f2, f3))?
     } else {
-        Err(Error::from(result))
+        PrivPoly::new_with_tern_poly(igf.tern_poly(params.get_df1())?)
+    };
+    let g = igf.tern_poly_with(params.get_dg(), params.get_dg().saturating_sub(1))?;
+
+    let (fq, invertible) = t.invert(q - 1);
+    if !invertible {
+        return Err(Error::InvalidKey);
+    }
+    let mut h = fq.mult_tern(&g, q - 1)?;
+    h.mult_fac(3);
+    h.mod_mask(q - 1);
+    Ok(KeyPair::new(PrivateKey::new(q, t), PublicKey::new(q, h)))
+}
+
+/// SHA-256 of the data, with the implementation of libntru
+fn sha256_digest(data: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    unsafe { ffi::ntru_sha256(data.as_ptr(), data.len() as u16, &mut digest[0]) };
+    digest
+}
+
+/// Version of the system libntru the crate was built against
+///
+/// With the `system-libntru` feature, the crate links the libntru found by pkg-config instead of
+/// compiling the bundled one, and this is the version pkg-config gave. The build fails if it is
+/// not a version with the ABI of the bundled libntru, but a shared library can be replaced after
+/// the build, so `check_library()` checks it again at runtime. Without the feature, it is `None`.
+pub fn system_libntru_version() -> Option<&'static str> {
+    option_env!("NTRU_SYSTEM_LIBNTRU_VERSION")
+}
+
+/// Checks that the linked libntru has the ABI of the bundled one
+///
+/// libntru does not report its version at runtime, so this checks that it reads the parameter
+/// sets with the same layout as this crate: the length of the encrypted messages computed by
+/// libntru must be the one of `EncParams::enc_len()` for all the built-in parameter sets. On
+/// little-endian targets, it also checks that libntru packs polynomials in the canonical layout of
+/// the crate. On big-endian targets, libntru packs them in the native byte order, so the crate
+/// packs them itself. It is also the first check of `self_test()`. Returns
+/// `SelfTestError::Library` otherwise.
+pub fn check_library() -> Result<(), SelfTestError> {
+    for params in encparams::ALL_PARAM_SETS.iter() {
+        if unsafe { ffi::ntru_enc_len(params) } != params.enc_len() {
+            return Err(SelfTestError::Library);
+        }
+    }
+    if cfg!(target_endian = "little") && !types::libntru_is_canonical() {
+        return Err(SelfTestError::Library);
     }
+    Ok(())
 }
+
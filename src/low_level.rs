@@ -0,0 +1,710 @@
+//! Low level primitives
+//!
+//! This module exposes the building blocks of P1363.1 that libntru uses internally when
+//! encrypting, so that test vectors can be reproduced without reimplementing them:
+//!
+//! * `BitString`, the bit string the IGF extracts its indices from.
+//! * `IndexGenerator`, the index generation function (IGF-2), used to derive the blinding
+//!   polynomial `r` from the seed.
+//! * `mgf()`, the mask generation function (MGF-TP-1), used to derive the mask polynomial.
+//! * `encrypt()` and `decrypt()`, the SVES encryption scheme built on them.
+//! * `export_private()` and `import_private()`, the layout of the exported private keys.
+//!
+//! They are implemented in Rust and follow libntru bit for bit. The hash counter is appended to the
+//! seed as 2 bytes in little-endian order, as libntru does on little-endian targets, so the
+//! results are the same on big-endian ones. On big-endian targets, where libntru would encode the
+//! encrypted messages and the private keys in the native byte order, the crate encrypts, decrypts,
+//! exports and imports private keys with these functions instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::encparams::EES439EP1;
+//! use ntru::low_level::{IndexGenerator, mgf};
+//!
+//! let mut igf = IndexGenerator::new(b"seed", &EES439EP1).unwrap();
+//! let r = igf.tern_poly(EES439EP1.get_n() / 3).unwrap();
+//! assert_eq!(r.get_ones().len(), (EES439EP1.get_n() / 3) as usize);
+//!
+//! let mask = mgf(b"seed", &EES439EP1).unwrap();
+//! assert!(mask.get_coeffs().iter().all(|&c| c >= -1 && c <= 1));
+//! ```
+use types::{self, IntPoly, TernPoly, ProdPoly, PrivPoly, PrivateKey, PublicKey, Error, MAX_ONES};
+use encparams::EncParams;
+use rand::{self, RandContext};
+use poly;
+use ct::{self, Choice, ConstantTimeEq, ConstantTimeGreater, ConditionallySelectable};
+
+/// A string of bits
+///
+/// Bits are appended after the most significant bit, so the first byte holds the first 8 bits
+/// appended, starting at its least significant bit. The leading bits are the last ones appended.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitString {
+    /// The bytes holding the bits, the unused bits of the last one are always 0
+    buf: Vec<u8>,
+    /// Number of bits used in the last byte, from 1 to 8, or 0 if empty
+    last_byte_bits: u8,
+}
+
+impl BitString {
+    /// Creates an empty bit string
+    pub fn new() -> BitString {
+        Default::default()
+    }
+
+    /// Get the number of bits
+    pub fn num_bits(&self) -> usize {
+        if self.buf.is_empty() {
+            0
+        } else {
+            (self.buf.len() - 1) * 8 + self.last_byte_bits as usize
+        }
+    }
+
+    /// Get the bytes of the bit string
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Appends the 8 bits of a byte
+    pub fn append_byte(&mut self, b: u8) {
+        if self.buf.is_empty() || self.last_byte_bits == 8 {
+            self.buf.push(b);
+            self.last_byte_bits = 8;
+        } else {
+            let last = self.buf.len() - 1;
+            self.buf[last] |= b << self.last_byte_bits;
+            self.buf.push(b >> (8 - self.last_byte_bits));
+        }
+    }
+
+    /// Appends all the bits of the bytes
+    pub fn append(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.append_byte(*b);
+        }
+    }
+
+    /// Returns a bit string with the `num_bits` trailing bits, i.e. the first ones appended
+    ///
+    /// If the bit string is shorter, it is returned whole.
+    pub fn trailing(&self, num_bits: usize) -> BitString {
+        let num_bits = if num_bits < self.num_bits() { num_bits } else { self.num_bits() };
+        let mut trailing = BitString {
+            buf: self.buf[..num_bits.div_ceil(8)].to_vec(),
+            last_byte_bits: 0,
+        };
+        trailing.set_num_bits(num_bits);
+        trailing
+    }
+
+    /// Returns the `num_bits` leading bits as an integer, with the last bit appended as its most
+    /// significant bit
+    ///
+    /// At most 32 bits are returned. If the bit string is shorter, all of its bits are returned.
+    pub fn leading(&self, num_bits: usize) -> u32 {
+        let total = self.num_bits();
+        let num_bits = if num_bits < 32 { num_bits } else { 32 };
+        let num_bits = if num_bits < total { num_bits } else { total };
+        let start = total - num_bits;
+        (0..num_bits).fold(0, |sum, i| {
+            let bit = start + i;
+            sum | ((self.buf[bit / 8] >> (bit % 8)) as u32 & 1) << i
+        })
+    }
+
+    /// Removes the `num_bits` leading bits
+    ///
+    /// If the bit string is shorter, it will be empty.
+    pub fn truncate(&mut self, num_bits: usize) {
+        let total = self.num_bits();
+        let new_len = total.saturating_sub(num_bits);
+        self.buf.truncate(new_len.div_ceil(8));
+        self.set_num_bits(new_len);
+    }
+
+    /// Sets the number of used bits of the last byte and clears the unused ones
+    fn set_num_bits(&mut self, num_bits: usize) {
+        if self.buf.is_empty() {
+            self.last_byte_bits = 0;
+        } else if num_bits.is_multiple_of(8) {
+            self.last_byte_bits = 8;
+        } else {
+            self.last_byte_bits = (num_bits % 8) as u8;
+            let last = self.buf.len() - 1;
+            self.buf[last] &= (1 << self.last_byte_bits) - 1;
+        }
+    }
+}
+
+/// Index generation function
+///
+/// Generates pseudorandom indices in `[0, N)` from a seed, as IGF-2 in P1363.1. Each candidate
+/// index is taken from the `c` leading bits of a bit string filled with `hash(seed || counter)`,
+/// and rejected if it would bias the result modulo `N`.
+#[derive(Debug, Clone)]
+pub struct IndexGenerator<'a> {
+    /// The parameters, for the hash function
+    params: &'a EncParams,
+    /// The seed
+    seed: Box<[u8]>,
+    /// The number of coefficients
+    n: u16,
+    /// The number of bits of each candidate index
+    c: u16,
+    /// Candidates greater than or equal to this threshold are rejected
+    rnd_thresh: u32,
+    /// The hash counter
+    counter: u16,
+    /// The number of unused bits in `buf`
+    rem_len: usize,
+    /// The hash output
+    buf: BitString,
+}
+
+impl<'a> IndexGenerator<'a> {
+    /// Creates an index generator for the seed
+    ///
+    /// It does the minimum number of hash calls of the parameter set. Returns
+    /// `Error::InvalidParam` if the parameter set is not valid.
+    pub fn new(seed: &[u8], params: &'a EncParams) -> Result<IndexGenerator<'a>, Error> {
+        let mut igfs = IndexGenerator::new_batch(&[seed.to_vec()], params)?;
+        Ok(igfs.remove(0))
+    }
+
+    /// Creates an index generator for each seed
+    ///
+    /// The initial hash calls of all of them are done at once with `EncParams::hash_many()`.
+    pub(crate) fn new_batch(seeds: &[Vec<u8>],
+                            params: &'a EncParams)
+                            -> Result<Vec<IndexGenerator<'a>>, Error> {
+        let n = params.get_n();
+        let c = params.get_c();
+        if n == 0 || c == 0 || c > 16 || params.get_hlen() == 0 {
+            return Err(Error::InvalidParam);
+        }
+
+        let mut igfs: Vec<IndexGenerator> = seeds.iter()
+            .map(|seed| {
+                IndexGenerator {
+                    params,
+                    seed: seed.clone().into_boxed_slice(),
+                    n,
+                    c,
+                    rnd_thresh: (1 << c) - (1 << c) % n as u32,
+                    counter: 0,
+                    rem_len: params.get_min_calls_r() as usize * 8 * params.get_hlen() as usize,
+                    buf: BitString::new(),
+                }
+            })
+            .collect();
+        for counter in 0..params.get_min_calls_r() {
+            let inputs: Vec<Vec<u8>> =
+                seeds.iter().map(|seed| with_counter(seed, counter)).collect();
+            for (igf, h) in igfs.iter_mut().zip(params.hash_many(&inputs)) {
+                igf.buf.append(&h);
+                igf.counter += 1;
+            }
+        }
+        Ok(igfs)
+    }
+
+    /// Generates the next index
+    pub fn next_index(&mut self) -> u16 {
+        let c = self.c as usize;
+        loop {
+            if self.rem_len < c {
+                let mut m = self.buf.trailing(self.rem_len);
+                let hlen = self.params.get_hlen() as usize;
+                let hash_calls = (c - self.rem_len).div_ceil(hlen);
+                for _ in 0..hash_calls {
+                    let h = self.hash_next();
+                    m.append(&h);
+                    self.rem_len += 8 * hlen;
+                }
+                self.buf = m;
+            }
+
+            let i = self.buf.leading(c);
+            self.buf.truncate(c);
+            self.rem_len -= c;
+            if i < self.rnd_thresh {
+                return (i % self.n as u32) as u16;
+            }
+        }
+    }
+
+    /// Generates a ternary polynomial with `df` ones and `df` negative ones
+    ///
+    /// The indices of the ones are generated first, then the ones of the negative ones, skipping
+    /// the repeated ones. Returns `Error::InvalidWeight` if the polynomial cannot have that many
+    /// non-zero coefficients.
+    pub fn tern_poly(&mut self, df: u16) -> Result<TernPoly, Error> {
+        self.tern_poly_with(df, df)
+    }
+
+    /// Generates a ternary polynomial with `num_ones` ones and `num_neg_ones` negative ones
+    ///
+    /// Works like `tern_poly()`, for polynomials such as `g`, that has one negative one less than
+    /// ones.
+    pub fn tern_poly_with(&mut self,
+                          num_ones: u16,
+                          num_neg_ones: u16)
+                          -> Result<TernPoly, Error> {
+        if num_ones as usize > MAX_ONES || num_neg_ones as usize > MAX_ONES ||
+           num_ones as usize + num_neg_ones as usize > self.n as usize {
+            return Err(Error::InvalidWeight);
+        }
+
+        let mut used = vec![false; self.n as usize];
+        let mut ones = Vec::with_capacity(num_ones as usize);
+        while ones.len() < num_ones as usize {
+            let idx = self.next_index();
+            if !used[idx as usize] {
+                used[idx as usize] = true;
+                ones.push(idx);
+            }
+        }
+        let mut neg_ones = Vec::with_capacity(num_neg_ones as usize);
+        while neg_ones.len() < num_neg_ones as usize {
+            let idx = self.next_index();
+            if !used[idx as usize] {
+                used[idx as usize] = true;
+                neg_ones.push(idx);
+            }
+        }
+        Ok(TernPoly::new(self.n, &ones, &neg_ones))
+    }
+
+    /// Hashes the seed with the current counter and increments it
+    fn hash_next(&mut self) -> Vec<u8> {
+        let h = self.params.hash(&with_counter(&self.seed, self.counter));
+        self.counter = self.counter.wrapping_add(1);
+        h
+    }
+}
+
+/// Mask generation function
+///
+/// Generates a polynomial with `N` coefficients in `{-1, 0, 1}` from a seed, as MGF-TP-1 in
+/// P1363.1. The seed is hashed first if the parameter set requires it, and then each byte of
+/// `hash(seed || counter)` lower than 3^5 gives 5 coefficients. Returns `Error::InvalidParam`
+/// if the parameter set is not valid.
+pub fn mgf(seed: &[u8], params: &EncParams) -> Result<IntPoly, Error> {
+    let mut masks = mgf_batch(&[seed.to_vec()], params)?;
+    Ok(masks.remove(0))
+}
+
+/// Generates a mask polynomial for each seed, as `mgf()`
+///
+/// The hashes of the seeds and the initial hash calls of all of them are done at once with
+/// `EncParams::hash_many()`.
+fn mgf_batch(seeds: &[Vec<u8>], params: &EncParams) -> Result<Vec<IntPoly>, Error> {
+    if params.get_n() == 0 || params.get_hlen() == 0 {
+        return Err(Error::InvalidParam);
+    }
+
+    let zs = if params.hashes_seed() {
+        params.hash_many(seeds)
+    } else {
+        seeds.to_vec()
+    };
+    let min_calls = params.get_min_calls_mask();
+    let mut bufs = vec![Vec::new(); zs.len()];
+    for counter in 0..min_calls {
+        let inputs: Vec<Vec<u8>> = zs.iter().map(|z| with_counter(z, counter)).collect();
+        for (buf, h) in bufs.iter_mut().zip(params.hash_many(&inputs)) {
+            buf.extend_from_slice(&h);
+        }
+    }
+    Ok(zs.iter().zip(bufs).map(|(z, buf)| mask_poly(z, buf, min_calls, params)).collect())
+}
+
+/// Takes the coefficients of the mask from the hash output `buf`, hashing `z` with the next
+/// counters when more are needed
+fn mask_poly(z: &[u8], mut buf: Vec<u8>, mut counter: u16, params: &EncParams) -> IntPoly {
+    let n = params.get_n() as usize;
+    let mut coeffs = Vec::with_capacity(n);
+    loop {
+        for &o in &buf {
+            // 3^5 = 243, larger bytes would bias the coefficients
+            if o >= 243 {
+                continue;
+            }
+            let mut o = o as i16;
+            for _ in 0..5 {
+                coeffs.push(o % 3 - 1);
+                if coeffs.len() == n {
+                    return IntPoly::new(&coeffs);
+                }
+                o /= 3;
+            }
+        }
+
+        buf = params.hash(&with_counter(z, counter));
+        counter = counter.wrapping_add(1);
+    }
+}
+
+/// Encrypts a message with the SVES scheme of P1363.1
+///
+/// Works like `ntru::encrypt()`, and gives the same encrypted message as libntru on
+/// little-endian targets for the same random bytes, on any target. The blinding polynomial is
+/// generated with `IndexGenerator` and the mask with `mgf()`.
+pub fn encrypt(msg: &[u8],
+               public: &PublicKey,
+               params: &EncParams,
+               rand_ctx: &RandContext)
+               -> Result<Box<[u8]>, Error> {
+    let mut enc = vec![0u8; params.enc_len() as usize];
+    encrypt_into(msg, public.get_h(), params, rand_ctx, &mut enc)?;
+    Ok(enc.into_boxed_slice())
+}
+
+/// Decrypts a message with the SVES scheme of P1363.1
+///
+/// Works like `ntru::decrypt()`, with the private key and the public key it was encrypted with,
+/// but returns the detailed error of the check that failed, as libntru does.
+pub fn decrypt(enc: &[u8],
+               private: &PrivateKey,
+               public: &PublicKey,
+               params: &EncParams)
+               -> Result<Box<[u8]>, Error> {
+    let mut dec = vec![0u8; params.max_msg_len() as usize];
+    let result = decrypt_into(enc, private, public.get_h(), params, &mut dec)
+        .map(|len| dec[..len].to_vec().into_boxed_slice());
+    types::wipe(&mut dec);
+    result
+}
+
+/// Exports a private key
+///
+/// Works like `PrivateKey::export()`: `N` and `q` are written as 2 byte big-endian integers,
+/// followed by a flags byte and each ternary polynomial of the key, with its number of ones and of
+/// negative ones as 2 byte big-endian integers, and their indices packed with the bits needed for
+/// `N - 1`, least significant bit first.
+pub fn export_private(private: &PrivateKey, params: &EncParams) -> Box<[u8]> {
+    let mut arr = vec![0u8; params.private_len() as usize];
+    export_private_into(private, params, &mut arr);
+    arr.into_boxed_slice()
+}
+
+/// Imports a private key of the parameter set, exported with `export_private()`
+///
+/// Returns `Error::InvalidKey` if the array does not have the layout of the private keys of the
+/// parameter set, or an index is not lower than `N`.
+pub fn import_private(arr: &[u8], params: &EncParams) -> Result<PrivateKey, Error> {
+    types::check_private_layout(arr, params)?;
+    let n = params.get_n();
+    let bits = types::private_index_bits(n);
+    let mut polys = Vec::with_capacity(3);
+    let mut pos = 5;
+    for df in types::private_weights(params) {
+        let df = df as usize;
+        let len = (bits as usize * 2 * df).div_ceil(8);
+        let packed = arr.get(pos + 4..pos + 4 + len).ok_or(Error::InvalidKey)?;
+        let mut indices = vec![0i16; 2 * df];
+        poly::unpack(packed, bits, &mut indices);
+        let indices: Vec<u16> = indices.iter().map(|&i| i as u16).collect();
+        polys.push(TernPoly::try_new(n, &indices[..df], &indices[df..])
+            .map_err(|_| Error::InvalidKey)?);
+        pos += 4 + len;
+    }
+
+    let mut polys = polys.into_iter();
+    let mut next = || polys.next().ok_or(Error::InvalidKey);
+    let t = if params.is_product() {
+        let (f1, f2, f3) = (next()?, next()?, next()?);
+        PrivPoly::from_prod_poly(ProdPoly::new(n, f1, f2, f3))?
+    } else {
+        PrivPoly::new_with_tern_poly(next()?)
+    };
+    Ok(PrivateKey::new(params.get_q(), t))
+}
+
+/// Exports a private key into `arr`, that must be `params.private_len()` bytes long
+pub(crate) fn export_private_into(private: &PrivateKey, params: &EncParams, arr: &mut [u8]) {
+    let (n, q) = (params.get_n(), private.get_q());
+    let t = private.get_t();
+    // libntru sets the bits of the ternary polynomials and of f = 1 + 3t, and one for product form
+    let flags = if t.is_product() { 7 } else { 3 };
+    let polys = if t.is_product() {
+        let prod = t.get_poly_prod();
+        vec![prod.get_f1(), prod.get_f2(), prod.get_f3()]
+    } else {
+        vec![t.get_poly_tern()]
+    };
+
+    if let Some(header) = arr.get_mut(..5) {
+        header.copy_from_slice(&[(n >> 8) as u8, n as u8, (q >> 8) as u8, q as u8, flags]);
+    }
+    let bits = types::private_index_bits(n);
+    let mut pos = 5;
+    for p in polys {
+        let (ones, neg_ones) = (p.get_ones(), p.get_neg_ones());
+        let len = (bits as usize * (ones.len() + neg_ones.len())).div_ceil(8);
+        let out = match arr.get_mut(pos..pos + 4 + len) {
+            Some(out) => out,
+            None => return,
+        };
+        let (num_ones, num_neg_ones) = (ones.len() as u16, neg_ones.len() as u16);
+        out[..4].copy_from_slice(&[(num_ones >> 8) as u8,
+                                   num_ones as u8,
+                                   (num_neg_ones >> 8) as u8,
+                                   num_neg_ones as u8]);
+        poly::pack(ones.iter().chain(neg_ones).cloned(), bits, &mut out[4..]);
+        pos += 4 + len;
+    }
+}
+
+/// Encrypts a message for the public key `h` into `enc`, that must be `params.enc_len()` bytes
+/// long
+pub(crate) fn encrypt_into(msg: &[u8],
+                           h: &IntPoly,
+                           params: &EncParams,
+                           rand_ctx: &RandContext,
+                           enc: &mut [u8])
+                           -> Result<(), Error> {
+    encrypt_batch_into(&[msg], h, params, rand_ctx, &mut [enc])
+}
+
+/// Encrypts the messages for the public key `h` into `encs`, that must be `params.enc_len()`
+/// bytes long each
+///
+/// The blinding polynomials and the masks of all the messages are generated together, so their
+/// hashes are computed with the 4-way and 8-way hash functions of the parameter set. The messages
+/// whose encryption has to be retried are encrypted again together.
+pub(crate) fn encrypt_batch_into(msgs: &[&[u8]],
+                                 h: &IntPoly,
+                                 params: &EncParams,
+                                 rand_ctx: &RandContext,
+                                 encs: &mut [&mut [u8]])
+                                 -> Result<(), Error> {
+    let n = params.get_n() as usize;
+    let q = params.get_q();
+    let blen = params.get_db() as usize / 8;
+    if n == 0 || q < 2 || q & (q - 1) != 0 || blen == 0 || encs.len() != msgs.len() ||
+       encs.iter().any(|enc| enc.len() != params.enc_len() as usize) {
+        return Err(Error::InvalidParam);
+    }
+    if h.get_coeffs().len() != n {
+        return Err(Error::InvalidKey);
+    }
+    if msgs.iter().any(|msg| msg.len() > params.max_msg_len() as usize) {
+        return Err(Error::MessageTooLong);
+    }
+
+    let htrunc = h.to_arr(params);
+    let htrunc = &htrunc[..params.get_pklen() as usize / 8];
+    let mut pending: Vec<usize> = (0..msgs.len()).collect();
+    while !pending.is_empty() {
+        let mut mtrins = Vec::with_capacity(pending.len());
+        let mut seeds = Vec::with_capacity(pending.len());
+        for &i in &pending {
+            let msg = msgs[i];
+            // M = b || the length of the message || the message || zeros
+            let mut b = rand::generate(blen as u16, rand_ctx)?;
+            let mut m = vec![0u8; (n * 3 / 2).div_ceil(8) + 1];
+            m[..blen].copy_from_slice(&b);
+            m[blen] = msg.len() as u8;
+            m[blen + 1..blen + 1 + msg.len()].copy_from_slice(msg);
+            mtrins.push(from_sves(&m, n));
+            types::wipe(&mut m);
+            seeds.push(blinding_seed(msg, &b, htrunc, params));
+            types::wipe(&mut b);
+        }
+
+        let igfs = IndexGenerator::new_batch(&seeds, params);
+        for seed in &mut seeds {
+            types::wipe(seed);
+        }
+        let mut big_rs = Vec::with_capacity(pending.len());
+        for mut igf in igfs? {
+            big_rs.push(h.mult_priv(&igf_blinding_poly(&mut igf, params)?, q - 1)?);
+        }
+        let masks = mgf_batch(&big_rs.iter().map(to_arr4).collect::<Vec<_>>(), params)?;
+
+        let mut retry = Vec::new();
+        for (((&i, mtrin), big_r), mask) in pending.iter().zip(mtrins).zip(&big_rs).zip(&masks) {
+            let mtrin: Vec<i16> = mtrin.iter()
+                .zip(mask.get_coeffs())
+                .map(|(&m, &k)| (m + k).rem_euclid(3))
+                .collect();
+            if !bool::from(ct::min_rep_weight(&mtrin, params.get_dm0())) {
+                retry.push(i);
+                continue;
+            }
+
+            let e = big_r.get_coeffs()
+                .iter()
+                .zip(&mtrin)
+                .map(|(&r, &m)| r.wrapping_add(m) as u16);
+            poly::pack(e, q.trailing_zeros(), encs[i]);
+        }
+        pending = retry;
+    }
+    Ok(())
+}
+
+/// Decrypts a message with the private key and the public key `h` into `dec`, that must be at
+/// least `params.max_msg_len()` bytes long, and returns its length
+///
+/// Like libntru, all the checks are done before returning the error of the first one that failed.
+/// The checks of the message representative, the padding and the blinding polynomial are done in
+/// constant time with the helpers of the `ct` module.
+pub(crate) fn decrypt_into(enc: &[u8],
+                           private: &PrivateKey,
+                           h: &IntPoly,
+                           params: &EncParams,
+                           dec: &mut [u8])
+                           -> Result<usize, Error> {
+    let n = params.get_n() as usize;
+    let q = params.get_q();
+    let blen = params.get_db() as usize / 8;
+    let max_len = params.max_msg_len() as usize;
+    if n == 0 || q < 2 || q & (q - 1) != 0 || dec.len() < max_len {
+        return Err(Error::InvalidParam);
+    }
+    if enc.len() != params.enc_len() as usize {
+        return Err(Error::InvalidCiphertextLength {
+            expected: params.enc_len() as usize,
+            actual: enc.len(),
+        });
+    }
+    if h.get_coeffs().len() != n {
+        return Err(Error::InvalidKey);
+    }
+
+    // ci = f * e = e + 3 * t * e, centered modulo q and reduced modulo 3
+    let e = IntPoly::from_arr(enc, n as u16, q);
+    let mut ci = e.mult_priv(private.get_t(), q - 1)?;
+    ci.mult_fac(3);
+    let mut ci = IntPoly::new(&ci.get_coeffs()
+        .iter()
+        .zip(e.get_coeffs())
+        .map(|(&c, &e)| c.wrapping_add(e))
+        .collect::<Vec<i16>>());
+    ci.mod_center(q);
+    ci.mod3();
+    let weight_ok = ct::min_rep_weight(ci.get_coeffs(), params.get_dm0());
+
+    let mut c_r = IntPoly::new(&e.get_coeffs()
+        .iter()
+        .zip(ci.get_coeffs())
+        .map(|(&e, &c)| e.wrapping_sub(c))
+        .collect::<Vec<i16>>());
+    c_r.mod_mask(q - 1);
+    let mask = mgf(&to_arr4(&c_r), params)?;
+    let cmtrin: Vec<i16> = ci.get_coeffs()
+        .iter()
+        .zip(mask.get_coeffs())
+        .map(|(&c, &k)| (c - k).rem_euclid(3))
+        .collect();
+    let mut cm = vec![0u8; (n * 3).div_ceil(2).div_ceil(8)];
+    let sves_ok = to_sves(&cmtrin, &mut cm);
+
+    let len_ok = !(cm[blen] as u16).ct_gt(&(max_len as u16));
+    let cl = u16::conditional_select(&(max_len as u16), &(cm[blen] as u16), len_ok) as usize;
+    let (cb, rest) = cm.split_at(blen);
+    let (cmsg, pad) = rest[1..].split_at(cl);
+    let pad_ok = ct::is_zero(pad);
+
+    let cr = blinding_poly(cmsg, cb, h, params)?;
+    let mut c_r_prime = h.mult_priv(&cr, q - 1)?;
+    c_r_prime.mod_mask(q - 1);
+    let r_ok = c_r_prime.get_coeffs().ct_eq(c_r.get_coeffs());
+
+    let valid = weight_ok & sves_ok & len_ok & pad_ok & r_ok;
+    ct::conditional_copy(&mut dec[..cl], cmsg, valid)?;
+    types::wipe(&mut cm);
+    let checks = [(weight_ok, Error::Md0Violation),
+                  (sves_ok, Error::InvalidEncoding),
+                  (len_ok, Error::MessageTooLong),
+                  (pad_ok, Error::NoZeroPad),
+                  (r_ok, Error::InvalidEncoding)];
+    match checks.iter().find(|&&(ok, _)| !bool::from(ok)) {
+        Some(&(_, e)) => Err(e),
+        None => Ok(cl),
+    }
+}
+
+/// Generates the blinding polynomial `r` from the seed `OID || m || b || htrunc`, where `htrunc`
+/// is the first `pklen / 8` bytes of the packed public key
+fn blinding_poly(msg: &[u8], b: &[u8], h: &IntPoly, params: &EncParams) -> Result<PrivPoly, Error> {
+    let htrunc = h.to_arr(params);
+    let mut seed = blinding_seed(msg, b, &htrunc[..params.get_pklen() as usize / 8], params);
+
+    let igf = IndexGenerator::new(&seed, params);
+    types::wipe(&mut seed);
+    igf_blinding_poly(&mut igf?, params)
+}
+
+/// Builds the seed `OID || m || b || htrunc` of the blinding polynomial
+fn blinding_seed(msg: &[u8], b: &[u8], htrunc: &[u8], params: &EncParams) -> Vec<u8> {
+    let mut seed = params.get_oid().to_vec();
+    seed.extend_from_slice(msg);
+    seed.extend_from_slice(b);
+    seed.extend_from_slice(htrunc);
+    seed
+}
+
+/// Generates the blinding polynomial with the weights of the parameter set from the IGF
+fn igf_blinding_poly(igf: &mut IndexGenerator, params: &EncParams) -> Result<PrivPoly, Error> {
+    if params.is_product() {
+        let f1 = igf.tern_poly(params.get_df1())?;
+        let f2 = igf.tern_poly(params.get_df2())?;
+        let f3 = igf.tern_poly(params.get_df3())?;
+        PrivPoly::from_prod_poly(ProdPoly::new(params.get_n(), f1, f2, f3))
+    } else {
+        Ok(PrivPoly::new_with_tern_poly(igf.tern_poly(params.get_df1())?))
+    }
+}
+
+/// Converts bytes to coefficients modulo 3
+///
+/// Each 3 bits, least significant bit first, give 2 coefficients: read as an integer from 0 to 7
+/// with the first bit as the most significant one, they are its 2 digits in base 3. If `N` is odd,
+/// the last coefficient is 0.
+fn from_sves(m: &[u8], n: usize) -> Vec<i16> {
+    let bit = |i: usize| m.get(i / 8).map_or(0, |b| (b >> (i % 8)) as i16 & 1);
+    let mut coeffs = vec![0i16; n];
+    for (i, pair) in coeffs.chunks_exact_mut(2).enumerate() {
+        let digits = bit(3 * i) << 2 | bit(3 * i + 1) << 1 | bit(3 * i + 2);
+        pair[0] = digits / 3;
+        pair[1] = digits % 3;
+    }
+    coeffs
+}
+
+/// Converts coefficients modulo 3 back to bytes, as written by `from_sves()`
+///
+/// Returns an unset `Choice` if 2 coefficients are both 2, that `from_sves()` never gives.
+fn to_sves(coeffs: &[i16], m: &mut [u8]) -> Choice {
+    let mut valid = Choice::from(1);
+    for (i, pair) in coeffs.chunks_exact(2).enumerate() {
+        let digits = pair[0] * 3 + pair[1];
+        valid &= !(digits as u16).ct_gt(&7);
+        for (j, shift) in [2, 1, 0].iter().enumerate() {
+            let bit = 3 * i + j;
+            if let Some(b) = m.get_mut(bit / 8) {
+                *b |= (((digits >> shift) & 1) as u8) << (bit % 8);
+            }
+        }
+    }
+    valid
+}
+
+/// Packs the coefficients modulo 4, 2 bits each, least significant bit first
+fn to_arr4(p: &IntPoly) -> Vec<u8> {
+    let mut arr = vec![0u8; (p.get_coeffs().len() * 2).div_ceil(8)];
+    poly::pack(p.get_coeffs().iter().map(|&c| c as u16), 2, &mut arr);
+    arr
+}
+
+/// Appends the counter to the seed, in little-endian order, as the input of the hash calls
+fn with_counter(seed: &[u8], counter: u16) -> Vec<u8> {
+    let mut input = seed.to_vec();
+    input.extend_from_slice(&counter.to_le_bytes());
+    input
+}
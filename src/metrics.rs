@@ -0,0 +1,143 @@
+//! Operation metrics
+//!
+//! With the `metrics` feature, the crate counts the key generations, encryptions, decryptions
+//! and inversions it performs, and how many rounds the encryptions needed. `stats()` returns a
+//! snapshot of the counters, that are global to the process and can be cleared with `reset()`.
+//!
+//! An encryption needs more than one round when the `dm0` check rejects the blinding value `b`,
+//! that is then generated again. The rounds are counted from the calls to the random number
+//! generator, so they are only recorded with the generators implemented in Rust, not with
+//! `RNG_DEVURANDOM` or `RNG_DEFAULT`. The inversions counted are the ones done by
+//! `PrivPoly::invert()` and its variants, including the ones of `generate_key_pair_from()`: the
+//! retries of libntru while generating a key pair are internal to it and cannot be observed.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::{self, RNG_CTR_DRBG};
+//! use ntru::encparams::EES401EP1;
+//!
+//! ntru::metrics::reset();
+//! let rand_ctx = rand::init_det(&RNG_CTR_DRBG, b"metrics").unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//! let enc = ntru::encrypt(b"Hello", kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+//!
+//! let stats = ntru::metrics::stats();
+//! assert!(stats.key_generations >= 1);
+//! assert!(stats.encryptions >= 1);
+//! # let _ = enc;
+//! ```
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of buckets of the histogram of encryption rounds
+pub const ROUND_BUCKETS: usize = 4;
+
+static KEY_GENERATIONS: AtomicU64 = AtomicU64::new(0);
+static KEY_GENERATION_FAILURES: AtomicU64 = AtomicU64::new(0);
+static INVERSIONS: AtomicU64 = AtomicU64::new(0);
+static FAILED_INVERSIONS: AtomicU64 = AtomicU64::new(0);
+static ENCRYPTIONS: AtomicU64 = AtomicU64::new(0);
+static ENCRYPTION_FAILURES: AtomicU64 = AtomicU64::new(0);
+static DM0_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+static DECRYPTIONS: AtomicU64 = AtomicU64::new(0);
+static DECRYPTION_FAILURES: AtomicU64 = AtomicU64::new(0);
+static ENCRYPTION_ROUNDS: [AtomicU64; ROUND_BUCKETS] =
+    [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+/// Snapshot of the counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// Key generation calls, including `generate_public()`
+    pub key_generations: u64,
+    /// Key generation calls that returned an error
+    pub key_generation_failures: u64,
+    /// Inversions modulo q of private polynomials
+    pub inversions: u64,
+    /// Inversions of polynomials that were not invertible
+    pub failed_inversions: u64,
+    /// Encryption calls
+    pub encryptions: u64,
+    /// Encryption calls that returned an error
+    pub encryption_failures: u64,
+    /// Histogram of the rounds of the encryptions, with 1, 2, 3 and 4 or more rounds
+    pub encryption_rounds: [u64; ROUND_BUCKETS],
+    /// Blinding values rejected by the `dm0` check, i.e. the total extra rounds
+    pub dm0_rejections: u64,
+    /// Decryption calls
+    pub decryptions: u64,
+    /// Decryption calls that returned an error
+    pub decryption_failures: u64,
+}
+
+/// Gets a snapshot of the counters
+pub fn stats() -> Stats {
+    let mut encryption_rounds = [0; ROUND_BUCKETS];
+    for (count, counter) in encryption_rounds.iter_mut().zip(ENCRYPTION_ROUNDS.iter()) {
+        *count = counter.load(Ordering::Relaxed);
+    }
+    Stats {
+        key_generations: KEY_GENERATIONS.load(Ordering::Relaxed),
+        key_generation_failures: KEY_GENERATION_FAILURES.load(Ordering::Relaxed),
+        inversions: INVERSIONS.load(Ordering::Relaxed),
+        failed_inversions: FAILED_INVERSIONS.load(Ordering::Relaxed),
+        encryptions: ENCRYPTIONS.load(Ordering::Relaxed),
+        encryption_failures: ENCRYPTION_FAILURES.load(Ordering::Relaxed),
+        encryption_rounds,
+        dm0_rejections: DM0_REJECTIONS.load(Ordering::Relaxed),
+        decryptions: DECRYPTIONS.load(Ordering::Relaxed),
+        decryption_failures: DECRYPTION_FAILURES.load(Ordering::Relaxed),
+    }
+}
+
+/// Resets all the counters to 0
+pub fn reset() {
+    for counter in [&KEY_GENERATIONS,
+                    &KEY_GENERATION_FAILURES,
+                    &INVERSIONS,
+                    &FAILED_INVERSIONS,
+                    &ENCRYPTIONS,
+                    &ENCRYPTION_FAILURES,
+                    &DM0_REJECTIONS,
+                    &DECRYPTIONS,
+                    &DECRYPTION_FAILURES]
+        .iter() {
+        counter.store(0, Ordering::Relaxed);
+    }
+    for counter in ENCRYPTION_ROUNDS.iter() {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Records a call to a public operation, by the name it is traced with
+pub(crate) fn record_op(op: &str, ok: bool) {
+    let (calls, failures) = match op {
+        "encrypt" => (&ENCRYPTIONS, &ENCRYPTION_FAILURES),
+        "decrypt" => (&DECRYPTIONS, &DECRYPTION_FAILURES),
+        _ => (&KEY_GENERATIONS, &KEY_GENERATION_FAILURES),
+    };
+    let _ = calls.fetch_add(1, Ordering::Relaxed);
+    if !ok {
+        let _ = failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Records an inversion
+pub(crate) fn record_inversion(invertible: bool) {
+    let _ = INVERSIONS.fetch_add(1, Ordering::Relaxed);
+    if !invertible {
+        let _ = FAILED_INVERSIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Records the rounds of a successful encryption
+///
+/// Nothing is recorded for 0 rounds, i.e. if the random number generator does not count its
+/// calls.
+pub(crate) fn record_encryption_rounds(rounds: u64) {
+    if rounds == 0 {
+        return;
+    }
+    let bucket = if rounds < ROUND_BUCKETS as u64 { rounds - 1 } else { ROUND_BUCKETS as u64 - 1 };
+    let _ = ENCRYPTION_ROUNDS[bucket as usize].fetch_add(1, Ordering::Relaxed);
+    let _ = DM0_REJECTIONS.fetch_add(rounds - 1, Ordering::Relaxed);
+}
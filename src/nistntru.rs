@@ -0,0 +1,719 @@
+//! NIST round 3 NTRU
+//!
+//! This module implements the NTRU key encapsulation mechanism submitted to the third round of the
+//! NIST post-quantum standardization process, with the `ntruhps2048677`, `ntruhps4096821` and
+//! `ntruhrss701` parameter sets. Unlike the IEEE 1363.1 parameter sets in `encparams`, it is a
+//! KEM: it does not encrypt messages, but establishes a random 32 byte shared secret.
+//!
+//! Keys and ciphertexts use the encodings of the specification, and the randomness is requested as
+//! in the reference implementation, so a random context of `kat::NistDrbg` reproduces the NIST
+//! known-answer tests. Note that this implementation is written in pure Rust and is not hardened
+//! against timing attacks.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::nistntru::NTRUHPS2048677;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::nistntru::generate_key_pair(&NTRUHPS2048677, &rand_ctx).unwrap();
+//!
+//! let (ciphertext, shared1) = ntru::nistntru::encapsulate(kp.get_public(), &rand_ctx).unwrap();
+//! let shared2 = ntru::nistntru::decapsulate(&ciphertext, kp.get_private()).unwrap();
+//!
+//! assert_eq!(shared1, shared2);
+//! ```
+use std::fmt;
+use sha3::{Digest, Sha3_256};
+use rand::RandContext;
+use types::Error;
+use ct::{self, Choice};
+
+/// Length of the PRF key stored in the private key
+const PRF_KEY_LEN: usize = 32;
+/// Length of the shared secret
+const SHARED_KEY_LEN: usize = 32;
+
+/// A set of parameters for the NIST round 3 NTRU KEM
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NistParams {
+    /// Name of the parameter set
+    name: &'static str,
+    /// Degree of the polynomials
+    n: usize,
+    /// Base 2 logarithm of the modulus
+    log_q: u32,
+    /// Whether this is an NTRU-HRSS parameter set, as opposed to NTRU-HPS
+    hrss: bool,
+}
+
+impl fmt::Debug for NistParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "param: {}", self.name)
+    }
+}
+
+impl NistParams {
+    /// Get the name of the parameter set
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the degree of the polynomials
+    pub fn get_n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the modulus
+    pub fn get_q(&self) -> u16 {
+        1 << self.log_q
+    }
+
+    /// Whether this is an NTRU-HRSS parameter set
+    pub fn is_hrss(&self) -> bool {
+        self.hrss
+    }
+
+    /// Public key length
+    pub fn public_len(&self) -> usize {
+        self.packed_q_len()
+    }
+
+    /// Private key length
+    pub fn private_len(&self) -> usize {
+        2 * self.trinary_len() + self.packed_q_len() + PRF_KEY_LEN
+    }
+
+    /// Ciphertext length
+    pub fn ciphertext_len(&self) -> usize {
+        self.packed_q_len()
+    }
+
+    /// Shared secret length
+    pub fn shared_secret_len(&self) -> usize {
+        SHARED_KEY_LEN
+    }
+
+    /// Mask to reduce modulo q
+    fn q_mask(&self) -> u16 {
+        self.get_q() - 1
+    }
+
+    /// Weight of the fixed type polynomials, only used by NTRU-HPS
+    fn weight(&self) -> usize {
+        self.get_q() as usize / 8 - 2
+    }
+
+    /// Length of a packed polynomial modulo 3
+    fn trinary_len(&self) -> usize {
+        (self.n - 1).div_ceil(5)
+    }
+
+    /// Length of a packed polynomial modulo q
+    fn packed_q_len(&self) -> usize {
+        (self.log_q as usize * (self.n - 1)).div_ceil(8)
+    }
+
+    /// Random bytes needed to sample an i.i.d. polynomial
+    fn sample_iid_len(&self) -> usize {
+        self.n - 1
+    }
+
+    /// Random bytes needed to sample a fixed type polynomial
+    fn sample_fixed_type_len(&self) -> usize {
+        (30 * (self.n - 1)).div_ceil(8)
+    }
+
+    /// Random bytes needed to sample two polynomials, `(f, g)` or `(r, m)`
+    fn sample_pair_len(&self) -> usize {
+        if self.hrss {
+            2 * self.sample_iid_len()
+        } else {
+            self.sample_iid_len() + self.sample_fixed_type_len()
+        }
+    }
+}
+
+/// ntruhps2048677 parameters
+pub const NTRUHPS2048677: NistParams = NistParams {
+    name: "ntruhps2048677",
+    n: 677,
+    log_q: 11,
+    hrss: false,
+};
+
+/// ntruhps4096821 parameters
+pub const NTRUHPS4096821: NistParams = NistParams {
+    name: "ntruhps4096821",
+    n: 821,
+    log_q: 12,
+    hrss: false,
+};
+
+/// ntruhrss701 parameters
+pub const NTRUHRSS701: NistParams = NistParams {
+    name: "ntruhrss701",
+    n: 701,
+    log_q: 13,
+    hrss: true,
+};
+
+/// All the NIST round 3 NTRU parameter sets
+pub const ALL_NIST_PARAM_SETS: [NistParams; 3] = [NTRUHPS2048677, NTRUHPS4096821, NTRUHRSS701];
+
+/// Ciphertext and shared secret returned by the encapsulation
+pub type Encapsulated = (Box<[u8]>, Box<[u8]>);
+
+/// NIST round 3 NTRU public key
+#[derive(Debug, PartialEq, Clone)]
+pub struct PublicKey {
+    /// The parameters of the key
+    params: NistParams,
+    /// The encoded key
+    bytes: Box<[u8]>,
+}
+
+impl PublicKey {
+    /// Import a public key
+    pub fn import(arr: &[u8], params: &NistParams) -> Result<PublicKey, Error> {
+        if arr.len() != params.public_len() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(PublicKey {
+            params: *params,
+            bytes: arr.to_vec().into_boxed_slice(),
+        })
+    }
+
+    /// Export the public key
+    pub fn export(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the parameters of the key
+    pub fn get_params(&self) -> &NistParams {
+        &self.params
+    }
+}
+
+/// NIST round 3 NTRU private key
+///
+/// As in the specification, the private key includes the PRF key used for implicit rejection.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PrivateKey {
+    /// The parameters of the key
+    params: NistParams,
+    /// The encoded key
+    bytes: Box<[u8]>,
+}
+
+impl PrivateKey {
+    /// Import a private key
+    pub fn import(arr: &[u8], params: &NistParams) -> Result<PrivateKey, Error> {
+        if arr.len() != params.private_len() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(PrivateKey {
+            params: *params,
+            bytes: arr.to_vec().into_boxed_slice(),
+        })
+    }
+
+    /// Export the private key
+    pub fn export(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the parameters of the key
+    pub fn get_params(&self) -> &NistParams {
+        &self.params
+    }
+}
+
+/// NIST round 3 NTRU key pair
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyPair {
+    /// Private key
+    private: PrivateKey,
+    /// Public key
+    public: PublicKey,
+}
+
+impl KeyPair {
+    /// The private key
+    pub fn get_private(&self) -> &PrivateKey {
+        &self.private
+    }
+
+    /// The public key
+    pub fn get_public(&self) -> &PublicKey {
+        &self.public
+    }
+}
+
+/// Key generation
+///
+/// Generates a NIST round 3 NTRU key pair. If a deterministic RNG is used, the key pair will be
+/// deterministic for a given random seed.
+pub fn generate_key_pair(params: &NistParams, rand_ctx: &RandContext) -> Result<KeyPair, Error> {
+    let n = params.n;
+    let seed = random_bytes(params.sample_pair_len(), rand_ctx)?;
+    let (mut f, mut g) = if params.hrss {
+        (sample_iid_plus(&seed[..n - 1], params),
+         sample_iid_plus(&seed[n - 1..], params))
+    } else {
+        (sample_iid(&seed[..n - 1], params), sample_fixed_type(&seed[n - 1..], params))
+    };
+
+    let finv3 = s3_inv(&f, params);
+    let mut sk = Vec::with_capacity(params.private_len());
+    sk.extend_from_slice(&s3_to_bytes(&f, params));
+    sk.extend_from_slice(&s3_to_bytes(&finv3, params));
+
+    z3_to_zq(&mut f, params);
+    z3_to_zq(&mut g, params);
+    let mask = params.q_mask();
+    if params.hrss {
+        // g = 3 * (x - 1) * g
+        for i in (1..n).rev() {
+            g[i] = 3u16.wrapping_mul(g[i - 1].wrapping_sub(g[i])) & mask;
+        }
+        g[0] = 0u16.wrapping_sub(3u16.wrapping_mul(g[0])) & mask;
+    } else {
+        for x in g.iter_mut() {
+            *x = 3u16.wrapping_mul(*x) & mask;
+        }
+    }
+
+    let gf = rq_mul(&g, &f, params);
+    let invgf = rq_inv(&gf, params);
+
+    let tmp = rq_mul(&invgf, &f, params);
+    let invh = sq_mul(&tmp, &f, params);
+    sk.extend_from_slice(&sq_to_bytes(&invh, params));
+
+    let tmp = rq_mul(&invgf, &g, params);
+    let h = rq_mul(&tmp, &g, params);
+    let pk = sq_to_bytes(&h, params);
+    sk.extend_from_slice(&random_bytes(PRF_KEY_LEN, rand_ctx)?);
+
+    Ok(KeyPair {
+        private: PrivateKey {
+            params: *params,
+            bytes: sk.into_boxed_slice(),
+        },
+        public: PublicKey {
+            params: *params,
+            bytes: pk.into_boxed_slice(),
+        },
+    })
+}
+
+/// Encapsulation
+///
+/// Generates a random shared secret for the public key, and returns the ciphertext that
+/// encapsulates it and the shared secret itself.
+pub fn encapsulate(public: &PublicKey,
+                   rand_ctx: &RandContext)
+                   -> Result<Encapsulated, Error> {
+    let params = &public.params;
+    let n = params.n;
+    let seed = random_bytes(params.sample_pair_len(), rand_ctx)?;
+    let mut r = sample_iid(&seed[..n - 1], params);
+    let m = if params.hrss {
+        sample_iid(&seed[n - 1..], params)
+    } else {
+        sample_fixed_type(&seed[n - 1..], params)
+    };
+
+    let mut rm = s3_to_bytes(&r, params);
+    rm.extend_from_slice(&s3_to_bytes(&m, params));
+    let k = Sha3_256::digest(&rm);
+
+    z3_to_zq(&mut r, params);
+    let h = rq_sum_zero_from_bytes(&public.bytes, params);
+    let mut c = rq_mul(&r, &h, params);
+    let liftm = lift(&m, params);
+    for (ci, mi) in c.iter_mut().zip(liftm.iter()) {
+        *ci = ci.wrapping_add(*mi) & params.q_mask();
+    }
+
+    Ok((sq_to_bytes(&c, params).into_boxed_slice(), k.to_vec().into_boxed_slice()))
+}
+
+/// Decapsulation
+///
+/// Recovers the shared secret encapsulated in the ciphertext. Invalid ciphertexts are implicitly
+/// rejected: a pseudorandom shared secret, unrelated to any real secret, is returned instead of an
+/// error. Only ciphertexts with an invalid length return `Error::InvalidCiphertextLength`.
+pub fn decapsulate(ciphertext: &[u8], private: &PrivateKey) -> Result<Box<[u8]>, Error> {
+    let params = &private.params;
+    if ciphertext.len() != params.ciphertext_len() {
+        return Err(Error::InvalidCiphertextLength {
+            expected: params.ciphertext_len(),
+            actual: ciphertext.len(),
+        });
+    }
+
+    let trinary_len = params.trinary_len();
+    let sk = &private.bytes;
+    let c = rq_sum_zero_from_bytes(ciphertext, params);
+    let mut f = s3_from_bytes(&sk[..trinary_len], params);
+    z3_to_zq(&mut f, params);
+
+    let cf = rq_mul(&c, &f, params);
+    let mf = rq_to_s3(&cf, params);
+    let finv3 = s3_from_bytes(&sk[trinary_len..2 * trinary_len], params);
+    let m = s3_mul(&mf, &finv3, params);
+
+    let mut fail = check_ciphertext(ciphertext, params);
+    if !params.hrss {
+        fail |= check_m(&m, params);
+    }
+
+    // b = c - lift(m), r = b / h
+    let liftm = lift(&m, params);
+    let b: Vec<u16> = c.iter()
+        .zip(liftm.iter())
+        .map(|(ci, mi)| ci.wrapping_sub(*mi) & params.q_mask())
+        .collect();
+    let invh = sq_from_bytes(&sk[2 * trinary_len..2 * trinary_len + params.packed_q_len()],
+                             params);
+    let mut r = sq_mul(&b, &invh, params);
+    fail |= check_r(&r, params);
+    trinary_zq_to_z3(&mut r, params);
+
+    let mut rm = s3_to_bytes(&r, params);
+    rm.extend_from_slice(&s3_to_bytes(&m, params));
+    let mut k = Sha3_256::digest(&rm).to_vec();
+
+    let mut hasher = Sha3_256::new();
+    hasher.update(&sk[params.private_len() - PRF_KEY_LEN..]);
+    hasher.update(ciphertext);
+    let rejection = hasher.finalize();
+
+    ct::conditional_copy(&mut k, &rejection, Choice::from(fail))?;
+    Ok(k.into_boxed_slice())
+}
+
+/// Checks that the unused bits of the last byte of the ciphertext are zero, returns 1 on failure
+fn check_ciphertext(ciphertext: &[u8], params: &NistParams) -> u8 {
+    let used_bits = (params.log_q as usize * (params.n - 1)) & 7;
+    let t = ciphertext[ciphertext.len() - 1] as u16 & (0xff << (8 - used_bits));
+    ((t.wrapping_neg() >> 15) & 1) as u8
+}
+
+/// Checks that `r` has coefficients in {0, 1, q - 1} and `r[n - 1] = 0`, returns 1 on failure
+fn check_r(r: &[u16], params: &NistParams) -> u8 {
+    let n = params.n;
+    let mut t = 0u32;
+    for c in &r[..n - 1] {
+        t |= (c.wrapping_add(1) & (params.get_q() - 4)) as u32;
+        t |= (c.wrapping_add(2) & 4) as u32;
+    }
+    t |= r[n - 1] as u32;
+    ((t.wrapping_neg() >> 31) & 1) as u8
+}
+
+/// Checks that `m` has as many ones as twos and the right weight, returns 1 on failure
+fn check_m(m: &[u16], params: &NistParams) -> u8 {
+    let mut ps = 0u32;
+    let mut ms = 0u32;
+    for c in m {
+        ps += (c & 1) as u32;
+        ms += (c & 2) as u32;
+    }
+    let t = (ps ^ (ms >> 1)) | (ms ^ params.weight() as u32);
+    ((t.wrapping_neg() >> 31) & 1) as u8
+}
+
+/// Random bytes from the rand context
+fn random_bytes(len: usize, rand_ctx: &RandContext) -> Result<Box<[u8]>, Error> {
+    if len > u16::MAX as usize {
+        return Err(Error::InvalidParam);
+    }
+    ::rand::generate(len as u16, rand_ctx)
+}
+
+/// Samples a polynomial with i.i.d. coefficients in {0, 1, 2}
+fn sample_iid(bytes: &[u8], params: &NistParams) -> Vec<u16> {
+    let mut r: Vec<u16> = bytes[..params.n - 1].iter().map(|b| (b % 3) as u16).collect();
+    r.push(0);
+    r
+}
+
+/// Samples an i.i.d. polynomial with a non-negative correlation between adjacent coefficients
+fn sample_iid_plus(bytes: &[u8], params: &NistParams) -> Vec<u16> {
+    let mut r = sample_iid(bytes, params);
+
+    // Map {0, 1, 2} to {0, 1, -1} and compute <x * r, r>
+    let signed: Vec<i32> = r.iter().map(|c| if *c == 2 { -1 } else { *c as i32 }).collect();
+    let s: i32 = signed.windows(2).map(|w| w[0] * w[1]).sum();
+    if s < 0 {
+        for c in r.iter_mut().step_by(2) {
+            *c = (3 - *c) % 3;
+        }
+    }
+    r
+}
+
+/// Samples a polynomial with `q / 16 - 1` ones and `q / 16 - 1` twos
+fn sample_fixed_type(u: &[u8], params: &NistParams) -> Vec<u16> {
+    let n = params.n;
+    let weight = params.weight();
+    let mut s = Vec::with_capacity(n - 1);
+    for u in u.chunks(15).take((n - 1) / 4) {
+        let u: Vec<u32> = u.iter().map(|b| *b as u32).collect();
+        s.push((u[0] << 2) + (u[1] << 10) + (u[2] << 18) + (u[3] << 26));
+        s.push(((u[3] & 0xc0) >> 4) + (u[4] << 4) + (u[5] << 12) + (u[6] << 20) + (u[7] << 28));
+        s.push(((u[7] & 0xf0) >> 2) + (u[8] << 6) + (u[9] << 14) + (u[10] << 22) +
+               (u[11] << 30));
+        s.push((u[11] & 0xfc) + (u[12] << 8) + (u[13] << 16) + (u[14] << 24));
+    }
+    for (i, x) in s.iter_mut().enumerate() {
+        if i < weight / 2 {
+            *x |= 1;
+        } else if i < weight {
+            *x |= 2;
+        }
+    }
+    // The reference implementation sorts the words as signed integers
+    s.sort_by_key(|x| *x as i32);
+
+    let mut r: Vec<u16> = s.iter().map(|x| (x & 3) as u16).collect();
+    r.push(0);
+    r
+}
+
+/// Maps coefficients {0, 1, 2} to {0, 1, q - 1}
+fn z3_to_zq(r: &mut [u16], params: &NistParams) {
+    for c in r.iter_mut() {
+        *c |= 0u16.wrapping_sub(*c >> 1) & params.q_mask();
+    }
+}
+
+/// Maps coefficients {0, 1, q - 1} to {0, 1, 2}
+fn trinary_zq_to_z3(r: &mut [u16], params: &NistParams) {
+    for c in r.iter_mut() {
+        *c &= params.q_mask();
+        *c = 3 & (*c ^ (*c >> (params.log_q - 1)));
+    }
+}
+
+/// Multiplication modulo `(q, x^n - 1)`
+fn rq_mul(a: &[u16], b: &[u16], params: &NistParams) -> Vec<u16> {
+    let n = params.n;
+    let mut r = vec![0u16; n];
+    for (i, ai) in a.iter().enumerate() {
+        for (j, bj) in b.iter().enumerate() {
+            let k = if i + j < n { i + j } else { i + j - n };
+            r[k] = r[k].wrapping_add(ai.wrapping_mul(*bj));
+        }
+    }
+    for c in r.iter_mut() {
+        *c &= params.q_mask();
+    }
+    r
+}
+
+/// Multiplication modulo `(q, Phi_n)`
+fn sq_mul(a: &[u16], b: &[u16], params: &NistParams) -> Vec<u16> {
+    let mut r = rq_mul(a, b, params);
+    mod_q_phi_n(&mut r, params);
+    r
+}
+
+/// Multiplication modulo `(3, Phi_n)` of polynomials with coefficients in {0, 1, 2}
+fn s3_mul(a: &[u16], b: &[u16], params: &NistParams) -> Vec<u16> {
+    let n = params.n;
+    let mut r = vec![0u32; n];
+    for (i, ai) in a.iter().enumerate() {
+        for (j, bj) in b.iter().enumerate() {
+            let k = if i + j < n { i + j } else { i + j - n };
+            r[k] += *ai as u32 * *bj as u32;
+        }
+    }
+    let mut r: Vec<u16> = r.iter().map(|c| (c % 3) as u16).collect();
+    mod_3_phi_n(&mut r);
+    r
+}
+
+/// Reduces a polynomial modulo `(q, Phi_n)` from `(q, x^n - 1)`
+fn mod_q_phi_n(r: &mut [u16], params: &NistParams) {
+    let last = r[params.n - 1];
+    for c in r.iter_mut() {
+        *c = c.wrapping_sub(last) & params.q_mask();
+    }
+}
+
+/// Reduces a polynomial with coefficients in {0, 1, 2} modulo `(3, Phi_n)` from `(3, x^n - 1)`
+fn mod_3_phi_n(r: &mut [u16]) {
+    let last = r[r.len() - 1];
+    for c in r.iter_mut() {
+        *c = (*c + 2 * last) % 3;
+    }
+}
+
+/// Converts a polynomial modulo `(q, x^n - 1)` to `(3, Phi_n)`
+fn rq_to_s3(a: &[u16], params: &NistParams) -> Vec<u16> {
+    let q = params.get_q() as i32;
+    let mut r: Vec<u16> = a.iter()
+        .map(|c| {
+            // Centered representative in [-q/2, q/2)
+            let c = (c & params.q_mask()) as i32;
+            let c = if c >= q / 2 { c - q } else { c };
+            ((c % 3 + 3) % 3) as u16
+        })
+        .collect();
+    mod_3_phi_n(&mut r);
+    r
+}
+
+/// Lifts a message polynomial from `(3, Phi_n)` to `(q, x^n - 1)`
+fn lift(m: &[u16], params: &NistParams) -> Vec<u16> {
+    let mut m = m.to_vec();
+    if !params.hrss {
+        z3_to_zq(&mut m, params);
+        return m;
+    }
+
+    // b = m / (x - 1) modulo (3, Phi_n), lifted to Zq and multiplied by (x - 1)
+    let n = params.n;
+    let mut x_minus_1 = vec![0u16; n];
+    x_minus_1[0] = 2;
+    x_minus_1[1] = 1;
+    let mut b = s3_mul(&m, &s3_inv(&x_minus_1, params), params);
+    z3_to_zq(&mut b, params);
+    m[0] = 0u16.wrapping_sub(b[0]) & params.q_mask();
+    for i in 0..n - 1 {
+        m[i + 1] = b[i].wrapping_sub(b[i + 1]) & params.q_mask();
+    }
+    m
+}
+
+/// Inverse modulo `(prime, Phi_n)`, for a prime 2 or 3
+fn inv_mod_phi_n(a: &[u16], prime: u16, params: &NistParams) -> Vec<u16> {
+    let n = params.n;
+    let mut f = vec![1u16; n];
+    let mut g = vec![0u16; n];
+    let mut v = vec![0u16; n];
+    let mut w = vec![0u16; n];
+    w[0] = 1;
+    for i in 0..n - 1 {
+        g[n - 2 - i] = ((a[i] % prime) + (prime - 1) * (a[n - 1] % prime)) % prime;
+    }
+    let mut delta = 1i32;
+
+    for _ in 0..2 * (n - 1) - 1 {
+        for i in (1..n).rev() {
+            v[i] = v[i - 1];
+        }
+        v[0] = 0;
+
+        // f[0] is its own inverse modulo 2 and 3
+        let sign = (prime - g[0] * f[0] % prime) % prime;
+        if delta > 0 && g[0] != 0 {
+            delta = -delta;
+            ::std::mem::swap(&mut f, &mut g);
+            ::std::mem::swap(&mut v, &mut w);
+        }
+        delta += 1;
+
+        for i in 0..n {
+            g[i] = (g[i] + sign * f[i]) % prime;
+            w[i] = (w[i] + sign * v[i]) % prime;
+        }
+        for i in 0..n - 1 {
+            g[i] = g[i + 1];
+        }
+        g[n - 1] = 0;
+    }
+
+    let mut r = vec![0u16; n];
+    for i in 0..n - 1 {
+        r[i] = f[0] * v[n - 2 - i] % prime;
+    }
+    r
+}
+
+/// Inverse modulo `(3, Phi_n)`
+fn s3_inv(a: &[u16], params: &NistParams) -> Vec<u16> {
+    inv_mod_phi_n(a, 3, params)
+}
+
+/// Inverse modulo `(q, Phi_n)`, lifted from the inverse modulo 2 with Newton iteration
+fn rq_inv(a: &[u16], params: &NistParams) -> Vec<u16> {
+    let mut r = inv_mod_phi_n(a, 2, params);
+    let b: Vec<u16> = a.iter().map(|c| c.wrapping_neg() & params.q_mask()).collect();
+    // Each iteration doubles the number of correct bits, 2^4 >= log_q
+    for _ in 0..4 {
+        let mut c = sq_mul(&r, &b, params);
+        c[0] = (c[0] + 2) & params.q_mask();
+        r = sq_mul(&c, &r, params);
+    }
+    r
+}
+
+/// Packs the first `n - 1` coefficients of a polynomial modulo 3, 5 coefficients per byte
+fn s3_to_bytes(a: &[u16], params: &NistParams) -> Vec<u8> {
+    a[..params.n - 1]
+        .chunks(5)
+        .map(|chunk| chunk.iter().rev().fold(0u8, |c, x| c.wrapping_mul(3).wrapping_add(*x as u8)))
+        .collect()
+}
+
+/// Unpacks a polynomial modulo 3
+fn s3_from_bytes(bytes: &[u8], params: &NistParams) -> Vec<u16> {
+    let n = params.n;
+    let mut r: Vec<u16> = (0..n - 1)
+        .map(|i| {
+            let mut c = bytes[i / 5] as u16;
+            for _ in 0..i % 5 {
+                c /= 3;
+            }
+            c % 3
+        })
+        .collect();
+    r.push(0);
+    r
+}
+
+/// Packs the first `n - 1` coefficients of a polynomial modulo q, `log_q` bits each
+fn sq_to_bytes(a: &[u16], params: &NistParams) -> Vec<u8> {
+    let mut bytes = vec![0u8; params.packed_q_len()];
+    let mut bit = 0;
+    for c in &a[..params.n - 1] {
+        let c = (c & params.q_mask()) as u32;
+        for j in 0..params.log_q as usize {
+            bytes[(bit + j) / 8] |= (((c >> j) & 1) as u8) << ((bit + j) % 8);
+        }
+        bit += params.log_q as usize;
+    }
+    bytes
+}
+
+/// Unpacks a polynomial modulo `(q, Phi_n)`
+fn sq_from_bytes(bytes: &[u8], params: &NistParams) -> Vec<u16> {
+    let mut r = Vec::with_capacity(params.n);
+    let mut bit = 0;
+    for _ in 0..params.n - 1 {
+        let mut c = 0u16;
+        for j in 0..params.log_q as usize {
+            c |= (((bytes[(bit + j) / 8] >> ((bit + j) % 8)) & 1) as u16) << j;
+        }
+        r.push(c);
+        bit += params.log_q as usize;
+    }
+    r.push(0);
+    r
+}
+
+/// Unpacks a polynomial modulo `(q, x^n - 1)` whose coefficients sum to zero
+fn rq_sum_zero_from_bytes(bytes: &[u8], params: &NistParams) -> Vec<u16> {
+    let mut r = sq_from_bytes(bytes, params);
+    let sum = r.iter().fold(0u16, |s, c| s.wrapping_add(*c));
+    let n = params.n;
+    r[n - 1] = sum.wrapping_neg() & params.q_mask();
+    r
+}
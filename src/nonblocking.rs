@@ -0,0 +1,118 @@
+//! Asynchronous operations
+//!
+//! Key generation, and to a lesser extent encryption and decryption, are CPU-heavy: generating a
+//! key pair for `EES1499EP1` can block an asynchronous executor for a long time. The functions of
+//! this module run them on the blocking thread pool of Tokio, and return a future that resolves to
+//! the result. Only available with the `tokio` feature, and they must be called from within a
+//! Tokio runtime.
+//!
+//! The parameter sets, the random number generators and the keys are `Send` and `Sync`, so they
+//! can be shared between tasks. A `RandContext`, however, is neither `Send` nor `Sync`, since it
+//! keeps the state of the generator, so these functions take the `RandGen` instead, and initialize
+//! a context for it in the blocking thread. The message and the keys are copied, so the futures
+//! are `'static`.
+//!
+//! If the operation panics, the panic is resumed when the future is polled.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate ntru;
+//! extern crate tokio;
+//!
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//! use ntru::nonblocking::{generate_key_pair_async, encrypt_async, decrypt_async};
+//!
+//! # fn main() {
+//! let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! let kp = runtime.block_on(generate_key_pair_async(&EES401EP1, &RNG_DEFAULT)).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = runtime.block_on(encrypt_async(msg, kp.get_public(), &EES401EP1,
+//!                                                &RNG_DEFAULT)).unwrap();
+//! let decrypted = runtime.block_on(decrypt_async(&encrypted, &kp, &EES401EP1)).unwrap();
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! # }
+//! ```
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::task::{self, JoinHandle};
+use types::{KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use rand::{self, RandGen};
+
+/// Future of an operation running on the blocking thread pool
+struct Blocking<T> {
+    /// The handle of the blocking task
+    handle: JoinHandle<Result<T, Error>>,
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = Result<T, Error>;
+
+    #[cfg_attr(feature = "no-panic", allow(clippy::panic))]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, Error>> {
+        match Pin::new(&mut self.handle).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(e)) => {
+                if e.is_panic() {
+                    panic::resume_unwind(e.into_panic())
+                } else {
+                    panic!("the blocking task was cancelled")
+                }
+            }
+        }
+    }
+}
+
+/// Runs the operation on the blocking thread pool
+fn spawn<T, F>(f: F) -> Blocking<T>
+    where T: Send + 'static,
+          F: FnOnce() -> Result<T, Error> + Send + 'static
+{
+    Blocking { handle: task::spawn_blocking(f) }
+}
+
+/// Generates a key pair on the blocking thread pool
+///
+/// Works like `generate_key_pair()`, with a new context of the random number generator.
+pub fn generate_key_pair_async(params: &'static EncParams,
+                               rand_gen: &'static RandGen)
+                               -> impl Future<Output = Result<KeyPair, Error>> + Send {
+    spawn(move || {
+        let rand_ctx = rand::init(rand_gen)?;
+        ::generate_key_pair(params, &rand_ctx)
+    })
+}
+
+/// Encrypts a message on the blocking thread pool
+///
+/// Works like `encrypt()`, with a new context of the random number generator.
+pub fn encrypt_async(msg: &[u8],
+                     public: &PublicKey,
+                     params: &'static EncParams,
+                     rand_gen: &'static RandGen)
+                     -> impl Future<Output = Result<Box<[u8]>, Error>> + Send {
+    let msg = msg.to_vec();
+    let public = public.clone();
+    spawn(move || {
+        let rand_ctx = rand::init(rand_gen)?;
+        ::encrypt(&msg, &public, params, &rand_ctx)
+    })
+}
+
+/// Decrypts a message on the blocking thread pool
+///
+/// Works like `decrypt()`.
+pub fn decrypt_async(enc: &[u8],
+                     kp: &KeyPair,
+                     params: &'static EncParams)
+                     -> impl Future<Output = Result<Box<[u8]>, Error>> + Send {
+    let enc = enc.to_vec();
+    let kp = kp.clone();
+    spawn(move || ::decrypt(&enc, &kp, params))
+}
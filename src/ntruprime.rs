@@ -0,0 +1,760 @@
+//! Streamlined NTRU Prime
+//!
+//! This module implements the Streamlined NTRU Prime key encapsulation mechanism, as specified in
+//! the third round NIST submission, in pure Rust. Unlike NTRUEncrypt, it works in the ring
+//! `Z[x]/(x^p - x - 1)` with a prime `p` and a prime modulus `q`, and it uses rounding instead of
+//! random blinding polynomials. The `SNTRUP761` parameter set is the one used by OpenSSH.
+//!
+//! Keys and ciphertexts use the encodings of the specification, so they are interoperable with
+//! other implementations, and the randomness is requested as in the reference implementation, so
+//! a random context of `kat::NistDrbg` reproduces the NIST known-answer tests. Note that this
+//! implementation is not hardened against timing attacks.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::ntruprime::SNTRUP761;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx).unwrap();
+//!
+//! let (ciphertext, shared1) = ntru::ntruprime::encapsulate(kp.get_public(), &rand_ctx).unwrap();
+//! let shared2 = ntru::ntruprime::decapsulate(&ciphertext, kp.get_private()).unwrap();
+//!
+//! assert_eq!(shared1, shared2);
+//! ```
+use std::fmt;
+use sha2::{Digest, Sha512};
+use rand::RandContext;
+use types::Error;
+use ct;
+
+/// Length of the hashes used by the KEM
+const HASH_LEN: usize = 32;
+
+/// A set of parameters for Streamlined NTRU Prime
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PrimeParams {
+    /// Name of the parameter set
+    name: &'static str,
+    /// Degree of the polynomial ring, prime
+    p: usize,
+    /// Modulus, prime
+    q: i32,
+    /// Weight of the short polynomials
+    w: usize,
+}
+
+impl fmt::Debug for PrimeParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "param: {}", self.name)
+    }
+}
+
+impl PrimeParams {
+    /// Get the name of the parameter set
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the degree of the polynomial ring
+    pub fn get_p(&self) -> usize {
+        self.p
+    }
+
+    /// Get the modulus
+    pub fn get_q(&self) -> i32 {
+        self.q
+    }
+
+    /// Get the weight of the short polynomials
+    pub fn get_w(&self) -> usize {
+        self.w
+    }
+
+    /// Public key length
+    pub fn public_len(&self) -> usize {
+        self.rq_len()
+    }
+
+    /// Private key length
+    pub fn private_len(&self) -> usize {
+        2 * self.small_len() + self.rq_len() + self.small_len() + HASH_LEN
+    }
+
+    /// Ciphertext length
+    pub fn ciphertext_len(&self) -> usize {
+        self.rounded_len() + HASH_LEN
+    }
+
+    /// Shared secret length
+    pub fn shared_secret_len(&self) -> usize {
+        HASH_LEN
+    }
+
+    /// Length of an encoded small polynomial
+    fn small_len(&self) -> usize {
+        self.p.div_ceil(4)
+    }
+
+    /// Length of an encoded polynomial modulo q
+    fn rq_len(&self) -> usize {
+        encoded_len(&vec![self.q as u32; self.p])
+    }
+
+    /// Length of an encoded rounded polynomial
+    fn rounded_len(&self) -> usize {
+        encoded_len(&vec![(self.q as u32).div_ceil(3); self.p])
+    }
+
+    /// `(q - 1) / 2`
+    fn q12(&self) -> i32 {
+        (self.q - 1) / 2
+    }
+}
+
+/// sntrup653 parameters
+pub const SNTRUP653: PrimeParams = PrimeParams {
+    name: "sntrup653",
+    p: 653,
+    q: 4621,
+    w: 288,
+};
+
+/// sntrup761 parameters, used by OpenSSH
+pub const SNTRUP761: PrimeParams = PrimeParams {
+    name: "sntrup761",
+    p: 761,
+    q: 4591,
+    w: 286,
+};
+
+/// sntrup857 parameters
+pub const SNTRUP857: PrimeParams = PrimeParams {
+    name: "sntrup857",
+    p: 857,
+    q: 5167,
+    w: 322,
+};
+
+/// All the Streamlined NTRU Prime parameter sets
+pub const ALL_PRIME_PARAM_SETS: [PrimeParams; 3] = [SNTRUP653, SNTRUP761, SNTRUP857];
+
+/// Ciphertext and shared secret returned by the encapsulation
+pub type Encapsulated = (Box<[u8]>, Box<[u8]>);
+
+/// Streamlined NTRU Prime public key
+#[derive(Debug, PartialEq, Clone)]
+pub struct PublicKey {
+    /// The parameters of the key
+    params: PrimeParams,
+    /// The encoded key
+    bytes: Box<[u8]>,
+}
+
+impl PublicKey {
+    /// Import a public key
+    pub fn import(arr: &[u8], params: &PrimeParams) -> Result<PublicKey, Error> {
+        if arr.len() != params.public_len() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(PublicKey {
+            params: *params,
+            bytes: arr.to_vec().into_boxed_slice(),
+        })
+    }
+
+    /// Export the public key
+    pub fn export(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the parameters of the key
+    pub fn get_params(&self) -> &PrimeParams {
+        &self.params
+    }
+}
+
+/// Streamlined NTRU Prime private key
+///
+/// As in the specification, the private key includes the public key, the implicit rejection
+/// value and the hash of the public key.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PrivateKey {
+    /// The parameters of the key
+    params: PrimeParams,
+    /// The encoded key
+    bytes: Box<[u8]>,
+}
+
+impl PrivateKey {
+    /// Import a private key
+    pub fn import(arr: &[u8], params: &PrimeParams) -> Result<PrivateKey, Error> {
+        if arr.len() != params.private_len() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(PrivateKey {
+            params: *params,
+            bytes: arr.to_vec().into_boxed_slice(),
+        })
+    }
+
+    /// Export the private key
+    pub fn export(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the parameters of the key
+    pub fn get_params(&self) -> &PrimeParams {
+        &self.params
+    }
+
+    /// Get the public key included in the private key
+    pub fn get_public(&self) -> PublicKey {
+        let start = 2 * self.params.small_len();
+        PublicKey {
+            params: self.params,
+            bytes: self.bytes[start..start + self.params.public_len()].to_vec().into_boxed_slice(),
+        }
+    }
+}
+
+/// Streamlined NTRU Prime key pair
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyPair {
+    /// Private key
+    private: PrivateKey,
+    /// Public key
+    public: PublicKey,
+}
+
+impl KeyPair {
+    /// The private key
+    pub fn get_private(&self) -> &PrivateKey {
+        &self.private
+    }
+
+    /// The public key
+    pub fn get_public(&self) -> &PublicKey {
+        &self.public
+    }
+}
+
+/// Key generation
+///
+/// Generates a Streamlined NTRU Prime key pair. If a deterministic RNG is used, the key pair will
+/// be deterministic for a given random seed.
+pub fn generate_key_pair(params: &PrimeParams, rand_ctx: &RandContext) -> Result<KeyPair, Error> {
+    let p = params.p;
+
+    // g must be invertible modulo 3
+    let mut g = vec![0i8; p];
+    let mut ginv = vec![0i8; p];
+    loop {
+        small_random(&mut g, rand_ctx)?;
+        if r3_recip(&mut ginv, &g) {
+            break;
+        }
+    }
+    let mut f = vec![0i8; p];
+    short_random(&mut f, params, rand_ctx)?;
+
+    // f is always invertible modulo q, since x^p - x - 1 is irreducible modulo q
+    let mut finv = vec![0i16; p];
+    let _ = rq_recip3(&mut finv, &f, params);
+    let mut h = vec![0i16; p];
+    rq_mult_small(&mut h, &finv, &g, params);
+
+    let mut pk = vec![0u8; params.public_len()];
+    rq_encode(&mut pk, &h, params);
+
+    let small_len = params.small_len();
+    let mut sk = Vec::with_capacity(params.private_len());
+    sk.extend_from_slice(&small_encode(&f));
+    sk.extend_from_slice(&small_encode(&ginv));
+    sk.extend_from_slice(&pk);
+    sk.extend_from_slice(&random_bytes(small_len, rand_ctx)?);
+    sk.extend_from_slice(&hash_prefix(4, &[&pk]));
+
+    Ok(KeyPair {
+        private: PrivateKey {
+            params: *params,
+            bytes: sk.into_boxed_slice(),
+        },
+        public: PublicKey {
+            params: *params,
+            bytes: pk.into_boxed_slice(),
+        },
+    })
+}
+
+/// Encapsulation
+///
+/// Generates a random shared secret for the public key, and returns the ciphertext that
+/// encapsulates it and the shared secret itself.
+pub fn encapsulate(public: &PublicKey,
+                   rand_ctx: &RandContext)
+                   -> Result<Encapsulated, Error> {
+    let params = &public.params;
+    let cache = hash_prefix(4, &[&public.bytes]);
+    let mut r = vec![0i8; params.p];
+    short_random(&mut r, params, rand_ctx)?;
+
+    let (c, r_enc) = hide(&r, &public.bytes, &cache, params);
+    let k = hash_session(1, &r_enc, &c);
+    Ok((c.into_boxed_slice(), k.to_vec().into_boxed_slice()))
+}
+
+/// Decapsulation
+///
+/// Recovers the shared secret encapsulated in the ciphertext. Invalid ciphertexts are implicitly
+/// rejected: a pseudorandom shared secret, unrelated to any real secret, is returned instead of an
+/// error. Only ciphertexts with an invalid length return `Error::InvalidCiphertextLength`.
+pub fn decapsulate(ciphertext: &[u8], private: &PrivateKey) -> Result<Box<[u8]>, Error> {
+    let params = &private.params;
+    if ciphertext.len() != params.ciphertext_len() {
+        return Err(Error::InvalidCiphertextLength {
+            expected: params.ciphertext_len(),
+            actual: ciphertext.len(),
+        });
+    }
+
+    let p = params.p;
+    let small_len = params.small_len();
+    let sk = &private.bytes;
+    let pk = &sk[2 * small_len..2 * small_len + params.public_len()];
+    let rho = &sk[2 * small_len + params.public_len()..3 * small_len + params.public_len()];
+    let cache = &sk[3 * small_len + params.public_len()..];
+
+    let f = small_decode(&sk[..small_len], p);
+    let v = small_decode(&sk[small_len..2 * small_len], p);
+    let c = rounded_decode(&ciphertext[..params.rounded_len()], params);
+    let r = decrypt(&c, &f, &v, params);
+
+    let (cnew, mut r_enc) = hide(&r, pk, cache, params);
+    let same = ct::bytes_eq(ciphertext, &cnew);
+    ct::conditional_copy(&mut r_enc, rho, !same)?;
+    let k = hash_session(same.unwrap_u8(), &r_enc, ciphertext);
+    Ok(k.to_vec().into_boxed_slice())
+}
+
+/// Encrypts the short polynomial `r` and appends the confirmation hash
+///
+/// Returns the ciphertext and the encoding of `r`.
+fn hide(r: &[i8], pk: &[u8], cache: &[u8], params: &PrimeParams) -> (Vec<u8>, Vec<u8>) {
+    let r_enc = small_encode(r);
+    let h = rq_decode(pk, params);
+    let mut hr = vec![0i16; params.p];
+    rq_mult_small(&mut hr, &h, r, params);
+    for coeff in hr.iter_mut() {
+        *coeff -= f3_freeze(*coeff as i32) as i16;
+    }
+
+    let mut c = vec![0u8; params.ciphertext_len()];
+    rounded_encode(&mut c[..params.rounded_len()], &hr, params);
+    let confirm = hash_prefix(2, &[&hash_prefix(3, &[&r_enc]), cache]);
+    c[params.rounded_len()..].copy_from_slice(&confirm);
+    (c, r_enc)
+}
+
+/// Recovers the short polynomial from a rounded ciphertext polynomial
+fn decrypt(c: &[i16], f: &[i8], ginv: &[i8], params: &PrimeParams) -> Vec<i8> {
+    let p = params.p;
+    let mut cf = vec![0i16; p];
+    rq_mult_small(&mut cf, c, f, params);
+    let e: Vec<i8> = cf.iter().map(|x| f3_freeze(fq_freeze(3 * *x as i32, params) as i32)).collect();
+    let mut ev = vec![0i8; p];
+    r3_mult(&mut ev, &e, ginv);
+
+    let weight = ev.iter().filter(|x| **x != 0).count();
+    if weight == params.w {
+        ev
+    } else {
+        // Fixed weight w polynomial, so that the re-encryption check fails
+        let mut r = vec![0i8; p];
+        for x in r.iter_mut().take(params.w) {
+            *x = 1;
+        }
+        r
+    }
+}
+
+/// Session key hash
+fn hash_session(b: u8, r_enc: &[u8], c: &[u8]) -> [u8; HASH_LEN] {
+    hash_prefix(b, &[&hash_prefix(3, &[r_enc]), c])
+}
+
+/// SHA-512 of the prefix byte followed by the inputs, truncated to 32 bytes
+fn hash_prefix(b: u8, inputs: &[&[u8]]) -> [u8; HASH_LEN] {
+    let mut hasher = Sha512::new();
+    hasher.update([b]);
+    for input in inputs {
+        hasher.update(input);
+    }
+    let mut out = [0u8; HASH_LEN];
+    out.copy_from_slice(&hasher.finalize()[..HASH_LEN]);
+    out
+}
+
+/// Random bytes from the rand context
+fn random_bytes(len: usize, rand_ctx: &RandContext) -> Result<Box<[u8]>, Error> {
+    if len > u16::MAX as usize {
+        return Err(Error::InvalidParam);
+    }
+    ::rand::generate(len as u16, rand_ctx)
+}
+
+/// Random little endian 32 bit words from the rand context
+///
+/// Each word is a separate request to the RNG, as in the reference implementation, so that the
+/// NIST known-answer tests can be reproduced.
+fn random_u32(len: usize, rand_ctx: &RandContext) -> Result<Vec<u32>, Error> {
+    (0..len)
+        .map(|_| {
+            let c = random_bytes(4, rand_ctx)?;
+            Ok(c[0] as u32 | (c[1] as u32) << 8 | (c[2] as u32) << 16 | (c[3] as u32) << 24)
+        })
+        .collect()
+}
+
+/// Random polynomial with coefficients in {-1, 0, 1}
+fn small_random(out: &mut [i8], rand_ctx: &RandContext) -> Result<(), Error> {
+    let l = random_u32(out.len(), rand_ctx)?;
+    for (x, l) in out.iter_mut().zip(l) {
+        *x = ((((l & 0x3fff_ffff) * 3) >> 30) as i8) - 1;
+    }
+    Ok(())
+}
+
+/// Random polynomial with exactly w coefficients equal to -1 or 1
+fn short_random(out: &mut [i8], params: &PrimeParams, rand_ctx: &RandContext) -> Result<(), Error> {
+    let mut l = random_u32(params.p, rand_ctx)?;
+    for (i, x) in l.iter_mut().enumerate() {
+        if i < params.w {
+            *x &= !1;
+        } else {
+            *x = (*x & !3) | 1;
+        }
+    }
+    l.sort();
+    for (x, l) in out.iter_mut().zip(l) {
+        *x = (l & 3) as i8 - 1;
+    }
+    Ok(())
+}
+
+/// Reduces modulo 3 to {-1, 0, 1}
+fn f3_freeze(x: i32) -> i8 {
+    (((x + 1) % 3 + 3) % 3 - 1) as i8
+}
+
+/// Reduces modulo q to {-(q-1)/2, ..., (q-1)/2}
+fn fq_freeze(x: i32, params: &PrimeParams) -> i16 {
+    let q = params.q;
+    (((x + params.q12()) % q + q) % q - params.q12()) as i16
+}
+
+/// Inverse modulo q
+fn fq_recip(a: i16, params: &PrimeParams) -> i16 {
+    // a^(q-2) by square and multiply
+    let mut result = 1i32;
+    let mut base = a as i32;
+    let mut exp = params.q - 2;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = fq_freeze(result * base, params) as i32;
+        }
+        base = fq_freeze(base * base, params) as i32;
+        exp >>= 1;
+    }
+    result as i16
+}
+
+/// Multiplication in `Z_3[x]/(x^p - x - 1)`
+fn r3_mult(h: &mut [i8], f: &[i8], g: &[i8]) {
+    let p = f.len();
+    let mut fg = vec![0i32; 2 * p - 1];
+    for (i, fi) in f.iter().enumerate() {
+        for (j, gj) in g.iter().enumerate() {
+            fg[i + j] += *fi as i32 * *gj as i32;
+        }
+    }
+    for i in (p..2 * p - 1).rev() {
+        fg[i - p] += fg[i];
+        fg[i - p + 1] += fg[i];
+    }
+    for (hi, x) in h.iter_mut().zip(fg.iter()) {
+        *hi = f3_freeze(*x);
+    }
+}
+
+/// Multiplication of a polynomial modulo q by a small polynomial in `Z_q[x]/(x^p - x - 1)`
+fn rq_mult_small(h: &mut [i16], f: &[i16], g: &[i8], params: &PrimeParams) {
+    let p = params.p;
+    let mut fg = vec![0i32; 2 * p - 1];
+    for (i, fi) in f.iter().enumerate() {
+        for (j, gj) in g.iter().enumerate() {
+            fg[i + j] += *fi as i32 * *gj as i32;
+        }
+    }
+    for i in (p..2 * p - 1).rev() {
+        fg[i - p] = fq_freeze(fg[i - p] + fg[i], params) as i32;
+        fg[i - p + 1] = fq_freeze(fg[i - p + 1] + fg[i], params) as i32;
+    }
+    for (hi, x) in h.iter_mut().zip(fg.iter()) {
+        *hi = fq_freeze(*x, params);
+    }
+}
+
+/// Inverse in `Z_3[x]/(x^p - x - 1)`, returns whether the polynomial is invertible
+fn r3_recip(out: &mut [i8], input: &[i8]) -> bool {
+    let p = input.len();
+    let mut f = vec![0i8; p + 1];
+    let mut g = vec![0i8; p + 1];
+    let mut v = vec![0i8; p + 1];
+    let mut r = vec![0i8; p + 1];
+    r[0] = 1;
+    f[0] = 1;
+    f[p - 1] = -1;
+    f[p] = -1;
+    for i in 0..p {
+        g[p - 1 - i] = input[i];
+    }
+    let mut delta = 1i32;
+
+    for _ in 0..2 * p - 1 {
+        for i in (1..p + 1).rev() {
+            v[i] = v[i - 1];
+        }
+        v[0] = 0;
+
+        let sign = -(g[0] as i32) * f[0] as i32;
+        if delta > 0 && g[0] != 0 {
+            delta = -delta;
+            ::std::mem::swap(&mut f, &mut g);
+            ::std::mem::swap(&mut v, &mut r);
+        }
+        delta += 1;
+
+        for i in 0..p + 1 {
+            g[i] = f3_freeze(g[i] as i32 + sign * f[i] as i32);
+            r[i] = f3_freeze(r[i] as i32 + sign * v[i] as i32);
+        }
+        for i in 0..p {
+            g[i] = g[i + 1];
+        }
+        g[p] = 0;
+    }
+
+    let sign = f[0];
+    for i in 0..p {
+        out[i] = sign * v[p - 1 - i];
+    }
+    delta == 0
+}
+
+/// Inverse of 3 times the polynomial in `Z_q[x]/(x^p - x - 1)`, returns whether the polynomial is
+/// invertible
+fn rq_recip3(out: &mut [i16], input: &[i8], params: &PrimeParams) -> bool {
+    let p = params.p;
+    let mut f = vec![0i16; p + 1];
+    let mut g = vec![0i16; p + 1];
+    let mut v = vec![0i16; p + 1];
+    let mut r = vec![0i16; p + 1];
+    r[0] = fq_recip(3, params);
+    f[0] = 1;
+    f[p - 1] = -1;
+    f[p] = -1;
+    for i in 0..p {
+        g[p - 1 - i] = input[i] as i16;
+    }
+    let mut delta = 1i32;
+
+    for _ in 0..2 * p - 1 {
+        for i in (1..p + 1).rev() {
+            v[i] = v[i - 1];
+        }
+        v[0] = 0;
+
+        if delta > 0 && g[0] != 0 {
+            delta = -delta;
+            ::std::mem::swap(&mut f, &mut g);
+            ::std::mem::swap(&mut v, &mut r);
+        }
+        delta += 1;
+
+        let f0 = f[0] as i32;
+        let g0 = g[0] as i32;
+        for i in 0..p + 1 {
+            g[i] = fq_freeze(f0 * g[i] as i32 - g0 * f[i] as i32, params);
+            r[i] = fq_freeze(f0 * r[i] as i32 - g0 * v[i] as i32, params);
+        }
+        for i in 0..p {
+            g[i] = g[i + 1];
+        }
+        g[p] = 0;
+    }
+
+    let scale = fq_recip(f[0], params) as i32;
+    for i in 0..p {
+        out[i] = fq_freeze(scale * v[p - 1 - i] as i32, params);
+    }
+    delta == 0
+}
+
+/// Encodes a small polynomial, 4 coefficients per byte
+fn small_encode(f: &[i8]) -> Vec<u8> {
+    f.chunks(4)
+        .map(|c| c.iter().enumerate().fold(0u8, |x, (i, fi)| x | (((fi + 1) as u8) << (2 * i))))
+        .collect()
+}
+
+/// Decodes a small polynomial
+fn small_decode(s: &[u8], p: usize) -> Vec<i8> {
+    (0..p).map(|i| ((s[i / 4] >> (2 * (i % 4))) & 3) as i8 - 1).collect()
+}
+
+/// Encodes a polynomial modulo q
+fn rq_encode(s: &mut [u8], r: &[i16], params: &PrimeParams) {
+    let values: Vec<u32> = r.iter().map(|x| (*x as i32 + params.q12()) as u32).collect();
+    let moduli = vec![params.q as u32; params.p];
+    encode(s, &values, &moduli);
+}
+
+/// Decodes a polynomial modulo q
+fn rq_decode(s: &[u8], params: &PrimeParams) -> Vec<i16> {
+    let moduli = vec![params.q as u32; params.p];
+    decode(s, &moduli).iter().map(|x| (*x as i32 - params.q12()) as i16).collect()
+}
+
+/// Encodes a rounded polynomial, whose coefficients are multiples of 3
+fn rounded_encode(s: &mut [u8], r: &[i16], params: &PrimeParams) {
+    let values: Vec<u32> = r.iter()
+        .map(|x| (((*x as i32 + params.q12()) * 10923) >> 15) as u32)
+        .collect();
+    let moduli = vec![(params.q as u32).div_ceil(3); params.p];
+    encode(s, &values, &moduli);
+}
+
+/// Decodes a rounded polynomial
+fn rounded_decode(s: &[u8], params: &PrimeParams) -> Vec<i16> {
+    let moduli = vec![(params.q as u32).div_ceil(3); params.p];
+    decode(s, &moduli).iter().map(|x| (*x as i32 * 3 - params.q12()) as i16).collect()
+}
+
+/// Length of the encoding of values with the given moduli
+fn encoded_len(moduli: &[u32]) -> usize {
+    if moduli.len() == 1 {
+        let mut m = moduli[0];
+        let mut len = 0;
+        while m > 1 {
+            len += 1;
+            m = (m + 255) >> 8;
+        }
+        return len;
+    }
+
+    let mut len = 0;
+    let mut moduli2 = Vec::with_capacity(moduli.len().div_ceil(2));
+    for pair in moduli.chunks(2) {
+        if pair.len() == 2 {
+            let mut m = pair[0] * pair[1];
+            while m >= 16384 {
+                len += 1;
+                m = (m + 255) >> 8;
+            }
+            moduli2.push(m);
+        } else {
+            moduli2.push(pair[0]);
+        }
+    }
+    len + encoded_len(&moduli2)
+}
+
+/// Encodes values `0 <= values[i] < moduli[i] < 16384` in as few bytes as possible
+fn encode(out: &mut [u8], values: &[u32], moduli: &[u32]) {
+    if values.len() == 1 {
+        let mut r = values[0];
+        let mut m = moduli[0];
+        let mut pos = 0;
+        while m > 1 {
+            out[pos] = r as u8;
+            pos += 1;
+            r >>= 8;
+            m = (m + 255) >> 8;
+        }
+        return;
+    }
+
+    let mut pos = 0;
+    let mut values2 = Vec::with_capacity(values.len().div_ceil(2));
+    let mut moduli2 = Vec::with_capacity(values.len().div_ceil(2));
+    for (r_pair, m_pair) in values.chunks(2).zip(moduli.chunks(2)) {
+        if r_pair.len() == 2 {
+            let mut r = r_pair[0] + r_pair[1] * m_pair[0];
+            let mut m = m_pair[0] * m_pair[1];
+            while m >= 16384 {
+                out[pos] = r as u8;
+                pos += 1;
+                r >>= 8;
+                m = (m + 255) >> 8;
+            }
+            values2.push(r);
+            moduli2.push(m);
+        } else {
+            values2.push(r_pair[0]);
+            moduli2.push(m_pair[0]);
+        }
+    }
+    encode(&mut out[pos..], &values2, &moduli2);
+}
+
+/// Decodes values encoded with `encode()`
+fn decode(s: &[u8], moduli: &[u32]) -> Vec<u32> {
+    if moduli.len() == 1 {
+        let m = moduli[0];
+        return vec![if m == 1 {
+                        0
+                    } else if m <= 256 {
+                        s[0] as u32 % m
+                    } else {
+                        (s[0] as u32 + ((s[1] as u32) << 8)) % m
+                    }];
+    }
+
+    let mut pos = 0;
+    let mut bottom = Vec::with_capacity(moduli.len() / 2);
+    let mut moduli2 = Vec::with_capacity(moduli.len().div_ceil(2));
+    for pair in moduli.chunks(2) {
+        if pair.len() == 2 {
+            let m = pair[0] * pair[1];
+            if m > 256 * 16383 {
+                bottom.push((256 * 256, s[pos] as u32 + 256 * s[pos + 1] as u32));
+                pos += 2;
+                moduli2.push((((m + 255) >> 8) + 255) >> 8);
+            } else if m >= 16384 {
+                bottom.push((256, s[pos] as u32));
+                pos += 1;
+                moduli2.push((m + 255) >> 8);
+            } else {
+                bottom.push((1, 0));
+                moduli2.push(m);
+            }
+        } else {
+            moduli2.push(pair[0]);
+        }
+    }
+
+    let values2 = decode(&s[pos..], &moduli2);
+    let mut out = Vec::with_capacity(moduli.len());
+    for (i, &(t, r)) in bottom.iter().enumerate() {
+        let r = r + t * values2[i];
+        out.push(r % moduli[2 * i]);
+        out.push((r / moduli[2 * i]) % moduli[2 * i + 1]);
+    }
+    if moduli.len() % 2 == 1 {
+        out.push(values2[values2.len() - 1]);
+    }
+    out
+}
@@ -0,0 +1,413 @@
+//! Native polynomial arithmetic
+//!
+//! Polynomial arithmetic implemented in Rust, used instead of libntru where it is faster. All the
+//! arithmetic is done modulo 2^16, so the results can be reduced with any power of 2 mask.
+//!
+//! libntru only has SIMD code for x86, so on aarch64 the inner loops use NEON instructions, if
+//! they are detected at runtime. Otherwise, with the `portable-simd` feature they use `std::simd`,
+//! which needs a nightly compiler, and plain scalar code without it.
+//!
+//! Polynomials are also packed into bytes here, so their encoding does not depend on the byte
+//! order of the target.
+
+use std::mem;
+
+/// Whether libntru has SIMD polynomial multiplication for the target
+pub const LIBNTRU_SIMD: bool = cfg!(any(target_arch = "x86", target_arch = "x86_64"));
+
+/// Number of coefficients below which Karatsuba falls back to schoolbook multiplication
+const KARATSUBA_CUTOFF: usize = 32;
+
+/// Multiplies a polynomial by a ternary polynomial in `Z[X]/(X^N-1)`
+///
+/// The ternary polynomial is given by the indices of its +1 and -1 coefficients, which must be
+/// lower than `N`. The coefficients of the result are reduced with `mod_mask`.
+pub fn mult_tern(a: &[i16], ones: &[u16], neg_ones: &[u16], mod_mask: u16) -> Vec<i16> {
+    let n = a.len();
+    let a: Vec<u16> = a.iter().map(|c| *c as u16).collect();
+    let mut c = vec![0u16; n];
+
+    // Multiplying by X^j rotates the coefficients j positions
+    for j in ones.iter().map(|j| *j as usize) {
+        add_assign(&mut c[j..], &a[..n - j]);
+        add_assign(&mut c[..j], &a[n - j..]);
+    }
+    for j in neg_ones.iter().map(|j| *j as usize) {
+        sub_assign(&mut c[j..], &a[..n - j]);
+        sub_assign(&mut c[..j], &a[n - j..]);
+    }
+    mask(&mut c, mod_mask);
+    c.iter().map(|c| *c as i16).collect()
+}
+
+/// Multiplies two polynomials in `Z[X]/(X^N-1)` with the Karatsuba algorithm
+///
+/// Both polynomials must have the same number of coefficients `N`, which must be at least one.
+/// The coefficients of the result are reduced with `mod_mask`.
+pub fn mult_int_karatsuba(a: &[i16], b: &[i16], mod_mask: u16) -> Vec<i16> {
+    debug_assert!(a.len() == b.len() && !a.is_empty());
+    let a: Vec<u16> = a.iter().map(|c| *c as u16).collect();
+    let b: Vec<u16> = b.iter().map(|c| *c as u16).collect();
+    let mut c = mult_cyclic(&a, &b);
+    mask(&mut c, mod_mask);
+    c.iter().map(|c| *c as i16).collect()
+}
+
+/// Inverts a polynomial in `(Z/qZ)[X]/(X^N-1)`, where `q = mod_mask + 1` is a power of 2
+///
+/// The polynomial is first inverted modulo 2 with the almost inverse algorithm, described in
+/// "Almost Inverses and Fast NTRU Key Generation", and the inverse is then lifted to modulo q with
+/// Newton iteration. Returns `None` if the polynomial is not invertible.
+pub fn invert(a: &[i16], mod_mask: u16) -> Option<Vec<i16>> {
+    let n = a.len();
+
+    // f and g have N+1 coefficients, b and c are reduced modulo X^N-1
+    let mut f: Vec<u8> = a.iter().map(|c| (*c & 1) as u8).collect();
+    f.push(0);
+    let mut g = vec![0u8; n + 1];
+    g[0] = 1;
+    g[n] = 1;
+    let mut b = vec![0u8; n];
+    b[0] = 1;
+    let mut c = vec![0u8; n];
+    let mut k = 0;
+
+    let mut deg_f = degree(&f)?;
+    let mut deg_g = n;
+    loop {
+        while f[0] == 0 {
+            f.rotate_left(1);
+            deg_f -= 1;
+            c.rotate_right(1);
+            k += 1;
+        }
+        if deg_f == 0 {
+            break;
+        }
+        if deg_f < deg_g {
+            mem::swap(&mut f, &mut g);
+            mem::swap(&mut b, &mut c);
+            mem::swap(&mut deg_f, &mut deg_g);
+        }
+        for (x, y) in f.iter_mut().zip(g.iter()) {
+            *x ^= *y;
+        }
+        for (x, y) in b.iter_mut().zip(c.iter()) {
+            *x ^= *y;
+        }
+        deg_f = degree(&f)?;
+    }
+
+    // The inverse modulo 2 is X^(N-k)*b
+    b.rotate_right((n - k % n) % n);
+
+    // Each iteration of inv = inv*(2-a*inv) doubles the number of correct bits
+    let a: Vec<u16> = a.iter().map(|c| *c as u16).collect();
+    let mut inv: Vec<u16> = b.iter().map(|c| *c as u16).collect();
+    let mut correct_mod = 2u32;
+    while correct_mod <= mod_mask as u32 {
+        let mut e = mult_cyclic(&a, &inv);
+        for x in e.iter_mut() {
+            *x = x.wrapping_neg();
+        }
+        e[0] = e[0].wrapping_add(2);
+        inv = mult_cyclic(&inv, &e);
+        correct_mod *= correct_mod;
+    }
+    mask(&mut inv, mod_mask);
+    Some(inv.iter().map(|c| *c as i16).collect())
+}
+
+/// Packs coefficients with `bits` bits each into `arr`, least significant bit first
+///
+/// This is the layout of libntru on little-endian targets, built byte by byte so that it is the
+/// same on big-endian ones. The higher bits of the coefficients are dropped, and so are the ones
+/// that do not fit in `arr`. The unused bits of the last byte are 0.
+pub fn pack<I: IntoIterator<Item = u16>>(coeffs: I, bits: u32, arr: &mut [u8]) {
+    let mut bytes = arr.iter_mut();
+    let mut buf = 0u32;
+    let mut buf_bits = 0;
+    for c in coeffs {
+        buf |= (c as u32 & ((1 << bits) - 1)) << buf_bits;
+        buf_bits += bits;
+        while buf_bits >= 8 {
+            if let Some(b) = bytes.next() {
+                *b = buf as u8;
+            }
+            buf >>= 8;
+            buf_bits -= 8;
+        }
+    }
+    if buf_bits > 0 {
+        if let Some(b) = bytes.next() {
+            *b = buf as u8;
+        }
+    }
+    for b in bytes {
+        *b = 0;
+    }
+}
+
+/// Unpacks coefficients with `bits` bits each from `arr`, least significant bit first
+///
+/// The inverse of `pack()`. The bits past the end of `arr` are read as 0.
+pub fn unpack(arr: &[u8], bits: u32, coeffs: &mut [i16]) {
+    let mut bytes = arr.iter();
+    let mut buf = 0u32;
+    let mut buf_bits = 0;
+    for c in coeffs.iter_mut() {
+        while buf_bits < bits {
+            buf |= (*bytes.next().unwrap_or(&0) as u32) << buf_bits;
+            buf_bits += 8;
+        }
+        *c = (buf & ((1 << bits) - 1)) as u16 as i16;
+        buf >>= bits;
+        buf_bits -= bits;
+    }
+}
+
+/// Index of the highest non-zero coefficient, or `None` for the zero polynomial
+fn degree(a: &[u8]) -> Option<usize> {
+    a.iter().rposition(|c| *c != 0)
+}
+
+/// Multiplies two polynomials in `Z[X]/(X^N-1)` modulo 2^16, with the Karatsuba algorithm
+fn mult_cyclic(a: &[u16], b: &[u16]) -> Vec<u16> {
+    let n = a.len();
+    let mut prod = karatsuba(a, b);
+
+    // X^N = 1, so the coefficient of X^(k+N) is added to the one of X^k
+    let (low, high) = prod.split_at_mut(n);
+    add_assign(&mut low[..n - 1], high);
+    prod.truncate(n);
+    prod
+}
+
+/// Multiplies two polynomials with the same number of coefficients, without reduction
+///
+/// Returns the `2N-1` coefficients of the product, modulo 2^16.
+fn karatsuba(a: &[u16], b: &[u16]) -> Vec<u16> {
+    let n = a.len();
+    if n <= KARATSUBA_CUTOFF {
+        let mut c = vec![0u16; 2 * n - 1];
+        for (i, ai) in a.iter().enumerate() {
+            mul_add_assign(&mut c[i..i + n], b, *ai);
+        }
+        return c;
+    }
+
+    // a = a0 + a1*X^h and b = b0 + b1*X^h, where a1 and b1 are at least as long as a0 and b0
+    let h = n / 2;
+    let (a0, a1) = a.split_at(h);
+    let (b0, b1) = b.split_at(h);
+    let z0 = karatsuba(a0, b0);
+    let z2 = karatsuba(a1, b1);
+
+    let mut sum_a = a1.to_vec();
+    add_assign(&mut sum_a[..h], a0);
+    let mut sum_b = b1.to_vec();
+    add_assign(&mut sum_b[..h], b0);
+    let mut z1 = karatsuba(&sum_a, &sum_b);
+    sub_assign(&mut z1[..z0.len()], &z0);
+    sub_assign(&mut z1, &z2);
+
+    let mut c = vec![0u16; 2 * n - 1];
+    add_assign(&mut c[..z0.len()], &z0);
+    add_assign(&mut c[h..h + z1.len()], &z1);
+    add_assign(&mut c[2 * h..], &z2);
+    c
+}
+
+/// Adds `b` to `a`, which must have the same length
+fn add_assign(a: &mut [u16], b: &[u16]) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if is_aarch64_feature_detected!("neon") {
+            return unsafe { neon::add_assign(a, b) };
+        }
+    }
+    portable::add_assign(a, b)
+}
+
+/// Subtracts `b` from `a`, which must have the same length
+fn sub_assign(a: &mut [u16], b: &[u16]) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if is_aarch64_feature_detected!("neon") {
+            return unsafe { neon::sub_assign(a, b) };
+        }
+    }
+    portable::sub_assign(a, b)
+}
+
+/// Adds `b` multiplied by `factor` to `a`, which must have the same length
+fn mul_add_assign(a: &mut [u16], b: &[u16], factor: u16) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if is_aarch64_feature_detected!("neon") {
+            return unsafe { neon::mul_add_assign(a, b, factor) };
+        }
+    }
+    portable::mul_add_assign(a, b, factor)
+}
+
+/// Reduces the coefficients with the given mask
+fn mask(a: &mut [u16], mod_mask: u16) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if is_aarch64_feature_detected!("neon") {
+            return unsafe { neon::mask(a, mod_mask) };
+        }
+    }
+    portable::mask(a, mod_mask)
+}
+
+#[cfg(feature = "portable-simd")]
+use self::simd as portable;
+#[cfg(not(feature = "portable-simd"))]
+use self::scalar as portable;
+
+/// Scalar versions of the inner loops
+mod scalar {
+    /// Adds `b` to `a`, which must have the same length
+    pub fn add_assign(a: &mut [u16], b: &[u16]) {
+        for (x, y) in a.iter_mut().zip(b.iter()) {
+            *x = x.wrapping_add(*y);
+        }
+    }
+
+    /// Subtracts `b` from `a`, which must have the same length
+    pub fn sub_assign(a: &mut [u16], b: &[u16]) {
+        for (x, y) in a.iter_mut().zip(b.iter()) {
+            *x = x.wrapping_sub(*y);
+        }
+    }
+
+    /// Adds `b` multiplied by `factor` to `a`, which must have the same length
+    pub fn mul_add_assign(a: &mut [u16], b: &[u16], factor: u16) {
+        for (x, y) in a.iter_mut().zip(b.iter()) {
+            *x = x.wrapping_add(y.wrapping_mul(factor));
+        }
+    }
+
+    /// Reduces the coefficients with the given mask
+    pub fn mask(a: &mut [u16], mod_mask: u16) {
+        for x in a.iter_mut() {
+            *x &= mod_mask;
+        }
+    }
+}
+
+/// Portable SIMD versions of the inner loops, 16 coefficients at a time
+#[cfg(feature = "portable-simd")]
+mod simd {
+    use std::simd::u16x16;
+    use super::scalar;
+
+    /// Number of coefficients in a vector
+    const LANES: usize = 16;
+
+    /// Adds `b` to `a`, which must have the same length
+    pub fn add_assign(a: &mut [u16], b: &[u16]) {
+        let mut a_chunks = a.chunks_exact_mut(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            (u16x16::from_slice(x) + u16x16::from_slice(y)).copy_to_slice(x);
+        }
+        scalar::add_assign(a_chunks.into_remainder(), b_chunks.remainder());
+    }
+
+    /// Subtracts `b` from `a`, which must have the same length
+    pub fn sub_assign(a: &mut [u16], b: &[u16]) {
+        let mut a_chunks = a.chunks_exact_mut(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            (u16x16::from_slice(x) - u16x16::from_slice(y)).copy_to_slice(x);
+        }
+        scalar::sub_assign(a_chunks.into_remainder(), b_chunks.remainder());
+    }
+
+    /// Adds `b` multiplied by `factor` to `a`, which must have the same length
+    pub fn mul_add_assign(a: &mut [u16], b: &[u16], factor: u16) {
+        let factor_vec = u16x16::splat(factor);
+        let mut a_chunks = a.chunks_exact_mut(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            (u16x16::from_slice(x) + u16x16::from_slice(y) * factor_vec).copy_to_slice(x);
+        }
+        scalar::mul_add_assign(a_chunks.into_remainder(), b_chunks.remainder(), factor);
+    }
+
+    /// Reduces the coefficients with the given mask
+    pub fn mask(a: &mut [u16], mod_mask: u16) {
+        let mask_vec = u16x16::splat(mod_mask);
+        let mut chunks = a.chunks_exact_mut(LANES);
+        for x in &mut chunks {
+            (u16x16::from_slice(x) & mask_vec).copy_to_slice(x);
+        }
+        scalar::mask(chunks.into_remainder(), mod_mask);
+    }
+}
+
+/// NEON versions of the inner loops, 8 coefficients at a time
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::{vaddq_u16, vandq_u16, vdupq_n_u16, vld1q_u16, vmlaq_n_u16, vst1q_u16,
+                             vsubq_u16};
+
+    /// Adds `b` to `a`, which must have the same length
+    #[target_feature(enable = "neon")]
+    pub unsafe fn add_assign(a: &mut [u16], b: &[u16]) {
+        assert_eq!(a.len(), b.len());
+        let vec_len = a.len() & !7;
+        for i in (0..vec_len).step_by(8) {
+            let sum = vaddq_u16(vld1q_u16(a.as_ptr().add(i)), vld1q_u16(b.as_ptr().add(i)));
+            vst1q_u16(a.as_mut_ptr().add(i), sum);
+        }
+        for i in vec_len..a.len() {
+            a[i] = a[i].wrapping_add(b[i]);
+        }
+    }
+
+    /// Subtracts `b` from `a`, which must have the same length
+    #[target_feature(enable = "neon")]
+    pub unsafe fn sub_assign(a: &mut [u16], b: &[u16]) {
+        assert_eq!(a.len(), b.len());
+        let vec_len = a.len() & !7;
+        for i in (0..vec_len).step_by(8) {
+            let diff = vsubq_u16(vld1q_u16(a.as_ptr().add(i)), vld1q_u16(b.as_ptr().add(i)));
+            vst1q_u16(a.as_mut_ptr().add(i), diff);
+        }
+        for i in vec_len..a.len() {
+            a[i] = a[i].wrapping_sub(b[i]);
+        }
+    }
+
+    /// Adds `b` multiplied by `factor` to `a`, which must have the same length
+    #[target_feature(enable = "neon")]
+    pub unsafe fn mul_add_assign(a: &mut [u16], b: &[u16], factor: u16) {
+        assert_eq!(a.len(), b.len());
+        let vec_len = a.len() & !7;
+        for i in (0..vec_len).step_by(8) {
+            let sum = vmlaq_n_u16(vld1q_u16(a.as_ptr().add(i)),
+                                  vld1q_u16(b.as_ptr().add(i)),
+                                  factor);
+            vst1q_u16(a.as_mut_ptr().add(i), sum);
+        }
+        for i in vec_len..a.len() {
+            a[i] = a[i].wrapping_add(b[i].wrapping_mul(factor));
+        }
+    }
+    /// Reduces the coefficients with the given mask
+    #[target_feature(enable = "neon")]
+    pub unsafe fn mask(a: &mut [u16], mod_mask: u16) {
+        let vec_len = a.len() & !7;
+        let mask_vec = vdupq_n_u16(mod_mask);
+        for i in (0..vec_len).step_by(8) {
+            vst1q_u16(a.as_mut_ptr().add(i), vandq_u16(vld1q_u16(a.as_ptr().add(i)), mask_vec));
+        }
+        for x in a[vec_len..].iter_mut() {
+            *x &= mod_mask;
+        }
+    }
+}
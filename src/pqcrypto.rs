@@ -0,0 +1,179 @@
+//! pqcrypto compatible API
+//!
+//! This module mirrors the conventions of the [`pqcrypto`](https://docs.rs/pqcrypto) crates, so
+//! that code written against them can switch to this crate with few changes. Each KEM parameter
+//! set has its own module with `PublicKey`, `SecretKey`, `Ciphertext` and `SharedSecret` types,
+//! the `*_bytes()` length functions, and `keypair()`, `encapsulate()` and `decapsulate()`.
+//!
+//! As in `pqcrypto`, randomness comes from the per-thread default rand context. Unlike
+//! `pqcrypto`, the functions return a `Result`, with the error of the rand context or of the KEM,
+//! instead of panicking. The lengths come from the parameter sets of the `ntruprime` and
+//! `nistntru` modules. Secret keys and shared secrets are not `Copy`, are compared in constant
+//! time, are wiped when dropped and do not show their bytes in their `Debug` output.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use ntru::pqcrypto::sntrup761::{keypair, encapsulate, decapsulate};
+//!
+//! let (pk, sk) = keypair().unwrap();
+//! let (ss1, ct) = encapsulate(&pk).unwrap();
+//! let ss2 = decapsulate(&ct, &sk).unwrap();
+//!
+//! assert_eq!(ss1, ss2);
+//! ```
+
+/// Defines a byte string newtype of a fixed length with `as_bytes()` and `from_bytes()`
+///
+/// The `secret` variant compares in constant time, wipes the bytes on drop and redacts them in
+/// its `Debug` output.
+macro_rules! fixed_bytes {
+    ($name:ident, $len:expr, $doc:expr, $err:expr) => {
+        #[doc = $doc]
+        #[derive(Clone, PartialEq, Eq, Debug)]
+        pub struct $name(Box<[u8]>);
+
+        fixed_bytes!(@methods $name, $len, $err);
+    };
+    (secret $name:ident, $len:expr, $doc:expr, $err:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name(Box<[u8]>);
+
+        fixed_bytes!(@methods $name, $len, $err);
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &$name) -> bool {
+                ct::bytes_eq(&self.0, &other.0).into()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_struct(stringify!($name)).field("len", &self.0.len()).finish()
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                types::wipe(&mut self.0);
+            }
+        }
+    };
+    (@methods $name:ident, $len:expr, $err:expr) => {
+        impl $name {
+            /// Get the bytes
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+
+            /// Construct it from bytes, that must have the exact length
+            pub fn from_bytes(bytes: &[u8]) -> Result<$name, Error> {
+                if bytes.len() != $len {
+                    return Err(($err)(bytes.len()));
+                }
+                Ok($name(bytes.to_vec().into_boxed_slice()))
+            }
+        }
+    };
+}
+
+/// Defines a pqcrypto style module for a KEM parameter set
+macro_rules! pqcrypto_kem {
+    ($name:ident, $doc:expr, $kem:ident, $params:ident) => {
+        #[doc = $doc]
+        pub mod $name {
+            use std::fmt;
+            use rand::default_context;
+            use types::{self, Error};
+            use ct;
+            use $kem;
+
+            fixed_bytes!(PublicKey,
+                         public_key_bytes(),
+                         "Public key",
+                         |_| Error::InvalidKey);
+            fixed_bytes!(secret SecretKey,
+                         secret_key_bytes(),
+                         "Secret key",
+                         |_| Error::InvalidKey);
+            fixed_bytes!(Ciphertext,
+                         ciphertext_bytes(),
+                         "Ciphertext",
+                         |actual| {
+                             Error::InvalidCiphertextLength {
+                                 expected: ciphertext_bytes(),
+                                 actual,
+                             }
+                         });
+            fixed_bytes!(secret SharedSecret,
+                         shared_secret_bytes(),
+                         "Shared secret",
+                         |_| Error::InvalidParam);
+
+            /// Public key length
+            pub fn public_key_bytes() -> usize {
+                $kem::$params.public_len()
+            }
+
+            /// Secret key length
+            pub fn secret_key_bytes() -> usize {
+                $kem::$params.private_len()
+            }
+
+            /// Ciphertext length
+            pub fn ciphertext_bytes() -> usize {
+                $kem::$params.ciphertext_len()
+            }
+
+            /// Shared secret length
+            pub fn shared_secret_bytes() -> usize {
+                $kem::$params.shared_secret_len()
+            }
+
+            /// Generates a key pair
+            ///
+            /// Returns the error of the default rand context if it can't be initialized.
+            pub fn keypair() -> Result<(PublicKey, SecretKey), Error> {
+                let rand_ctx = default_context()?;
+                let kp = $kem::generate_key_pair(&$kem::$params, &rand_ctx)?;
+                Ok((PublicKey(kp.get_public().export().to_vec().into_boxed_slice()),
+                    SecretKey(kp.get_private().export().to_vec().into_boxed_slice())))
+            }
+
+            /// Generates a shared secret and its ciphertext for the public key
+            ///
+            /// Returns the error of the default rand context if it can't be initialized.
+            pub fn encapsulate(pk: &PublicKey) -> Result<(SharedSecret, Ciphertext), Error> {
+                let rand_ctx = default_context()?;
+                let public = $kem::PublicKey::import(&pk.0, &$kem::$params)?;
+                let (c, k) = $kem::encapsulate(&public, &rand_ctx)?;
+                Ok((SharedSecret(k), Ciphertext(c)))
+            }
+
+            /// Recovers the shared secret from the ciphertext
+            ///
+            /// Invalid ciphertexts are implicitly rejected, so a pseudorandom shared secret is
+            /// returned for them.
+            pub fn decapsulate(ct: &Ciphertext, sk: &SecretKey) -> Result<SharedSecret, Error> {
+                let private = $kem::PrivateKey::import(&sk.0, &$kem::$params)?;
+                Ok(SharedSecret($kem::decapsulate(&ct.0, &private)?))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ntru-prime")]
+pqcrypto_kem!(sntrup653, "sntrup653 KEM", ntruprime, SNTRUP653);
+#[cfg(feature = "ntru-prime")]
+pqcrypto_kem!(sntrup761, "sntrup761 KEM", ntruprime, SNTRUP761);
+#[cfg(feature = "ntru-prime")]
+pqcrypto_kem!(sntrup857, "sntrup857 KEM", ntruprime, SNTRUP857);
+#[cfg(feature = "nist-ntru")]
+pqcrypto_kem!(ntruhps2048677, "ntruhps2048677 KEM", nistntru, NTRUHPS2048677);
+#[cfg(feature = "nist-ntru")]
+pqcrypto_kem!(ntruhps4096821, "ntruhps4096821 KEM", nistntru, NTRUHPS4096821);
+#[cfg(feature = "nist-ntru")]
+pqcrypto_kem!(ntruhrss701, "ntruhrss701 KEM", nistntru, NTRUHRSS701);
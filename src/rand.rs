@@ -3,11 +3,18 @@
 //! This module includes all the needed structs and functions to interact with the randomness
 //! needed by NTRU. Both, key generation and encryption need a source of randomness, for that they
 //! need a `RandContext`, that can be generated from a `RandGen`. The recommended RNG is the
-//! `RNG_DEFAULT`. If needed, in this module random data can be generated with the `generate()`
-//! function. Also both random `TernPoly` and `ProdPoly` can be generated.
-use std::{slice, ptr};
-use libc::{uint8_t, uint16_t, c_void};
-use types::{Error, TernPoly};
+//! `RNG_DEFAULT`, a `CTR_DRBG` seeded from the operating system through the `getrandom` crate.
+//! If needed, in this module random data can be generated with the `generate()` function. Also
+//! both random `TernPoly` and `ProdPoly` can be generated. Custom sources of randomness can be
+//! used by implementing the safe `RandomSource` trait and creating the context with
+//! `init_source()`.
+use std::{slice, ptr, panic};
+use std::panic::AssertUnwindSafe;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use libc::{uint8_t, uint16_t, c_int, c_void};
+use getrandom;
+use types::{Error, TernPoly, MAX_DEGREE, MAX_ONES};
 use super::ffi;
 
 /// A random context for key generation and encryption
@@ -21,15 +28,24 @@ pub struct RandContext {
     pub seed_len: uint16_t,
     /// The current context state
     pub state: *const c_void,
+    /// Number of generate calls between automatic reseeds, 0 if disabled
+    reseed_interval: Cell<u64>,
+    /// Number of generate calls since the last automatic reseed
+    reseed_counter: Cell<u64>,
+    /// Number of generate calls of the RNGs implemented in Rust
+    generate_calls: Cell<u64>,
 }
 
 impl Default for RandContext {
     fn default() -> RandContext {
         RandContext {
-            rand_gen: &mut RNG_DEFAULT,
+            rand_gen: &RNG_DEFAULT,
             seed: ptr::null(),
             seed_len: 0,
             state: ptr::null(),
+            reseed_interval: Cell::new(0),
+            reseed_counter: Cell::new(0),
+            generate_calls: Cell::new(0),
         }
     }
 }
@@ -37,9 +53,15 @@ impl Default for RandContext {
 impl Drop for RandContext {
     fn drop(&mut self) {
         let result = unsafe { ffi::ntru_rand_release(self) };
-        if result != 0 {
-            panic!()
+        // With the no-panic feature, a context that fails to be released is leaked instead
+        #[cfg(not(feature = "no-panic"))]
+        {
+            if result != 0 {
+                panic!()
+            }
         }
+        #[cfg(feature = "no-panic")]
+        let _ = result;
     }
 }
 
@@ -53,6 +75,98 @@ impl RandContext {
     pub fn get_rng(&self) -> &RandGen {
         unsafe { &*self.rand_gen }
     }
+
+    /// Reseeds the RandContext
+    ///
+    /// Reseeds the `CTR_DRBG` of the context with fresh entropy from the operating system, as
+    /// described in SP 800-90A section 10.2.1.4. The given additional input, that can be empty, is
+    /// mixed in the new state. Only `CTR_DRBG` based RNGs, such as `RNG_DEFAULT` and
+    /// `RNG_CTR_DRBG`, can be reseeded; for other RNGs an `Error::Prng` will be returned. Note that
+    /// a deterministic context will no longer be deterministic after reseeding it.
+    pub fn reseed(&mut self, additional_input: &[u8]) -> Result<(), Error> {
+        self.reseed_drbg(additional_input)
+    }
+
+    /// Sets the automatic reseed interval
+    ///
+    /// The `CTR_DRBG` of the context will be reseeded with fresh entropy from the operating system
+    /// every `interval` generate calls. `DEFAULT_RESEED_INTERVAL` is a sensible value for long
+    /// lived contexts. An interval of 0, the default, disables automatic reseeding. It only has
+    /// effect on `CTR_DRBG` based RNGs.
+    pub fn set_reseed_interval(&mut self, interval: u64) {
+        self.reseed_interval.set(interval);
+        self.reseed_counter.set(0);
+    }
+
+    /// Gets the automatic reseed interval, 0 if automatic reseeding is disabled
+    pub fn get_reseed_interval(&self) -> u64 {
+        self.reseed_interval.get()
+    }
+
+    /// Gets every byte generated so far by an `RNG_RECORDING` context
+    ///
+    /// The recording can be played back with `RNG_REPLAY`. Returns `None` for any other RNG.
+    pub fn get_recording(&self) -> Option<Box<[u8]>> {
+        if self.get_rng().kind != RngKind::Recording || self.state.is_null() {
+            return None;
+        }
+        let state = unsafe { &*(self.state as *const RecordingState) };
+        let recorded = state.recorded.borrow();
+        Some(recorded.clone().into_boxed_slice())
+    }
+
+    /// Gets the number of generate calls so far
+    ///
+    /// Only the RNGs implemented in Rust count their calls, it is always 0 for the RNGs of
+    /// libntru, such as `RNG_DEVURANDOM`.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn generate_calls(&self) -> u64 {
+        self.generate_calls.get()
+    }
+
+    /// Counts a generate call
+    ///
+    /// Reseeds the `CTR_DRBG` if the automatic reseed interval of the context has been reached.
+    fn count_generate(&self) -> Result<(), Error> {
+        let interval = self.reseed_interval.get();
+        if interval > 0 {
+            let counter = self.reseed_counter.get() + 1;
+            if counter >= interval {
+                return self.reseed_drbg(&[]);
+            }
+            self.reseed_counter.set(counter);
+        }
+        Ok(())
+    }
+
+    /// Reseeds the `CTR_DRBG` state with entropy from the operating system
+    fn reseed_drbg(&self, additional_input: &[u8]) -> Result<(), Error> {
+        if self.get_rng().kind != RngKind::CtrDrbg || self.state.is_null() {
+            return Err(Error::Prng);
+        }
+
+        let mut entropy = [0u8; DEFAULT_SEED_LEN];
+        if getrandom::getrandom(&mut entropy).is_err() {
+            return Err(Error::Prng);
+        }
+        let result = unsafe {
+            ffi::nist_ctr_drbg_reseed(self.state as *mut c_void,
+                                      entropy.as_ptr() as *const c_void,
+                                      entropy.len() as c_int,
+                                      additional_input.as_ptr() as *const c_void,
+                                      additional_input.len() as c_int)
+        };
+        for b in entropy.iter_mut() {
+            unsafe { ptr::write_volatile(b, 0) };
+        }
+
+        if result == 0 {
+            self.reseed_counter.set(0);
+            Ok(())
+        } else {
+            Err(Error::Prng)
+        }
+    }
 }
 
 #[repr(C)]
@@ -69,6 +183,39 @@ pub struct RandGen {
                                           -> uint8_t,
     /// The rng release function
     release_fn: unsafe extern "C" fn(rand_ctx: *mut RandContext) -> uint8_t,
+    /// The kind of RNG, which determines what its state is
+    kind: RngKind,
+}
+
+/// The kind of a `RandGen`
+#[repr(u8)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum RngKind {
+    /// An RNG whose state is managed by libntru
+    Native,
+    /// A `CTR_DRBG` that can be reseeded
+    CtrDrbg,
+    /// The state is a `CountingState`
+    Counting,
+    /// The state is a `ReplayState`
+    Replay,
+    /// The state is a `RecordingState`
+    Recording,
+    /// The state is a `SourceState`
+    Source,
+}
+
+/// A source of random data
+///
+/// This trait allows using any source of randomness, such as a hardware RNG or an HSM, for key
+/// generation and encryption without writing FFI callbacks. A `RandContext` can be created from
+/// any implementation with `init_source()`.
+pub trait RandomSource {
+    /// Fills the whole buffer with random data
+    ///
+    /// If the buffer cannot be completely filled an error must be returned, usually
+    /// `Error::Prng`.
+    fn fill(&mut self, buf: &mut [u8]) -> Result<(), Error>;
 }
 
 impl RandGen {
@@ -102,6 +249,7 @@ pub const RNG_WINCRYPT: RandGen = RandGen {
     init_fn: ffi::ntru_rand_wincrypt_init,
     generate_fn: ffi::ntru_rand_wincrypt_generate,
     release_fn: ffi::ntru_rand_wincrypt_release,
+    kind: RngKind::Native,
 };
 
 #[cfg(not(target_os = "windows"))]
@@ -110,6 +258,7 @@ pub const RNG_DEVURANDOM: RandGen = RandGen {
     init_fn: ffi::ntru_rand_devurandom_init,
     generate_fn: ffi::ntru_rand_devurandom_generate,
     release_fn: ffi::ntru_rand_devurandom_release,
+    kind: RngKind::Native,
 };
 #[cfg(not(target_os = "windows"))]
 /// Unix RNG, /dev/random
@@ -117,24 +266,384 @@ pub const RNG_DEVRANDOM: RandGen = RandGen {
     init_fn: ffi::ntru_rand_devrandom_init,
     generate_fn: ffi::ntru_rand_devrandom_generate,
     release_fn: ffi::ntru_rand_devrandom_release,
+    kind: RngKind::Native,
+};
+
+/// Operating system RNG
+///
+/// Reads every random byte directly from the operating system entropy source through the
+/// `getrandom` crate, so it works on every platform `getrandom` supports.
+pub const RNG_GETRANDOM: RandGen = RandGen {
+    init_fn: getrandom_init,
+    generate_fn: getrandom_generate,
+    release_fn: getrandom_release,
+    kind: RngKind::Native,
 };
 
 /// Default RNG
 ///
-/// `CTR_DRBG` seeded from `/dev/urandom` (on *nix) or `CryptGenRandom()` (on Windows)
+/// `CTR_DRBG` seeded from the operating system entropy source through the `getrandom` crate.
 pub const RNG_DEFAULT: RandGen = RandGen {
-    init_fn: ffi::ntru_rand_default_init,
-    generate_fn: ffi::ntru_rand_default_generate,
-    release_fn: ffi::ntru_rand_default_release,
+    init_fn: default_init,
+    generate_fn: ctr_drbg_generate,
+    release_fn: ffi::ntru_rand_ctr_drbg_release,
+    kind: RngKind::CtrDrbg,
 };
 
 /// Deterministic RNG based on `CTR_DRBG`
 pub const RNG_CTR_DRBG: RandGen = RandGen {
     init_fn: ffi::ntru_rand_ctr_drbg_init,
-    generate_fn: ffi::ntru_rand_ctr_drbg_generate,
+    generate_fn: ctr_drbg_generate,
     release_fn: ffi::ntru_rand_ctr_drbg_release,
+    kind: RngKind::CtrDrbg,
+};
+
+/// Counting RNG, for testing only
+///
+/// Produces the predictable byte sequence `0, 1, 2, ..., 255, 0, 1, ...`, starting over for every
+/// new context. It must never be used to generate real keys or encrypt real messages.
+pub const RNG_COUNTING: RandGen = RandGen {
+    init_fn: counting_init,
+    generate_fn: counting_generate,
+    release_fn: counting_release,
+    kind: RngKind::Counting,
+};
+
+/// Replay RNG, for testing only
+///
+/// Plays back a recorded byte stream, that must be given as the seed of `init_det()`. Once the
+/// stream is exhausted, every generate call fails with `Error::Prng`. Together with
+/// `RNG_RECORDING` it allows reproducing the exact randomness consumed by an operation. It must
+/// never be used to generate real keys or encrypt real messages.
+pub const RNG_REPLAY: RandGen = RandGen {
+    init_fn: replay_init,
+    generate_fn: replay_generate,
+    release_fn: replay_release,
+    kind: RngKind::Replay,
+};
+
+/// Recording RNG
+///
+/// Works as `RNG_DEFAULT`, but it keeps a copy of every byte it generates, that can be retrieved
+/// with `RandContext::get_recording()` and played back with `RNG_REPLAY`. Note that the recording
+/// contains all the randomness used by an operation, so it must be handled as secret as the keys
+/// or messages generated with it.
+pub const RNG_RECORDING: RandGen = RandGen {
+    init_fn: recording_init,
+    generate_fn: recording_generate,
+    release_fn: recording_release,
+    kind: RngKind::Recording,
+};
+
+/// A random number generator for real keys and messages, to choose one without its constant
+///
+/// The RNGs for testing only, `RNG_COUNTING` and `RNG_REPLAY`, cannot be chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rng {
+    /// `RNG_DEFAULT`
+    Default,
+    /// `RNG_GETRANDOM`
+    GetRandom,
+    /// `RNG_CTR_DRBG`
+    CtrDrbg,
+    /// `RNG_DEVURANDOM`
+    #[cfg(not(target_os = "windows"))]
+    DevUrandom,
+    /// `RNG_DEVRANDOM`
+    #[cfg(not(target_os = "windows"))]
+    DevRandom,
+    /// `RNG_WINCRYPT`
+    #[cfg(target_os = "windows")]
+    WinCrypt,
+}
+
+impl Rng {
+    /// Gets the constant of the RNG
+    pub fn get_rand_gen(&self) -> &'static RandGen {
+        match *self {
+            Rng::Default => &RNG_DEFAULT,
+            Rng::GetRandom => &RNG_GETRANDOM,
+            Rng::CtrDrbg => &RNG_CTR_DRBG,
+            #[cfg(not(target_os = "windows"))]
+            Rng::DevUrandom => &RNG_DEVURANDOM,
+            #[cfg(not(target_os = "windows"))]
+            Rng::DevRandom => &RNG_DEVRANDOM,
+            #[cfg(target_os = "windows")]
+            Rng::WinCrypt => &RNG_WINCRYPT,
+        }
+    }
+
+    /// Whether the RNG is a `CTR_DRBG`, that can be seeded with `init_det()`
+    pub fn is_ctr_drbg(&self) -> bool {
+        self.get_rand_gen().kind == RngKind::CtrDrbg
+    }
+}
+
+/// RNG backed by a `RandomSource`, only used through `init_source()`
+static RNG_SOURCE: RandGen = RandGen {
+    init_fn: source_init,
+    generate_fn: source_generate,
+    release_fn: source_release,
+    kind: RngKind::Source,
 };
 
+/// State of the `RNG_SOURCE` RNG
+type SourceState = RefCell<Box<dyn RandomSource>>;
+
+/// State of the `RNG_COUNTING` RNG
+struct CountingState {
+    /// The next byte to generate
+    next: Cell<u8>,
+}
+
+/// State of the `RNG_REPLAY` RNG
+struct ReplayState {
+    /// The recorded stream
+    data: Box<[u8]>,
+    /// The position of the next byte to play back
+    pos: Cell<usize>,
+}
+
+/// State of the `RNG_RECORDING` RNG
+struct RecordingState {
+    /// The context that generates the actual random data
+    inner: RandContext,
+    /// Every byte generated so far
+    recorded: RefCell<Vec<u8>>,
+}
+
+/// Initialization function of `RNG_COUNTING`
+unsafe extern "C" fn counting_init(rand_ctx: *mut RandContext,
+                                   _rand_gen: *const RandGen)
+                                   -> uint8_t {
+    let state = Box::new(CountingState { next: Cell::new(0) });
+    (*rand_ctx).state = Box::into_raw(state) as *const c_void;
+    1
+}
+
+/// Generation function of `RNG_COUNTING`
+unsafe extern "C" fn counting_generate(rand_data: *mut uint8_t,
+                                       len: uint16_t,
+                                       rand_ctx: *const RandContext)
+                                       -> uint8_t {
+    (*rand_ctx).generate_calls.set((*rand_ctx).generate_calls.get() + 1);
+    let state = match ((*rand_ctx).state as *const CountingState).as_ref() {
+        Some(state) => state,
+        None => return 0,
+    };
+    for i in 0..len as usize {
+        *rand_data.add(i) = state.next.get();
+        state.next.set(state.next.get().wrapping_add(1));
+    }
+    1
+}
+
+/// Release function of `RNG_COUNTING`
+unsafe extern "C" fn counting_release(rand_ctx: *mut RandContext) -> uint8_t {
+    if !(*rand_ctx).state.is_null() {
+        drop(Box::from_raw((*rand_ctx).state as *mut CountingState));
+        (*rand_ctx).state = ptr::null();
+    }
+    1
+}
+
+/// Initialization function of `RNG_REPLAY`, it copies the seed of the context as the stream
+unsafe extern "C" fn replay_init(rand_ctx: *mut RandContext, _rand_gen: *const RandGen) -> uint8_t {
+    let data = if (*rand_ctx).seed.is_null() {
+        Vec::new().into_boxed_slice()
+    } else {
+        slice::from_raw_parts((*rand_ctx).seed, (*rand_ctx).seed_len as usize)
+            .to_vec()
+            .into_boxed_slice()
+    };
+    let state = Box::new(ReplayState {
+        data,
+        pos: Cell::new(0),
+    });
+    (*rand_ctx).state = Box::into_raw(state) as *const c_void;
+    1
+}
+
+/// Generation function of `RNG_REPLAY`
+unsafe extern "C" fn replay_generate(rand_data: *mut uint8_t,
+                                     len: uint16_t,
+                                     rand_ctx: *const RandContext)
+                                     -> uint8_t {
+    (*rand_ctx).generate_calls.set((*rand_ctx).generate_calls.get() + 1);
+    let state = match ((*rand_ctx).state as *const ReplayState).as_ref() {
+        Some(state) => state,
+        None => return 0,
+    };
+    let pos = state.pos.get();
+    let len = len as usize;
+    if state.data.len() - pos < len {
+        return 0;
+    }
+    ptr::copy_nonoverlapping(state.data[pos..].as_ptr(), rand_data, len);
+    state.pos.set(pos + len);
+    1
+}
+
+/// Release function of `RNG_REPLAY`
+unsafe extern "C" fn replay_release(rand_ctx: *mut RandContext) -> uint8_t {
+    if !(*rand_ctx).state.is_null() {
+        drop(Box::from_raw((*rand_ctx).state as *mut ReplayState));
+        (*rand_ctx).state = ptr::null();
+    }
+    1
+}
+
+/// Initialization function of `RNG_RECORDING`
+unsafe extern "C" fn recording_init(rand_ctx: *mut RandContext,
+                                    _rand_gen: *const RandGen)
+                                    -> uint8_t {
+    let inner = match init(&RNG_DEFAULT) {
+        Ok(inner) => inner,
+        Err(_) => return 0,
+    };
+    let state = Box::new(RecordingState {
+        inner,
+        recorded: RefCell::new(Vec::new()),
+    });
+    (*rand_ctx).state = Box::into_raw(state) as *const c_void;
+    1
+}
+
+/// Generation function of `RNG_RECORDING`
+unsafe extern "C" fn recording_generate(rand_data: *mut uint8_t,
+                                        len: uint16_t,
+                                        rand_ctx: *const RandContext)
+                                        -> uint8_t {
+    (*rand_ctx).generate_calls.set((*rand_ctx).generate_calls.get() + 1);
+    let state = match ((*rand_ctx).state as *const RecordingState).as_ref() {
+        Some(state) => state,
+        None => return 0,
+    };
+    if ffi::ntru_rand_generate(rand_data, len, &state.inner) != 0 {
+        return 0;
+    }
+    state.recorded.borrow_mut().extend_from_slice(slice::from_raw_parts(rand_data, len as usize));
+    1
+}
+
+/// Release function of `RNG_RECORDING`
+unsafe extern "C" fn recording_release(rand_ctx: *mut RandContext) -> uint8_t {
+    if !(*rand_ctx).state.is_null() {
+        drop(Box::from_raw((*rand_ctx).state as *mut RecordingState));
+        (*rand_ctx).state = ptr::null();
+    }
+    1
+}
+
+/// Initialization function of `RNG_SOURCE`
+///
+/// A source based context can only be created with `init_source()`, so this always fails.
+unsafe extern "C" fn source_init(_rand_ctx: *mut RandContext,
+                                 _rand_gen: *const RandGen)
+                                 -> uint8_t {
+    0
+}
+
+/// Generation function of `RNG_SOURCE`
+///
+/// A panic in the source is reported as an RNG failure, since it cannot cross the FFI boundary.
+unsafe extern "C" fn source_generate(rand_data: *mut uint8_t,
+                                     len: uint16_t,
+                                     rand_ctx: *const RandContext)
+                                     -> uint8_t {
+    (*rand_ctx).generate_calls.set((*rand_ctx).generate_calls.get() + 1);
+    let state = match ((*rand_ctx).state as *const SourceState).as_ref() {
+        Some(state) => state,
+        None => return 0,
+    };
+    if len == 0 {
+        return 1;
+    }
+    let data = slice::from_raw_parts_mut(rand_data, len as usize);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match state.try_borrow_mut() {
+        Ok(mut source) => source.fill(data).is_ok(),
+        Err(_) => false,
+    }));
+    if let Ok(true) = result { 1 } else { 0 }
+}
+
+/// Release function of `RNG_SOURCE`
+unsafe extern "C" fn source_release(rand_ctx: *mut RandContext) -> uint8_t {
+    if !(*rand_ctx).state.is_null() {
+        drop(Box::from_raw((*rand_ctx).state as *mut SourceState));
+        (*rand_ctx).state = ptr::null();
+    }
+    1
+}
+
+/// Length of the seed taken from the operating system for the default RNG
+const DEFAULT_SEED_LEN: usize = 32;
+
+/// Recommended automatic reseed interval, in generate calls, for long lived contexts
+pub const DEFAULT_RESEED_INTERVAL: u64 = 1 << 20;
+
+/// Generation function of the `CTR_DRBG` based RNGs
+///
+/// Reseeds the `CTR_DRBG` first if the automatic reseed interval of the context has been reached.
+unsafe extern "C" fn ctr_drbg_generate(rand_data: *mut uint8_t,
+                                       len: uint16_t,
+                                       rand_ctx: *const RandContext)
+                                       -> uint8_t {
+    (*rand_ctx).generate_calls.set((*rand_ctx).generate_calls.get() + 1);
+    if (*rand_ctx).count_generate().is_err() {
+        return 0;
+    }
+    ffi::ntru_rand_ctr_drbg_generate(rand_data, len, rand_ctx)
+}
+
+/// Initialization function of `RNG_GETRANDOM`, it keeps no state
+unsafe extern "C" fn getrandom_init(_rand_ctx: *mut RandContext,
+                                    _rand_gen: *const RandGen)
+                                    -> uint8_t {
+    1
+}
+
+/// Generation function of `RNG_GETRANDOM`
+unsafe extern "C" fn getrandom_generate(rand_data: *mut uint8_t,
+                                        len: uint16_t,
+                                        rand_ctx: *const RandContext)
+                                        -> uint8_t {
+    (*rand_ctx).generate_calls.set((*rand_ctx).generate_calls.get() + 1);
+    if len == 0 {
+        return 1;
+    }
+    let data = slice::from_raw_parts_mut(rand_data, len as usize);
+    if getrandom::getrandom(data).is_ok() { 1 } else { 0 }
+}
+
+/// Release function of `RNG_GETRANDOM`, there is nothing to release
+unsafe extern "C" fn getrandom_release(_rand_ctx: *mut RandContext) -> uint8_t {
+    1
+}
+
+/// Initialization function of `RNG_DEFAULT`
+///
+/// Seeds a `CTR_DRBG` with entropy from the operating system. The seed of the context is only
+/// replaced while the `CTR_DRBG` is being instantiated.
+unsafe extern "C" fn default_init(rand_ctx: *mut RandContext, rand_gen: *const RandGen) -> uint8_t {
+    let mut seed = [0u8; DEFAULT_SEED_LEN];
+    if getrandom::getrandom(&mut seed).is_err() {
+        return 0;
+    }
+
+    let (old_seed, old_seed_len) = ((*rand_ctx).seed, (*rand_ctx).seed_len);
+    (*rand_ctx).seed = &seed[0];
+    (*rand_ctx).seed_len = DEFAULT_SEED_LEN as uint16_t;
+    let result = ffi::ntru_rand_ctr_drbg_init(rand_ctx, rand_gen);
+    (*rand_ctx).seed = old_seed;
+    (*rand_ctx).seed_len = old_seed_len;
+
+    for b in seed.iter_mut() {
+        ptr::write_volatile(b, 0);
+    }
+    result
+}
+
 /// Initialize a new rand context
 pub fn init(rand_gen: &RandGen) -> Result<RandContext, Error> {
     let mut rand_ctx: RandContext = Default::default();
@@ -146,8 +655,53 @@ pub fn init(rand_gen: &RandGen) -> Result<RandContext, Error> {
     }
 }
 
+/// Initialize a new rand context from a `RandomSource`
+///
+/// All the random data used by the context will be taken from the given source.
+pub fn init_source<S: RandomSource + 'static>(source: S) -> Result<RandContext, Error> {
+    let state: Box<SourceState> = Box::new(RefCell::new(Box::new(source)));
+    let mut rand_ctx: RandContext = Default::default();
+    rand_ctx.rand_gen = &RNG_SOURCE;
+    rand_ctx.state = Box::into_raw(state) as *const c_void;
+    Ok(rand_ctx)
+}
+
+thread_local! {
+    /// The lazily initialized default context of the thread
+    static DEFAULT_CONTEXT: RefCell<Option<Rc<RandContext>>> = const { RefCell::new(None) }
+}
+
+/// Get the default rand context of the current thread
+///
+/// The context uses `RNG_DEFAULT` and is created the first time this function is called in each
+/// thread, the following calls will return the same context. This avoids creating and releasing a
+/// context for every operation. The `CTR_DRBG` of the context is automatically reseeded every
+/// `DEFAULT_RESEED_INTERVAL` generate calls, since it will usually live as long as the thread.
+///
+/// Contexts cannot be shared between threads, so each thread has its own independent context. If
+/// the initialization fails, the error is returned and it will be tried again in the next call.
+pub fn default_context() -> Result<Rc<RandContext>, Error> {
+    DEFAULT_CONTEXT.with(|default_ctx| {
+        let mut default_ctx = default_ctx.borrow_mut();
+        if let Some(ref rand_ctx) = *default_ctx {
+            return Ok(rand_ctx.clone());
+        }
+
+        let mut rand_ctx = init(&RNG_DEFAULT)?;
+        rand_ctx.set_reseed_interval(DEFAULT_RESEED_INTERVAL);
+        let rand_ctx = Rc::new(rand_ctx);
+        *default_ctx = Some(rand_ctx.clone());
+        Ok(rand_ctx)
+    })
+}
+
 /// Generate a new deterministic rand context
+///
+/// The seed must not be empty, otherwise an `Error::InvalidParam` will be returned.
 pub fn init_det(rand_gen: &RandGen, seed: &[u8]) -> Result<RandContext, Error> {
+    if seed.is_empty() {
+        return Err(Error::InvalidParam);
+    }
     let mut rand_ctx: RandContext = Default::default();
     let result = unsafe {
         ffi::ntru_rand_init_det(&mut rand_ctx, rand_gen, &seed[0], seed.len() as uint16_t)
@@ -159,6 +713,40 @@ pub fn init_det(rand_gen: &RandGen, seed: &[u8]) -> Result<RandContext, Error> {
     }
 }
 
+/// Generate a new deterministic rand context with a personalization string
+///
+/// Works like `init_det()`, but the personalization string is mixed into the initial `CTR_DRBG`
+/// state, as described in SP 800-90A section 8.7.1. Contexts created from the same seed with
+/// different personalization strings produce independent streams, which allows, for example,
+/// separating key generation randomness from message randomness derived from the same master seed.
+/// Only `CTR_DRBG` based RNGs accept a personalization string; for other RNGs an `Error::Prng`
+/// will be returned.
+pub fn init_det_with(rand_gen: &RandGen,
+                     seed: &[u8],
+                     personalization: &[u8])
+                     -> Result<RandContext, Error> {
+    if rand_gen.kind != RngKind::CtrDrbg {
+        return Err(Error::Prng);
+    }
+    let rand_ctx = init_det(rand_gen, seed)?;
+
+    // Instantiate the already allocated state again, this time with the personalization string
+    let result = unsafe {
+        ffi::nist_ctr_drbg_instantiate(rand_ctx.state as *mut c_void,
+                                       seed.as_ptr() as *const c_void,
+                                       seed.len() as c_int,
+                                       ptr::null(),
+                                       0,
+                                       personalization.as_ptr() as *const c_void,
+                                       personalization.len() as c_int)
+    };
+    if result == 0 {
+        Ok(rand_ctx)
+    } else {
+        Err(Error::Prng)
+    }
+}
+
 /// Generate random data
 pub fn generate(length: u16, rand_ctx: &RandContext) -> Result<Box<[u8]>, Error> {
     let mut plain = vec![0u8; length as usize];
@@ -171,18 +759,63 @@ pub fn generate(length: u16, rand_ctx: &RandContext) -> Result<Box<[u8]>, Error>
     }
 }
 
+/// Generate random data with additional input
+///
+/// The additional input is mixed into the `CTR_DRBG` state for this call only, as described in
+/// SP 800-90A section 10.2.1.5. Only `CTR_DRBG` based RNGs accept additional input; for other RNGs
+/// an `Error::Prng` will be returned.
+pub fn generate_with(length: u16,
+                     additional_input: &[u8],
+                     rand_ctx: &RandContext)
+                     -> Result<Box<[u8]>, Error> {
+    if rand_ctx.get_rng().kind != RngKind::CtrDrbg || rand_ctx.state.is_null() {
+        return Err(Error::Prng);
+    }
+    rand_ctx.count_generate()?;
+
+    let mut plain = vec![0u8; length as usize];
+    let result = unsafe {
+        ffi::nist_ctr_drbg_generate(rand_ctx.state as *mut c_void,
+                                    plain.as_mut_ptr() as *mut c_void,
+                                    length as c_int,
+                                    additional_input.as_ptr() as *const c_void,
+                                    additional_input.len() as c_int)
+    };
+
+    if result == 0 {
+        Ok(plain.into_boxed_slice())
+    } else {
+        Err(Error::Prng)
+    }
+}
+
 impl TernPoly {
     /// Random ternary polynomial
     ///
-    /// Generates a random ternary polynomial. If an error occurs, it will return None.
+    /// Generates a random ternary polynomial. `n` must be between 1 and `MAX_DEGREE - 1`,
+    /// otherwise an `Error::InvalidDegree` will be returned. The number of ones and negative ones
+    /// must be at most `MAX_ONES` each, and `n` in total, otherwise an `Error::InvalidWeight` will
+    /// be returned. If the random number generator fails, `Error::Prng` will be returned.
     pub fn rand(n: u16,
                 num_ones: u16,
                 num_neg_ones: u16,
                 rand_ctx: &RandContext)
-                -> Option<TernPoly> {
+                -> Result<TernPoly, Error> {
+        if n == 0 || n as usize >= MAX_DEGREE {
+            return Err(Error::InvalidDegree);
+        }
+        if num_ones as usize > MAX_ONES || num_neg_ones as usize > MAX_ONES ||
+           num_ones as u32 + num_neg_ones as u32 > n as u32 {
+            return Err(Error::InvalidWeight);
+        }
+
         let mut poly: TernPoly = Default::default();
         let result = unsafe { ffi::ntru_rand_tern(n, num_ones, num_neg_ones, &mut poly, rand_ctx) };
 
-        if result == 0 { None } else { Some(poly) }
+        if result == 0 {
+            Err(Error::Prng)
+        } else {
+            Ok(poly)
+        }
     }
 }
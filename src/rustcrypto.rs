@@ -0,0 +1,149 @@
+//! RustCrypto KEM traits
+//!
+//! This module implements the `Encapsulate` and `Decapsulate` traits of the
+//! [`kem`](https://docs.rs/kem) crate for the key types of the KEMs in this crate, so they can be
+//! used in generic code written against the RustCrypto traits. Public keys encapsulate, private
+//! keys decapsulate, and both encapsulated keys and shared secrets are byte slices. The module is
+//! only available if the `ntru-prime` or `nist-ntru` features are enabled too.
+//!
+//! Keys convert to and from their encoded form with `TryFrom<&[u8]>` and `From<&Key> for
+//! Vec<u8>`. The parameter set of an encoded key is found from its length.
+//!
+//! The RustCrypto RNG passed to `encapsulate()` is used to seed a CTR_DRBG rand context, which
+//! then generates the randomness of the encapsulation.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use kem::{Decapsulate, Encapsulate};
+//!
+//! let (ciphertext, shared1) = kp.get_public().encapsulate(&mut rng).unwrap();
+//! let shared2 = kp.get_private().decapsulate(&ciphertext).unwrap();
+//!
+//! assert_eq!(shared1, shared2);
+//! ```
+use std::convert::TryFrom;
+use kem::{Decapsulate, Encapsulate};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "ntru-prime")]
+use ntruprime;
+#[cfg(feature = "nist-ntru")]
+use nistntru;
+use rand::{RandContext, RNG_CTR_DRBG};
+use types::Error;
+
+/// Length of the seed drawn from the RustCrypto RNG
+const SEED_LEN: usize = 32;
+
+/// Initializes a CTR_DRBG rand context seeded from a RustCrypto RNG
+fn seeded_context(rng: &mut impl CryptoRngCore) -> Result<RandContext, Error> {
+    let mut seed = [0u8; SEED_LEN];
+    rng.fill_bytes(&mut seed);
+    let rand_ctx = ::rand::init_det(&RNG_CTR_DRBG, &seed);
+    for b in seed.iter_mut() {
+        *b = 0;
+    }
+    rand_ctx
+}
+
+/// Implements the encoded key conversions of a key type, given its parameter sets and the
+/// function returning the encoded length for a parameter set
+macro_rules! encoded_key {
+    ($key:ty, $param_sets:expr, $len:ident) => {
+        impl<'a> TryFrom<&'a [u8]> for $key {
+            type Error = Error;
+
+            /// Imports an encoded key, taking the parameter set with a matching key length
+            fn try_from(arr: &[u8]) -> Result<$key, Error> {
+                match $param_sets.iter().find(|params| params.$len() == arr.len()) {
+                    Some(params) => <$key>::import(arr, params),
+                    None => Err(Error::InvalidKey),
+                }
+            }
+        }
+
+        impl<'a> From<&'a $key> for Vec<u8> {
+            /// Exports a key, as `export()`
+            fn from(key: &$key) -> Vec<u8> {
+                key.export().to_vec()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ntru-prime")]
+encoded_key!(ntruprime::PublicKey, ntruprime::ALL_PRIME_PARAM_SETS, public_len);
+#[cfg(feature = "ntru-prime")]
+encoded_key!(ntruprime::PrivateKey, ntruprime::ALL_PRIME_PARAM_SETS, private_len);
+#[cfg(feature = "nist-ntru")]
+encoded_key!(nistntru::PublicKey, nistntru::ALL_NIST_PARAM_SETS, public_len);
+#[cfg(feature = "nist-ntru")]
+encoded_key!(nistntru::PrivateKey, nistntru::ALL_NIST_PARAM_SETS, private_len);
+
+#[cfg(feature = "ntru-prime")]
+impl Encapsulate<Box<[u8]>, Box<[u8]>> for ntruprime::PublicKey {
+    type Error = Error;
+
+    fn encapsulate(&self,
+                   rng: &mut impl CryptoRngCore)
+                   -> Result<ntruprime::Encapsulated, Error> {
+        ntruprime::encapsulate(self, &seeded_context(rng)?)
+    }
+}
+
+#[cfg(feature = "ntru-prime")]
+impl Decapsulate<Box<[u8]>, Box<[u8]>> for ntruprime::PrivateKey {
+    type Error = Error;
+
+    fn decapsulate(&self, encapsulated_key: &Box<[u8]>) -> Result<Box<[u8]>, Error> {
+        ntruprime::decapsulate(encapsulated_key, self)
+    }
+}
+
+#[cfg(feature = "ntru-prime")]
+impl AsRef<[u8]> for ntruprime::PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.export()
+    }
+}
+
+#[cfg(feature = "ntru-prime")]
+impl AsRef<[u8]> for ntruprime::PrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        self.export()
+    }
+}
+
+#[cfg(feature = "nist-ntru")]
+impl Encapsulate<Box<[u8]>, Box<[u8]>> for nistntru::PublicKey {
+    type Error = Error;
+
+    fn encapsulate(&self,
+                   rng: &mut impl CryptoRngCore)
+                   -> Result<nistntru::Encapsulated, Error> {
+        nistntru::encapsulate(self, &seeded_context(rng)?)
+    }
+}
+
+#[cfg(feature = "nist-ntru")]
+impl Decapsulate<Box<[u8]>, Box<[u8]>> for nistntru::PrivateKey {
+    type Error = Error;
+
+    fn decapsulate(&self, encapsulated_key: &Box<[u8]>) -> Result<Box<[u8]>, Error> {
+        nistntru::decapsulate(encapsulated_key, self)
+    }
+}
+
+#[cfg(feature = "nist-ntru")]
+impl AsRef<[u8]> for nistntru::PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.export()
+    }
+}
+
+#[cfg(feature = "nist-ntru")]
+impl AsRef<[u8]> for nistntru::PrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        self.export()
+    }
+}
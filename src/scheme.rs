@@ -0,0 +1,98 @@
+//! Generic public key encryption
+//!
+//! The `PublicKeyScheme` trait abstracts the key generation, encryption and decryption of a public
+//! key encryption scheme, so code written against it works with NTRU or with any other scheme that
+//! implements it. It is implemented by `NtruContext`, that uses its own random number generator,
+//! and by `EncParams`, that uses `RNG_DEFAULT`.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::context::NtruContext;
+//! use ntru::encparams::DEFAULT_PARAMS_128_BITS;
+//! use ntru::scheme::PublicKeyScheme;
+//!
+//! fn round_trip<S: PublicKeyScheme>(scheme: &S, msg: &[u8]) -> Result<Box<[u8]>, S::Error> {
+//!     let (public, private) = scheme.keygen()?;
+//!     let encrypted = scheme.encrypt(msg, &public)?;
+//!     scheme.decrypt(&encrypted, &private)
+//! }
+//!
+//! let msg = b"Hello from Rust!";
+//! let ctx = NtruContext::with_default_rng(&DEFAULT_PARAMS_128_BITS).unwrap();
+//! assert_eq!(&round_trip(&ctx, msg).unwrap()[..], &msg[..]);
+//! assert_eq!(&round_trip(&DEFAULT_PARAMS_128_BITS, msg).unwrap()[..], &msg[..]);
+//! ```
+use types::{KeyPair, PublicKey, Error};
+use encparams::EncParams;
+use context::NtruContext;
+use rand::{self, RNG_DEFAULT};
+
+/// A public key encryption scheme
+pub trait PublicKeyScheme {
+    /// The public key, used to encrypt
+    type PublicKey;
+    /// The private key, used to decrypt
+    type PrivateKey;
+    /// The encrypted message
+    type Ciphertext: AsRef<[u8]>;
+    /// The error of the operations
+    type Error;
+
+    /// Generates a public key and its private key
+    fn keygen(&self) -> Result<(Self::PublicKey, Self::PrivateKey), Self::Error>;
+
+    /// Encrypts a message with a public key
+    fn encrypt(&self,
+               msg: &[u8],
+               public: &Self::PublicKey)
+               -> Result<Self::Ciphertext, Self::Error>;
+
+    /// Decrypts a message with a private key
+    fn decrypt(&self,
+               enc: &Self::Ciphertext,
+               private: &Self::PrivateKey)
+               -> Result<Box<[u8]>, Self::Error>;
+}
+
+impl PublicKeyScheme for NtruContext {
+    type PublicKey = PublicKey;
+    /// NTRU needs the public key to decrypt, so the private key is the whole key pair
+    type PrivateKey = KeyPair;
+    type Ciphertext = Box<[u8]>;
+    type Error = Error;
+
+    fn keygen(&self) -> Result<(PublicKey, KeyPair), Error> {
+        let kp = NtruContext::keygen(self)?;
+        Ok((kp.get_public().clone(), kp))
+    }
+
+    fn encrypt(&self, msg: &[u8], public: &PublicKey) -> Result<Box<[u8]>, Error> {
+        NtruContext::encrypt(self, msg, public)
+    }
+
+    fn decrypt(&self, enc: &Box<[u8]>, kp: &KeyPair) -> Result<Box<[u8]>, Error> {
+        NtruContext::decrypt(self, enc, kp)
+    }
+}
+
+impl PublicKeyScheme for EncParams {
+    type PublicKey = PublicKey;
+    /// NTRU needs the public key to decrypt, so the private key is the whole key pair
+    type PrivateKey = KeyPair;
+    type Ciphertext = Box<[u8]>;
+    type Error = Error;
+
+    fn keygen(&self) -> Result<(PublicKey, KeyPair), Error> {
+        let kp = ::generate_key_pair(self, &rand::init(&RNG_DEFAULT)?)?;
+        Ok((kp.get_public().clone(), kp))
+    }
+
+    fn encrypt(&self, msg: &[u8], public: &PublicKey) -> Result<Box<[u8]>, Error> {
+        ::encrypt(msg, public, self, &rand::init(&RNG_DEFAULT)?)
+    }
+
+    fn decrypt(&self, enc: &Box<[u8]>, kp: &KeyPair) -> Result<Box<[u8]>, Error> {
+        ::decrypt(enc, kp, self)
+    }
+}
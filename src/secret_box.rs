@@ -0,0 +1,152 @@
+//! Locked memory for private keys
+//!
+//! This module keeps key pairs in memory that is locked with `mlock()`, so it is never written
+//! to swap, and that is left out of core dumps, with `MADV_DONTDUMP` on Linux and `MADV_NOCORE`
+//! on FreeBSD and DragonFly BSD. The memory is also protected with `mprotect()`, so it cannot
+//! be read at all while the key is not in use: `SecretKeyBox::decrypt()` only makes it readable
+//! for the duration of the decryption. The memory is wiped when the box is dropped. Only
+//! available with the `secret-box` feature, on Unix.
+//!
+//! The key pair is stored in the fixed layout that libntru decrypts with, so the private key is
+//! never copied out of the locked memory. The private key of the `KeyPair` given to
+//! `SecretKeyBox::new()` is wiped, but copies of it made before, such as when it was generated or
+//! imported, are not, so keys should be put in a box as soon as possible.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//! use ntru::secret_box::SecretKeyBox;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//! let secret_box = SecretKeyBox::new(kp).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ntru::encrypt(msg, secret_box.get_public(), &EES401EP1, &rand_ctx).unwrap();
+//! let decrypted = secret_box.decrypt(&encrypted, &EES401EP1).unwrap();
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use std::{fmt, ptr};
+use libc::{self, c_int, c_void};
+use types::{KeyPair, CKeyPair, PublicKey, Error};
+use encparams::EncParams;
+
+/// A key pair in locked memory, only readable while decrypting
+///
+/// The box can be sent to other threads, but not shared between them, since each decryption
+/// changes the protection of the memory.
+pub struct SecretKeyBox {
+    /// The locked memory, holding the key pair
+    region: *mut c_void,
+    /// The length of the locked memory, a multiple of the page size
+    len: usize,
+    /// The public key, also kept out of the locked memory
+    public: PublicKey,
+}
+
+unsafe impl Send for SecretKeyBox {}
+
+impl SecretKeyBox {
+    /// Moves a key pair to locked memory
+    ///
+    /// The private key of the key pair is wiped. If the memory cannot be allocated, locked or
+    /// left out of core dumps, an `Error::OutOfMemory` will be returned. Locking memory is limited
+    /// by `RLIMIT_MEMLOCK`, so only a few boxes may be available to unprivileged processes.
+    pub fn new(mut kp: KeyPair) -> Result<SecretKeyBox, Error> {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let len = size_of::<CKeyPair>().div_ceil(page_size) * page_size;
+        let region = unsafe {
+            libc::mmap(ptr::null_mut(),
+                       len,
+                       libc::PROT_READ | libc::PROT_WRITE,
+                       libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                       -1,
+                       0)
+        };
+        if region == libc::MAP_FAILED {
+            kp.wipe();
+            return Err(Error::OutOfMemory);
+        }
+
+        // From here on, the memory is released when the box is dropped
+        let secret_box = SecretKeyBox {
+            region,
+            len,
+            public: kp.get_public().clone(),
+        };
+        if unsafe { libc::mlock(region, len) } != 0 || !exclude_from_dumps(region, len) {
+            kp.wipe();
+            return Err(Error::OutOfMemory);
+        }
+        unsafe { ptr::copy_nonoverlapping(&*kp.to_c(), region as *mut CKeyPair, 1) };
+        kp.wipe();
+        secret_box.protect(libc::PROT_NONE)?;
+        Ok(secret_box)
+    }
+
+    /// Get the public key of the key pair
+    pub fn get_public(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// Decrypts a message with the key pair
+    ///
+    /// Works like `ntru::decrypt()`, with the memory of the key pair readable only during the
+    /// decryption. If the protection of the memory cannot be changed, an `Error::OutOfMemory`
+    /// will be returned.
+    pub fn decrypt(&self, enc: &[u8], params: &EncParams) -> Result<Box<[u8]>, Error> {
+        self.protect(libc::PROT_READ)?;
+        let result = ::decrypt_c(enc, unsafe { &*(self.region as *const CKeyPair) }, params);
+        self.protect(libc::PROT_NONE)?;
+        result
+    }
+
+    /// Changes the protection of the locked memory
+    fn protect(&self, prot: c_int) -> Result<(), Error> {
+        if unsafe { libc::mprotect(self.region, self.len, prot) } == 0 {
+            Ok(())
+        } else {
+            Err(Error::OutOfMemory)
+        }
+    }
+}
+
+impl Drop for SecretKeyBox {
+    fn drop(&mut self) {
+        unsafe {
+            if libc::mprotect(self.region, self.len, libc::PROT_READ | libc::PROT_WRITE) == 0 {
+                for i in 0..self.len {
+                    ptr::write_volatile((self.region as *mut u8).add(i), 0);
+                }
+            }
+            let _ = libc::munlock(self.region, self.len);
+            let _ = libc::munmap(self.region, self.len);
+        }
+    }
+}
+
+impl fmt::Debug for SecretKeyBox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretKeyBox").field("public", &self.public).finish()
+    }
+}
+
+/// Leaves the memory out of core dumps, where the system supports it
+#[cfg(target_os = "linux")]
+fn exclude_from_dumps(region: *mut c_void, len: usize) -> bool {
+    unsafe { libc::madvise(region, len, libc::MADV_DONTDUMP) == 0 }
+}
+
+/// Leaves the memory out of core dumps, where the system supports it
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+fn exclude_from_dumps(region: *mut c_void, len: usize) -> bool {
+    unsafe { libc::madvise(region, len, libc::MADV_NOCORE) == 0 }
+}
+
+/// Leaves the memory out of core dumps, where the system supports it
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly")))]
+fn exclude_from_dumps(_: *mut c_void, _: usize) -> bool {
+    true
+}
@@ -0,0 +1,238 @@
+//! Encryption and decryption sessions
+//!
+//! When many messages are encrypted for the same recipient, an `EncryptSession` is bound to the
+//! public key, the parameters and the random number generator. It checks the parameter policy
+//! once, and copies the public key into the layout of libntru once, instead of in each call as
+//! `ntru::encrypt()` does. `encrypt_into()` writes the encrypted message to a caller provided
+//! buffer, without any allocation.
+//!
+//! Nothing else is precomputed: libntru packs and hashes the public key for the seed of the
+//! blinding polynomial, and uses its own scratch buffers, in each call, since its API does not
+//! allow to pass them. So a session saves the copy of the key, not the cost of the encryption.
+//!
+//! In the same way, a `DecryptSession` is bound to a key pair, copies it into the layout of
+//! libntru once, and reuses one output buffer for all the decrypted messages, that is wiped when a
+//! decryption fails and when the session is dropped.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::DEFAULT_PARAMS_256_BITS;
+//! use ntru::session::EncryptSession;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&DEFAULT_PARAMS_256_BITS, &rand_ctx).unwrap();
+//!
+//! let session = EncryptSession::new(kp.get_public(), &DEFAULT_PARAMS_256_BITS,
+//!                                   &rand_ctx).unwrap();
+//! for msg in &[&b"Hello"[..], &b"from"[..], &b"Rust!"[..]] {
+//!     let encrypted = session.encrypt(msg).unwrap();
+//!     let decrypted = ntru::decrypt(&encrypted, &kp, &DEFAULT_PARAMS_256_BITS).unwrap();
+//!     assert_eq!(&msg[..], &decrypted[..]);
+//! }
+//! ```
+//!
+//! ```
+//! # use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::DEFAULT_PARAMS_256_BITS;
+//! use ntru::session::DecryptSession;
+//! #
+//! # let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! # let kp = ntru::generate_key_pair(&DEFAULT_PARAMS_256_BITS, &rand_ctx).unwrap();
+//!
+//! let mut session = DecryptSession::new(&kp, &DEFAULT_PARAMS_256_BITS).unwrap();
+//! for msg in &[&b"Hello"[..], &b"from"[..], &b"Rust!"[..]] {
+//!     let encrypted = ntru::encrypt(msg, kp.get_public(), &DEFAULT_PARAMS_256_BITS,
+//!                                   &rand_ctx).unwrap();
+//!     assert_eq!(&msg[..], session.decrypt(&encrypted).unwrap());
+//! }
+//! ```
+use std::ptr;
+use types::{self, KeyPair, CKeyPair, PublicKey, CPublicKey, Error};
+use encparams::EncParams;
+use rand::RandContext;
+use low_level;
+use ffi;
+
+/// Encryption session for a public key
+pub struct EncryptSession<'a> {
+    /// The public key of the recipient, in the layout of libntru
+    public: Box<CPublicKey>,
+    /// The parameters of the public key
+    params: &'a EncParams,
+    /// The random number generator
+    rand_ctx: &'a RandContext,
+    /// Encrypted message length
+    enc_len: usize,
+    /// Maximum message length
+    max_msg_len: usize,
+}
+
+impl<'a> EncryptSession<'a> {
+    /// Creates an encryption session
+    ///
+    /// Returns `Error::InvalidParam` if the parameters are not valid.
+    pub fn new(public: &'a PublicKey,
+               params: &'a EncParams,
+               rand_ctx: &'a RandContext)
+               -> Result<EncryptSession<'a>, Error> {
+        ::check_policy(params)?;
+        let enc_len = params.enc_len() as usize;
+        if enc_len == 0 {
+            return Err(Error::InvalidParam);
+        }
+        Ok(EncryptSession {
+            public: public.to_c(),
+            params,
+            rand_ctx,
+            enc_len,
+            max_msg_len: params.max_msg_len() as usize,
+        })
+    }
+
+    /// Encrypted message length
+    pub fn enc_len(&self) -> usize {
+        self.enc_len
+    }
+
+    /// Maximum message length
+    pub fn max_msg_len(&self) -> usize {
+        self.max_msg_len
+    }
+
+    /// Encrypts a message
+    ///
+    /// The message can be at most `max_msg_len()` bytes long, otherwise an
+    /// `Error::MessageTooLong` will be returned.
+    pub fn encrypt(&self, msg: &[u8]) -> Result<Box<[u8]>, Error> {
+        let mut enc = vec![0u8; self.enc_len];
+        self.encrypt_into(msg, &mut enc)?;
+        Ok(enc.into_boxed_slice())
+    }
+
+    /// Encrypts a message into the given buffer
+    ///
+    /// The buffer must be exactly `enc_len()` bytes long, otherwise an
+    /// `Error::InvalidCiphertextLength` will be returned.
+    pub fn encrypt_into(&self, msg: &[u8], enc: &mut [u8]) -> Result<(), Error> {
+        if msg.len() > self.max_msg_len {
+            return Err(Error::MessageTooLong);
+        }
+        if enc.len() != self.enc_len {
+            return Err(Error::InvalidCiphertextLength {
+                expected: self.enc_len,
+                actual: enc.len(),
+            });
+        }
+
+        if cfg!(target_endian = "big") {
+            let public = PublicKey::from_c(&self.public);
+            return low_level::encrypt_into(msg, public.get_h(), self.params, self.rand_ctx, enc);
+        }
+        let result = unsafe {
+            ffi::ntru_encrypt(if !msg.is_empty() {
+                                  &msg[0]
+                              } else {
+                                  ptr::null()
+                              },
+                              msg.len() as u16,
+                              &*self.public,
+                              self.params,
+                              self.rand_ctx,
+                              &mut enc[0])
+        };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(Error::from(result))
+        }
+    }
+}
+
+/// Decryption session for a key pair
+pub struct DecryptSession<'a> {
+    /// The key pair of the recipient, in the layout of libntru
+    kp: Box<CKeyPair>,
+    /// The parameters of the key pair
+    params: &'a EncParams,
+    /// Encrypted message length
+    enc_len: usize,
+    /// Output buffer, reused for all the decrypted messages
+    buffer: Vec<u8>,
+}
+
+impl<'a> DecryptSession<'a> {
+    /// Creates a decryption session
+    ///
+    /// Returns `Error::InvalidParam` if the parameters are not valid.
+    pub fn new(kp: &'a KeyPair, params: &'a EncParams) -> Result<DecryptSession<'a>, Error> {
+        let enc_len = params.enc_len() as usize;
+        if enc_len == 0 {
+            return Err(Error::InvalidParam);
+        }
+        Ok(DecryptSession {
+            kp: kp.to_c(),
+            params,
+            enc_len,
+            buffer: vec![0u8; params.max_msg_len() as usize],
+        })
+    }
+
+    /// Encrypted message length
+    pub fn enc_len(&self) -> usize {
+        self.enc_len
+    }
+
+    /// Decrypts a message
+    ///
+    /// The decrypted message is written to the output buffer of the session, so it is only valid
+    /// until the next call. The encrypted message must be exactly `enc_len()` bytes long,
+    /// otherwise an `Error::InvalidCiphertextLength` will be returned. Messages that cannot be
+    /// decrypted return an `Error::DecryptionFailed`, as in `ntru::decrypt()`.
+    pub fn decrypt(&mut self, enc: &[u8]) -> Result<&[u8], Error> {
+        if enc.len() != self.enc_len {
+            return Err(Error::InvalidCiphertextLength {
+                expected: self.enc_len,
+                actual: enc.len(),
+            });
+        }
+
+        let result = if cfg!(target_endian = "big") {
+            let public = PublicKey::from_c(self.kp.get_public());
+            low_level::decrypt_into(enc,
+                                    self.kp.get_private(),
+                                    public.get_h(),
+                                    self.params,
+                                    &mut self.buffer)
+        } else {
+            let mut dec_len = 0u16;
+            match unsafe {
+                ffi::ntru_decrypt(&enc[0],
+                                  &*self.kp,
+                                  self.params,
+                                  &mut self.buffer[0],
+                                  &mut dec_len)
+            } {
+                0 => Ok(dec_len as usize),
+                result => Err(Error::from(result)),
+            }
+        };
+
+        match result {
+            Ok(len) => Ok(&self.buffer[..len]),
+            Err(e) => {
+                // On failure, the buffer may hold part of the message
+                types::wipe(&mut self.buffer);
+                Err(::decryption_error(e))
+            }
+        }
+    }
+}
+
+impl<'a> Drop for DecryptSession<'a> {
+    fn drop(&mut self) {
+        types::wipe(&mut self.buffer);
+    }
+}
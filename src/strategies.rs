@@ -0,0 +1,83 @@
+//! Proptest strategies
+//!
+//! This module provides `proptest` strategies for the core types, so that crates using NTRU can
+//! property-test their round-trip and interoperability invariants. Only available with the
+//! `proptest` feature.
+//!
+//! Key pairs are generated deterministically from a random seed, so failing cases can be shrunk
+//! and replayed. Note that key generation is slow for the largest parameter sets, so tests that
+//! generate many key pairs should use a small number of cases.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate ntru;
+//! #[macro_use]
+//! extern crate proptest;
+//!
+//! use proptest::prelude::*;
+//! use ntru::encparams::EES401EP1;
+//! use ntru::strategies::{key_pairs, plaintexts};
+//!
+//! proptest! {
+//!     #![proptest_config(ProptestConfig::with_cases(4))]
+//!     fn round_trip(kp in key_pairs(&EES401EP1), msg in plaintexts(&EES401EP1)) {
+//!         let rand_ctx = ntru::rand::init(&ntru::rand::RNG_DEFAULT).unwrap();
+//!         let enc = ntru::encrypt(&msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+//!         prop_assert_eq!(ntru::decrypt(&enc, &kp, &EES401EP1).unwrap().to_vec(), msg);
+//!     }
+//! }
+//!
+//! fn main() {
+//!     round_trip();
+//! }
+//! ```
+#![cfg_attr(feature = "no-panic", allow(clippy::unwrap_used))]
+use proptest::prelude::*;
+use proptest::collection::vec;
+use proptest::sample::select;
+use encparams::{EncParams, PARAM_SETS};
+use types::KeyPair;
+use rand::{self, RNG_CTR_DRBG};
+
+/// Length of the seed of the generated key pairs
+const SEED_LEN: usize = 32;
+
+/// Any of the built-in parameter sets that are allowed and whose keys can be generated
+///
+/// The product-form parameter sets are left out with the `avoid-hamming-wt-patent` feature, since
+/// they have no `EncParams::sampler()`, and the sets refused by `EncParams::is_allowed()` are left
+/// out with the `deny-deprecated-params` feature.
+pub fn param_sets() -> impl Strategy<Value = &'static EncParams> {
+    let usable: Vec<&'static EncParams> = PARAM_SETS.iter()
+        .filter(|params| params.is_allowed() && params.sampler().is_some())
+        .collect();
+    select(usable)
+}
+
+/// Key pairs for the parameter set, generated from a random seed
+pub fn key_pairs(params: &'static EncParams) -> impl Strategy<Value = KeyPair> {
+    vec(any::<u8>(), SEED_LEN).prop_map(move |seed| {
+        let rand_ctx = rand::init_det(&RNG_CTR_DRBG, &seed).unwrap();
+        ::generate_key_pair(params, &rand_ctx).unwrap()
+    })
+}
+
+/// A parameter set and a key pair for it
+pub fn params_and_key_pairs() -> impl Strategy<Value = (&'static EncParams, KeyPair)> {
+    param_sets().prop_flat_map(|params| (Just(params), key_pairs(params)))
+}
+
+/// Messages that can be encrypted with the parameter set, from empty to `params.max_msg_len()`
+/// bytes long
+pub fn plaintexts(params: &EncParams) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..params.max_msg_len() as usize + 1)
+}
+
+/// Random byte strings with the length of the encrypted messages of the parameter set
+///
+/// Almost none of them are valid encrypted messages, so they are useful to test the handling of
+/// decryption errors.
+pub fn ciphertexts(params: &EncParams) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), params.enc_len() as usize)
+}
@@ -0,0 +1,213 @@
+//! Threshold decryption
+//!
+//! This module splits a private key into `count` shares, so that any `threshold` of them can
+//! decrypt, and fewer reveal nothing about the key. It is Shamir's secret sharing over GF(2^8),
+//! applied to each byte of the exported private key: share `i` holds the values at `x = i` of
+//! random polynomials of degree `threshold - 1`, whose values at `x = 0` are the bytes of the key.
+//! The shares are combined by Lagrange interpolation at `x = 0`.
+//!
+//! The private key only exists while the shares are combined for a decryption, and it is wiped
+//! right after, so no single operator has to hold it. The public key is not secret, so it is not
+//! shared, and is given along with the shares to decrypt.
+//!
+//! An exported share is its index, the threshold and the shared bytes, one byte each for the
+//! index and the threshold. The shares do not tell whether they are valid: combining wrong shares
+//! gives a wrong private key, that fails to decrypt.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//! let shares = ntru::threshold::split(kp.get_private(), &EES401EP1, 2, 3, &rand_ctx).unwrap();
+//!
+//! let msg = b"Hello from Rust!";
+//! let encrypted = ntru::encrypt(msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+//! let decrypted = ntru::threshold::decrypt(&encrypted, &shares[1..], kp.get_public(),
+//!                                          &EES401EP1).unwrap();
+//! assert_eq!(&msg[..], &decrypted[..]);
+//! ```
+use std::fmt;
+use types::{self, KeyPair, PrivateKey, PublicKey, SecretVec, Error};
+use encparams::EncParams;
+use rand::RandContext;
+
+/// A share of a private key
+pub struct Share {
+    /// The `x` coordinate of the share, from 1 on
+    index: u8,
+    /// The number of shares needed to recover the key
+    threshold: u8,
+    /// The values of the polynomials at `x = index`, one for each byte of the key
+    data: SecretVec,
+}
+
+impl Share {
+    /// Get the index of the share, from 1 to the number of shares
+    pub fn get_index(&self) -> u8 {
+        self.index
+    }
+
+    /// Get the number of shares needed to recover the private key
+    pub fn get_threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Exports the share
+    pub fn export(&self) -> SecretVec {
+        let mut arr = Vec::with_capacity(self.data.len() + 2);
+        arr.push(self.index);
+        arr.push(self.threshold);
+        arr.extend_from_slice(&self.data);
+        SecretVec::new(arr)
+    }
+
+    /// Imports a share
+    ///
+    /// Returns `Error::InvalidEncoding` if the array is too short, or the index or the threshold
+    /// are 0.
+    pub fn import(arr: &[u8]) -> Result<Share, Error> {
+        if arr.len() < 3 || arr[0] == 0 || arr[1] == 0 {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(Share {
+            index: arr[0],
+            threshold: arr[1],
+            data: SecretVec::new(arr[2..].to_vec()),
+        })
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+/// Splits a private key into shares
+///
+/// Returns `count` shares, with the indices from 1 to `count`, any `threshold` of which recover
+/// the key. If `threshold` is 0 or greater than `count`, an `Error::InvalidParam` will be
+/// returned.
+pub fn split(private: &PrivateKey,
+             params: &EncParams,
+             threshold: u8,
+             count: u8,
+             rand_ctx: &RandContext)
+             -> Result<Vec<Share>, Error> {
+    if threshold == 0 || threshold > count {
+        return Err(Error::InvalidParam);
+    }
+    let mut key = private.export(params);
+    let mut data: Vec<Vec<u8>> = vec![key.to_vec(); count as usize];
+    types::wipe(&mut key);
+
+    // Adds the terms of each degree to the key, the constant term, so each coefficient is only
+    // generated once and used for all the shares
+    let mut powers: Vec<u8> = vec![1; count as usize];
+    for _ in 1..threshold {
+        let mut coeffs = ::rand::generate(data[0].len() as u16, rand_ctx)?;
+        for (i, (values, power)) in data.iter_mut().zip(powers.iter_mut()).enumerate() {
+            *power = gf_mul(*power, i as u8 + 1);
+            for (value, &coeff) in values.iter_mut().zip(coeffs.iter()) {
+                *value ^= gf_mul(coeff, *power);
+            }
+        }
+        types::wipe(&mut coeffs);
+    }
+    Ok(data.into_iter()
+        .enumerate()
+        .map(|(i, values)| {
+            Share {
+                index: i as u8 + 1,
+                threshold,
+                data: SecretVec::new(values),
+            }
+        })
+        .collect())
+}
+
+/// Combines shares into the private key
+///
+/// The first `threshold` shares are used. Returns `Error::InvalidParam` if there are fewer shares
+/// than their threshold, or the shares have different thresholds or lengths, or the same index,
+/// and `Error::InvalidKey` if the combined key is not a private key of the parameter set.
+pub fn combine(shares: &[Share], params: &EncParams) -> Result<PrivateKey, Error> {
+    let threshold = match shares.first() {
+        Some(share) => share.threshold as usize,
+        None => return Err(Error::InvalidParam),
+    };
+    if shares.len() < threshold {
+        return Err(Error::InvalidParam);
+    }
+    let shares = &shares[..threshold];
+    for (i, share) in shares.iter().enumerate() {
+        if share.threshold as usize != threshold || share.data.len() != shares[0].data.len() ||
+           shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(Error::InvalidParam);
+        }
+    }
+
+    let mut key = vec![0u8; shares[0].data.len()];
+    for (i, share) in shares.iter().enumerate() {
+        // The Lagrange basis polynomial of the share at x = 0, subtraction being xor
+        let (num, den) = shares.iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold((1, 1), |(num, den), (_, other)| {
+                (gf_mul(num, other.index), gf_mul(den, other.index ^ share.index))
+            });
+        let basis = gf_mul(num, gf_inv(den));
+        for (byte, &value) in key.iter_mut().zip(share.data.iter()) {
+            *byte ^= gf_mul(value, basis);
+        }
+    }
+    let result = PrivateKey::import_checked(&key, params);
+    types::wipe(&mut key);
+    result
+}
+
+/// Combines shares and decrypts a message
+///
+/// Works like `ntru::decrypt()`, with the key pair of the public key and the private key combined
+/// from the shares with `combine()`, that is wiped after the decryption.
+pub fn decrypt(enc: &[u8],
+               shares: &[Share],
+               public: &PublicKey,
+               params: &EncParams)
+               -> Result<Box<[u8]>, Error> {
+    let mut kp = KeyPair::new(combine(shares, params)?, public.clone());
+    let result = ::decrypt(enc, &kp, params);
+    kp.wipe();
+    result
+}
+
+/// Multiplies in GF(2^8), modulo `x^8 + x^4 + x^3 + x + 1`, in constant time
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut b = b;
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        a = a << 1 ^ 0x1b & (a >> 7).wrapping_neg();
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverts in GF(2^8), as `a^254`, in constant time
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut power = a;
+    for _ in 0..7 {
+        power = gf_mul(power, power);
+        result = gf_mul(result, power);
+    }
+    result
+}
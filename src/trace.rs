@@ -0,0 +1,52 @@
+//! Tracing instrumentation
+//!
+//! With the `tracing` feature, key generation, encryption and decryption run inside a `ntru`
+//! span with the name of the operation, the parameter set and the length of the input, and emit
+//! an event with the duration and, if it failed, the error. Key material and messages are never
+//! recorded. Without the feature, the operations are called directly. In both cases, with the
+//! `metrics` feature, the calls are counted.
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+use types::Error;
+use encparams::EncParams;
+
+/// Runs the operation inside its span
+#[cfg(feature = "tracing")]
+pub(crate) fn instrument<T, F>(op: &'static str,
+                               params: &EncParams,
+                               len: usize,
+                               f: F)
+                               -> Result<T, Error>
+    where F: FnOnce() -> Result<T, Error>
+{
+    let name = params.get_name();
+    let span = debug_span!("ntru", op = op, params = name.trim_end_matches('\0'), len = len);
+    let _enter = span.enter();
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed_us = start.elapsed().as_micros() as u64;
+    match result {
+        Ok(_) => debug!(elapsed_us = elapsed_us, "completed"),
+        Err(ref e) => warn!(elapsed_us = elapsed_us, error = ?e, "failed"),
+    }
+    #[cfg(feature = "metrics")]
+    ::metrics::record_op(op, result.is_ok());
+    result
+}
+
+/// Runs the operation
+#[cfg(not(feature = "tracing"))]
+#[inline]
+pub(crate) fn instrument<T, F>(_op: &'static str,
+                               _params: &EncParams,
+                               _len: usize,
+                               f: F)
+                               -> Result<T, Error>
+    where F: FnOnce() -> Result<T, Error>
+{
+    let result = f();
+    #[cfg(feature = "metrics")]
+    ::metrics::record_op(_op, result.is_ok());
+    result
+}
@@ -2,158 +2,294 @@
 //!
 //! This module includes all the needed structs and enums for NTRU encryption library. All of them
 //! with their needed methods.
-use std::ops::{Add, Sub};
+use std::ops::{Add, AddAssign, Sub, SubAssign, Deref};
 use std::default::Default;
-use std::{fmt, mem, error};
+use std::{cmp, fmt, mem, error, iter, ptr};
+use std::io::{self, Read, Write};
+use std::hash::{Hash, Hasher};
+use std::str::{self, FromStr};
+use std::convert::TryFrom;
+use std::alloc::{self, Layout};
 use libc::{int16_t, uint8_t, uint16_t};
 use ffi;
-use encparams::EncParams;
+use poly;
+use encparams::{self, EncParams};
 use rand::RandContext;
+use encoding;
+use x509;
 
 /// Max `N` value for all param sets; +1 for `ntru_invert_...()`
-pub const MAX_DEGREE: usize = (1499 + 1);
+pub const MAX_DEGREE: usize = 1499 + 1;
 /// (Max `coefficients` + 16) rounded to a multiple of 8
-const INT_POLY_SIZE: usize = ((MAX_DEGREE + 16 + 7) & 0xFFF8);
+const INT_POLY_SIZE: usize = (MAX_DEGREE + 16 + 7) & 0xFFF8;
 /// `max(df1, df2, df3, dg)`
 pub const MAX_ONES: usize = 499;
+/// Minimum number of coefficients for `IntPoly::mult_int()` to use Karatsuba multiplication on x86
+pub const KARATSUBA_MIN_N: usize = 1000;
+/// Maximum length of an exported public key, with `N < MAX_DEGREE` and `q <= 2^15`
+const MAX_PUBLIC_LEN: usize = 4 + ((MAX_DEGREE - 1) * 15).div_ceil(8);
+/// Maximum length of an exported private key, a product-form key with `MAX_ONES` ones and
+/// negative ones in each polynomial, and 11 bit indices
+const MAX_PRIVATE_LEN: usize = 5 + 3 * (4 + (11 * 2 * MAX_ONES).div_ceil(8));
 
-#[repr(C)]
 /// A polynomial with integer coefficients.
+///
+/// The coefficients are stored in the heap, and there are exactly `n` of them. The fixed layout
+/// used by libntru is only built when calling into it.
+#[derive(Clone, PartialEq, Eq, Default)]
 pub struct IntPoly {
     /// The number of coefficients
-    n: uint16_t,
+    n: u16,
     /// The coefficients
-    coeffs: [int16_t; INT_POLY_SIZE],
+    coeffs: Box<[i16]>,
 }
 
-impl Default for IntPoly {
-    fn default() -> IntPoly {
-        IntPoly {
-            n: 0,
-            coeffs: [0; INT_POLY_SIZE],
-        }
-    }
+/// Distribution of the coefficients of a random `IntPoly`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoeffDistribution {
+    /// Uniform in `[0, q)`
+    Uniform,
+    /// Uniform in `[-q/2, q/2)`
+    Centered,
+    /// Ternary, with the given number of 1 and -1 coefficients, and the rest 0
+    Ternary {
+        /// Number of 1 coefficients
+        ones: u16,
+        /// Number of -1 coefficients
+        neg_ones: u16,
+    },
 }
 
-impl Clone for IntPoly {
-    fn clone(&self) -> IntPoly {
-        let mut new_coeffs = [0i16; INT_POLY_SIZE];
-        new_coeffs.clone_from_slice(&self.coeffs);
+impl Add for IntPoly {
+    type Output = IntPoly;
+    fn add(mut self, rhs: IntPoly) -> Self::Output {
+        self += &rhs;
+        self
+    }
+}
 
-        IntPoly {
-            n: self.n,
-            coeffs: new_coeffs,
-        }
+impl Add<&IntPoly> for IntPoly {
+    type Output = IntPoly;
+    fn add(mut self, rhs: &IntPoly) -> Self::Output {
+        self += rhs;
+        self
     }
 }
 
-impl Add for IntPoly {
+impl Add<&IntPoly> for &IntPoly {
     type Output = IntPoly;
-    fn add(self, rhs: IntPoly) -> Self::Output {
+    fn add(self, rhs: &IntPoly) -> Self::Output {
         let mut out = self.clone();
-        unsafe { ffi::ntru_add(&mut out, &rhs) };
+        out += rhs;
         out
     }
 }
 
+impl AddAssign<&IntPoly> for IntPoly {
+    fn add_assign(&mut self, rhs: &IntPoly) {
+        for (a, b) in self.coeffs.iter_mut().zip(rhs.coeffs.iter()) {
+            *a = a.wrapping_add(*b);
+        }
+    }
+}
+
 impl Sub for IntPoly {
     type Output = IntPoly;
-    fn sub(self, rhs: IntPoly) -> Self::Output {
+    fn sub(mut self, rhs: IntPoly) -> Self::Output {
+        self -= &rhs;
+        self
+    }
+}
+
+impl Sub<&IntPoly> for IntPoly {
+    type Output = IntPoly;
+    fn sub(mut self, rhs: &IntPoly) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl Sub<&IntPoly> for &IntPoly {
+    type Output = IntPoly;
+    fn sub(self, rhs: &IntPoly) -> Self::Output {
         let mut out = self.clone();
-        unsafe { ffi::ntru_sub(&mut out, &rhs) };
+        out -= rhs;
         out
     }
 }
 
+impl SubAssign<&IntPoly> for IntPoly {
+    fn sub_assign(&mut self, rhs: &IntPoly) {
+        for (a, b) in self.coeffs.iter_mut().zip(rhs.coeffs.iter()) {
+            *a = a.wrapping_sub(*b);
+        }
+    }
+}
+
 impl fmt::Debug for IntPoly {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f,
                "{{ n: {}, coeffs: [{}...{}] }}",
                self.n,
-               self.coeffs[0],
-               self.coeffs[INT_POLY_SIZE - 1])
-    }
-}
-
-impl PartialEq for IntPoly {
-    fn eq(&self, other: &IntPoly) -> bool {
-        self.n == other.n &&
-        {
-            for i in 0..self.n as usize {
-                if self.coeffs[i] != other.coeffs[i] {
-                    return false;
-                }
-            }
-            true
-        }
+               self.coeffs.first().unwrap_or(&0),
+               self.coeffs.last().unwrap_or(&0))
     }
 }
 
 impl IntPoly {
     /// Create a new IntPoly
     pub fn new(coeffs: &[i16]) -> IntPoly {
-        let mut new_coeffs = [0; INT_POLY_SIZE];
-
-        for (i, coeff) in coeffs.iter().enumerate() {
-            new_coeffs[i] = *coeff;
-        }
         IntPoly {
             n: coeffs.len() as u16,
-            coeffs: new_coeffs,
+            coeffs: coeffs.to_vec().into_boxed_slice(),
+        }
+    }
+
+    /// Create a new IntPoly, checking the number of coefficients
+    ///
+    /// Works like `new()`, but returns an `Error::InvalidDegree` if there are `MAX_DEGREE`
+    /// coefficients or more, since libntru can not multiply such polynomials.
+    pub fn try_new(coeffs: &[i16]) -> Result<IntPoly, Error> {
+        if coeffs.len() >= MAX_DEGREE {
+            return Err(Error::InvalidDegree);
         }
+        Ok(IntPoly::new(coeffs))
     }
 
     /// Create a new random IntPoly
-    pub fn rand(n: u16, pow2q: u16, rand_ctx: &RandContext) -> IntPoly {
-        let rand_data = rand_ctx.get_rng().generate(n * 2, rand_ctx).unwrap();
+    ///
+    /// The coefficients are uniformly distributed in `[0, 2^pow2q)`. Same as
+    /// `IntPoly::rand_with()` with `CoeffDistribution::Uniform`.
+    pub fn rand(n: u16, pow2q: u16, rand_ctx: &RandContext) -> Result<IntPoly, Error> {
+        IntPoly::rand_with(n, pow2q, CoeffDistribution::Uniform, rand_ctx)
+    }
 
-        let mut coeffs = [0i16; INT_POLY_SIZE];
-        let shift = 16 - pow2q;
-        for i in (n as usize)..0usize {
-            coeffs[i] = rand_data[i] as i16 >> shift;
+    /// Create a new random IntPoly with the given coefficient distribution
+    ///
+    /// `q` is `2^pow2q`, and `pow2q` must be between 1 and 15 for uniformly distributed
+    /// coefficients, that must fit in `[0, q)`, and between 1 and 16 for the other distributions,
+    /// otherwise an `Error::InvalidParam` will be returned. `n` must be between 1 and
+    /// `MAX_DEGREE - 1`, otherwise an `Error::InvalidDegree` will be returned.
+    pub fn rand_with(n: u16,
+                     pow2q: u16,
+                     distribution: CoeffDistribution,
+                     rand_ctx: &RandContext)
+                     -> Result<IntPoly, Error> {
+        let max_pow2q = if distribution == CoeffDistribution::Uniform { 15 } else { 16 };
+        if pow2q < 1 || pow2q > max_pow2q {
+            return Err(Error::InvalidParam);
+        }
+        if n == 0 || n as usize >= MAX_DEGREE {
+            return Err(Error::InvalidDegree);
+        }
+        if let CoeffDistribution::Ternary { ones, neg_ones } = distribution {
+            return TernPoly::rand(n, ones, neg_ones, rand_ctx).map(|tern| tern.to_int_poly());
         }
 
-        IntPoly {
-            n: n,
-            coeffs: coeffs,
+        let rand_data = rand_ctx.get_rng().generate(n * 2, rand_ctx)?;
+        let shift = 16 - pow2q;
+        let offset = if distribution == CoeffDistribution::Centered {
+            1i32 << (pow2q - 1)
+        } else {
+            0
+        };
+
+        let coeffs: Vec<i16> = rand_data.chunks(2)
+            .map(|b| {
+                let value = ((b[0] as u16) << 8 | b[1] as u16) >> shift;
+                (value as i32 - offset) as i16
+            })
+            .collect();
+        Ok(IntPoly::new(&coeffs))
+    }
+
+    /// Convert array to IntPoly, checking the array
+    ///
+    /// Works like `from_arr()`, but returns an `Error::InvalidParam` if `N` is not lower than
+    /// `MAX_DEGREE` or `q` is not a power of 2, and an `Error::InvalidEncoding` if the array is
+    /// shorter than `N` coefficients of `log2(q)` bits.
+    pub fn try_from_arr(arr: &[u8], n: u16, q: u16) -> Result<IntPoly, Error> {
+        if n as usize >= MAX_DEGREE || q < 2 || q & (q - 1) != 0 {
+            return Err(Error::InvalidParam);
         }
+        if arr.is_empty() || arr.len() < (n as usize * q.trailing_zeros() as usize).div_ceil(8) {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(IntPoly::from_arr(arr, n, q))
     }
 
     /// Convert array to IntPoly
+    ///
+    /// The array holds the `N` coefficients with `log2(q)` bits each, least significant bit first,
+    /// as written by `to_arr()`. The coefficients past the end of the array are 0.
     pub fn from_arr(arr: &[u8], n: u16, q: u16) -> IntPoly {
-        let mut p: IntPoly = Default::default();
-        unsafe { ffi::ntru_from_arr(&arr[0], n, q, &mut p) };
-
-        p
+        let mut coeffs = vec![0; n as usize];
+        poly::unpack(arr, q.trailing_zeros(), &mut coeffs);
+        IntPoly::new(&coeffs)
     }
 
     /// Get the coefficients
     pub fn get_coeffs(&self) -> &[i16] {
-        &self.coeffs[0..self.n as usize]
+        &self.coeffs
     }
 
     /// Set the coefficients
+    ///
+    /// The rest of the coefficients are set to 0. Panics if there are more than `n` coefficients.
     pub fn set_coeffs(&mut self, coeffs: &[i16]) {
-        self.coeffs = [0; INT_POLY_SIZE];
-        for (i, coeff) in coeffs.iter().enumerate() {
-            self.coeffs[i] = *coeff;
+        for c in self.coeffs.iter_mut() {
+            *c = 0;
+        }
+        self.coeffs[..coeffs.len()].copy_from_slice(coeffs);
+    }
+
+    /// Set the coefficients, without panicking
+    ///
+    /// Works like `set_coeffs()`, but returns an `Error::InvalidDegree` if there are more than
+    /// `n` coefficients, and leaves the polynomial unchanged.
+    pub fn try_set_coeffs(&mut self, coeffs: &[i16]) -> Result<(), Error> {
+        if coeffs.len() > self.coeffs.len() {
+            return Err(Error::InvalidDegree);
         }
+        self.set_coeffs(coeffs);
+        Ok(())
     }
 
     /// Set a coefficient
+    ///
+    /// Panics if the index is not lower than `n`.
     pub fn set_coeff(&mut self, index: usize, value: i16) {
         self.coeffs[index] = value
     }
 
+    /// Set a coefficient, without panicking
+    ///
+    /// Returns an `Error::InvalidDegree` if the index is not lower than `n`.
+    pub fn try_set_coeff(&mut self, index: usize, value: i16) -> Result<(), Error> {
+        match self.coeffs.get_mut(index) {
+            Some(c) => {
+                *c = value;
+                Ok(())
+            }
+            None => Err(Error::InvalidDegree),
+        }
+    }
+
     /// Modifies the IntPoly with the given mask
     pub fn mod_mask(&mut self, mod_mask: u16) {
-        unsafe { ffi::ntru_mod_mask(self, mod_mask) };
+        for c in self.coeffs.iter_mut() {
+            *c = (*c as u16 & mod_mask) as i16;
+        }
     }
 
-    /// Converts the IntPoly to a byte array using 32 bit arithmetic
+    /// Converts the IntPoly to a byte array
+    ///
+    /// The coefficients are reduced modulo `q` and packed with `log2(q)` bits each, least
+    /// significant bit first, into `params.enc_len()` bytes. This is the layout of libntru on
+    /// little-endian targets, and it is the same on big-endian ones.
     pub fn to_arr(&self, params: &EncParams) -> Box<[u8]> {
         let mut a = vec![0u8; params.enc_len() as usize];
-        unsafe { ffi::ntru_to_arr(self, params.get_q(), &mut a[0]) };
+        poly::pack(self.coeffs.iter().map(|&c| c as u16), params.get_q().trailing_zeros(), &mut a);
 
         a.into_boxed_slice()
     }
@@ -161,25 +297,27 @@ impl IntPoly {
     /// General polynomial by ternary polynomial multiplication
     ///
     /// Multiplies a IntPoly by a TernPoly. The number of coefficients must be the same for both
-    /// polynomials. It also returns if the number of coefficients differ or not.
-    pub fn mult_tern(&self, b: &TernPoly, mod_mask: u16) -> (IntPoly, bool) {
-        if self.n != b.n {
-            panic!("To multiply a IntPoly by a TernPoly the number of coefficients must \
-                    be the same for both polynomials")
+    /// polynomials, otherwise an `Error::InvalidDegree` will be returned.
+    ///
+    /// This dispatches to the fastest variant for the target. On x86 it calls `ntru_mult_tern()`,
+    /// that uses the SSE variant if libntru was compiled with SSSE3 and the 64 or 32 bit variant
+    /// otherwise. On other targets libntru has no SIMD code, so `mult_tern_native()` is used.
+    pub fn mult_tern(&self, b: &TernPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        if !poly::LIBNTRU_SIMD {
+            return self.mult_tern_native(b, mod_mask);
         }
-        let mut c: IntPoly = Default::default();
-        let result = unsafe { ffi::ntru_mult_tern(self, b, &mut c, mod_mask) };
-        (c, result == 1)
+        self.mult_tern_with(b, mod_mask, ffi::ntru_mult_tern)
     }
 
     /// Add a ternary polynomial
     ///
     /// Adds a ternary polynomial to the general polynomial. Returns a new general polynomial.
+    /// Panics if an index of the ternary polynomial is not lower than `n`.
     pub fn add_tern(&self, b: &TernPoly) -> IntPoly {
         IntPoly {
             n: self.n,
             coeffs: {
-                let mut coeffs = [0; INT_POLY_SIZE];
+                let mut coeffs = self.coeffs.to_vec();
                 let tern_ones = b.get_ones();
                 let tern_neg_ones = b.get_neg_ones();
 
@@ -188,9 +326,9 @@ impl IntPoly {
                 }
 
                 for neg_one in tern_neg_ones.iter() {
-                    coeffs[*neg_one as usize] = self.coeffs[*neg_one as usize] + 1;
+                    coeffs[*neg_one as usize] = self.coeffs[*neg_one as usize] - 1;
                 }
-                coeffs
+                coeffs.into_boxed_slice()
             },
         }
     }
@@ -198,57 +336,180 @@ impl IntPoly {
     /// General polynomial by product-form polynomial multiplication
     ///
     /// Multiplies a IntPoly by a ProdPoly. The number of coefficients must be the same for both
-    /// polynomials. It also returns if the number of coefficients differ or not.
-    pub fn mult_prod(&self, b: &ProdPoly, mod_mask: u16) -> (IntPoly, bool) {
-        if self.n != b.n {
-            panic!("To multiply a IntPoly by a ProdPoly the number of coefficients must \
-                    be the same for both polynomials")
+    /// polynomials, otherwise an `Error::InvalidDegree` will be returned.
+    pub fn mult_prod(&self, b: &ProdPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        if self.n != b.n || !self.fits_c() {
+            return Err(Error::InvalidDegree);
         }
-        let mut c: IntPoly = Default::default();
-        let result = unsafe { ffi::ntru_mult_prod(self, b, &mut c, mod_mask) };
-        (c, result == 1)
+        let mut c = boxed_zeroed::<CIntPoly>();
+        let result = unsafe { ffi::ntru_mult_prod(&*self.to_c(), b, &mut *c, mod_mask) };
+        IntPoly::from_c_mult(&c, result)
     }
 
     /// General polynomial by private polynomial multiplication
     ///
     /// Multiplies a IntPoly by a PrivPoly, i.e. a TernPoly or a ProdPoly. The number of
-    /// coefficients must be the same for both polynomials. It also returns if the number of
-    /// coefficients differ or not.
-    pub fn mult_priv(&self, b: &PrivPoly, mod_mask: u16) -> (IntPoly, bool) {
+    /// coefficients must be the same for both polynomials, otherwise an `Error::InvalidDegree`
+    /// will be returned.
+    pub fn mult_priv(&self, b: &PrivPoly, mod_mask: u16) -> Result<IntPoly, Error> {
         if (b.is_product() && self.n != b.get_poly_prod().n) ||
-           (!b.is_product() && self.n != b.get_poly_tern().n) {
-            panic!("To multiply a IntPoly by a ProdPoly the number of coefficients must \
-                    be the same for both polynomials")
+           (!b.is_product() && self.n != b.get_poly_tern().n) || !self.fits_c() {
+            return Err(Error::InvalidDegree);
         }
-        let mut c: IntPoly = Default::default();
-        let result = unsafe { ffi::ntru_mult_priv(b, self, &mut c, mod_mask) };
-        (c, result == 1)
+        let mut c = boxed_zeroed::<CIntPoly>();
+        let result = unsafe { ffi::ntru_mult_priv(b, &*self.to_c(), &mut *c, mod_mask) };
+        IntPoly::from_c_mult(&c, result)
     }
 
     /// General polynomial by general polynomial multiplication
     ///
-    /// Multiplies a IntPoly by another IntPoly, i.e. a TernPoly or a ProdPoly. The number of
-    /// coefficients must be the same for both polynomials. It also returns if the number of
-    /// coefficients differ or not.
-    pub fn mult_int(&self, b: &IntPoly, mod_mask: u16) -> (IntPoly, bool) {
-        let mut c: IntPoly = Default::default();
-        let result = unsafe { ffi::ntru_mult_int(self, b, &mut c, mod_mask) };
-        (c, result == 1)
+    /// Multiplies a IntPoly by another IntPoly. The number of coefficients must be the same for
+    /// both polynomials, otherwise an `Error::InvalidDegree` will be returned.
+    ///
+    /// This dispatches to the fastest variant for the target. Polynomials with at least
+    /// `KARATSUBA_MIN_N` coefficients use `mult_int_karatsuba()`, smaller ones the schoolbook
+    /// multiplication of libntru, `ntru_mult_int()`. On targets without SIMD code in libntru, i.e.
+    /// other than x86, `mult_int_karatsuba()` is used for all sizes.
+    pub fn mult_int(&self, b: &IntPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        if self.n as usize >= KARATSUBA_MIN_N || !poly::LIBNTRU_SIMD {
+            return self.mult_int_karatsuba(b, mod_mask);
+        }
+        self.mult_int_with(b, mod_mask, ffi::ntru_mult_int)
+    }
+
+    /// Ternary multiplication with 32 bit arithmetic
+    ///
+    /// Same as `mult_tern()`, but always using `ntru_mult_tern_32()`.
+    pub fn mult_tern_32(&self, b: &TernPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        self.mult_tern_with(b, mod_mask, ffi::ntru_mult_tern_32)
+    }
+
+    /// Ternary multiplication with 64 bit arithmetic
+    ///
+    /// Same as `mult_tern()`, but always using `ntru_mult_tern_64()`.
+    pub fn mult_tern_64(&self, b: &TernPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        self.mult_tern_with(b, mod_mask, ffi::ntru_mult_tern_64)
+    }
+
+    /// Ternary multiplication with SSE instructions
+    ///
+    /// Same as `mult_tern()`, but always using `ntru_mult_tern_sse()`. Only available if libntru
+    /// was compiled with SSSE3 support.
+    #[cfg(SSE3)]
+    pub fn mult_tern_sse(&self, b: &TernPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        self.mult_tern_with(b, mod_mask, ffi::ntru_mult_tern_sse)
+    }
+
+    /// Ternary multiplication in Rust
+    ///
+    /// Same as `mult_tern()`, but always using the Rust implementation, with NEON instructions on
+    /// aarch64 if available, or `std::simd` with the `portable-simd` feature.
+    pub fn mult_tern_native(&self, b: &TernPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        if self.n != b.n {
+            return Err(Error::InvalidDegree);
+        }
+        let coeffs = poly::mult_tern(&self.coeffs, b.get_ones(), b.get_neg_ones(), mod_mask);
+        Ok(IntPoly::new(&coeffs))
+    }
+
+    /// General multiplication with 16 bit arithmetic
+    ///
+    /// Same as `mult_int()`, but always using `ntru_mult_int_16()`.
+    pub fn mult_int_16(&self, b: &IntPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        self.mult_int_with(b, mod_mask, ffi::ntru_mult_int_16)
+    }
+
+    /// General multiplication with 64 bit arithmetic
+    ///
+    /// Same as `mult_int()`, but always using `ntru_mult_int_64()`.
+    pub fn mult_int_64(&self, b: &IntPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        self.mult_int_with(b, mod_mask, ffi::ntru_mult_int_64)
+    }
+
+    /// General multiplication with the Karatsuba algorithm
+    ///
+    /// Same as `mult_int()`, but always using the Rust Karatsuba implementation, with NEON
+    /// instructions on aarch64 if available, or `std::simd` with the `portable-simd` feature.
+    pub fn mult_int_karatsuba(&self, b: &IntPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        if self.n != b.n || self.n == 0 {
+            return Err(Error::InvalidDegree);
+        }
+        let coeffs = poly::mult_int_karatsuba(&self.coeffs, &b.coeffs, mod_mask);
+        Ok(IntPoly::new(&coeffs))
+    }
+
+    /// Multiplies by a TernPoly with the given libntru function
+    fn mult_tern_with(&self,
+                      b: &TernPoly,
+                      mod_mask: u16,
+                      mult: unsafe extern "C" fn(*const CIntPoly,
+                                                 *const TernPoly,
+                                                 *mut CIntPoly,
+                                                 uint16_t)
+                                                 -> uint8_t)
+                      -> Result<IntPoly, Error> {
+        if self.n != b.n || !self.fits_c() {
+            return Err(Error::InvalidDegree);
+        }
+        let mut c = boxed_zeroed::<CIntPoly>();
+        let result = unsafe { mult(&*self.to_c(), b, &mut *c, mod_mask) };
+        IntPoly::from_c_mult(&c, result)
+    }
+
+    /// Multiplies by an IntPoly with the given libntru function
+    fn mult_int_with(&self,
+                     b: &IntPoly,
+                     mod_mask: u16,
+                     mult: unsafe extern "C" fn(*const CIntPoly,
+                                                *const CIntPoly,
+                                                *mut CIntPoly,
+                                                uint16_t)
+                                                -> uint8_t)
+                     -> Result<IntPoly, Error> {
+        if self.n != b.n || !self.fits_c() || !b.fits_c() {
+            return Err(Error::InvalidDegree);
+        }
+        let mut c = boxed_zeroed::<CIntPoly>();
+        let result = unsafe { mult(&*self.to_c(), &*b.to_c(), &mut *c, mod_mask) };
+        IntPoly::from_c_mult(&c, result)
+    }
+
+    /// Creates the result of a libntru multiplication, that returns 0 if the number of
+    /// coefficients differ
+    fn from_c_mult(c: &CIntPoly, result: uint8_t) -> Result<IntPoly, Error> {
+        if result == 1 {
+            Ok(IntPoly::from_c(c))
+        } else {
+            Err(Error::InvalidDegree)
+        }
     }
 
     /// Multiply by factor
     pub fn mult_fac(&mut self, factor: i16) {
-        unsafe { ffi::ntru_mult_fac(self, factor) };
+        for c in self.coeffs.iter_mut() {
+            *c = c.wrapping_mul(factor);
+        }
     }
 
-    /// Calls `ntru_mod_center()` in this polinomial.
+    /// Reduces the coefficients modulo a power of 2, to `[-modulus/2, modulus/2)`, like
+    /// `ntru_mod_center()`.
     pub fn mod_center(&mut self, modulus: u16) {
-        unsafe { ffi::ntru_mod_center(self, modulus) };
+        let mod_mask = modulus.wrapping_sub(1);
+        for c in self.coeffs.iter_mut() {
+            let reduced = (*c as u16 & mod_mask) as i32;
+            *c = if reduced >= (modulus / 2) as i32 {
+                reduced - modulus as i32
+            } else {
+                reduced
+            } as i16;
+        }
     }
 
-    /// Calls `ntru_mod3()` in this polinomial.
+    /// Reduces the coefficients modulo 3, to `[0, 2]`, like `ntru_mod3()`.
     pub fn mod3(&mut self) {
-        unsafe { ffi::ntru_mod3(self) };
+        for c in self.coeffs.iter_mut() {
+            *c = (*c as i32).rem_euclid(3) as i16;
+        }
     }
 
     /// Check if both polynomials are equals given a modulus
@@ -271,7 +532,53 @@ impl IntPoly {
                 return false;
             }
         }
-        self.coeffs[0] == 1
+        self.coeffs.first() == Some(&1)
+    }
+
+    /// Checks that the polynomial fits in the fixed layout of libntru
+    fn fits_c(&self) -> bool {
+        self.coeffs.len() < MAX_DEGREE
+    }
+
+    /// Converts the polynomial to the fixed layout of libntru
+    ///
+    /// The number of coefficients must have been checked with `fits_c()`, since the ones that do
+    /// not fit are left out.
+    pub(crate) fn to_c(&self) -> Box<CIntPoly> {
+        let mut p = boxed_zeroed::<CIntPoly>();
+        self.write_c(&mut p);
+        p
+    }
+
+    /// Writes the polynomial into a polynomial in the fixed layout of libntru
+    fn write_c(&self, p: &mut CIntPoly) {
+        p.n = self.n;
+        for (c, coeff) in p.coeffs.iter_mut().zip(self.coeffs.iter()) {
+            *c = *coeff;
+        }
+    }
+
+    /// Creates a polynomial from the fixed layout of libntru
+    pub(crate) fn from_c(p: &CIntPoly) -> IntPoly {
+        IntPoly::new(&p.coeffs[..p.n as usize])
+    }
+}
+
+#[repr(C)]
+/// A polynomial with integer coefficients, in the fixed layout of libntru
+pub(crate) struct CIntPoly {
+    /// The number of coefficients
+    n: uint16_t,
+    /// The coefficients
+    coeffs: [int16_t; INT_POLY_SIZE],
+}
+
+impl Default for CIntPoly {
+    fn default() -> CIntPoly {
+        CIntPoly {
+            n: 0,
+            coeffs: [0; INT_POLY_SIZE],
+        }
     }
 }
 
@@ -348,6 +655,8 @@ impl PartialEq for TernPoly {
 
 impl TernPoly {
     /// Creates a new TernPoly
+    ///
+    /// Panics if there are more than `MAX_ONES` ones or negative ones.
     pub fn new(n: u16, ones: &[u16], neg_ones: &[u16]) -> TernPoly {
         let mut new_ones = [0; MAX_ONES];
         let mut new_neg_ones = [0; MAX_ONES];
@@ -361,7 +670,7 @@ impl TernPoly {
         }
 
         TernPoly {
-            n: n,
+            n,
             num_ones: ones.len() as u16,
             num_neg_ones: neg_ones.len() as u16,
             ones: new_ones,
@@ -369,6 +678,18 @@ impl TernPoly {
         }
     }
 
+    /// Creates a new TernPoly, without panicking
+    ///
+    /// Works like `new()`, but returns an `Error::InvalidWeight` if there are more than
+    /// `MAX_ONES` ones or negative ones, or an index is not lower than `n`.
+    pub fn try_new(n: u16, ones: &[u16], neg_ones: &[u16]) -> Result<TernPoly, Error> {
+        if ones.len() > MAX_ONES || neg_ones.len() > MAX_ONES ||
+           ones.iter().chain(neg_ones).any(|&i| i >= n) {
+            return Err(Error::InvalidWeight);
+        }
+        Ok(TernPoly::new(n, ones, neg_ones))
+    }
+
     /// Get the
     pub fn get_n(&self) -> u16 {
         self.n
@@ -384,6 +705,29 @@ impl TernPoly {
         &self.neg_ones[0..self.num_neg_ones as usize]
     }
 
+    /// Checks that the polynomial has `n` coefficients, and exactly `num_ones` ones and
+    /// `num_neg_ones` negative ones at distinct indices
+    pub(crate) fn check_weights(&self,
+                                n: u16,
+                                num_ones: u16,
+                                num_neg_ones: u16)
+                                -> Result<(), Error> {
+        if self.n != n {
+            return Err(Error::InvalidDegree);
+        }
+        if self.num_ones != num_ones || self.num_neg_ones != num_neg_ones {
+            return Err(Error::InvalidWeight);
+        }
+        let mut used = vec![false; n as usize];
+        for &i in self.get_ones().iter().chain(self.get_neg_ones()) {
+            if i >= n || used[i as usize] {
+                return Err(Error::InvalidWeight);
+            }
+            used[i as usize] = true;
+        }
+        Ok(())
+    }
+
     /// Ternary to general integer polynomial
     ///
     /// Converts a TernPoly to an equivalent IntPoly.
@@ -391,7 +735,7 @@ impl TernPoly {
         IntPoly {
             n: self.n,
             coeffs: {
-                let mut coeffs = [0; INT_POLY_SIZE];
+                let mut coeffs = vec![0; self.n as usize];
 
                 for i in 0..self.num_ones {
                     coeffs[self.ones[i as usize] as usize] = 1;
@@ -400,14 +744,14 @@ impl TernPoly {
                     coeffs[self.neg_ones[i as usize] as usize] = -1;
                 }
 
-                coeffs
+                coeffs.into_boxed_slice()
             },
         }
     }
 }
 
 #[repr(C)]
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 /// A product-form polynomial, i.e. a polynomial of the form f1*f2+f3 where f1,f2,f3 are very
 /// sparsely populated ternary polynomials.
 pub struct ProdPoly {
@@ -417,28 +761,33 @@ pub struct ProdPoly {
     f3: TernPoly,
 }
 
-impl Default for ProdPoly {
-    fn default() -> ProdPoly {
-        ProdPoly {
-            n: 0,
-            f1: Default::default(),
-            f2: Default::default(),
-            f3: Default::default(),
-        }
-    }
-}
 
 impl ProdPoly {
     /// Creates a new `ProdPoly` from three `TernPoly`s
     pub fn new(n: u16, f1: TernPoly, f2: TernPoly, f3: TernPoly) -> ProdPoly {
         ProdPoly {
-            n: n,
-            f1: f1,
-            f2: f2,
-            f3: f3,
+            n,
+            f1,
+            f2,
+            f3,
         }
     }
 
+    /// Get the first factor
+    pub fn get_f1(&self) -> &TernPoly {
+        &self.f1
+    }
+
+    /// Get the second factor
+    pub fn get_f2(&self) -> &TernPoly {
+        &self.f2
+    }
+
+    /// Get the ternary polynomial added to the product
+    pub fn get_f3(&self) -> &TernPoly {
+        &self.f3
+    }
+
     /// Random product-form polynomial
     ///
     /// Generates a random product-form polynomial consisting of 3 random ternary polynomials.
@@ -450,49 +799,71 @@ impl ProdPoly {
     /// * *df3_ones*: number of ones ones in the third ternary polynomial
     /// * *df3_neg_ones*: number of negative ones in the third ternary polynomial
     /// * *rand_ctx*: a random number generator
+    ///
+    /// It returns the same errors as `TernPoly::rand()`.
     pub fn rand(n: u16,
                 df1: u16,
                 df2: u16,
                 df3_ones: u16,
                 df3_neg_ones: u16,
                 rand_ctx: &RandContext)
-                -> Option<ProdPoly> {
-        let f1 = TernPoly::rand(n, df1, df1, rand_ctx);
-        if f1.is_none() {
-            return None;
-        }
-        let f1 = f1.unwrap();
+                -> Result<ProdPoly, Error> {
+        let f1 = TernPoly::rand(n, df1, df1, rand_ctx)?;
+        let f2 = TernPoly::rand(n, df2, df2, rand_ctx)?;
+        let f3 = TernPoly::rand(n, df3_ones, df3_neg_ones, rand_ctx)?;
 
-        let f2 = TernPoly::rand(n, df2, df2, rand_ctx);
-        if f2.is_none() {
-            return None;
-        }
-        let f2 = f2.unwrap();
+        Ok(ProdPoly::new(n, f1, f2, f3))
+    }
 
-        let f3 = TernPoly::rand(n, df3_ones, df3_neg_ones, rand_ctx);
-        if f3.is_none() {
-            return None;
+    /// Returns an IntPoly equivalent to the ProdPoly
+    ///
+    /// Computes `f1*f2+f3`, with the coefficients reduced modulo `modulus`, that must be a power
+    /// of 2.
+    ///
+    /// Returns `Error::InvalidParam` if the modulus is not a power of 2, `Error::InvalidDegree` if
+    /// the factors do not have the same number of coefficients and `Error::InvalidWeight` if they
+    /// have indices out of range.
+    pub fn to_int_poly(&self, modulus: u16) -> Result<IntPoly, Error> {
+        if modulus < 2 || modulus & (modulus - 1) != 0 {
+            return Err(Error::InvalidParam);
         }
-        let f3 = f3.unwrap();
-
-        Some(ProdPoly::new(n, f1, f2, f3))
+        for f in &[&self.f1, &self.f2, &self.f3] {
+            f.check_weights(self.n, f.num_ones, f.num_neg_ones)?;
+        }
+        let mod_mask = modulus - 1;
+        let f1f2 = self.f1.to_int_poly().mult_tern(&self.f2, mod_mask)?;
+        let mut c = f1f2.add_tern(&self.f3);
+        c.mod_mask(mod_mask);
+        Ok(c)
     }
 
-    /// Returns an IntPoly equivalent to the ProdPoly
-    pub fn to_int_poly(&self, modulus: u16) -> IntPoly {
-        let c = IntPoly {
-            n: self.n,
-            coeffs: [0; INT_POLY_SIZE],
-        };
+    /// Product-form polynomial by general polynomial multiplication
+    ///
+    /// Same as `b.mult_prod(self, mod_mask)`.
+    pub fn mult_int(&self, b: &IntPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        b.mult_prod(self, mod_mask)
+    }
 
-        let mod_mask = modulus - 1;
-        let (c, _) = c.mult_tern(&self.f2, mod_mask);
-        c.add_tern(&self.f3)
+    /// Product-form polynomial by general polynomial multiplication in Rust
+    ///
+    /// Computes `(b*f1)*f2+b*f3` with three ternary multiplications in Rust. Returns
+    /// `Error::InvalidDegree` if the number of coefficients differ.
+    pub fn mult_int_native(&self, b: &IntPoly, mod_mask: u16) -> Result<IntPoly, Error> {
+        let bf1 = b.mult_tern_native(&self.f1, mod_mask)?;
+        let mut c = bf1.mult_tern_native(&self.f2, mod_mask)?;
+        c += &b.mult_tern_native(&self.f3, mod_mask)?;
+        c.mod_mask(mod_mask);
+        Ok(c)
     }
 }
 
-/// The size of the union in 16 bit words
+/// The size of the union in 16 bit words, the size of a `ProdPoly`
+#[cfg(not(feature = "avoid-hamming-wt-patent"))]
 const PRIVUNION_SIZE: usize = 3004;
+/// The size of the union in 16 bit words, the size of a `TernPoly`: like libntru, with the
+/// `avoid-hamming-wt-patent` feature the union cannot hold a `ProdPoly`
+#[cfg(feature = "avoid-hamming-wt-patent")]
+const PRIVUNION_SIZE: usize = 1001;
 
 #[repr(C)]
 /// Union for the private key polynomial
@@ -519,6 +890,7 @@ impl Clone for PrivUnion {
 
 impl PrivUnion {
     /// Create a new union from a ProdPoly
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
     unsafe fn new_from_prod(poly: ProdPoly) -> PrivUnion {
         let arr: &[uint16_t; 3004] = mem::transmute(&poly);
         let mut data = [0; PRIVUNION_SIZE];
@@ -527,7 +899,7 @@ impl PrivUnion {
             data[i] = *b;
         }
 
-        PrivUnion { data: data }
+        PrivUnion { data }
     }
 
     /// Create a new union from a TernPoly
@@ -539,10 +911,11 @@ impl PrivUnion {
             data[i] = *b;
         }
 
-        PrivUnion { data: data }
+        PrivUnion { data }
     }
 
     /// Get the union as a ProdPoly
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
     unsafe fn prod(&self) -> &ProdPoly {
         mem::transmute(&self.data)
     }
@@ -554,7 +927,7 @@ impl PrivUnion {
 }
 
 #[repr(C)]
-#[derive(Clone)]
+#[derive(Clone, Default)]
 /// Private polynomial, can be ternary or product-form
 pub struct PrivPoly {
     // maybe we could do conditional compilation?
@@ -563,14 +936,6 @@ pub struct PrivPoly {
     poly: PrivUnion,
 }
 
-impl Default for PrivPoly {
-    fn default() -> PrivPoly {
-        PrivPoly {
-            prod_flag: 0,
-            poly: Default::default(),
-        }
-    }
-}
 
 impl fmt::Debug for PrivPoly {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -597,6 +962,9 @@ impl PartialEq for PrivPoly {
 
 impl PrivPoly {
     /// Create a new PrivPoly with a ProdPoly
+    ///
+    /// Not available with the `avoid-hamming-wt-patent` feature.
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
     pub fn new_with_prod_poly(poly: ProdPoly) -> PrivPoly {
         PrivPoly {
             prod_flag: 1,
@@ -604,6 +972,19 @@ impl PrivPoly {
         }
     }
 
+    /// Creates a new PrivPoly with a ProdPoly, if product-form polynomials are available
+    ///
+    /// Returns `Error::UnknownParamSet` with the `avoid-hamming-wt-patent` feature.
+    pub(crate) fn from_prod_poly(poly: ProdPoly) -> Result<PrivPoly, Error> {
+        #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+        return Ok(PrivPoly::new_with_prod_poly(poly));
+        #[cfg(feature = "avoid-hamming-wt-patent")]
+        {
+            let _ = poly;
+            Err(Error::UnknownParamSet)
+        }
+    }
+
     /// Create a new PrivPoly with a TernPoly
     pub fn new_with_tern_poly(poly: TernPoly) -> PrivPoly {
         PrivPoly {
@@ -613,28 +994,75 @@ impl PrivPoly {
     }
 
     /// If the PrivPoly contains a ProdPoly
+    ///
+    /// Always `false` with the `avoid-hamming-wt-patent` feature.
     pub fn is_product(&self) -> bool {
-        self.prod_flag == 1
+        !encparams::AVOID_HAMMING_WT_PATENT && self.prod_flag == 1
     }
 
     /// Get the ProdPoly of the union
     ///
     /// Panics if the union is actually a TernPoly
+    #[cfg_attr(feature = "no-panic", allow(clippy::panic))]
     pub fn get_poly_prod(&self) -> &ProdPoly {
-        if self.prod_flag != 1 {
-            panic!("Trying to get PrivPoly from an union that is TernPoly.");
+        match self.try_get_poly_prod() {
+            Ok(poly) => poly,
+            Err(_) => panic!("Trying to get PrivPoly from an union that is TernPoly."),
         }
-        unsafe { &*self.poly.prod() }
     }
 
     /// Get the TernPoly of the union
     ///
     /// Panics if the union is actually a ProdPoly
+    #[cfg_attr(feature = "no-panic", allow(clippy::panic))]
     pub fn get_poly_tern(&self) -> &TernPoly {
-        if self.prod_flag != 0 {
+        if self.is_product() {
             panic!("Trying to get TernPoly from an union that is ProdPoly.");
         }
-        unsafe { &*self.poly.tern() }
+        unsafe { self.poly.tern() }
+    }
+
+    /// Get the ProdPoly of the union, without panicking
+    ///
+    /// Returns an `Error::InvalidParam` if the union is actually a TernPoly.
+    pub fn try_get_poly_prod(&self) -> Result<&ProdPoly, Error> {
+        if !self.is_product() {
+            return Err(Error::InvalidParam);
+        }
+        #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+        return Ok(unsafe { self.poly.prod() });
+        #[cfg(feature = "avoid-hamming-wt-patent")]
+        Err(Error::InvalidParam)
+    }
+
+    /// Get the TernPoly of the union, without panicking
+    ///
+    /// Returns an `Error::InvalidParam` if the union is actually a ProdPoly.
+    pub fn try_get_poly_tern(&self) -> Result<&TernPoly, Error> {
+        if self.is_product() {
+            return Err(Error::InvalidParam);
+        }
+        Ok(unsafe { self.poly.tern() })
+    }
+
+    /// Checks that the polynomial has the form and the weights of the private polynomials of the
+    /// parameter set
+    pub(crate) fn check_weights(&self, params: &EncParams) -> Result<(), Error> {
+        if self.is_product() != params.is_product() {
+            return Err(Error::InvalidWeight);
+        }
+        let n = params.get_n();
+        if self.is_product() {
+            let prod = self.get_poly_prod();
+            if prod.n != n {
+                return Err(Error::InvalidDegree);
+            }
+            prod.f1.check_weights(n, params.get_df1(), params.get_df1())?;
+            prod.f2.check_weights(n, params.get_df2(), params.get_df2())?;
+            prod.f3.check_weights(n, params.get_df3(), params.get_df3())
+        } else {
+            self.get_poly_tern().check_weights(n, params.get_df1(), params.get_df1())
+        }
     }
 
     /// Inverse modulo q
@@ -645,31 +1073,93 @@ impl PrivPoly {
     /// The algorithm is described in "Almost Inverses and Fast NTRU Key Generation" at
     /// http://www.securityinnovation.com/uploads/Crypto/NTRUTech014.pdf
     pub fn invert(&self, mod_mask: u16) -> (IntPoly, bool) {
-        let mut fq: IntPoly = Default::default();
-        let result = unsafe { ffi::ntru_invert(self, mod_mask, &mut fq) };
+        self.invert_with(mod_mask, ffi::ntru_invert)
+    }
+
+    /// Inverse modulo q with 32 bit arithmetic
+    ///
+    /// Same as `invert()`, but always using `ntru_invert_32()`.
+    pub fn invert_32(&self, mod_mask: u16) -> (IntPoly, bool) {
+        self.invert_with(mod_mask, ffi::ntru_invert_32)
+    }
+
+    /// Inverse modulo q with 64 bit arithmetic
+    ///
+    /// Same as `invert()`, but always using `ntru_invert_64()`.
+    pub fn invert_64(&self, mod_mask: u16) -> (IntPoly, bool) {
+        self.invert_with(mod_mask, ffi::ntru_invert_64)
+    }
+
+    /// Inverse modulo q in Rust
+    ///
+    /// Same as `invert()`, but implemented in Rust: the almost inverse algorithm computes the
+    /// inverse modulo 2, and Newton iteration lifts it to modulo q.
+    pub fn invert_native(&self, mod_mask: u16) -> (IntPoly, bool) {
+        let t = if self.is_product() {
+            match self.get_poly_prod().to_int_poly(mod_mask.wrapping_add(1)) {
+                Ok(t) => t,
+                Err(_) => return (Default::default(), false),
+            }
+        } else {
+            self.get_poly_tern().to_int_poly()
+        };
+        if t.n == 0 {
+            return (Default::default(), false);
+        }
+
+        let mut a: Vec<i16> = t.coeffs.iter().map(|c| c.wrapping_mul(3)).collect();
+        a[0] = a[0].wrapping_add(1);
+        let fq = poly::invert(&a, mod_mask);
+        #[cfg(feature = "metrics")]
+        ::metrics::record_inversion(fq.is_some());
+        match fq {
+            Some(fq) => (IntPoly::new(&fq), true),
+            None => (Default::default(), false),
+        }
+    }
 
-        (fq, result == 1)
+    /// Inverse modulo q with the given libntru function
+    fn invert_with(&self,
+                   mod_mask: u16,
+                   invert: unsafe extern "C" fn(*const PrivPoly, uint16_t, *mut CIntPoly)
+                                                -> uint8_t)
+                   -> (IntPoly, bool) {
+        let mut fq = boxed_zeroed::<CIntPoly>();
+        let result = unsafe { invert(self, mod_mask, &mut *fq) };
+        #[cfg(feature = "metrics")]
+        ::metrics::record_inversion(result == 1);
+
+        (IntPoly::from_c(&fq), result == 1)
     }
 }
 
 #[repr(C)]
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 /// NTRU encryption private key
 pub struct PrivateKey {
     q: uint16_t,
     t: PrivPoly,
 }
 
-impl Default for PrivateKey {
-    fn default() -> PrivateKey {
+
+impl PrivateKey {
+    /// Creates a private key
+    pub(crate) fn new(q: u16, t: PrivPoly) -> PrivateKey {
         PrivateKey {
-            q: 0,
-            t: Default::default(),
+            q,
+            t,
+        }
+    }
+
+    /// Overwrites the private key with zeros, with writes that are not optimized away
+    pub(crate) fn wipe(&mut self) {
+        self.q = 0;
+        self.t.prod_flag = 0;
+        for w in self.t.poly.data.iter_mut() {
+            unsafe { ptr::write_volatile(w, 0) };
         }
     }
-}
 
-impl PrivateKey {
     /// Gets the q parameter of the PrivateKey
     pub fn get_q(&self) -> u16 {
         self.q
@@ -681,52 +1171,378 @@ impl PrivateKey {
     }
 
     /// Get params from the private key
+    ///
+    /// The parameter sets registered with `encparams::register()` are found too.
     pub fn get_params(&self) -> Result<EncParams, Error> {
         let mut params: EncParams = Default::default();
         let result = unsafe { ffi::ntru_params_from_priv_key(self, &mut params) };
 
         if result == 0 {
-            Ok(params)
-        } else {
-            Err(Error::from(result))
+            // libntru only fills in its fields of the built-in parameter sets, not the hash
+            // algorithm
+            return Ok(encparams::from_oid(params.get_oid()).cloned().unwrap_or(params));
         }
+        encparams::registered()
+            .into_iter()
+            .find(|params| params.get_q() == self.q && self.t.check_weights(params).is_ok())
+            .cloned()
+            .ok_or_else(|| Error::from(result))
     }
 
-    /// Import private key
-    pub fn import(arr: &[u8]) -> PrivateKey {
-        let mut key: PrivateKey = Default::default();
-        unsafe { ffi::ntru_import_priv(&arr[0], &mut key) };
-
-        key
+    /// Generates public keys for this private key on demand
+    ///
+    /// Returns an endless iterator that generates a new public key each time it is advanced, so
+    /// the number of keys does not need to be known beforehand as in
+    /// `ntru::generate_multiple_key_pairs()`. All the keys can be used interchangeably, and the
+    /// private key decrypts messages encrypted for any of them.
+    pub fn public_keys<'a>(&'a self,
+                           params: &'a EncParams,
+                           rand_ctx: &'a RandContext)
+                           -> impl Iterator<Item = Result<PublicKey, Error>> + 'a {
+        iter::repeat(()).map(move |_| ::generate_public(params, self, rand_ctx))
     }
 
-    /// Export private key
-    pub fn export(&self, params: &EncParams) -> Box<[u8]> {
-        let mut arr = vec![0u8; params.private_len() as usize];
-        let _ = unsafe { ffi::ntru_export_priv(self, &mut arr[..][0]) };
-
-        arr.into_boxed_slice()
+    /// Import private key
+    ///
+    /// The key must be exported with `export()`, for a built-in or registered parameter set,
+    /// otherwise an `Error::InvalidKey` will be returned.
+    pub fn import(arr: &[u8]) -> Result<PrivateKey, Error> {
+        PrivateKey::import_known(arr)
     }
-}
 
-#[repr(C)]
-#[derive(Debug, PartialEq, Clone)]
-/// NTRU encryption public key
+    /// Import private key, directly on the heap
+    ///
+    /// Works like `import()`, but the key, of several kilobytes, is never on the stack, except
+    /// on big-endian targets, where it is decoded in Rust.
+    pub fn import_boxed(arr: &[u8]) -> Result<Box<PrivateKey>, Error> {
+        for params in encparams::known() {
+            if check_private_layout(arr, params).is_err() {
+                continue;
+            }
+            let mut key = boxed_zeroed::<PrivateKey>();
+            if cfg!(target_endian = "big") {
+                match ::low_level::import_private(arr, params) {
+                    Ok(mut imported) => {
+                        key.copy_from(&imported);
+                        imported.wipe();
+                    }
+                    Err(_) => continue,
+                }
+            } else {
+                unsafe { ffi::ntru_import_priv(&arr[0], &mut *key) };
+            }
+            match key.get_params() {
+                Ok(ref key_params) if key_params == params => return Ok(key),
+                _ => key.wipe(),
+            }
+        }
+        Err(Error::InvalidKey)
+    }
+
+    /// Copies another private key into this one, in place
+    fn copy_from(&mut self, other: &PrivateKey) {
+        self.q = other.q;
+        self.t.prod_flag = other.t.prod_flag;
+        self.t.poly.data.copy_from_slice(&other.t.poly.data);
+    }
+
+    /// Imports a private key in any of the supported formats
+    ///
+    /// Detects the format with `KeyFormat::detect()`, and imports the key accordingly: raw keys
+    /// must be exported with `export()` or `export_versioned()` for one of the standard parameter
+    /// sets, and DER and PEM keys must be PKCS #8 `PrivateKeyInfo` structures, as in the `x509`
+    /// module. Returns the key and the detected format, or the error of the import in that
+    /// format.
+    pub fn import_any(bytes: &[u8]) -> Result<(PrivateKey, KeyFormat), Error> {
+        let format = KeyFormat::detect(bytes);
+        let private = match format {
+            KeyFormat::Raw => PrivateKey::import_known(bytes)?,
+            KeyFormat::Versioned => PrivateKey::import_versioned(bytes)?,
+            KeyFormat::Der => x509::private_from_pkcs8(bytes)?.0,
+            KeyFormat::Pem => x509::private_from_pem(KeyFormat::pem_str(bytes)?)?.0,
+        };
+        Ok((private, format))
+    }
+
+    /// Imports a private key exported with `export_versioned()`
+    ///
+    /// Keys exported with `export()` by older versions, without the header, are also accepted.
+    /// Returns `Error::InvalidEncoding` if the key has a header with an unknown version, and
+    /// `Error::InvalidKey` if it is not a valid private key of one of the standard parameter sets.
+    pub fn import_versioned(arr: &[u8]) -> Result<PrivateKey, Error> {
+        PrivateKey::import_known(versioned_body(arr, &PRIVATE_KEY_MAGIC)?)
+    }
+
+    /// Imports a private key of any of the built-in or registered parameter sets, checking it
+    fn import_known(arr: &[u8]) -> Result<PrivateKey, Error> {
+        encparams::known()
+            .into_iter()
+            .find_map(|params| PrivateKey::import_checked(arr, params).ok())
+            .ok_or(Error::InvalidKey)
+    }
+
+    /// Imports a private key, checking that the array is a private key of the parameter set
+    ///
+    /// The exported key starts with `N` and `q`, as 2 byte big-endian integers. Returns
+    /// `Error::InvalidKey` if the array does not have the length of the private keys of the
+    /// parameter set, or it is not a valid private key for it.
+    pub(crate) fn import_checked(arr: &[u8], params: &EncParams) -> Result<PrivateKey, Error> {
+        check_private_layout(arr, params)?;
+        let key = if cfg!(target_endian = "big") {
+            // libntru would read the indices in the native byte order
+            ::low_level::import_private(arr, params)?
+        } else {
+            let mut key: PrivateKey = Default::default();
+            unsafe { ffi::ntru_import_priv(&arr[0], &mut key) };
+            key
+        };
+        match key.get_params() {
+            Ok(ref key_params) if key_params == params => Ok(key),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+
+    /// Export private key
+    ///
+    /// The key is exported in the layout of libntru on little-endian targets, on any target,
+    /// without a header. To store keys, prefer `export_versioned()`, that can be told apart from
+    /// other data. If the key is not a private key of the parameter set, the exported key will
+    /// not be valid.
+    pub fn export(&self, params: &EncParams) -> Box<[u8]> {
+        let mut arr = vec![0u8; params.private_len() as usize];
+        self.export_into(params, &mut arr);
+
+        arr.into_boxed_slice()
+    }
+
+    /// Checks that the key has the `q` and the private polynomial of the parameter set
+    fn check_params(&self, params: &EncParams) -> Result<(), Error> {
+        if self.q != params.get_q() {
+            return Err(Error::InvalidKey);
+        }
+        self.t.check_weights(params).map_err(|_| Error::InvalidKey)
+    }
+
+    /// Exports the private key into `arr`, that must be `params.private_len()` bytes long
+    ///
+    /// libntru writes as many bytes as the key needs, so keys of other parameter sets are
+    /// exported with the bounds checked encoder.
+    fn export_into(&self, params: &EncParams, arr: &mut [u8]) {
+        if cfg!(target_endian = "big") || self.check_params(params).is_err() {
+            // libntru would write the indices in the native byte order
+            ::low_level::export_private_into(self, params, arr);
+        } else {
+            let _ = unsafe { ffi::ntru_export_priv(self, &mut arr[0]) };
+        }
+    }
+
+    /// Exports the private key into an array, without allocating
+    ///
+    /// `LEN` must be `params.private_len()`, such as `EES443EP1_PRIVATE_LEN` for `EES443EP1`,
+    /// otherwise an `Error::InvalidParam` will be returned. If the key is not a private key of
+    /// the parameter set, an `Error::InvalidKey` will be returned.
+    pub fn export_array<const LEN: usize>(&self, params: &EncParams) -> Result<[u8; LEN], Error> {
+        if LEN != params.private_len() as usize {
+            return Err(Error::InvalidParam);
+        }
+        self.check_params(params)?;
+        let mut arr = [0u8; LEN];
+        self.export_into(params, &mut arr);
+        Ok(arr)
+    }
+
+    /// Exports the private key with the versioned header
+    ///
+    /// The key starts with `PRIVATE_KEY_MAGIC` and `KEY_FORMAT_VERSION`, followed by the key as
+    /// exported with `export()`.
+    pub fn export_versioned(&self, params: &EncParams) -> Box<[u8]> {
+        versioned(&PRIVATE_KEY_MAGIC, &self.export(params))
+    }
+
+    /// Exports the private key, with the length of its own parameter set
+    ///
+    /// Works like `export()`, but takes the parameter set from `get_params()`, so it cannot be
+    /// mismatched with the key. Returns `Error::InvalidKey` if the parameter set of the key is not
+    /// known, as for default keys.
+    pub fn export_auto(&self) -> Result<Box<[u8]>, Error> {
+        let params = self.get_params().map_err(|_| Error::InvalidKey)?;
+        Ok(self.export(&params))
+    }
+
+    /// Writes the private key to a stream, as exported with `export_auto()`
+    ///
+    /// The key is exported into a buffer on the stack, so nothing is allocated, and the buffer is
+    /// wiped before returning. If the parameter set of the key is not known, an `io::Error` of
+    /// kind `InvalidInput` will be returned.
+    pub fn export_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let params = self.get_params()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, Error::InvalidKey))?;
+        let len = params.private_len() as usize;
+        if len > MAX_PRIVATE_LEN {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, Error::InvalidKey));
+        }
+        let mut arr = [0u8; MAX_PRIVATE_LEN];
+        self.export_into(&params, &mut arr[..len]);
+        let result = w.write_all(&arr[..len]);
+        wipe(&mut arr);
+        result
+    }
+
+    /// Reads a private key of a built-in or registered parameter set from a stream
+    ///
+    /// Reads the `N` and `q` header first, then only as many bytes as the private keys of the
+    /// parameter sets with that `N` and `q`, trying the shortest ones first, so the stream can
+    /// go on after the key. If the stream ends before the key, an `io::Error` of kind
+    /// `UnexpectedEof` will be returned, and if it is not a valid private key, an `io::Error` of
+    /// kind `InvalidData`. The bytes read are wiped from the buffer before returning.
+    pub fn import_from<R: Read>(r: &mut R) -> io::Result<PrivateKey> {
+        let mut arr = [0u8; MAX_PRIVATE_LEN];
+        let result = PrivateKey::import_from_into(r, &mut arr);
+        wipe(&mut arr);
+        result
+    }
+
+    /// Reads a private key from a stream as `import_from()`, using `arr` as the buffer
+    fn import_from_into<R: Read>(r: &mut R,
+                                 arr: &mut [u8; MAX_PRIVATE_LEN])
+                                 -> io::Result<PrivateKey> {
+        r.read_exact(&mut arr[..4])?;
+        let n = (arr[0] as u16) << 8 | arr[1] as u16;
+        let q = (arr[2] as u16) << 8 | arr[3] as u16;
+        let mut candidates: Vec<&EncParams> = encparams::known()
+            .into_iter()
+            .filter(|params| {
+                params.get_n() == n && params.get_q() == q &&
+                params.private_len() as usize <= MAX_PRIVATE_LEN
+            })
+            .collect();
+        candidates.sort_by_key(|params| params.private_len());
+
+        let mut read = 4;
+        for params in candidates {
+            let len = params.private_len() as usize;
+            r.read_exact(&mut arr[read..len])?;
+            read = len;
+            if let Ok(key) = PrivateKey::import_checked(&arr[..len], params) {
+                return Ok(key);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::InvalidData, Error::InvalidKey))
+    }
+}
+
+impl TryFrom<&[u8]> for PrivateKey {
+    type Error = Error;
+
+    /// Imports a private key of a built-in or registered parameter set, as exported with
+    /// `export()`
+    fn try_from(arr: &[u8]) -> Result<PrivateKey, Error> {
+        PrivateKey::import_known(arr)
+    }
+}
+
+impl TryFrom<&PrivateKey> for Vec<u8> {
+    type Error = Error;
+
+    /// Exports a private key, as `PrivateKey::export_auto()`
+    fn try_from(private: &PrivateKey) -> Result<Vec<u8>, Error> {
+        private.export_auto().map(|arr| arr.into_vec())
+    }
+}
+
+/// Magic bytes at the start of versioned public keys
+pub const PUBLIC_KEY_MAGIC: [u8; 4] = *b"NTRP";
+/// Magic bytes at the start of versioned private keys
+pub const PRIVATE_KEY_MAGIC: [u8; 4] = *b"NTRS";
+/// Current version of the versioned key format
+pub const KEY_FORMAT_VERSION: u8 = 1;
+/// Length of the header of versioned keys, the magic bytes and the version
+pub const KEY_HEADER_LEN: usize = 5;
+
+/// Prepends the versioned header to an exported key
+fn versioned(magic: &[u8; 4], exported: &[u8]) -> Box<[u8]> {
+    let mut arr = Vec::with_capacity(KEY_HEADER_LEN + exported.len());
+    arr.extend_from_slice(magic);
+    arr.push(KEY_FORMAT_VERSION);
+    arr.extend_from_slice(exported);
+    arr.into_boxed_slice()
+}
+
+/// Gets the exported key of a versioned key, or the whole array for legacy keys
+///
+/// Legacy keys start with the high byte of `N`, so they never start with the magic bytes.
+fn versioned_body<'a>(arr: &'a [u8], magic: &[u8; 4]) -> Result<&'a [u8], Error> {
+    if !arr.starts_with(magic) {
+        Ok(arr)
+    } else if arr.len() < KEY_HEADER_LEN || arr[4] != KEY_FORMAT_VERSION {
+        Err(Error::InvalidEncoding)
+    } else {
+        Ok(&arr[KEY_HEADER_LEN..])
+    }
+}
+
+/// Format of an encoded key, as detected by `KeyFormat::detect()`
+///
+/// More formats may be added in future versions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum KeyFormat {
+    /// The raw format of `PublicKey::export()` and `PrivateKey::export()`.
+    Raw,
+    /// The versioned format of `PublicKey::export_versioned()` and
+    /// `PrivateKey::export_versioned()`.
+    Versioned,
+    /// A DER `SubjectPublicKeyInfo` or PKCS #8 `PrivateKeyInfo`.
+    Der,
+    /// A PEM `SubjectPublicKeyInfo` or PKCS #8 `PrivateKeyInfo`.
+    Pem,
+}
+
+impl KeyFormat {
+    /// Detects the format of an encoded key
+    ///
+    /// Only looks at the first bytes: versioned keys start with their magic bytes, PEM starts
+    /// with `-----BEGIN`, after optional whitespace, DER starts with the tag of a `SEQUENCE`, and
+    /// anything else is taken as a raw key. Raw keys never start like the others, since their
+    /// first byte is the high byte of `N`.
+    pub fn detect(bytes: &[u8]) -> KeyFormat {
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+        if bytes.starts_with(&PUBLIC_KEY_MAGIC) || bytes.starts_with(&PRIVATE_KEY_MAGIC) {
+            KeyFormat::Versioned
+        } else if bytes[start..].starts_with(b"-----BEGIN") {
+            KeyFormat::Pem
+        } else if bytes.first() == Some(&0x30) {
+            KeyFormat::Der
+        } else {
+            KeyFormat::Raw
+        }
+    }
+
+    /// Gets the text of a PEM key
+    fn pem_str(bytes: &[u8]) -> Result<&str, Error> {
+        str::from_utf8(bytes).map_err(|_| Error::InvalidEncoding)
+    }
+}
+
+/// Human-readable part of the Bech32m encoding of public keys
+pub const PUBLIC_KEY_HRP: &str = "ntrupk";
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+/// NTRU encryption public key
 pub struct PublicKey {
-    q: uint16_t,
+    q: u16,
     h: IntPoly,
 }
 
-impl Default for PublicKey {
-    fn default() -> PublicKey {
+impl PublicKey {
+    /// Creates a public key
+    pub(crate) fn new(q: u16, h: IntPoly) -> PublicKey {
         PublicKey {
-            q: 0,
-            h: Default::default(),
+            q,
+            h,
         }
     }
-}
 
-impl PublicKey {
     /// Get the q parameter of the PublicKey
     pub fn get_q(&self) -> u16 {
         self.q
@@ -737,25 +1553,436 @@ impl PublicKey {
         &self.h
     }
 
-    /// Import a public key
-    pub fn import(arr: &[u8]) -> PublicKey {
-        let mut key: PublicKey = Default::default();
-        let _ = unsafe { ffi::ntru_import_pub(&arr[0], &mut key) };
+    /// Get params from the public key
+    ///
+    /// A public key only has `N` and `q`, so this finds the built-in or registered parameter set
+    /// with them. Returns `Error::UnknownParamSet` if there is none, or if there are several ones,
+    /// such as `EES401EP1` and `EES401EP2`, that encrypt differently. `candidate_params()` gets
+    /// all of them.
+    pub fn get_params(&self) -> Result<EncParams, Error> {
+        match self.candidate_params()[..] {
+            [params] => Ok(params.clone()),
+            _ => Err(Error::UnknownParamSet),
+        }
+    }
 
-        key
+    /// Get the built-in and registered parameter sets with the `N` and `q` of the public key
+    pub fn candidate_params(&self) -> Vec<&'static EncParams> {
+        encparams::known()
+            .into_iter()
+            .filter(|params| params.get_n() == self.h.n && params.get_q() == self.q)
+            .collect()
     }
 
     /// Export public key
+    ///
+    /// The key is exported in the layout of libntru, without a header. To store keys, prefer
+    /// `export_versioned()`, that can be told apart from other data.
+    ///
+    /// The layout is canonical, the same on all targets: `N` and `q` as 2 byte big-endian integers,
+    /// followed by the coefficients of `h` packed as with `IntPoly::to_arr()`.
     pub fn export(&self, params: &EncParams) -> Box<[u8]> {
         let mut arr = vec![0u8; params.public_len() as usize];
-        unsafe { ffi::ntru_export_pub(self, &mut arr[..][0]) };
+        self.export_into(&mut arr);
+
+        arr.into_boxed_slice()
+    }
+
+    /// Exports the public key into an array, without allocating
+    ///
+    /// `LEN` must be `params.public_len()`, such as `EES443EP1_PUBLIC_LEN` for `EES443EP1`,
+    /// otherwise an `Error::InvalidParam` will be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::rand::RNG_DEFAULT;
+    /// use ntru::encparams::{EES443EP1, EES443EP1_PUBLIC_LEN};
+    ///
+    /// let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    /// let kp = ntru::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+    ///
+    /// let arr: [u8; EES443EP1_PUBLIC_LEN] = kp.get_public().export_array(&EES443EP1).unwrap();
+    /// assert_eq!(&arr[..], &kp.get_public().export(&EES443EP1)[..]);
+    /// ```
+    pub fn export_array<const LEN: usize>(&self, params: &EncParams) -> Result<[u8; LEN], Error> {
+        if LEN != params.public_len() as usize {
+            return Err(Error::InvalidParam);
+        }
+        let mut arr = [0u8; LEN];
+        self.export_into(&mut arr);
+        Ok(arr)
+    }
+
+    /// Exports the public key, with the length given by its own `N` and `q`
+    ///
+    /// Works like `export()`, but does not need the parameter set, so it cannot be mismatched
+    /// with the key. Returns `Error::InvalidKey` if the key has no coefficients or an invalid `q`,
+    /// as default keys.
+    pub fn export_auto(&self) -> Result<Box<[u8]>, Error> {
+        if self.h.n == 0 || self.q < 2 || self.q & (self.q - 1) != 0 {
+            return Err(Error::InvalidKey);
+        }
+        Ok(self.export_self())
+    }
+
+    /// Writes the public key to a stream, as exported with `export_auto()`
+    ///
+    /// The key is exported into a buffer on the stack, so nothing is allocated. If the key has no
+    /// coefficients or an invalid `q`, an `io::Error` of kind `InvalidInput` will be returned.
+    pub fn export_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.h.n == 0 || self.h.n as usize >= MAX_DEGREE || self.q < 2 ||
+           self.q & (self.q - 1) != 0 || self.q > 1 << 15 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, Error::InvalidKey));
+        }
+        let mut arr = [0u8; MAX_PUBLIC_LEN];
+        self.export_into(&mut arr);
+        w.write_all(&arr[..PublicKey::export_len(self.h.n, self.q)])
+    }
+
+    /// Reads a public key from a stream
+    ///
+    /// Reads the `N` and `q` header first, then only the length it gives, so the stream can go on
+    /// after the key. If the stream ends before the key, an `io::Error` of kind `UnexpectedEof`
+    /// will be returned, and if the header is not valid, an `io::Error` of kind `InvalidData`.
+    pub fn import_from<R: Read>(r: &mut R) -> io::Result<PublicKey> {
+        let mut arr = [0u8; MAX_PUBLIC_LEN];
+        r.read_exact(&mut arr[..4])?;
+        let n = (arr[0] as u16) << 8 | arr[1] as u16;
+        let q = (arr[2] as u16) << 8 | arr[3] as u16;
+        if n == 0 || n as usize >= MAX_DEGREE || q < 2 || q & (q - 1) != 0 || q > 1 << 15 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, Error::InvalidKey));
+        }
+        let len = PublicKey::export_len(n, q);
+        r.read_exact(&mut arr[4..len])?;
+        PublicKey::import(&arr[..len])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Exports the public key with the versioned header
+    ///
+    /// The key starts with `PUBLIC_KEY_MAGIC` and `KEY_FORMAT_VERSION`, followed by the key as
+    /// exported with `export()`.
+    pub fn export_versioned(&self, params: &EncParams) -> Box<[u8]> {
+        versioned(&PUBLIC_KEY_MAGIC, &self.export(params))
+    }
+
+    /// Imports a public key exported with `export_versioned()`
+    ///
+    /// Keys exported with `export()` by older versions, without the header, are also accepted.
+    /// Returns `Error::InvalidEncoding` if the key has a header with an unknown version, and
+    /// `Error::InvalidKey` if it is not a valid public key.
+    pub fn import_versioned(arr: &[u8]) -> Result<PublicKey, Error> {
+        PublicKey::import(versioned_body(arr, &PUBLIC_KEY_MAGIC)?)
+    }
+
+    /// Encodes the exported public key in lowercase hexadecimal
+    ///
+    /// This is also the format of `Display`.
+    pub fn to_hex(&self) -> String {
+        encoding::to_hex(&self.export_self())
+    }
+
+    /// Decodes a public key exported in hexadecimal
+    ///
+    /// Returns `Error::InvalidEncoding` if the string is not valid hexadecimal, and
+    /// `Error::InvalidKey` if it is not an exported public key. This is also the format of
+    /// `FromStr`.
+    pub fn from_hex(s: &str) -> Result<PublicKey, Error> {
+        PublicKey::import(&encoding::from_hex(s)?)
+    }
+
+    /// Encodes the exported public key in standard base64, with padding
+    pub fn to_base64(&self) -> String {
+        encoding::to_base64(&self.export_self())
+    }
+
+    /// Decodes a public key exported in standard base64
+    ///
+    /// Returns `Error::InvalidEncoding` if the string is not valid base64, and
+    /// `Error::InvalidKey` if it is not an exported public key.
+    pub fn from_base64(s: &str) -> Result<PublicKey, Error> {
+        PublicKey::import(&encoding::from_base64(s)?)
+    }
+
+    /// Encodes the exported public key in Bech32m, with the `PUBLIC_KEY_HRP` prefix
+    ///
+    /// The result looks like `ntrupk1...`, and its checksum detects errors made when copying it
+    /// by hand. Since public keys are long, the result exceeds the 90 character limit of BIP-350.
+    pub fn to_bech32(&self) -> String {
+        encoding::to_bech32(PUBLIC_KEY_HRP, &self.export_self())
+    }
+
+    /// Decodes a public key exported in Bech32m
+    ///
+    /// Returns `Error::InvalidEncoding` if the string is not valid Bech32m with the
+    /// `PUBLIC_KEY_HRP` prefix or its checksum does not match, and `Error::InvalidKey` if it is
+    /// not an exported public key.
+    pub fn from_bech32(s: &str) -> Result<PublicKey, Error> {
+        PublicKey::import(&encoding::from_bech32(PUBLIC_KEY_HRP, s)?)
+    }
+
+    /// Encodes the exported public key in Base58Check
+    ///
+    /// It uses the alphabet of Bitcoin, that avoids ambiguous characters, and appends a 4 byte
+    /// checksum to the exported key.
+    pub fn to_base58(&self) -> String {
+        encoding::to_base58check(&self.export_self())
+    }
+
+    /// Decodes a public key exported in Base58Check
+    ///
+    /// Returns `Error::InvalidEncoding` if the string is not valid Base58 or the checksum does not
+    /// match, and `Error::InvalidKey` if it is not an exported public key.
+    pub fn from_base58(s: &str) -> Result<PublicKey, Error> {
+        PublicKey::import(&encoding::from_base58check(s)?)
+    }
+
+    /// Imports a public key in any of the supported formats
+    ///
+    /// Detects the format with `KeyFormat::detect()`, and imports the key accordingly: raw keys
+    /// must be exported with `export()` or `export_versioned()`, and DER and PEM keys must be
+    /// `SubjectPublicKeyInfo` structures, as in the `x509` module. Returns the key and the detected
+    /// format, or the error of the import in that format.
+    pub fn import_any(bytes: &[u8]) -> Result<(PublicKey, KeyFormat), Error> {
+        let format = KeyFormat::detect(bytes);
+        let public = match format {
+            KeyFormat::Raw => PublicKey::import(bytes)?,
+            KeyFormat::Versioned => PublicKey::import_versioned(bytes)?,
+            KeyFormat::Der => x509::public_from_spki(bytes)?.0,
+            KeyFormat::Pem => x509::public_from_pem(KeyFormat::pem_str(bytes)?)?.0,
+        };
+        Ok((public, format))
+    }
+
+    /// Import a public key
+    ///
+    /// The key must be exported with `export()`: it starts with `N` and `q`, as 2 byte
+    /// big-endian integers, followed by the `N` coefficients of `h` with `log2(q)` bits each.
+    /// Returns `Error::InvalidKey` if the array does not have the length given by its header, or
+    /// `N` is not lower than `MAX_DEGREE`, the coefficients that libntru can hold.
+    pub fn import(arr: &[u8]) -> Result<PublicKey, Error> {
+        if arr.len() < 4 {
+            return Err(Error::InvalidKey);
+        }
+        let n = (arr[0] as u16) << 8 | arr[1] as u16;
+        let q = (arr[2] as u16) << 8 | arr[3] as u16;
+        if n == 0 || n as usize >= MAX_DEGREE || q < 2 || q & (q - 1) != 0 ||
+           arr.len() != PublicKey::export_len(n, q) {
+            return Err(Error::InvalidKey);
+        }
+
+        Ok(PublicKey {
+            q,
+            h: IntPoly::from_arr(&arr[4..], n, q),
+        })
+    }
+
+    /// Imports a public key, checking that it is a public key of the parameter set
+    ///
+    /// Returns `Error::InvalidKey` if the array is not a valid exported public key with the `N`
+    /// and `q` of the parameter set.
+    pub(crate) fn import_for(arr: &[u8], params: &EncParams) -> Result<PublicKey, Error> {
+        if arr.len() != params.public_len() as usize {
+            return Err(Error::InvalidKey);
+        }
+        let public = PublicKey::import(arr)?;
+        if public.q != params.get_q() || public.h.n != params.get_n() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(public)
+    }
+
+    /// Gets the length of the exported public key for `N` and `q`
+    fn export_len(n: u16, q: u16) -> usize {
+        4 + (n as usize * q.trailing_zeros() as usize).div_ceil(8)
+    }
+
+    /// Exports the public key, with the length given by its own `N` and `q`
+    fn export_self(&self) -> Box<[u8]> {
+        let mut arr = vec![0u8; PublicKey::export_len(self.h.n, self.q)];
+        self.export_into(&mut arr);
 
         arr.into_boxed_slice()
     }
+
+    /// Exports the public key into `arr`, as `export()`
+    ///
+    /// The coefficients that do not fit in `arr` are left out.
+    fn export_into(&self, arr: &mut [u8]) {
+        export_public(self.q, &self.h.coeffs, arr)
+    }
+
+    /// Converts the public key to the fixed layout of libntru
+    pub(crate) fn to_c(&self) -> Box<CPublicKey> {
+        let mut key = boxed_zeroed::<CPublicKey>();
+        self.write_c(&mut key);
+        key
+    }
+
+    /// Writes the public key into a public key in the fixed layout of libntru
+    fn write_c(&self, key: &mut CPublicKey) {
+        key.q = self.q;
+        self.h.write_c(&mut key.h);
+    }
+
+    /// Creates a public key from the fixed layout of libntru
+    pub(crate) fn from_c(key: &CPublicKey) -> PublicKey {
+        PublicKey {
+            q: key.q,
+            h: IntPoly::from_c(&key.h),
+        }
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<PublicKey, Error> {
+        PublicKey::from_hex(s)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = Error;
+
+    /// Imports a public key as exported with `export()`, checking its length
+    fn try_from(arr: &[u8]) -> Result<PublicKey, Error> {
+        PublicKey::import(arr)
+    }
+}
+
+impl From<&PublicKey> for Vec<u8> {
+    /// Exports a public key, with the length given by its own `N` and `q`
+    fn from(public: &PublicKey) -> Vec<u8> {
+        public.export_self().into_vec()
+    }
+}
+
+impl Hash for PublicKey {
+    /// Hashes the content of the exported public key: `N`, `q` and the coefficients of `h`
+    /// reduced modulo `q`
+    ///
+    /// Equal keys have the same exported key, so they have the same hash. Nothing is allocated.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.h.n.hash(state);
+        self.q.hash(state);
+        let mask = self.q.wrapping_sub(1);
+        for &c in self.h.get_coeffs() {
+            (c as u16 & mask).hash(state);
+        }
+    }
 }
 
 #[repr(C)]
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Default)]
+/// NTRU encryption public key, in the fixed layout of libntru
+pub(crate) struct CPublicKey {
+    q: uint16_t,
+    h: CIntPoly,
+}
+
+impl CPublicKey {
+    /// Imports a public key exported with `PublicKey::export()`, without allocating
+    ///
+    /// `N` is limited to `MAX_DEGREE - 1`, the coefficients that libntru can hold.
+    pub(crate) fn import(arr: &[u8]) -> CPublicKey {
+        let n = cmp::min((arr[0] as u16) << 8 | arr[1] as u16, MAX_DEGREE as u16 - 1);
+        let q = (arr[2] as u16) << 8 | arr[3] as u16;
+        let mut h = CIntPoly {
+            n,
+            coeffs: [0; INT_POLY_SIZE],
+        };
+        poly::unpack(&arr[4..], q.trailing_zeros(), &mut h.coeffs[..n as usize]);
+        CPublicKey {
+            q,
+            h,
+        }
+    }
+
+    /// Exports the public key into `arr`, as `PublicKey::export()`, without allocating
+    pub(crate) fn export(&self, arr: &mut [u8]) {
+        export_public(self.q, &self.h.coeffs[..self.h.n as usize], arr)
+    }
+}
+
+/// Checks that an exported private key has the layout of the private keys of the parameter set
+///
+/// The key starts with `N` and `q`, as 2 byte big-endian integers, and a flags byte. Each ternary
+/// polynomial follows, with its number of ones and of negative ones, as 2 byte big-endian
+/// integers, and their indices. libntru trusts these numbers, so they must be checked against the
+/// parameter set before calling `ntru_import_priv()`. Returns `Error::InvalidKey` otherwise.
+pub(crate) fn check_private_layout(arr: &[u8], params: &EncParams) -> Result<(), Error> {
+    if arr.len() != params.private_len() as usize || arr.len() < 5 ||
+       (arr[0] as u16) << 8 | arr[1] as u16 != params.get_n() ||
+       (arr[2] as u16) << 8 | arr[3] as u16 != params.get_q() {
+        return Err(Error::InvalidKey);
+    }
+    let bits_per_idx = private_index_bits(params.get_n()) as usize;
+    let mut pos = 5;
+    for df in private_weights(params) {
+        let count = [(df >> 8) as u8, df as u8];
+        match arr.get(pos..pos + 4) {
+            Some(header) if header[..2] == count && header[2..] == count => {}
+            _ => return Err(Error::InvalidKey),
+        }
+        pos += 4 + (bits_per_idx * 2 * df as usize).div_ceil(8);
+    }
+    Ok(())
+}
+
+/// The number of ones, and of negative ones, of each ternary polynomial of the private keys of the
+/// parameter set
+pub(crate) fn private_weights(params: &EncParams) -> Vec<u16> {
+    if params.is_product() {
+        vec![params.get_df1(), params.get_df2(), params.get_df3()]
+    } else {
+        vec![params.get_df1()]
+    }
+}
+
+/// The number of bits of each index of an exported private key, enough for the indices up to
+/// `N - 1`
+pub(crate) fn private_index_bits(n: u16) -> u32 {
+    16 - n.saturating_sub(1).leading_zeros()
+}
+
+/// Whether libntru exports and imports public keys in the canonical layout of `export_public()`
+///
+/// Encryption packs the public key and the polynomials of the message with the same code of
+/// libntru, so this is also a check that its ciphertexts do not depend on the byte order.
+pub(crate) fn libntru_is_canonical() -> bool {
+    let (n, q) = (443, 2048);
+    let coeffs: Vec<i16> = (0..n).map(|i| (i * 1237 % q) as i16).collect();
+    let public = PublicKey::new(q as u16, IntPoly::new(&coeffs));
+
+    let mut canonical = vec![0u8; PublicKey::export_len(n as u16, q as u16)];
+    public.export_into(&mut canonical);
+    let mut exported = vec![0u8; canonical.len()];
+    unsafe { ffi::ntru_export_pub(&*public.to_c(), &mut exported[0]) };
+    let mut imported = boxed_zeroed::<CPublicKey>();
+    let _ = unsafe { ffi::ntru_import_pub(&canonical[0], &mut *imported) };
+
+    exported == canonical && PublicKey::from_c(&imported) == public
+}
+
+/// Exports a public key with `q` and the coefficients of `h` into `arr`
+///
+/// `N` and `q` are written as 2 byte big-endian integers and `h` is packed with `log2(q)` bits
+/// per coefficient, byte by byte, so the layout does not depend on the byte order of the target.
+fn export_public(q: u16, h: &[i16], arr: &mut [u8]) {
+    let (header, coeffs) = arr.split_at_mut(4);
+    header.copy_from_slice(&[(h.len() >> 8) as u8, h.len() as u8, (q >> 8) as u8, q as u8]);
+    poly::pack(h.iter().map(|&c| c as u16), q.trailing_zeros(), coeffs);
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
 /// NTRU encryption key pair
 pub struct KeyPair {
     /// Private key
@@ -764,24 +1991,66 @@ pub struct KeyPair {
     public: PublicKey,
 }
 
-impl Default for KeyPair {
-    fn default() -> KeyPair {
-        KeyPair {
-            private: Default::default(),
-            public: Default::default(),
-        }
-    }
-}
-
 impl KeyPair {
     /// Generate a new key pair
     pub fn new(private: PrivateKey, public: PublicKey) -> KeyPair {
         KeyPair {
-            private: private,
-            public: public,
+            private,
+            public,
         }
     }
 
+    /// Restores a key pair from a private key, with a new public key
+    ///
+    /// The public key is generated with `ntru::generate_public()`, so it differs from the original
+    /// public key of the private key. Decryption checks the encrypted message against the public
+    /// key of the key pair, so the restored key pair only decrypts the messages encrypted for its
+    /// new public key. Messages encrypted for the original public key need a key pair with that
+    /// public key, so when restoring from a backup, the public key should be backed up too, and
+    /// the key pair built with `KeyPair::new()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ntru::rand::RNG_DEFAULT;
+    /// use ntru::encparams::EES401EP1;
+    /// use ntru::types::KeyPair;
+    ///
+    /// let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    /// let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    ///
+    /// let restored = KeyPair::from_private(kp.get_private(), &EES401EP1, &rand_ctx).unwrap();
+    /// let encrypted = ntru::encrypt(b"Hello", restored.get_public(), &EES401EP1,
+    ///                               &rand_ctx).unwrap();
+    /// assert_eq!(&ntru::decrypt(&encrypted, &restored, &EES401EP1).unwrap()[..], b"Hello");
+    /// ```
+    pub fn from_private(private: &PrivateKey,
+                        params: &EncParams,
+                        rand_ctx: &RandContext)
+                        -> Result<KeyPair, Error> {
+        let public = ::generate_public(params, private, rand_ctx)?;
+        Ok(KeyPair::new(private.clone(), public))
+    }
+
+    /// Generates a key pair directly on the heap
+    ///
+    /// Works like `ntru::generate_key_pair()`, but the key pair, of several kilobytes, is never
+    /// on the stack, so it can be used on small embedded or thread stacks.
+    pub fn generate_boxed(params: &EncParams,
+                          rand_ctx: &RandContext)
+                          -> Result<Box<KeyPair>, Error> {
+        ::check_policy(params)?;
+        ::trace::instrument("generate_key_pair", params, 0, || {
+            let mut kp = boxed_zeroed::<CKeyPair>();
+            let result = unsafe { ffi::ntru_gen_key_pair(params, &mut *kp, rand_ctx) };
+            if result == 0 {
+                Ok(KeyPair::boxed_from_c(&kp))
+            } else {
+                Err(Error::from(result))
+            }
+        })
+    }
+
     /// Get params from the key pair
     pub fn get_params(&self) -> Result<EncParams, Error> {
         self.private.get_params()
@@ -795,6 +2064,205 @@ impl KeyPair {
     pub fn get_public(&self) -> &PublicKey {
         &self.public
     }
+
+    /// Overwrites the private key with zeros, with writes that are not optimized away
+    pub(crate) fn wipe(&mut self) {
+        self.private.wipe();
+    }
+
+    /// Converts the key pair to the fixed layout of libntru
+    pub(crate) fn to_c(&self) -> Box<CKeyPair> {
+        let mut kp = boxed_zeroed::<CKeyPair>();
+        kp.private.copy_from(&self.private);
+        self.public.write_c(&mut kp.public);
+        kp
+    }
+
+    /// Creates a key pair on the heap from the fixed layout of libntru
+    ///
+    /// The private key is copied in place, instead of being moved through the stack.
+    pub(crate) fn boxed_from_c(kp: &CKeyPair) -> Box<KeyPair> {
+        let mut boxed = Box::<KeyPair>::new_uninit();
+        unsafe {
+            let ptr = boxed.as_mut_ptr();
+            ptr::write(ptr::addr_of_mut!((*ptr).public), PublicKey::from_c(&kp.public));
+            ptr::copy_nonoverlapping(&kp.private, ptr::addr_of_mut!((*ptr).private), 1);
+            boxed.assume_init()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// A private key with several public keys, from `ntru::generate_multiple_key_pairs()`
+///
+/// The private key decrypts messages encrypted for any of the public keys. There is always at
+/// least one public key.
+pub struct MultiKeyPair {
+    /// The shared private key
+    private: PrivateKey,
+    /// The public keys
+    publics: Box<[PublicKey]>,
+}
+
+impl MultiKeyPair {
+    /// Creates a multiple key pair from a private key and its public keys
+    ///
+    /// Returns `Error::InvalidParam` if there are no public keys. The public keys are not checked
+    /// against the private key.
+    pub fn new(private: PrivateKey, publics: Box<[PublicKey]>) -> Result<MultiKeyPair, Error> {
+        if publics.is_empty() {
+            return Err(Error::InvalidParam);
+        }
+        Ok(MultiKeyPair {
+            private,
+            publics,
+        })
+    }
+
+    /// The shared private key
+    pub fn get_private(&self) -> &PrivateKey {
+        &self.private
+    }
+
+    /// The public keys
+    pub fn get_publics(&self) -> &[PublicKey] {
+        &self.publics
+    }
+
+    /// Gets the key pair of the private key and the public key at the index, if there is one
+    pub fn get_key_pair(&self, index: usize) -> Option<KeyPair> {
+        self.publics.get(index).map(|public| KeyPair::new(self.private.clone(), public.clone()))
+    }
+
+    /// Gets a key pair for each public key, in order
+    ///
+    /// Each key pair holds a copy of the private key.
+    pub fn key_pairs(&self) -> Vec<KeyPair> {
+        (0..self.publics.len()).filter_map(|i| self.get_key_pair(i)).collect()
+    }
+
+    /// Decrypts a message encrypted for any of the public keys, with `ntru::decrypt_any()`
+    pub fn decrypt(&self, enc: &[u8], params: &EncParams) -> Result<Box<[u8]>, Error> {
+        ::decrypt_any(enc, &self.private, &self.publics, params)
+    }
+
+    /// Splits the multiple key pair into the private key and the public keys
+    pub fn into_parts(self) -> (PrivateKey, Box<[PublicKey]>) {
+        (self.private, self.publics)
+    }
+}
+
+/// Secret bytes, such as a decrypted message, that are wiped from memory when dropped
+///
+/// Dereferences to the bytes, and does not show them when formatted with `{:?}`.
+pub struct SecretVec(Vec<u8>);
+
+impl SecretVec {
+    /// Wraps the bytes, taking over the vector without copying it
+    pub fn new(secret: Vec<u8>) -> SecretVec {
+        SecretVec(secret)
+    }
+}
+
+impl Deref for SecretVec {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SecretVec {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SecretVec {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl fmt::Debug for SecretVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretVec({} bytes)", self.0.len())
+    }
+}
+
+/// Overwrites the bytes with zeros, with writes that are not optimized away
+pub(crate) fn wipe(arr: &mut [u8]) {
+    for b in arr.iter_mut() {
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
+/// NTRU encryption key pair, in the fixed layout of libntru
+pub(crate) struct CKeyPair {
+    /// Private key
+    private: PrivateKey,
+    /// Public key
+    public: CPublicKey,
+}
+
+impl CKeyPair {
+    /// Creates a key pair in the fixed layout of libntru
+    pub(crate) fn new(private: PrivateKey, public: CPublicKey) -> CKeyPair {
+        CKeyPair {
+            private,
+            public,
+        }
+    }
+
+    /// Gets the private key
+    pub(crate) fn get_private(&self) -> &PrivateKey {
+        &self.private
+    }
+
+    /// Gets the public key
+    pub(crate) fn get_public(&self) -> &CPublicKey {
+        &self.public
+    }
+
+    /// Wipes the private key, overwriting it with zeros
+    pub(crate) fn wipe(&mut self) {
+        self.private.wipe();
+    }
+}
+
+impl Drop for CKeyPair {
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+/// The fixed layout types of libntru, whose `Default` value has all its bytes set to zero
+///
+/// # Safety
+///
+/// A value with all its bytes set to zero must be a valid value of the type, its default one.
+pub(crate) unsafe trait Zeroed: Default {}
+
+unsafe impl Zeroed for CIntPoly {}
+unsafe impl Zeroed for CPublicKey {}
+unsafe impl Zeroed for CKeyPair {}
+unsafe impl Zeroed for PrivateKey {}
+
+/// Allocates the default value of a fixed layout type on the heap
+///
+/// These values are several kilobytes long, so they are zeroed in place, instead of being built
+/// on the stack and moved into the box, as `Box::new(Default::default())` would do.
+pub(crate) fn boxed_zeroed<T: Zeroed>() -> Box<T> {
+    let layout = Layout::new::<T>();
+    unsafe {
+        let ptr = alloc::alloc_zeroed(layout) as *mut T;
+        if ptr.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        Box::from_raw(ptr)
+    }
 }
 
 /// The error enum
@@ -822,6 +2290,83 @@ pub enum Error {
     InvalidParam,
     /// Invalid key.
     InvalidKey,
+    /// The length of the encrypted message does not match the parameter set.
+    InvalidCiphertextLength {
+        /// The length expected by the parameter set.
+        expected: usize,
+        /// The length of the given encrypted message.
+        actual: usize,
+    },
+    /// The message authentication code does not match.
+    InvalidMac,
+    /// No key matches the key ID.
+    UnknownKey,
+    /// Invalid number of coefficients of a polynomial.
+    InvalidDegree,
+    /// Invalid number of non-zero coefficients of a polynomial.
+    InvalidWeight,
+    /// The parameter set is deprecated or too weak, and the parameter policy refuses it.
+    DeprecatedParamSet,
+    /// An error code of libntru that this version does not know.
+    Unknown(usize),
+    /// The encrypted message could not be decrypted, for any reason.
+    DecryptionFailed,
+    /// The encrypted message was not encrypted for the key.
+    KeyMismatch,
+}
+
+/// The origin of an error, to decide how to handle it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorCategory {
+    /// The input is invalid: a malformed key, message or encoding, or an unsuitable parameter.
+    /// Retrying with the same input fails again.
+    Input,
+    /// The environment failed: the memory could not be allocated, or the random number generator
+    /// failed. Retrying may succeed.
+    Environment,
+    /// A bug, in this crate or in libntru, or an error code it does not know.
+    Internal,
+}
+
+impl Error {
+    /// Gets the stable numeric code of the error
+    ///
+    /// The errors of libntru keep its codes, from 1 to 11, and `Error::Unknown` keeps the code it
+    /// was created from. The errors of this crate have the codes from 256 on, so they do not
+    /// collide with the codes libntru may add. The codes of the existing errors never change.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Error::OutOfMemory => 1,
+            Error::Prng => 2,
+            Error::MessageTooLong => 3,
+            Error::InvalidMaxLength => 4,
+            Error::Md0Violation => 5,
+            Error::NoZeroPad => 6,
+            Error::InvalidEncoding => 7,
+            Error::NullArgument => 8,
+            Error::UnknownParamSet => 9,
+            Error::InvalidParam => 10,
+            Error::InvalidKey => 11,
+            Error::Unknown(code) => code as u32,
+            Error::InvalidCiphertextLength { .. } => 256,
+            Error::InvalidMac => 257,
+            Error::UnknownKey => 258,
+            Error::InvalidDegree => 259,
+            Error::InvalidWeight => 260,
+            Error::DeprecatedParamSet => 261,
+            Error::DecryptionFailed => 262,
+            Error::KeyMismatch => 263,
+        }
+    }
+
+    /// Gets the category of the error
+    pub fn category(&self) -> ErrorCategory {
+        match *self {
+            Error::OutOfMemory | Error::Prng => ErrorCategory::Environment,
+            Error::NullArgument | Error::Unknown(_) => ErrorCategory::Internal,
+            _ => ErrorCategory::Input,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -830,7 +2375,39 @@ impl fmt::Display for Error {
     }
 }
 
+impl From<Error> for io::Error {
+    /// Converts the error to an `io::Error` with the error as its source
+    ///
+    /// Malformed data, such as encrypted messages or encodings, gives the kind `InvalidData`,
+    /// invalid arguments, such as keys or parameter sets, the kind `InvalidInput`, an unknown key
+    /// ID the kind `NotFound`, and the rest the kind `Other`, or `OutOfMemory`.
+    fn from(e: Error) -> io::Error {
+        let kind = match e {
+            Error::Md0Violation |
+            Error::NoZeroPad |
+            Error::InvalidEncoding |
+            Error::InvalidCiphertextLength { .. } |
+            Error::InvalidMac |
+            Error::DecryptionFailed |
+            Error::KeyMismatch => io::ErrorKind::InvalidData,
+            Error::MessageTooLong |
+            Error::InvalidMaxLength |
+            Error::UnknownParamSet |
+            Error::InvalidParam |
+            Error::InvalidKey |
+            Error::InvalidDegree |
+            Error::InvalidWeight |
+            Error::DeprecatedParamSet => io::ErrorKind::InvalidInput,
+            Error::UnknownKey => io::ErrorKind::NotFound,
+            Error::OutOfMemory => io::ErrorKind::OutOfMemory,
+            Error::Prng | Error::NullArgument | Error::Unknown(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
 impl From<uint8_t> for Error {
+    /// Converts an error code of libntru, keeping the codes it does not know as `Error::Unknown`
     fn from(error: uint8_t) -> Error {
         match error {
             1 => Error::OutOfMemory,
@@ -844,7 +2421,7 @@ impl From<uint8_t> for Error {
             9 => Error::UnknownParamSet,
             10 => Error::InvalidParam,
             11 => Error::InvalidKey,
-            _ => unreachable!(),
+            code => Error::Unknown(code as usize),
         }
     }
 }
@@ -863,6 +2440,54 @@ impl error::Error for Error {
             Error::UnknownParamSet => "Unknown parameter set.",
             Error::InvalidParam => "Invalid parameter.",
             Error::InvalidKey => "Invalid key.",
+            Error::InvalidMac => "The message authentication code does not match.",
+            Error::UnknownKey => "No key matches the key ID.",
+            Error::InvalidDegree => "Invalid number of coefficients of a polynomial.",
+            Error::InvalidWeight => "Invalid number of non-zero coefficients of a polynomial.",
+            Error::DeprecatedParamSet => {
+                "The parameter set is deprecated or too weak, and the parameter policy refuses it."
+            }
+            Error::InvalidCiphertextLength { .. } => "Invalid length of the encrypted message.",
+            Error::Unknown(_) => "Unknown error code of libntru.",
+            Error::DecryptionFailed => "The encrypted message could not be decrypted.",
+            Error::KeyMismatch => "The encrypted message was not encrypted for the key.",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+/// The check that failed in `ntru::self_test()`
+pub enum SelfTestError {
+    /// A hash function did not give the known answer.
+    Hash,
+    /// The deterministic random number generator did not give the same output for the same seed.
+    Rng,
+    /// The key generation failed, was not deterministic or did not give the known answer.
+    KeyGeneration,
+    /// The encryption failed or did not give the known answer.
+    Encryption,
+    /// The decryption failed or did not recover the message.
+    Decryption,
+    /// The linked libntru does not have the ABI of the bundled one, or its encoding depends on the
+    /// byte order of the target.
+    Library,
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl error::Error for SelfTestError {
+    fn description(&self) -> &str {
+        match *self {
+            SelfTestError::Hash => "A hash function did not give the known answer.",
+            SelfTestError::Rng => "The deterministic random number generator is not deterministic.",
+            SelfTestError::KeyGeneration => "The key generation did not give the known answer.",
+            SelfTestError::Encryption => "The encryption did not give the known answer.",
+            SelfTestError::Decryption => "The decryption failed or did not recover the message.",
+            SelfTestError::Library => "The linked libntru is not compatible with this crate.",
         }
     }
 }
@@ -0,0 +1,227 @@
+//! X.509 public keys and PKCS #8 private keys
+//!
+//! This module embeds NTRU public keys in DER encoded X.509 `SubjectPublicKeyInfo` structures,
+//! so they can be certified by a certificate authority, and extracts them back from
+//! `SubjectPublicKeyInfo` structures and DER encoded certificates. Signing and verifying the
+//! certificates is left to the X.509 tooling of the application: this module only parses the
+//! certificates to find the public key of the subject, and checks that it is a valid NTRU public
+//! key. Private keys are encoded as DER PKCS #8 `PrivateKeyInfo` structures, and both can be
+//! wrapped in PEM.
+//!
+//! There is no widely deployed OID for NTRU public keys, so the algorithm identifier of the
+//! public keys is `ALGORITHM_OID`, a UUID based OID (ITU-T X.667), with the 3 byte OID of the
+//! parameter set as an `OCTET STRING` parameter. The `BIT STRING` of the public key holds the
+//! exported public key, and the `OCTET STRING` of the private key holds the exported private key.
+//!
+//! # Examples
+//!
+//! ```
+//! use ntru::rand::RNG_DEFAULT;
+//! use ntru::encparams::EES401EP1;
+//!
+//! let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+//! let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+//!
+//! let spki = ntru::x509::public_to_spki(kp.get_public(), &EES401EP1);
+//! let (public, params) = ntru::x509::public_from_spki(&spki).unwrap();
+//! assert_eq!(&public, kp.get_public());
+//! assert!(params == &EES401EP1);
+//! ```
+use types::{PrivateKey, PublicKey, Error};
+use encparams::{self, EncParams};
+use encoding;
+
+/// OID of the algorithm of NTRU public keys, `2.25.207156111338499539556142250470799981633`
+///
+/// The DER encoding of the OID, without the tag and the length.
+pub const ALGORITHM_OID: [u8; 20] = [0x69, 0x82, 0xB7, 0xD8, 0xE8, 0xB6, 0x86, 0xB6, 0xDA, 0x80,
+                                     0xF3, 0xB7, 0xDB, 0x84, 0xC1, 0xCD, 0xC1, 0xE8, 0xF8, 0x41];
+
+/// Label of PEM public keys
+pub const PUBLIC_KEY_PEM_LABEL: &str = "PUBLIC KEY";
+/// Label of PEM private keys
+pub const PRIVATE_KEY_PEM_LABEL: &str = "PRIVATE KEY";
+
+/// Tag of `INTEGER`
+const TAG_INTEGER: u8 = 0x02;
+/// Tag of `BIT STRING`
+const TAG_BIT_STRING: u8 = 0x03;
+/// Tag of `OCTET STRING`
+const TAG_OCTET_STRING: u8 = 0x04;
+/// Tag of `OBJECT IDENTIFIER`
+const TAG_OID: u8 = 0x06;
+/// Tag of `SEQUENCE`
+const TAG_SEQUENCE: u8 = 0x30;
+/// Tag of the explicit version of `TBSCertificate`
+const TAG_VERSION: u8 = 0xA0;
+
+/// Encodes a public key as a DER `SubjectPublicKeyInfo`
+pub fn public_to_spki(public: &PublicKey, params: &EncParams) -> Vec<u8> {
+    let mut key = vec![0u8];
+    key.extend_from_slice(&public.export(params));
+
+    let mut spki = algorithm_identifier(params);
+    spki.extend_from_slice(&der(TAG_BIT_STRING, &key));
+    der(TAG_SEQUENCE, &spki)
+}
+
+/// Decodes a public key and its parameter set from a DER `SubjectPublicKeyInfo`
+///
+/// Returns `Error::InvalidEncoding` if it is not a DER `SubjectPublicKeyInfo` of an NTRU public
+/// key, `Error::UnknownParamSet` if the parameter set is not known, and `Error::InvalidKey` if the
+/// public key is not valid for the parameter set.
+pub fn public_from_spki(spki: &[u8]) -> Result<(PublicKey, &'static EncParams), Error> {
+    let (spki, rest) = read(spki, TAG_SEQUENCE)?;
+    let (algorithm, spki) = read(spki, TAG_SEQUENCE)?;
+    let (key, spki) = read(spki, TAG_BIT_STRING)?;
+    if !rest.is_empty() || !spki.is_empty() {
+        return Err(Error::InvalidEncoding);
+    }
+    let params = params_of_algorithm(algorithm)?;
+
+    // The first byte of the bit string is the number of unused bits
+    match key.split_first() {
+        Some((&0, key)) => Ok((PublicKey::import_for(key, params)?, params)),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Encodes a public key as a PEM `SubjectPublicKeyInfo`
+pub fn public_to_pem(public: &PublicKey, params: &EncParams) -> String {
+    encoding::to_pem(PUBLIC_KEY_PEM_LABEL, &public_to_spki(public, params))
+}
+
+/// Decodes a public key and its parameter set from a PEM `SubjectPublicKeyInfo`
+///
+/// Returns `Error::InvalidEncoding` if it is not PEM with the `PUBLIC KEY` label, and the same
+/// errors as `public_from_spki()` for its content.
+pub fn public_from_pem(pem: &str) -> Result<(PublicKey, &'static EncParams), Error> {
+    match encoding::from_pem(pem)? {
+        (ref label, ref spki) if label == PUBLIC_KEY_PEM_LABEL => public_from_spki(spki),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Encodes a private key as a DER PKCS #8 `PrivateKeyInfo`
+pub fn private_to_pkcs8(private: &PrivateKey, params: &EncParams) -> Vec<u8> {
+    let mut info = der(TAG_INTEGER, &[0]);
+    info.extend_from_slice(&algorithm_identifier(params));
+    info.extend_from_slice(&der(TAG_OCTET_STRING, &private.export(params)));
+    der(TAG_SEQUENCE, &info)
+}
+
+/// Decodes a private key and its parameter set from a DER PKCS #8 `PrivateKeyInfo`
+///
+/// Returns `Error::InvalidEncoding` if it is not a DER version 1 `PrivateKeyInfo` of an NTRU
+/// private key, `Error::UnknownParamSet` if the parameter set is not known, and
+/// `Error::InvalidKey` if the private key is not valid for the parameter set.
+pub fn private_from_pkcs8(pkcs8: &[u8]) -> Result<(PrivateKey, &'static EncParams), Error> {
+    let (info, rest) = read(pkcs8, TAG_SEQUENCE)?;
+    let (version, info) = read(info, TAG_INTEGER)?;
+    let (algorithm, info) = read(info, TAG_SEQUENCE)?;
+    let (key, info) = read(info, TAG_OCTET_STRING)?;
+    if !rest.is_empty() || version != [0] || !info.is_empty() {
+        return Err(Error::InvalidEncoding);
+    }
+    let params = params_of_algorithm(algorithm)?;
+    Ok((PrivateKey::import_checked(key, params)?, params))
+}
+
+/// Encodes a private key as a PEM PKCS #8 `PrivateKeyInfo`
+pub fn private_to_pem(private: &PrivateKey, params: &EncParams) -> String {
+    encoding::to_pem(PRIVATE_KEY_PEM_LABEL, &private_to_pkcs8(private, params))
+}
+
+/// Decodes a private key and its parameter set from a PEM PKCS #8 `PrivateKeyInfo`
+///
+/// Returns `Error::InvalidEncoding` if it is not PEM with the `PRIVATE KEY` label, and the same
+/// errors as `private_from_pkcs8()` for its content.
+pub fn private_from_pem(pem: &str) -> Result<(PrivateKey, &'static EncParams), Error> {
+    match encoding::from_pem(pem)? {
+        (ref label, ref pkcs8) if label == PRIVATE_KEY_PEM_LABEL => private_from_pkcs8(pkcs8),
+        _ => Err(Error::InvalidEncoding),
+    }
+}
+
+/// Extracts the public key of the subject of a DER certificate
+///
+/// The signature of the certificate is not verified. Returns `Error::InvalidEncoding` if it is
+/// not a DER certificate, and the same errors as `public_from_spki()` for its
+/// `SubjectPublicKeyInfo`.
+pub fn public_from_certificate(cert: &[u8]) -> Result<(PublicKey, &'static EncParams), Error> {
+    public_from_spki(spki_of_certificate(cert)?)
+}
+
+/// Finds the DER `SubjectPublicKeyInfo` of a DER certificate
+pub fn spki_of_certificate(cert: &[u8]) -> Result<&[u8], Error> {
+    let (cert, _) = read(cert, TAG_SEQUENCE)?;
+    let (mut tbs, _) = read(cert, TAG_SEQUENCE)?;
+    if tbs.first() == Some(&TAG_VERSION) {
+        tbs = read(tbs, TAG_VERSION)?.1;
+    }
+    // Skip the serial number, the signature algorithm, the issuer, the validity and the subject
+    tbs = read(tbs, TAG_INTEGER)?.1;
+    for _ in 0..4 {
+        tbs = read(tbs, TAG_SEQUENCE)?.1;
+    }
+    let (_, rest) = read(tbs, TAG_SEQUENCE)?;
+    Ok(&tbs[..tbs.len() - rest.len()])
+}
+
+/// Encodes the `AlgorithmIdentifier` of the keys of the parameter set
+fn algorithm_identifier(params: &EncParams) -> Vec<u8> {
+    let mut algorithm = der(TAG_OID, &ALGORITHM_OID);
+    algorithm.extend_from_slice(&der(TAG_OCTET_STRING, &params.get_oid()));
+    der(TAG_SEQUENCE, &algorithm)
+}
+
+/// Checks the content of an `AlgorithmIdentifier`, and gets its parameter set
+fn params_of_algorithm(algorithm: &[u8]) -> Result<&'static EncParams, Error> {
+    let (oid, algorithm) = read(algorithm, TAG_OID)?;
+    let (params_oid, algorithm) = read(algorithm, TAG_OCTET_STRING)?;
+    if oid != ALGORITHM_OID || params_oid.len() != 3 || !algorithm.is_empty() {
+        return Err(Error::InvalidEncoding);
+    }
+    encparams::from_oid([params_oid[0], params_oid[1], params_oid[2]])
+        .ok_or(Error::UnknownParamSet)
+}
+
+/// Encodes a DER element
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    if content.len() < 0x80 {
+        encoded.push(content.len() as u8);
+    } else {
+        let len_bytes = (content.len() as u32).to_be_bytes();
+        let skip = len_bytes.iter().take_while(|&&b| b == 0).count();
+        encoded.push(0x80 | (4 - skip) as u8);
+        encoded.extend_from_slice(&len_bytes[skip..]);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+/// Reads a DER element with the given tag, and returns its content and the rest of the input
+fn read(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), Error> {
+    if input.len() < 2 || input[0] != tag {
+        return Err(Error::InvalidEncoding);
+    }
+    let (len, header_len) = if input[1] < 0x80 {
+        (input[1] as usize, 2)
+    } else {
+        // Long form, with 1 to 4 bytes, and without leading zeros, as DER requires
+        let len_len = (input[1] & 0x7F) as usize;
+        if len_len == 0 || len_len > 4 || input.len() < 2 + len_len || input[2] == 0 {
+            return Err(Error::InvalidEncoding);
+        }
+        let len = input[2..2 + len_len].iter().fold(0usize, |len, &b| len << 8 | b as usize);
+        if len < 0x80 {
+            return Err(Error::InvalidEncoding);
+        }
+        (len, 2 + len_len)
+    };
+    if input.len() - header_len < len {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok((&input[header_len..header_len + len], &input[header_len + len..]))
+}
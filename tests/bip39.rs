@@ -0,0 +1,94 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "bip39")]
+
+extern crate ntru;
+
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES401EP2};
+use ntru::bip39::{self, SEED_LEN};
+use ntru::types::Error;
+
+fn from_hex(s: &str) -> Vec<u8> {
+    (0..s.len() / 2).map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn it_bip39_vectors() {
+    // Test vectors of the reference implementation, with the passphrase "TREZOR"
+    let vectors = [("00000000000000000000000000000000",
+                    "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                     abandon abandon about",
+                    "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a698\
+                     7599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"),
+                   ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                    "legal winner thank year wave sausage worth useful legal winner thank yellow",
+                    "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe12\
+                     96106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"),
+                   ("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                    "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo \
+                     zoo zoo zoo zoo vote",
+                    "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a\
+                     5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad")];
+    for &(entropy, mnemonic, seed) in vectors.iter() {
+        let entropy = from_hex(entropy);
+        assert_eq!(bip39::entropy_to_mnemonic(&entropy).unwrap(), mnemonic);
+        assert_eq!(&bip39::mnemonic_to_entropy(mnemonic).unwrap()[..], &entropy[..]);
+        let derived = bip39::to_seed(mnemonic, "TREZOR").unwrap();
+        assert_eq!(derived.len(), SEED_LEN);
+        assert_eq!(&derived[..], &from_hex(seed)[..]);
+    }
+}
+
+#[test]
+fn it_bip39_key_pair() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for &num_words in &[12, 15, 18, 21, 24] {
+        let mnemonic = bip39::generate_mnemonic(num_words, &rand_ctx).unwrap();
+        assert_eq!(mnemonic.split(' ').count(), num_words);
+        assert!(bip39::mnemonic_to_entropy(&mnemonic).is_ok());
+    }
+
+    let mnemonic = bip39::generate_mnemonic(24, &rand_ctx).unwrap();
+    let kp = bip39::generate_key_pair(&mnemonic, "passphrase", &EES401EP1).unwrap();
+    assert_eq!(bip39::generate_key_pair(&mnemonic, "passphrase", &EES401EP1).unwrap(), kp);
+    let spaced = mnemonic.replace(" ", "\n  ");
+    assert_eq!(bip39::generate_key_pair(&spaced, "passphrase", &EES401EP1).unwrap(), kp);
+    assert!(bip39::generate_key_pair(&mnemonic, "other", &EES401EP1).unwrap() != kp);
+    let other = bip39::generate_key_pair(&mnemonic, "passphrase", &EES401EP2).unwrap();
+    assert!(other.get_public().get_h() != kp.get_public().get_h());
+
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &EES401EP1).unwrap()[..], &msg[..]);
+}
+
+#[test]
+fn it_bip39_invalid() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    assert_eq!(bip39::generate_mnemonic(11, &rand_ctx), Err(Error::InvalidParam));
+    assert_eq!(bip39::generate_mnemonic(13, &rand_ctx), Err(Error::InvalidParam));
+    assert_eq!(bip39::generate_mnemonic(27, &rand_ctx), Err(Error::InvalidParam));
+    assert_eq!(bip39::entropy_to_mnemonic(&[0; 15]), Err(Error::InvalidParam));
+    assert_eq!(bip39::entropy_to_mnemonic(&[0; 36]), Err(Error::InvalidParam));
+
+    let invalid = ["abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                    abandon abandon abandon",
+                   "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                    abandon about",
+                   "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                    abandon abandon bitcoin",
+                   "Abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                    abandon abandon about",
+                   ""];
+    for mnemonic in invalid.iter() {
+        assert!(bip39::mnemonic_to_entropy(mnemonic).is_err());
+        assert_eq!(bip39::generate_key_pair(mnemonic, "", &EES401EP1).unwrap_err(),
+                   Error::InvalidEncoding);
+    }
+}
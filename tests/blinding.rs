@@ -0,0 +1,41 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "blinding")]
+
+extern crate ntru;
+use ntru::encparams::{EES401EP1, EES439EP1, EES1087EP2};
+use ntru::rand::RNG_DEFAULT;
+use ntru::types::Error;
+
+#[test]
+fn it_blinded_decrypt() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES439EP1, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        for msg in &[&b""[..], &b"Hello from Rust!"[..]] {
+            let encrypted = ntru::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap();
+            for _ in 0..3 {
+                let decrypted = ntru::blinding::decrypt(&encrypted, &kp, params, &rand_ctx)
+                    .unwrap();
+                assert_eq!(&msg[..], &decrypted[..]);
+            }
+        }
+
+        let mut encrypted = ntru::encrypt(b"Hello", kp.get_public(), params, &rand_ctx)
+            .unwrap()
+            .into_vec();
+        encrypted[0] ^= 1;
+        assert_eq!(ntru::blinding::decrypt(&encrypted, &kp, params, &rand_ctx),
+                   ntru::decrypt(&encrypted, &kp, params));
+        assert_eq!(ntru::blinding::decrypt(&encrypted[1..], &kp, params, &rand_ctx),
+                   Err(Error::InvalidCiphertextLength {
+                       expected: encrypted.len(),
+                       actual: encrypted.len() - 1,
+                   }));
+    }
+}
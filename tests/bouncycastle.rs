@@ -0,0 +1,87 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES401EP2, EES1087EP2, EES743EP1};
+use ntru::types::Error;
+use ntru::bouncycastle;
+
+#[test]
+fn it_public() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES401EP2, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let encoded = bouncycastle::export_public(kp.get_public(), params);
+
+        // The same packing of h as libntru, without the header
+        assert_eq!(&encoded[..], &kp.get_public().export(params)[4..]);
+        assert_eq!(&bouncycastle::import_public(&encoded, params).unwrap(),
+                   kp.get_public());
+        assert_eq!(bouncycastle::import_public(&encoded[1..], params).unwrap_err(),
+                   Error::InvalidKey);
+    }
+}
+
+#[test]
+fn it_key_pair() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES401EP2, EES1087EP2, EES743EP1] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let encoded = bouncycastle::export_key_pair(&kp, params);
+        let imported = bouncycastle::import_key_pair(&encoded, params).unwrap();
+        assert_eq!(imported, kp);
+
+        let msg = b"Hello from Rust!";
+        let encrypted = ntru::encrypt(msg, imported.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(encrypted.len(), params.enc_len() as usize);
+        assert_eq!(&ntru::decrypt(&encrypted, &imported, params).unwrap()[..], &msg[..]);
+
+        assert_eq!(bouncycastle::import_key_pair(&encoded[..encoded.len() - 1], params)
+                       .unwrap_err(),
+                   Error::InvalidKey);
+        let mut trailing = encoded.to_vec();
+        trailing.push(0);
+        assert_eq!(bouncycastle::import_key_pair(&trailing, params).unwrap_err(),
+                   Error::InvalidKey);
+    }
+}
+
+#[test]
+fn it_key_pair_invalid() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let public_len = EES401EP1.enc_len() as usize;
+
+    // A ternary polynomial with all the coefficients set to 0, which has the wrong weight
+    let mut zero = bouncycastle::export_key_pair(&kp, &EES401EP1).to_vec();
+    let t_len = zero.len() - public_len;
+    zero.truncate(public_len);
+    zero.extend_from_slice(&vec![0u8; t_len]);
+    assert_eq!(bouncycastle::import_key_pair(&zero, &EES401EP1).unwrap_err(),
+               Error::InvalidKey);
+
+    // A value larger than 3^N
+    let mut overflow = zero.clone();
+    for b in &mut overflow[public_len..] {
+        *b = 0xFF;
+    }
+    assert_eq!(bouncycastle::import_key_pair(&overflow, &EES401EP1).unwrap_err(),
+               Error::InvalidKey);
+
+    // Repeated indices in a product-form polynomial
+    let kp = ntru::generate_key_pair(&EES401EP2, &rand_ctx).unwrap();
+    let mut repeated = bouncycastle::export_key_pair(&kp, &EES401EP2).to_vec();
+    let public_len = EES401EP2.enc_len() as usize;
+    for b in &mut repeated[public_len..] {
+        *b = 0;
+    }
+    assert_eq!(bouncycastle::import_key_pair(&repeated, &EES401EP2).unwrap_err(),
+               Error::InvalidKey);
+}
@@ -0,0 +1,92 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "cbor")]
+
+extern crate ntru;
+
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES1087EP2};
+use ntru::types::Error;
+use ntru::cbor;
+
+#[test]
+fn it_cose_key() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+        let public_key = cbor::public_to_cose_key(kp.get_public(), params);
+        let (public, public_params) = cbor::public_from_cose_key(&public_key).unwrap();
+        assert_eq!(&public, kp.get_public());
+        assert!(public_params == params);
+        assert_eq!(cbor::key_pair_from_cose_key(&public_key).unwrap_err(),
+                   Error::InvalidEncoding);
+
+        let kp_key = cbor::key_pair_to_cose_key(&kp, params);
+        let (kp2, kp_params) = cbor::key_pair_from_cose_key(&kp_key).unwrap();
+        assert_eq!(kp2, kp);
+        assert!(kp_params == params);
+        let (public, _) = cbor::public_from_cose_key(&kp_key).unwrap();
+        assert_eq!(&public, kp.get_public());
+    }
+}
+
+#[test]
+fn it_cose_key_invalid() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let valid = cbor::key_pair_to_cose_key(&kp, &EES401EP1);
+
+    assert_eq!(cbor::public_from_cose_key(&[]).unwrap_err(), Error::InvalidEncoding);
+    assert_eq!(cbor::public_from_cose_key(&valid[..valid.len() - 1]).unwrap_err(),
+               Error::InvalidEncoding);
+
+    let mut trailing = valid.clone();
+    trailing.push(0);
+    assert_eq!(cbor::public_from_cose_key(&trailing).unwrap_err(), Error::InvalidEncoding);
+
+    let mut wrong_kty = valid.clone();
+    let pos = wrong_kty.windows(4).position(|w| w == b"NTRU").unwrap();
+    wrong_kty[pos..pos + 4].copy_from_slice(b"RSA!");
+    assert_eq!(cbor::public_from_cose_key(&wrong_kty).unwrap_err(), Error::InvalidEncoding);
+
+    let oid = &EES401EP1.get_oid();
+    let other = &EES1087EP2.get_oid();
+    let mut wrong_oid = valid.clone();
+    let pos = wrong_oid.windows(3).position(|w| w == oid).unwrap();
+    wrong_oid[pos..pos + 3].copy_from_slice(&[0xFF, 0xFF, 0xFF]);
+    assert_eq!(cbor::public_from_cose_key(&wrong_oid).unwrap_err(), Error::UnknownParamSet);
+
+    let mut other_oid = valid.clone();
+    other_oid[pos..pos + 3].copy_from_slice(other);
+    assert_eq!(cbor::public_from_cose_key(&other_oid).unwrap_err(), Error::InvalidKey);
+}
+
+#[test]
+fn it_ciphertext() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+
+    let encoded = cbor::encode_ciphertext(&encrypted, &EES401EP1);
+    let (decoded, params) = cbor::decode_ciphertext(&encoded).unwrap();
+    assert_eq!(decoded, encrypted);
+    assert!(params == &EES401EP1);
+    assert_eq!(&ntru::decrypt(&decoded, &kp, params).unwrap()[..], &msg[..]);
+
+    let short = cbor::encode_ciphertext(&encrypted[1..], &EES401EP1);
+    assert_eq!(cbor::decode_ciphertext(&short).unwrap_err(),
+               Error::InvalidCiphertextLength {
+                   expected: encrypted.len(),
+                   actual: encrypted.len() - 1,
+               });
+    assert_eq!(cbor::decode_ciphertext(&encoded[..encoded.len() - 1]).unwrap_err(),
+               Error::InvalidEncoding);
+    assert_eq!(cbor::decode_ciphertext(&[0x80]).unwrap_err(), Error::InvalidEncoding);
+}
@@ -0,0 +1,57 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::rand::{RNG_DEFAULT, RNG_CTR_DRBG};
+use ntru::encparams::{DEFAULT_PARAMS_128_BITS, ALL_PARAM_SETS};
+use ntru::cca::SHARED_KEY_LEN;
+use ntru::types::Error;
+
+#[test]
+fn it_cca_kem() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let (ciphertext, shared1) = ntru::cca::encapsulate(kp.get_public(), params, &rand_ctx)
+            .unwrap();
+        assert_eq!(ciphertext.len(), params.enc_len() as usize);
+        assert_eq!(shared1.len(), SHARED_KEY_LEN);
+
+        let shared2 = ntru::cca::decapsulate(&ciphertext, &kp, params).unwrap();
+        assert_eq!(shared1, shared2);
+    }
+}
+
+#[test]
+fn it_cca_implicit_rejection() {
+    let params = DEFAULT_PARAMS_128_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let (ciphertext, shared) = ntru::cca::encapsulate(kp.get_public(), &params, &rand_ctx)
+        .unwrap();
+
+    let mut modified = ciphertext.to_vec();
+    modified[10] ^= 1;
+    let rejected1 = ntru::cca::decapsulate(&modified, &kp, &params).unwrap();
+    let rejected2 = ntru::cca::decapsulate(&modified, &kp, &params).unwrap();
+    assert!(rejected1 != shared);
+    assert_eq!(rejected1, rejected2);
+
+    // A valid NTRU ciphertext that was not created by the KEM is rejected too
+    let det_ctx = ntru::rand::init_det(&RNG_CTR_DRBG, b"cca test").unwrap();
+    let secret = ntru::rand::generate(32, &det_ctx).unwrap();
+    let encrypted = ntru::encrypt(&secret, kp.get_public(), &params, &rand_ctx).unwrap();
+    let rejected = ntru::cca::decapsulate(&encrypted, &kp, &params).unwrap();
+    assert_eq!(rejected.len(), SHARED_KEY_LEN);
+
+    assert_eq!(ntru::cca::decapsulate(&ciphertext[1..], &kp, &params),
+               Err(Error::InvalidCiphertextLength {
+                   expected: ciphertext.len(),
+                   actual: ciphertext.len() - 1,
+               }));
+}
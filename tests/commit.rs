@@ -0,0 +1,54 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{DEFAULT_PARAMS_128_BITS, ALL_PARAM_SETS};
+use ntru::commit::COMMITMENT_LEN;
+use ntru::types::Error;
+
+#[test]
+fn it_commit() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let msg = b"Committed message";
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let encrypted = ntru::commit::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(encrypted.len(), params.enc_len() as usize + COMMITMENT_LEN);
+        assert!(ntru::commit::is_for_key(&encrypted, kp.get_public(), params));
+
+        let decrypted = ntru::commit::decrypt(&encrypted, &kp, params).unwrap();
+        assert_eq!(&msg[..], &decrypted[..]);
+    }
+}
+
+#[test]
+fn it_commit_other_key() {
+    let params = DEFAULT_PARAMS_128_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp1 = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let kp2 = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let encrypted = ntru::commit::encrypt(b"Hello", kp1.get_public(), &params, &rand_ctx).unwrap();
+
+    assert!(!ntru::commit::is_for_key(&encrypted, kp2.get_public(), &params));
+    assert_eq!(ntru::commit::decrypt(&encrypted, &kp2, &params), Err(Error::KeyMismatch));
+
+    for &i in &[0, params.enc_len() as usize] {
+        let mut modified = encrypted.to_vec();
+        modified[i] ^= 1;
+        assert!(!ntru::commit::is_for_key(&modified, kp1.get_public(), &params));
+        assert_eq!(ntru::commit::decrypt(&modified, &kp1, &params), Err(Error::KeyMismatch));
+    }
+
+    assert!(!ntru::commit::is_for_key(&encrypted[1..], kp1.get_public(), &params));
+    assert_eq!(ntru::commit::decrypt(&encrypted[1..], &kp1, &params),
+               Err(Error::InvalidCiphertextLength {
+                   expected: encrypted.len(),
+                   actual: encrypted.len() - 1,
+               }));
+}
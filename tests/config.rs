@@ -0,0 +1,159 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "config")]
+
+extern crate ntru;
+use ntru::encparams::{self, EncParams, ALL_PARAM_SETS, EES401EP1, EES613EP1};
+use ntru::rand::RNG_DEFAULT;
+use ntru::types::{Error, PrivateKey};
+
+const CUSTOM_TOML: &str = r#"
+name = "CUSTOM613"
+n = 613
+q = 2048
+prod_flag = 0
+df1 = 55
+dg = 204
+dm0 = 55
+db = 128
+c = 11
+min_calls_r = 16
+min_calls_mask = 13
+hash_seed = 1
+oid = [255, 0, 1]
+hash = "SHA-256"
+"#;
+
+#[test]
+fn it_from_base() {
+    for params in ALL_PARAM_SETS.iter() {
+        let name = params.get_name().trim_end_matches('\0').to_owned();
+        let config = format!("{{\"base\": \"{}\"}}", name);
+        assert!(&EncParams::from_json(&config).unwrap() == params);
+        let config = format!("base = \"{}\"", name);
+        assert!(&EncParams::from_toml(&config).unwrap() == params);
+    }
+
+    let params = EncParams::from_json(r#"{"base": "EES613EP1", "name": "FAST613",
+                                          "oid": [255, 0, 2], "min_calls_r": 20}"#)
+        .unwrap();
+    assert_eq!(params.get_name().trim_end_matches('\0'), "FAST613");
+    assert_eq!(params.get_oid(), [255, 0, 2]);
+    assert_eq!(params.get_min_calls_r(), 20);
+    assert_eq!(params.get_n(), EES613EP1.get_n());
+    assert_eq!(params.get_pklen(), EES613EP1.get_pklen());
+}
+
+#[test]
+fn it_from_toml() {
+    let params = EncParams::from_toml(CUSTOM_TOML).unwrap();
+    assert_eq!(params.to_string(),
+               "CUSTOM613 (n = 613, q = 2048, df = 55, dg = 204, dm0 = 55, SHA-256, 128 bits)");
+    assert_eq!(params.get_hlen(), 32);
+    assert_eq!(params.get_pklen(), 128);
+    assert_eq!(params.enc_len(), EES613EP1.enc_len());
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &params, &rand_ctx).unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &params).unwrap()[..], &msg[..]);
+}
+
+#[test]
+fn it_custom_failure_rate() {
+    // A smaller q leaves no room for the coefficients
+    let params = EncParams::from_json(r#"{"base": "EES401EP1", "name": "Q256", "q": 256,
+                                          "oid": [255, 1, 0]}"#)
+        .unwrap();
+    assert!(params.decryption_failure_rate_estimate() > 0.5);
+    let params = EncParams::from_json(r#"{"base": "EES401EP1", "name": "Q4096", "q": 4096,
+                                          "oid": [255, 1, 1]}"#)
+        .unwrap();
+    assert!(params.decryption_failure_rate_estimate() <
+            EES401EP1.decryption_failure_rate_estimate());
+}
+
+#[test]
+fn it_invalid_config() {
+    assert_eq!(EncParams::from_toml("n = "), Err(Error::InvalidEncoding));
+    assert_eq!(EncParams::from_json("[613]"), Err(Error::InvalidEncoding));
+    assert_eq!(EncParams::from_json(r#"{"base": "EES613EP1", "hlen": 32}"#),
+               Err(Error::InvalidEncoding));
+    assert_eq!(EncParams::from_json(r#"{"base": "EES613EP1", "hash": "MD5"}"#),
+               Err(Error::InvalidEncoding));
+    assert_eq!(EncParams::from_json(r#"{"base": "EES613EP1", "n": -1}"#),
+               Err(Error::InvalidEncoding));
+    assert_eq!(EncParams::from_json(r#"{"base": "EES123EP1"}"#), Err(Error::UnknownParamSet));
+    assert_eq!(EncParams::from_toml(&CUSTOM_TOML.replace("dg = 204\n", "")),
+               Err(Error::InvalidEncoding));
+
+    // Unsafe or inconsistent values
+    for &(from, to) in &[("n = 613", "n = 611"),
+                         ("n = 613", "n = 251"),
+                         ("q = 2048", "q = 2000"),
+                         ("df1 = 55", "df1 = 0"),
+                         ("dg = 204", "dg = 400"),
+                         ("dm0 = 55", "dm0 = 300"),
+                         ("db = 128", "db = 96"),
+                         ("c = 11", "c = 9"),
+                         ("hash_seed = 1", "hash_seed = 2"),
+                         ("prod_flag = 0", "prod_flag = 1"),
+                         ("name = \"CUSTOM613\"", "name = \"CUSTOM613EP1\""),
+                         ("oid = [255, 0, 1]", "oid = [0, 3, 4]")] {
+        let config = CUSTOM_TOML.replace(from, to);
+        let expected = if to == "prod_flag = 1" {
+            // df2 and df3 are needed for product-form parameter sets
+            Error::InvalidEncoding
+        } else {
+            Error::InvalidParam
+        };
+        assert_eq!(EncParams::from_toml(&config), Err(expected), "{}", to);
+    }
+}
+
+#[test]
+fn it_register() {
+    let params = EncParams::from_json(r#"{"base": "EES613EP1", "name": "REG613", "df1": 60,
+                                          "oid": [255, 0, 3]}"#)
+        .unwrap();
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let spki = ntru::x509::public_to_spki(kp.get_public(), &params);
+    let versioned = kp.get_private().export_versioned(&params);
+
+    // Custom parameter sets are not known before registering them
+    assert_eq!(ntru::x509::public_from_spki(&spki).unwrap_err(), Error::UnknownParamSet);
+    assert!(kp.get_private().get_params().is_err());
+    assert_eq!(PrivateKey::import_versioned(&versioned).unwrap_err(), Error::InvalidKey);
+
+    let registered = encparams::register(params.clone()).unwrap();
+    assert!(registered == &params);
+    assert!(encparams::registered().contains(&registered));
+    assert!(std::ptr::eq(encparams::register(params.clone()).unwrap(), registered));
+
+    let (public, spki_params) = ntru::x509::public_from_spki(&spki).unwrap();
+    assert_eq!(&public, kp.get_public());
+    assert!(std::ptr::eq(spki_params, registered));
+    assert!(kp.get_private().get_params().unwrap() == params);
+    assert_eq!(&PrivateKey::import_versioned(&versioned).unwrap(), kp.get_private());
+
+    // The OID and the name cannot be taken by a different parameter set
+    let same_oid = EncParams::from_json(r#"{"base": "EES613EP1", "name": "OTHER613",
+                                            "oid": [255, 0, 3]}"#);
+    assert_eq!(same_oid, Err(Error::InvalidParam));
+    let same_name = EncParams::from_json(r#"{"base": "EES613EP1", "name": "REG613",
+                                             "oid": [255, 0, 4]}"#)
+        .unwrap();
+    assert_eq!(encparams::register(same_name), Err(Error::InvalidParam));
+    let builtin_name = EncParams::from_json(r#"{"base": "EES613EP1", "oid": [255, 0, 5]}"#)
+        .unwrap();
+    assert_eq!(encparams::register(builtin_name), Err(Error::InvalidParam));
+
+    assert!(encparams::register(EES613EP1.clone()).unwrap() == &EES613EP1);
+}
@@ -0,0 +1,55 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::context::NtruContext;
+use ntru::encparams::{EES401EP1, EES439EP1, EES443EP1, EncParams};
+use ntru::rand::{RNG_CTR_DRBG, RNG_DEFAULT};
+use ntru::types::Error;
+
+#[test]
+fn it_context() {
+    let ctx = NtruContext::with_default_rng(&EES443EP1).unwrap();
+    assert_eq!(ctx.get_params(), &EES443EP1);
+    let kp = ctx.keygen().unwrap();
+
+    let msg = b"Hello from Rust!";
+    let encrypted = ctx.encrypt(msg, kp.get_public()).unwrap();
+    assert_eq!(encrypted.len(), EES443EP1.enc_len() as usize);
+    assert_eq!(&ctx.decrypt(&encrypted, &kp).unwrap()[..], &msg[..]);
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &EES443EP1).unwrap()[..], &msg[..]);
+    assert!(format!("{:?}", ctx).starts_with("NtruContext"));
+}
+
+#[test]
+fn it_context_deterministic() {
+    let seed = b"context seed";
+    let ctx1 = NtruContext::new(&EES401EP1, ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap())
+        .unwrap();
+    let ctx2 = NtruContext::new(&EES401EP1, ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap())
+        .unwrap();
+    assert_eq!(ctx1.get_rand_ctx().get_seed(), &seed[..]);
+    assert_eq!(ctx1.keygen().unwrap(), ctx2.keygen().unwrap());
+}
+
+#[test]
+fn it_context_other_params() {
+    let ctx = NtruContext::with_default_rng(&EES443EP1).unwrap();
+    let other = NtruContext::with_default_rng(&EES439EP1).unwrap();
+    let kp = ctx.keygen().unwrap();
+    let other_kp = other.keygen().unwrap();
+
+    assert_eq!(ctx.encrypt(b"Hello", other_kp.get_public()), Err(Error::InvalidKey));
+    let encrypted = other.encrypt(b"Hello", other_kp.get_public()).unwrap();
+    assert_eq!(ctx.decrypt(&encrypted, &other_kp), Err(Error::InvalidKey));
+    assert!(ctx.decrypt(&encrypted, &kp).is_err());
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    assert_eq!(NtruContext::new(&EncParams::default(), rand_ctx).unwrap_err(),
+               Error::InvalidParam);
+}
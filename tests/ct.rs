@@ -0,0 +1,80 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::ct::{self, Choice};
+use ntru::types::{IntPoly, TernPoly, PrivPoly, Error};
+
+#[test]
+fn it_bytes_eq() {
+    assert!(bool::from(ct::bytes_eq(b"tag", b"tag")));
+    assert!(!bool::from(ct::bytes_eq(b"tag", b"tah")));
+    assert!(!bool::from(ct::bytes_eq(b"tag", b"ta")));
+    assert!(bool::from(ct::is_zero(&[0u8; 32])));
+    assert!(!bool::from(ct::is_zero(&[0, 0, 1])));
+}
+
+#[test]
+fn it_conditional_select() {
+    let mut dst = [1u8, 2, 3];
+    ct::conditional_copy(&mut dst, &[4, 5, 6], Choice::from(0)).unwrap();
+    assert_eq!(dst, [1, 2, 3]);
+    ct::conditional_copy(&mut dst, &[4, 5, 6], Choice::from(1)).unwrap();
+    assert_eq!(dst, [4, 5, 6]);
+    assert_eq!(ct::select_bytes(&[1, 2], &[3, 4], Choice::from(1)), Ok(vec![3, 4]));
+
+    let a = IntPoly::new(&[1, -1, 0, 5]);
+    let b = IntPoly::new(&[0, 2, -3, 7]);
+    assert_eq!(ct::select_poly(&a, &b, Choice::from(0)), Ok(a.clone()));
+    assert_eq!(ct::select_poly(&a, &b, Choice::from(1)), Ok(b.clone()));
+
+    let mut c = a.clone();
+    ct::conditional_copy_poly(&mut c, &b, Choice::from(1)).unwrap();
+    assert_eq!(c, b);
+
+    let a = TernPoly::new(11, &[0, 3], &[5]);
+    let b = TernPoly::new(11, &[1, 7], &[10]);
+    assert_eq!(ct::select_tern_poly(&a, &b, Choice::from(0)), Ok(a.clone()));
+    assert_eq!(ct::select_tern_poly(&a, &b, Choice::from(1)), Ok(b.clone()));
+    let (a, b) = (PrivPoly::new_with_tern_poly(a), PrivPoly::new_with_tern_poly(b));
+    assert_eq!(ct::select_priv_poly(&a, &b, Choice::from(1)), Ok(b));
+}
+
+#[test]
+fn it_select_mismatch() {
+    let mut dst = [1u8, 2, 3];
+    assert_eq!(ct::conditional_copy(&mut dst, &[4, 5], Choice::from(1)),
+               Err(Error::InvalidParam));
+    assert_eq!(dst, [1, 2, 3]);
+    assert_eq!(ct::select_bytes(&[1, 2], &[3], Choice::from(0)), Err(Error::InvalidParam));
+
+    let a = IntPoly::new(&[1, -1, 0, 5]);
+    assert_eq!(ct::select_poly(&a, &IntPoly::new(&[1]), Choice::from(0)),
+               Err(Error::InvalidDegree));
+
+    let a = TernPoly::new(11, &[0, 3], &[5]);
+    assert_eq!(ct::select_tern_poly(&a, &TernPoly::new(13, &[1, 7], &[10]), Choice::from(1)),
+               Err(Error::InvalidDegree));
+    assert_eq!(ct::select_tern_poly(&a, &TernPoly::new(11, &[1], &[10]), Choice::from(1)),
+               Err(Error::InvalidWeight));
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+    {
+        let prod = ntru::types::ProdPoly::new(11, a.clone(), a.clone(), a.clone());
+        assert_eq!(ct::select_priv_poly(&PrivPoly::new_with_tern_poly(a),
+                                        &PrivPoly::new_with_prod_poly(prod),
+                                        Choice::from(1)),
+                   Err(Error::InvalidWeight));
+    }
+}
+
+#[test]
+fn it_min_rep_weight() {
+    assert!(bool::from(ct::min_rep_weight(&[0, 1, 2, 0, 1, 2], 2)));
+    assert!(!bool::from(ct::min_rep_weight(&[0, 1, 2, 0, 1, 1], 2)));
+    assert!(bool::from(ct::min_rep_weight(&[1, 1, 1], 0)));
+}
@@ -0,0 +1,116 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "embedded-rng")]
+
+extern crate ntru;
+extern crate embedded_hal;
+extern crate rand_core;
+use std::num::NonZeroU32;
+use embedded_hal::blocking::rng::Read;
+use rand_core::{CryptoRng, RngCore, impls};
+use ntru::embedded_rng::{self, HalRng, RngCoreRng};
+use ntru::encparams::EES439EP1;
+use ntru::rand::RandomSource;
+use ntru::types::Error;
+
+/// A TRNG peripheral for testing, that generates a xorshift sequence
+struct Trng {
+    state: u64,
+    broken: bool,
+}
+
+impl Trng {
+    fn new(broken: bool) -> Trng {
+        Trng {
+            state: 0x1234_5678,
+            broken,
+        }
+    }
+}
+
+impl Read for Trng {
+    type Error = ();
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), ()> {
+        if self.broken {
+            return Err(());
+        }
+        for b in buffer.iter_mut() {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            *b = self.state as u8;
+        }
+        Ok(())
+    }
+}
+
+impl RngCore for Trng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap();
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.read(dest).map_err(|_| NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap().into())
+    }
+}
+
+impl CryptoRng for Trng {}
+
+#[test]
+fn it_hal_rng() {
+    let params = &EES439EP1;
+    let rand_ctx1 = embedded_rng::init_hal(Trng::new(false)).unwrap();
+    let rand_ctx2 = embedded_rng::init_hal(Trng::new(false)).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx1).unwrap();
+    assert_eq!(kp, ntru::generate_key_pair(params, &rand_ctx2).unwrap());
+
+    let encrypted = ntru::encrypt(b"trng", kp.get_public(), params, &rand_ctx1).unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, params).unwrap()[..], b"trng");
+
+    let rand_ctx = embedded_rng::init_hal(Trng::new(true)).unwrap();
+    assert_eq!(ntru::generate_key_pair(params, &rand_ctx), Err(Error::Prng));
+}
+
+#[test]
+fn it_rng_core_rng() {
+    let params = &EES439EP1;
+    let rand_ctx1 = embedded_rng::init_rng_core(Trng::new(false)).unwrap();
+    let rand_ctx2 = embedded_rng::init_hal(Trng::new(false)).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx1).unwrap();
+    assert_eq!(kp, ntru::generate_key_pair(params, &rand_ctx2).unwrap());
+
+    let rand_ctx = embedded_rng::init_rng_core(Trng::new(true)).unwrap();
+    assert_eq!(ntru::rand::generate(10, &rand_ctx), Err(Error::Prng));
+}
+
+#[test]
+fn it_embedded_sources() {
+    let mut expected = [0u8; 16];
+    Trng::new(false).read(&mut expected).unwrap();
+
+    let mut hal = HalRng::new(Trng::new(false));
+    let mut buf = [0u8; 16];
+    hal.fill(&mut buf).unwrap();
+    assert_eq!(buf, expected);
+    assert!(!hal.into_inner().broken);
+
+    let mut rng_core = RngCoreRng::new(Trng::new(false));
+    let mut buf = [0u8; 16];
+    rng_core.fill(&mut buf).unwrap();
+    assert_eq!(buf, expected);
+    assert_eq!(RngCoreRng::new(Trng::new(true)).fill(&mut buf), Err(Error::Prng));
+}
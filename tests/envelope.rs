@@ -0,0 +1,82 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "envelope")]
+
+extern crate ntru;
+
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES1087EP2};
+use ntru::types::Error;
+use ntru::envelope::{Envelope, MAGIC, VERSION};
+
+#[test]
+fn it_seal_open() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp1 = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let kp2 = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let other = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+
+    for msg in &[&b""[..], &b"Hello from Rust!"[..], &[42u8; 10000][..]] {
+        let envelope = Envelope::seal(msg, &[kp1.get_public(), kp2.get_public()], &EES401EP1,
+                                      &rand_ctx)
+            .unwrap();
+        assert!(envelope.get_params() == &EES401EP1);
+        assert_eq!(envelope.get_recipients(),
+                   vec![ntru::keyring::key_id(kp1.get_public(), &EES401EP1),
+                        ntru::keyring::key_id(kp2.get_public(), &EES401EP1)]);
+
+        let encoded = envelope.to_bytes();
+        assert_eq!(&encoded[..4], &MAGIC);
+        assert_eq!(encoded[4], VERSION);
+        let decoded = Envelope::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, envelope);
+
+        assert_eq!(&decoded.open(&kp1).unwrap()[..], *msg);
+        assert_eq!(&decoded.open(&kp2).unwrap()[..], *msg);
+        assert_eq!(decoded.open(&other).unwrap_err(), Error::UnknownKey);
+    }
+
+    let kp = ntru::generate_key_pair(&EES1087EP2, &rand_ctx).unwrap();
+    let envelope = Envelope::seal(b"Hello", &[kp.get_public()], &EES1087EP2, &rand_ctx).unwrap();
+    let decoded = Envelope::from_bytes(&envelope.to_bytes()).unwrap();
+    assert_eq!(&decoded.open(&kp).unwrap()[..], b"Hello");
+}
+
+#[test]
+fn it_envelope_invalid() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    assert_eq!(Envelope::seal(b"Hello", &[], &EES401EP1, &rand_ctx).unwrap_err(),
+               Error::InvalidParam);
+
+    let encoded = Envelope::seal(b"Hello", &[kp.get_public()], &EES401EP1, &rand_ctx)
+        .unwrap()
+        .to_bytes();
+    assert_eq!(Envelope::from_bytes(&encoded[..encoded.len() - 1]).unwrap_err(),
+               Error::InvalidEncoding);
+
+    let mut wrong_version = encoded.clone();
+    wrong_version[4] = VERSION + 1;
+    assert_eq!(Envelope::from_bytes(&wrong_version).unwrap_err(), Error::InvalidEncoding);
+
+    let mut wrong_oid = encoded.clone();
+    wrong_oid[5..8].copy_from_slice(&[0xFF, 0xFF, 0xFF]);
+    assert_eq!(Envelope::from_bytes(&wrong_oid).unwrap_err(), Error::UnknownParamSet);
+
+    let mut no_recipients = encoded.clone();
+    no_recipients[8..10].copy_from_slice(&[0, 0]);
+    assert_eq!(Envelope::from_bytes(&no_recipients).unwrap_err(), Error::InvalidEncoding);
+
+    // Modifying the KEM ciphertext, the wrapped key or the payload is detected
+    for &pos in &[20, encoded.len() - 40, encoded.len() - 1] {
+        let mut modified = encoded.clone();
+        modified[pos] ^= 1;
+        let envelope = Envelope::from_bytes(&modified).unwrap();
+        assert_eq!(envelope.open(&kp).unwrap_err(), Error::InvalidMac);
+    }
+}
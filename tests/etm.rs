@@ -0,0 +1,56 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "etm")]
+
+extern crate ntru;
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{DEFAULT_PARAMS_128_BITS, ALL_PARAM_SETS};
+use ntru::types::Error;
+
+#[test]
+fn it_etm() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let msg = b"Authenticated message";
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let encrypted = ntru::etm::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(encrypted.len(), ntru::etm::envelope_len(params, msg.len()));
+
+        let decrypted = ntru::etm::decrypt(&encrypted, &kp, params).unwrap();
+        assert_eq!(&msg[..], &decrypted[..]);
+    }
+
+    // The message is not limited to the maximum length of NTRU messages
+    let params = &DEFAULT_PARAMS_128_BITS;
+    let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+    for len in &[0, params.max_msg_len() as usize + 1, 10000] {
+        let long = vec![0x42u8; *len];
+        let encrypted = ntru::etm::encrypt(&long, kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(&ntru::etm::decrypt(&encrypted, &kp, params).unwrap()[..], &long[..]);
+    }
+}
+
+#[test]
+fn it_etm_tampered() {
+    let params = DEFAULT_PARAMS_128_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let encrypted = ntru::etm::encrypt(b"Hello", kp.get_public(), &params, &rand_ctx).unwrap();
+    let enc_len = params.enc_len() as usize;
+    let tag_start = encrypted.len() - ntru::etm::TAG_LEN;
+
+    for &i in &[0, enc_len, enc_len + 2, tag_start] {
+        let mut modified = encrypted.to_vec();
+        modified[i] ^= 1;
+        assert_eq!(ntru::etm::decrypt(&modified, &kp, &params), Err(Error::InvalidMac));
+    }
+
+    assert_eq!(ntru::etm::decrypt(&encrypted[1..], &kp, &params), Err(Error::InvalidMac));
+    assert_eq!(ntru::etm::decrypt(&encrypted[..enc_len + ntru::etm::TAG_LEN - 1], &kp, &params),
+               Err(Error::InvalidEncoding));
+}
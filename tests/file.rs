@@ -0,0 +1,132 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+
+use std::{env, fs};
+use std::io::ErrorKind;
+use ntru::rand::{self, RNG_DEFAULT};
+use ntru::encparams::{EES401EP1, EES439EP1};
+use ntru::file;
+
+#[test]
+fn it_stream() {
+    let rand_ctx = rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let max_msg_len = EES401EP1.max_msg_len() as usize;
+    let enc_len = EES401EP1.enc_len() as usize;
+
+    for &len in &[0, 1, max_msg_len - 1, max_msg_len, max_msg_len + 1, 3 * max_msg_len, 1000] {
+        let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let mut encrypted = Vec::new();
+        file::encrypt_stream(&mut &data[..],
+                             &mut encrypted,
+                             kp.get_public(),
+                             &EES401EP1,
+                             &rand_ctx)
+            .unwrap();
+        assert_eq!(encrypted.len(), file::HEADER_LEN + (len / max_msg_len + 1) * enc_len);
+        assert_eq!(&encrypted[..4], &file::MAGIC[..]);
+
+        let mut decrypted = Vec::new();
+        file::decrypt_stream(&mut &encrypted[..], &mut decrypted, &kp, &EES401EP1).unwrap();
+        assert_eq!(data, decrypted);
+
+        let truncated = &encrypted[..encrypted.len() - 1];
+        let err = file::decrypt_stream(&mut &truncated[..], &mut Vec::new(), &kp, &EES401EP1)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut trailing = encrypted.clone();
+        trailing.push(0);
+        let err = file::decrypt_stream(&mut &trailing[..], &mut Vec::new(), &kp, &EES401EP1)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    let mut encrypted = Vec::new();
+    file::encrypt_stream(&mut &b"Hello"[..],
+                         &mut encrypted,
+                         kp.get_public(),
+                         &EES401EP1,
+                         &rand_ctx)
+        .unwrap();
+    let err = file::decrypt_stream(&mut &encrypted[..], &mut Vec::new(), &kp, &EES439EP1)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn it_path() {
+    let rand_ctx = rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+
+    let dir = env::temp_dir();
+    let src = dir.join(format!("ntru-file-test-{}.txt", std::process::id()));
+    let enc = src.with_extension("ntru");
+    let dec = src.with_extension("dec");
+
+    let data: Vec<u8> = (0..10000u32).map(|i| (i * 7) as u8).collect();
+    fs::write(&src, &data).unwrap();
+    file::encrypt_path(&src, &enc, kp.get_public(), &EES401EP1).unwrap();
+    file::decrypt_path(&enc, &dec, &kp, &EES401EP1).unwrap();
+    assert_eq!(fs::read(&dec).unwrap(), data);
+
+    for path in &[src, enc, dec] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn it_writer_reader() {
+    use std::io::{Read, Write};
+
+    let rand_ctx = rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES439EP1, &rand_ctx).unwrap();
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 13) as u8).collect();
+
+    let mut writer = file::EncryptingWriter::new(Vec::new(),
+                                                 kp.get_public(),
+                                                 &EES439EP1,
+                                                 &rand_ctx)
+        .unwrap();
+    for part in data.chunks(37) {
+        writer.write_all(part).unwrap();
+    }
+    let encrypted = writer.finish().unwrap();
+
+    let mut streamed = Vec::new();
+    file::decrypt_stream(&mut &encrypted[..], &mut streamed, &kp, &EES439EP1).unwrap();
+    assert_eq!(streamed, data);
+
+    let mut reader = file::DecryptingReader::new(&encrypted[..], &kp, &EES439EP1).unwrap();
+    let mut decrypted = Vec::new();
+    let mut buf = [0u8; 100];
+    loop {
+        let len = reader.read(&mut buf).unwrap();
+        if len == 0 {
+            break;
+        }
+        decrypted.extend_from_slice(&buf[..len]);
+    }
+    assert_eq!(decrypted, data);
+
+    // A writer dropped without finishing leaves the stream truncated
+    let mut unfinished = Vec::new();
+    {
+        let mut writer = file::EncryptingWriter::new(&mut unfinished,
+                                                     kp.get_public(),
+                                                     &EES439EP1,
+                                                     &rand_ctx)
+            .unwrap();
+        writer.write_all(&data).unwrap();
+    }
+    let mut reader = file::DecryptingReader::new(&unfinished[..], &kp, &EES439EP1).unwrap();
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
@@ -0,0 +1,112 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::encparams::{EncParams, EES401EP1, EES439EP1, EES1087EP2};
+use ntru::fixed;
+use ntru::rand::{RNG_CTR_DRBG, RNG_DEFAULT};
+use ntru::types::{Error, KeyPair, PrivateKey, PublicKey};
+
+const PARAMS: EncParams = EES439EP1;
+
+#[test]
+fn it_fixed() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let mut public = [0u8; PARAMS.public_len() as usize];
+    let private = fixed::generate_key_pair(&PARAMS, &rand_ctx, &mut public).unwrap();
+
+    let msg = b"Hello from Rust!";
+    let mut encrypted = [0u8; PARAMS.enc_len() as usize];
+    assert_eq!(fixed::encrypt(msg, &public, &PARAMS, &rand_ctx, &mut encrypted).unwrap(),
+               encrypted.len());
+
+    let mut decrypted = [0u8; PARAMS.max_msg_len() as usize];
+    let len = fixed::decrypt(&encrypted, &private, &public, &PARAMS, &mut decrypted).unwrap();
+    assert_eq!(&decrypted[..len], &msg[..]);
+
+    let kp = KeyPair::new(private, PublicKey::import(&public).unwrap());
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &PARAMS).unwrap()[..], &msg[..]);
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &PARAMS, &rand_ctx).unwrap();
+    let len = fixed::decrypt(&encrypted, kp.get_private(), &public, &PARAMS, &mut decrypted)
+        .unwrap();
+    assert_eq!(&decrypted[..len], &msg[..]);
+}
+
+#[test]
+fn it_fixed_deterministic() {
+    let rand_ctx = ntru::rand::init_det(&RNG_CTR_DRBG, b"fixed seed").unwrap();
+    let kp = ntru::generate_key_pair(&EES1087EP2, &rand_ctx).unwrap();
+
+    let rand_ctx = ntru::rand::init_det(&RNG_CTR_DRBG, b"fixed seed").unwrap();
+    let mut public = [0u8; EES1087EP2.public_len() as usize];
+    let private = fixed::generate_key_pair(&EES1087EP2, &rand_ctx, &mut public).unwrap();
+    assert_eq!(&private, kp.get_private());
+    assert_eq!(&public[..], &kp.get_public().export(&EES1087EP2)[..]);
+}
+
+#[test]
+fn it_fixed_errors() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let mut short = [0u8; 10];
+    assert_eq!(fixed::generate_key_pair(&PARAMS, &rand_ctx, &mut short),
+               Err(Error::InvalidParam));
+
+    let mut public = [0u8; PARAMS.public_len() as usize];
+    let private = fixed::generate_key_pair(&PARAMS, &rand_ctx, &mut public).unwrap();
+    let mut encrypted = [0u8; PARAMS.enc_len() as usize];
+    assert_eq!(fixed::encrypt(b"msg", &public, &PARAMS, &rand_ctx, &mut short),
+               Err(Error::InvalidParam));
+    assert_eq!(fixed::encrypt(b"msg", &public, &EES401EP1, &rand_ctx, &mut encrypted),
+               Err(Error::InvalidKey));
+    let long = [0u8; PARAMS.max_msg_len() as usize + 1];
+    assert_eq!(fixed::encrypt(&long, &public, &PARAMS, &rand_ctx, &mut encrypted),
+               Err(Error::MessageTooLong));
+
+    let _ = fixed::encrypt(b"msg", &public, &PARAMS, &rand_ctx, &mut encrypted).unwrap();
+    let mut decrypted = [0u8; PARAMS.max_msg_len() as usize];
+    assert_eq!(fixed::decrypt(&encrypted, &private, &public, &PARAMS, &mut short),
+               Err(Error::InvalidParam));
+    assert_eq!(fixed::decrypt(&encrypted[1..], &private, &public, &PARAMS, &mut decrypted),
+               Err(Error::InvalidCiphertextLength {
+                   expected: encrypted.len(),
+                   actual: encrypted.len() - 1,
+               }));
+    assert_eq!(fixed::decrypt(&encrypted, &PrivateKey::default(), &public, &PARAMS,
+                              &mut decrypted),
+               Err(Error::InvalidKey));
+
+    let mut other = [0u8; PARAMS.public_len() as usize];
+    let _ = fixed::generate_key_pair(&PARAMS, &rand_ctx, &mut other).unwrap();
+    assert_eq!(fixed::decrypt(&encrypted, &private, &other, &PARAMS, &mut decrypted),
+               Err(Error::DecryptionFailed));
+    assert!(decrypted.iter().all(|&b| b == 0));
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn it_fixed_heapless() {
+    const PUBLIC_LEN: usize = PARAMS.public_len() as usize;
+    const ENC_LEN: usize = PARAMS.enc_len() as usize;
+    const MSG_LEN: usize = PARAMS.max_msg_len() as usize;
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let (private, public) = fixed::generate_key_pair_vec::<PUBLIC_LEN>(&PARAMS, &rand_ctx)
+        .unwrap();
+    let msg = b"Hello from Rust!";
+    let encrypted = fixed::encrypt_vec::<ENC_LEN>(msg, &public, &PARAMS, &rand_ctx).unwrap();
+    let decrypted = fixed::decrypt_vec::<MSG_LEN>(&encrypted, &private, &public, &PARAMS)
+        .unwrap();
+    assert_eq!(&decrypted[..], &msg[..]);
+
+    assert_eq!(fixed::generate_key_pair_vec::<10>(&PARAMS, &rand_ctx).map(|_| ()),
+               Err(Error::InvalidParam));
+    assert_eq!(fixed::encrypt_vec::<10>(msg, &public, &PARAMS, &rand_ctx),
+               Err(Error::InvalidParam));
+    assert_eq!(fixed::decrypt_vec::<10>(&encrypted, &private, &public, &PARAMS),
+               Err(Error::InvalidParam));
+}
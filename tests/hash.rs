@@ -0,0 +1,38 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "extra-hashes")]
+
+extern crate ntru;
+use ntru::encparams::{HashAlgorithm, EES401EP1, EES401EP2};
+use ntru::low_level::mgf;
+use ntru::rand::RNG_DEFAULT;
+
+#[test]
+fn it_extra_hashes() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let hashes = [(HashAlgorithm::Sha3_256, "SHA3-256"),
+                  (HashAlgorithm::Blake2b256, "BLAKE2b-256")];
+    for (i, &(hash, hash_name)) in hashes.iter().enumerate() {
+        for (j, base) in [EES401EP1, EES401EP2].iter().enumerate() {
+            let name = format!("H{}{}", i, j);
+            let params = base.with_hash_algorithm(hash, &name, [255, 2, (2 * i + j) as u8])
+                .unwrap();
+            assert_eq!(params.get_hash_algorithm(), hash);
+            assert_eq!(params.get_hlen(), 32);
+            assert!(params.to_string().contains(hash_name));
+            assert!(mgf(b"seed", &params).unwrap() != mgf(b"seed", base).unwrap());
+
+            let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+            let msg = b"Hello from Rust!";
+            let encrypted = ntru::encrypt(msg, kp.get_public(), &params, &rand_ctx).unwrap();
+            let decrypted = ntru::decrypt(&encrypted, &kp, &params).unwrap();
+            assert_eq!(&msg[..], &decrypted[..]);
+        }
+    }
+    assert_eq!(ntru::self_test(), Ok(()));
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "hybrid-kem")]
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES443EP1, EES1087EP2};
+use ntru::hybrid_kem::{HybridPublicKey, X25519_LEN, SHARED_KEY_LEN};
+use ntru::types::Error;
+
+#[test]
+fn it_hybrid_kem() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES443EP1, EES1087EP2] {
+        let kp = ntru::hybrid_kem::generate_key_pair(params, &rand_ctx).unwrap();
+        let (ciphertext, shared1) =
+            ntru::hybrid_kem::encapsulate(&kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(ciphertext.len(), X25519_LEN + params.enc_len() as usize);
+        assert_eq!(shared1.len(), SHARED_KEY_LEN);
+
+        let shared2 = ntru::hybrid_kem::decapsulate(&ciphertext, &kp, params).unwrap();
+        assert_eq!(shared1, shared2);
+    }
+}
+
+#[test]
+fn it_hybrid_kem_tampered() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::hybrid_kem::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+    let (ciphertext, shared) =
+        ntru::hybrid_kem::encapsulate(&kp.get_public(), &EES443EP1, &rand_ctx).unwrap();
+
+    // A different ephemeral X25519 key gives a different shared key
+    let mut modified = ciphertext.to_vec();
+    modified[0] ^= 2;
+    match ntru::hybrid_kem::decapsulate(&modified, &kp, &EES443EP1) {
+        Ok(other) => assert!(other != shared),
+        Err(e) => assert_eq!(e, Error::InvalidKey),
+    }
+
+    assert_eq!(ntru::hybrid_kem::decapsulate(&ciphertext[1..], &kp, &EES443EP1),
+               Err(Error::InvalidCiphertextLength {
+                   expected: ciphertext.len(),
+                   actual: ciphertext.len() - 1,
+               }));
+}
+
+#[test]
+fn it_hybrid_public_key_export() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::hybrid_kem::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+    let public = kp.get_public();
+
+    let exported = public.export(&EES443EP1);
+    assert_eq!(exported.len(), X25519_LEN + EES443EP1.public_len() as usize);
+    assert_eq!(HybridPublicKey::import(&exported, &EES443EP1).unwrap(), public);
+    assert_eq!(HybridPublicKey::import(&exported[1..], &EES443EP1), Err(Error::InvalidKey));
+}
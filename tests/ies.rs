@@ -0,0 +1,75 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "ies")]
+
+extern crate ntru;
+
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES401EP2, EES1087EP2};
+use ntru::ies::TAG_LEN;
+use ntru::types::Error;
+
+#[test]
+fn it_ies() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let long_msg = vec![0x2a; 5000];
+    for params in &[EES401EP1, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        for msg in &[&b""[..], &b"Hello from Rust!"[..], &long_msg[..]] {
+            let (package, ephemeral) = ntru::ies::encrypt(msg, kp.get_public(), params, &rand_ctx)
+                .unwrap();
+            assert_eq!(package.len(),
+                       (params.public_len() + params.enc_len()) as usize + msg.len() + TAG_LEN);
+
+            let (decrypted, sender) = ntru::ies::decrypt(&package, &kp, params).unwrap();
+            assert_eq!(&msg[..], &decrypted[..]);
+            assert_eq!(&sender, ephemeral.get_public());
+
+            // The recipient answers with the ephemeral public key of the sender
+            let (answer, _) = ntru::ies::encrypt(b"Answer", &sender, params, &rand_ctx).unwrap();
+            let (decrypted, _) = ntru::ies::decrypt(&answer, &ephemeral, params).unwrap();
+            assert_eq!(&decrypted[..], b"Answer");
+        }
+    }
+}
+
+#[test]
+fn it_ies_fresh() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP2, &rand_ctx).unwrap();
+    let (package1, ephemeral1) = ntru::ies::encrypt(b"Hello", kp.get_public(), &EES401EP2,
+                                                    &rand_ctx).unwrap();
+    let (package2, ephemeral2) = ntru::ies::encrypt(b"Hello", kp.get_public(), &EES401EP2,
+                                                    &rand_ctx).unwrap();
+    assert!(package1 != package2);
+    assert!(ephemeral1.get_public() != ephemeral2.get_public());
+}
+
+#[test]
+fn it_ies_tampered() {
+    let params = EES401EP1;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let other = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let (package, _) = ntru::ies::encrypt(b"Hello", kp.get_public(), &params, &rand_ctx).unwrap();
+    let public_len = params.public_len() as usize;
+    let header_len = public_len + params.enc_len() as usize;
+
+    for &i in &[public_len - 1, public_len, header_len, package.len() - 1] {
+        let mut modified = package.to_vec();
+        modified[i] ^= 1;
+        assert_eq!(ntru::ies::decrypt(&modified, &kp, &params).unwrap_err(), Error::InvalidMac);
+    }
+    assert_eq!(ntru::ies::decrypt(&package, &other, &params).unwrap_err(), Error::InvalidMac);
+
+    let mut modified = package.to_vec();
+    modified[0] ^= 1;
+    assert_eq!(ntru::ies::decrypt(&modified, &kp, &params).unwrap_err(), Error::InvalidEncoding);
+    assert_eq!(ntru::ies::decrypt(&package[..header_len + TAG_LEN - 1], &kp, &params).unwrap_err(),
+               Error::InvalidEncoding);
+}
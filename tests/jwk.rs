@@ -0,0 +1,67 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "jose")]
+
+extern crate ntru;
+extern crate serde_json;
+
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES1087EP2};
+use ntru::types::Error;
+use ntru::jwk;
+use serde_json::Value;
+
+#[test]
+fn it_jwk() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+        let public_jwk = jwk::public_to_jwk(kp.get_public(), params);
+        assert!(public_jwk.get("d").is_none());
+        let (public, public_params) = jwk::public_from_jwk(&public_jwk).unwrap();
+        assert_eq!(&public, kp.get_public());
+        assert!(public_params == params);
+        assert_eq!(jwk::key_pair_from_jwk(&public_jwk).unwrap_err(), Error::InvalidEncoding);
+
+        let kp_jwk = jwk::key_pair_to_jwk(&kp, params);
+        let json = serde_json::to_string(&kp_jwk).unwrap();
+        let parsed: Value = serde_json::from_str(&json).unwrap();
+        let (kp2, kp_params) = jwk::key_pair_from_jwk(&parsed).unwrap();
+        assert_eq!(kp2, kp);
+        assert!(kp_params == params);
+    }
+}
+
+#[test]
+fn it_jwk_invalid() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let valid = jwk::key_pair_to_jwk(&kp, &EES401EP1);
+
+    let mut wrong_kty = valid.clone();
+    wrong_kty["kty"] = "OKP".into();
+    assert_eq!(jwk::public_from_jwk(&wrong_kty).unwrap_err(), Error::InvalidEncoding);
+
+    let mut wrong_ps = valid.clone();
+    wrong_ps["ps"] = "EES1EP1".into();
+    assert_eq!(jwk::public_from_jwk(&wrong_ps).unwrap_err(), Error::UnknownParamSet);
+
+    let mut other_ps = valid.clone();
+    other_ps["ps"] = "EES1087EP2".into();
+    assert_eq!(jwk::public_from_jwk(&other_ps).unwrap_err(), Error::InvalidKey);
+
+    let mut short_x = valid.clone();
+    let x = valid["x"].as_str().unwrap();
+    short_x["x"] = x[..x.len() - 4].into();
+    assert_eq!(jwk::public_from_jwk(&short_x).unwrap_err(), Error::InvalidKey);
+
+    let mut bad_d = valid.clone();
+    bad_d["d"] = "not base64!".into();
+    assert_eq!(jwk::key_pair_from_jwk(&bad_d).unwrap_err(), Error::InvalidEncoding);
+}
@@ -0,0 +1,107 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+#![cfg(feature = "kat")]
+
+extern crate ntru;
+use ntru::encparams::{EES401EP1, EES401EP2, EES1087EP2};
+use ntru::kat::{NistDrbg, NIST_ENTROPY_INPUT};
+
+fn field<'a>(file: &'a str, record: usize, name: &str) -> &'a str {
+    let prefix = format!("{} =", name);
+    file.lines()
+        .filter(|line| line.starts_with(&prefix))
+        .nth(record)
+        .unwrap()[prefix.len()..]
+        .trim()
+}
+
+#[test]
+fn it_nist_drbg() {
+    // The seed of the first record of every KAT file of the NIST submissions
+    let mut seed = [0u8; 48];
+    NistDrbg::new(&NIST_ENTROPY_INPUT).randombytes(&mut seed);
+    let hex: String = seed.iter().map(|b| format!("{:02X}", b)).collect();
+    assert_eq!(hex,
+               "061550234D158C5EC95595FE04EF7A25767F2E24CC2BC479D09D86DC9ABCFDE7056A8C266F9EF97E\
+                D08541DBD2E1FFA1");
+
+    // The output does not depend on how the requests are split in blocks
+    let mut drbg1 = NistDrbg::new(&NIST_ENTROPY_INPUT);
+    let mut drbg2 = NistDrbg::new(&NIST_ENTROPY_INPUT);
+    let mut a = [0u8; 40];
+    let mut b = [0u8; 40];
+    drbg1.randombytes(&mut a);
+    drbg2.randombytes(&mut b[..33]);
+    assert_eq!(a[..33], b[..33]);
+    drbg1.randombytes(&mut a);
+    drbg2.randombytes(&mut b);
+    assert_eq!(a[..], b[..]);
+}
+
+#[test]
+fn it_reference_files() {
+    let files = [(&EES401EP1,
+                  include_str!("data/EES401EP1.req"),
+                  include_str!("data/EES401EP1.rsp")),
+                 (&EES401EP2,
+                  include_str!("data/EES401EP2.req"),
+                  include_str!("data/EES401EP2.rsp"))];
+    for &(params, req_ref, rsp_ref) in &files {
+        let mut req = Vec::new();
+        let mut rsp = Vec::new();
+        ntru::kat::generate(params, &NIST_ENTROPY_INPUT, 10, &mut req, &mut rsp).unwrap();
+        assert_eq!(String::from_utf8(req).unwrap(), req_ref);
+        assert_eq!(String::from_utf8(rsp).unwrap(), rsp_ref);
+    }
+}
+
+#[test]
+fn it_generate() {
+    for params in &[EES401EP1, EES1087EP2] {
+        let mut req1 = Vec::new();
+        let mut rsp1 = Vec::new();
+        ntru::kat::generate(params, &NIST_ENTROPY_INPUT, 3, &mut req1, &mut rsp1).unwrap();
+        let mut req2 = Vec::new();
+        let mut rsp2 = Vec::new();
+        ntru::kat::generate(params, &NIST_ENTROPY_INPUT, 3, &mut req2, &mut rsp2).unwrap();
+        assert_eq!(req1, req2);
+        assert_eq!(rsp1, rsp2);
+
+        let req = String::from_utf8(req1).unwrap();
+        let rsp = String::from_utf8(rsp1).unwrap();
+        let header = format!("# {}\n\n", params.get_name().trim_end_matches('\0'));
+        assert!(req.starts_with(&header));
+        assert!(rsp.starts_with(&header));
+
+        for i in 0..3 {
+            assert_eq!(field(&req, i, "count"), i.to_string());
+            assert_eq!(field(&req, i, "seed"), field(&rsp, i, "seed"));
+            assert_eq!(field(&req, i, "msg"), field(&rsp, i, "msg"));
+            assert_eq!(field(&req, i, "pk"), "");
+            assert_eq!(field(&rsp, i, "seed").len(), 2 * ntru::kat::SEED_LEN);
+
+            let mlen: usize = field(&rsp, i, "mlen").parse().unwrap();
+            assert_eq!(mlen, std::cmp::min(16 * (i + 1), params.max_msg_len() as usize));
+            assert_eq!(field(&rsp, i, "msg").len(), 2 * mlen);
+            assert_eq!(field(&rsp, i, "pk").len(), 2 * params.public_len() as usize);
+            assert_eq!(field(&rsp, i, "sk").len(), 2 * params.private_len() as usize);
+            assert_eq!(field(&rsp, i, "clen"), params.enc_len().to_string());
+            assert_eq!(field(&rsp, i, "c").len(), 2 * params.enc_len() as usize);
+        }
+        assert!(field(&rsp, 0, "seed") != field(&rsp, 1, "seed"));
+    }
+
+    let mut req = Vec::new();
+    let mut rsp = Vec::new();
+    ntru::kat::generate(&EES401EP1, &[0x2a; 48], 1, &mut req, &mut rsp).unwrap();
+    let mut rsp_orig = Vec::new();
+    ntru::kat::generate(&EES401EP1, &NIST_ENTROPY_INPUT, 1, &mut Vec::new(), &mut rsp_orig)
+        .unwrap();
+    assert!(rsp != rsp_orig);
+}
@@ -7,13 +7,15 @@
     unused_qualifications, unused_results, variant_size_differences)]
 
 extern crate ntru;
-use ntru::encparams::{EES439EP1, EES1087EP2, ALL_PARAM_SETS};
+use std::convert::TryFrom;
+use ntru::encparams::*;
 use ntru::rand::RNG_DEFAULT;
-use ntru::types::{PublicKey, PrivateKey, PrivPoly, IntPoly};
+use ntru::types::{KeyPair, PublicKey, PrivateKey, PrivPoly, TernPoly, IntPoly, Error, KeyFormat,
+                  PUBLIC_KEY_MAGIC, PRIVATE_KEY_MAGIC, KEY_FORMAT_VERSION, KEY_HEADER_LEN};
 
 fn ntru_priv_to_int(a: &PrivPoly, modulus: u16) -> IntPoly {
     if a.is_product() {
-        a.get_poly_prod().to_int_poly(modulus)
+        a.get_poly_prod().to_int_poly(modulus).unwrap()
     } else {
         a.get_poly_tern().to_int_poly()
     }
@@ -30,12 +32,17 @@ fn it_export_import() {
 
         // Test public key
         let pub_arr = kp.get_public().export(params);
-        let imp_pub = PublicKey::import(&pub_arr);
+        let imp_pub = PublicKey::import(&pub_arr).unwrap();
         assert_eq!(kp.get_public().get_h(), imp_pub.get_h());
 
         // Test private key
         let priv_arr = kp.get_private().export(params);
-        let imp_priv = PrivateKey::import(&priv_arr);
+        let imp_priv = PrivateKey::import(&priv_arr).unwrap();
+        assert_eq!(PrivateKey::import(&priv_arr[..priv_arr.len() - 1]).err(),
+                   Some(Error::InvalidKey));
+        let mut bad_weight = priv_arr.to_vec();
+        bad_weight[6] = 0xFF;
+        assert_eq!(PrivateKey::import(&bad_weight).err(), Some(Error::InvalidKey));
 
         let t_int1 = ntru_priv_to_int(imp_priv.get_t(), params.get_q());
         let t_int2 = ntru_priv_to_int(kp.get_private().get_t(), params.get_q());
@@ -44,6 +51,187 @@ fn it_export_import() {
     }
 }
 
+#[test]
+fn it_text_encoding() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let public = kp.get_public();
+        let exported = public.export(params);
+
+        let hex = public.to_hex();
+        assert_eq!(hex.len(), 2 * exported.len());
+        assert_eq!(format!("{}", public), hex);
+        assert_eq!(&PublicKey::from_hex(&hex).unwrap(), public);
+        assert_eq!(&hex.parse::<PublicKey>().unwrap(), public);
+        assert_eq!(&PublicKey::from_hex(&hex.to_uppercase()).unwrap(), public);
+
+        let base64 = public.to_base64();
+        assert_eq!(base64.len(), exported.len().div_ceil(3) * 4);
+        assert_eq!(&PublicKey::from_base64(&base64).unwrap(), public);
+
+        assert_eq!(PublicKey::from_hex(&hex[2..]), Err(Error::InvalidKey));
+        assert_eq!(PublicKey::from_hex(&hex[1..]), Err(Error::InvalidEncoding));
+        assert_eq!(PublicKey::from_hex(&format!("{}zz", hex)), Err(Error::InvalidEncoding));
+        assert_eq!(PublicKey::from_base64(&base64[4..]), Err(Error::InvalidKey));
+        assert_eq!(PublicKey::from_base64(&base64[1..]), Err(Error::InvalidEncoding));
+    }
+    assert_eq!(PublicKey::from_hex(""), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_checksum_encoding() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let public = kp.get_public();
+
+    let bech32 = public.to_bech32();
+    assert!(bech32.starts_with("ntrupk1"));
+    assert_eq!(&PublicKey::from_bech32(&bech32).unwrap(), public);
+    assert_eq!(&PublicKey::from_bech32(&bech32.to_uppercase()).unwrap(), public);
+
+    let base58 = public.to_base58();
+    assert_eq!(&PublicKey::from_base58(&base58).unwrap(), public);
+
+    // A single mistyped character is detected
+    for encoded in &[bech32, base58] {
+        let mut typo = encoded.clone().into_bytes();
+        let i = typo.len() / 2;
+        typo[i] = if typo[i] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert_eq!(PublicKey::from_bech32(&typo), Err(Error::InvalidEncoding));
+        assert_eq!(PublicKey::from_base58(&typo), Err(Error::InvalidEncoding));
+    }
+    assert_eq!(PublicKey::from_bech32(&public.to_bech32().replacen("ntrupk", "ntrusk", 1)),
+               Err(Error::InvalidEncoding));
+}
+
+#[test]
+fn it_import_any() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES401EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let public = kp.get_public();
+        let private = kp.get_private();
+
+        let public_encodings = [(public.export(params).into_vec(), KeyFormat::Raw),
+                                (ntru::x509::public_to_spki(public, params), KeyFormat::Der),
+                                (ntru::x509::public_to_pem(public, params).into_bytes(),
+                                 KeyFormat::Pem)];
+        for &(ref encoded, format) in &public_encodings {
+            assert_eq!(KeyFormat::detect(encoded), format);
+            assert_eq!(PublicKey::import_any(encoded).unwrap(), (public.clone(), format));
+        }
+
+        let private_encodings = [(private.export(params).into_vec(), KeyFormat::Raw),
+                                 (ntru::x509::private_to_pkcs8(private, params), KeyFormat::Der),
+                                 (ntru::x509::private_to_pem(private, params).into_bytes(),
+                                  KeyFormat::Pem)];
+        for &(ref encoded, format) in &private_encodings {
+            assert_eq!(PrivateKey::import_any(encoded).unwrap(), (private.clone(), format));
+        }
+
+        // Public keys are not taken for private keys, and the other way around
+        assert!(PrivateKey::import_any(&public_encodings[1].0).is_err());
+        assert!(PrivateKey::import_any(&public_encodings[2].0).is_err());
+        assert!(PublicKey::import_any(&private_encodings[1].0).is_err());
+        assert!(PublicKey::import_any(&private_encodings[2].0).is_err());
+    }
+
+    assert_eq!(KeyFormat::detect(b"  \n-----BEGIN PUBLIC KEY-----"), KeyFormat::Pem);
+    assert_eq!(PublicKey::import_any(&[]), Err(Error::InvalidKey));
+    assert_eq!(PrivateKey::import_any(&[0, 1, 2, 3, 4]), Err(Error::InvalidKey));
+    assert_eq!(PublicKey::import_any(b"-----BEGIN PUBLIC KEY-----\nAAAA\n"),
+               Err(Error::InvalidEncoding));
+}
+
+#[test]
+fn it_versioned_export() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES401EP2, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let public = kp.get_public();
+        let private = kp.get_private();
+
+        let public_arr = public.export_versioned(params);
+        assert_eq!(&public_arr[..4], &PUBLIC_KEY_MAGIC);
+        assert_eq!(public_arr[4], KEY_FORMAT_VERSION);
+        assert_eq!(&public_arr[KEY_HEADER_LEN..], &public.export(params)[..]);
+        assert_eq!(&PublicKey::import_versioned(&public_arr).unwrap(), public);
+        assert_eq!(PublicKey::import_any(&public_arr).unwrap(),
+                   (public.clone(), KeyFormat::Versioned));
+
+        let private_arr = private.export_versioned(params);
+        assert_eq!(&private_arr[..4], &PRIVATE_KEY_MAGIC);
+        assert_eq!(&PrivateKey::import_versioned(&private_arr).unwrap(), private);
+        assert_eq!(PrivateKey::import_any(&private_arr).unwrap(),
+                   (private.clone(), KeyFormat::Versioned));
+
+        // Legacy keys, without the header
+        assert_eq!(&PublicKey::import_versioned(&public.export(params)).unwrap(), public);
+        assert_eq!(&PrivateKey::import_versioned(&private.export(params)).unwrap(), private);
+
+        // Public and private keys are not mixed up
+        assert_eq!(PublicKey::import_versioned(&private_arr), Err(Error::InvalidKey));
+        assert_eq!(PrivateKey::import_versioned(&public_arr), Err(Error::InvalidKey));
+
+        let mut future = public_arr.to_vec();
+        future[4] = KEY_FORMAT_VERSION + 1;
+        assert_eq!(PublicKey::import_versioned(&future), Err(Error::InvalidEncoding));
+    }
+    assert_eq!(PublicKey::import_versioned(&PUBLIC_KEY_MAGIC), Err(Error::InvalidEncoding));
+    assert_eq!(PrivateKey::import_versioned(&[0x42; 100]), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_export_array() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let public: [u8; EES401EP1_PUBLIC_LEN] = kp.get_public().export_array(&EES401EP1).unwrap();
+    assert_eq!(&public[..], &kp.get_public().export(&EES401EP1)[..]);
+    let private: [u8; EES401EP1_PRIVATE_LEN] = kp.get_private()
+        .export_array(&EES401EP1)
+        .unwrap();
+    assert_eq!(&private[..], &kp.get_private().export(&EES401EP1)[..]);
+
+    assert_eq!(kp.get_public().export_array::<EES401EP2_PUBLIC_LEN>(&EES401EP2).unwrap().len(),
+               EES401EP2.public_len() as usize);
+    assert_eq!(kp.get_public().export_array::<EES401EP1_PRIVATE_LEN>(&EES401EP1),
+               Err(Error::InvalidParam));
+    assert_eq!(kp.get_private().export_array::<EES401EP2_PRIVATE_LEN>(&EES401EP1),
+               Err(Error::InvalidParam));
+
+    // The keys of other parameter sets would not fit in the array
+    let kp_1087 = ntru::generate_key_pair(&EES1087EP2, &rand_ctx).unwrap();
+    assert_eq!(kp_1087.get_private().export_array::<EES401EP1_PRIVATE_LEN>(&EES401EP1),
+               Err(Error::InvalidKey));
+    assert_eq!(kp_1087.get_private().export(&EES401EP1).len(),
+               EES401EP1.private_len() as usize);
+
+    let lengths = [(EES401EP1_PUBLIC_LEN, EES401EP1_PRIVATE_LEN),
+                   (EES449EP1_PUBLIC_LEN, EES449EP1_PRIVATE_LEN),
+                   (EES677EP1_PUBLIC_LEN, EES677EP1_PRIVATE_LEN),
+                   (EES1087EP2_PUBLIC_LEN, EES1087EP2_PRIVATE_LEN),
+                   (EES541EP1_PUBLIC_LEN, EES541EP1_PRIVATE_LEN),
+                   (EES613EP1_PUBLIC_LEN, EES613EP1_PRIVATE_LEN),
+                   (EES887EP1_PUBLIC_LEN, EES887EP1_PRIVATE_LEN),
+                   (EES1171EP1_PUBLIC_LEN, EES1171EP1_PRIVATE_LEN),
+                   (EES659EP1_PUBLIC_LEN, EES659EP1_PRIVATE_LEN),
+                   (EES761EP1_PUBLIC_LEN, EES761EP1_PRIVATE_LEN),
+                   (EES1087EP1_PUBLIC_LEN, EES1087EP1_PRIVATE_LEN),
+                   (EES1499EP1_PUBLIC_LEN, EES1499EP1_PRIVATE_LEN),
+                   (EES401EP2_PUBLIC_LEN, EES401EP2_PRIVATE_LEN),
+                   (EES439EP1_PUBLIC_LEN, EES439EP1_PRIVATE_LEN),
+                   (EES443EP1_PUBLIC_LEN, EES443EP1_PRIVATE_LEN),
+                   (EES593EP1_PUBLIC_LEN, EES593EP1_PRIVATE_LEN),
+                   (EES587EP1_PUBLIC_LEN, EES587EP1_PRIVATE_LEN),
+                   (EES743EP1_PUBLIC_LEN, EES743EP1_PRIVATE_LEN)];
+    for (params, &(public_len, private_len)) in ALL_PARAM_SETS.iter().zip(lengths.iter()) {
+        assert_eq!(public_len, params.public_len() as usize);
+        assert_eq!(private_len, params.private_len() as usize);
+    }
+}
+
 #[test]
 fn it_params_from_key() {
     let param_arr = ALL_PARAM_SETS;
@@ -56,6 +244,18 @@ fn it_params_from_key() {
 
         let params2 = kp.get_private().get_params().unwrap();
         assert_eq!(params, &params2);
+        assert_eq!(params2.get_hash_algorithm(), params.get_hash_algorithm());
+
+        // Only EES401EP1 and EES401EP2, and EES1087EP1 and EES1087EP2 have the same N and q
+        let candidates = kp.get_public().candidate_params();
+        assert!(candidates.contains(&params));
+        if params.get_n() == 401 || params.get_n() == 1087 {
+            assert_eq!(candidates.len(), 2);
+            assert_eq!(kp.get_public().get_params(), Err(Error::UnknownParamSet));
+        } else {
+            assert_eq!(candidates.len(), 1);
+            assert_eq!(&kp.get_public().get_params().unwrap(), params);
+        }
     }
 
     for (i, params1) in param_arr.iter().enumerate() {
@@ -68,3 +268,238 @@ fn it_params_from_key() {
         }
     }
 }
+
+#[test]
+fn it_public_keys_iter() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES439EP1, &rand_ctx).unwrap();
+
+    let publics: Vec<PublicKey> = kp.get_private()
+        .public_keys(&EES439EP1, &rand_ctx)
+        .take(3)
+        .map(|key| key.unwrap())
+        .collect();
+    assert_eq!(publics.len(), 3);
+    assert!(publics[0] != publics[1]);
+
+    for public in publics {
+        let encrypted = ntru::encrypt(b"Enrolled", &public, &EES439EP1, &rand_ctx).unwrap();
+        let kp = KeyPair::new(kp.get_private().clone(), public);
+        assert_eq!(&ntru::decrypt(&encrypted, &kp, &EES439EP1).unwrap()[..], b"Enrolled");
+    }
+}
+
+#[test]
+fn it_generate_key_pair_from() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+    let sets = [EES401EP1, EES401EP2];
+    #[cfg(feature = "avoid-hamming-wt-patent")]
+    let sets = [EES401EP1];
+    for params in &sets {
+        let n = params.get_n();
+        let (t, g, kp) = loop {
+            #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+            let t = if params == &EES401EP2 {
+                let f = ntru::types::ProdPoly::rand(n, 8, 8, 6, 6, &rand_ctx).unwrap();
+                PrivPoly::new_with_prod_poly(f)
+            } else {
+                PrivPoly::new_with_tern_poly(TernPoly::rand(n, 113, 113, &rand_ctx).unwrap())
+            };
+            #[cfg(feature = "avoid-hamming-wt-patent")]
+            let t = PrivPoly::new_with_tern_poly(TernPoly::rand(n, 113, 113, &rand_ctx).unwrap());
+            let g = TernPoly::rand(n, 133, 132, &rand_ctx).unwrap();
+            match ntru::generate_key_pair_from(params, &t, &g) {
+                Ok(kp) => break (t, g, kp),
+                Err(e) => assert_eq!(e, Error::InvalidKey),
+            }
+        };
+        assert_eq!(kp.get_private().get_t(), &t);
+        assert_eq!(ntru::generate_key_pair_from(params, &t, &g), Ok(kp.clone()));
+
+        let msg = b"Hello from Rust!";
+        let encrypted = ntru::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap();
+        let decrypted = ntru::decrypt(&encrypted, &kp, params).unwrap();
+        assert_eq!(&msg[..], &decrypted[..]);
+
+        let g_heavy = TernPoly::rand(n, 133, 133, &rand_ctx).unwrap();
+        assert_eq!(ntru::generate_key_pair_from(params, &t, &g_heavy),
+                   Err(Error::InvalidWeight));
+        let g_short = TernPoly::rand(n - 1, 133, 132, &rand_ctx).unwrap();
+        assert_eq!(ntru::generate_key_pair_from(params, &t, &g_short),
+                   Err(Error::InvalidDegree));
+    }
+
+    let t = PrivPoly::new_with_tern_poly(TernPoly::rand(401, 113, 113, &rand_ctx).unwrap());
+    let g = TernPoly::rand(401, 133, 132, &rand_ctx).unwrap();
+    assert_eq!(ntru::generate_key_pair_from(&EES401EP2, &t, &g), Err(Error::InvalidWeight));
+}
+
+#[test]
+fn it_generate_key_pair_hardened() {
+    for params in &[EES401EP1, EES401EP2] {
+        let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+        let kp = ntru::generate_key_pair_hardened(params, &rand_ctx).unwrap();
+        assert_eq!(kp.get_private().get_t().is_product(), params.is_product());
+
+        let msg = b"Hello from Rust!";
+        let encrypted = ntru::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap();
+        let decrypted = ntru::decrypt(&encrypted, &kp, params).unwrap();
+        assert_eq!(&msg[..], &decrypted[..]);
+
+        let rand_ctx = ntru::rand::init_det(&ntru::rand::RNG_CTR_DRBG, b"hardened").unwrap();
+        let kp = ntru::generate_key_pair_hardened(params, &rand_ctx).unwrap();
+        let rand_ctx = ntru::rand::init_det(&ntru::rand::RNG_CTR_DRBG, b"hardened").unwrap();
+        assert_eq!(ntru::generate_key_pair_hardened(params, &rand_ctx), Ok(kp));
+    }
+}
+
+#[test]
+fn it_export_auto() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES401EP2, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        assert_eq!(kp.get_public().export_auto().unwrap(), kp.get_public().export(params));
+        assert_eq!(kp.get_private().export_auto().unwrap(), kp.get_private().export(params));
+    }
+
+    assert_eq!(PublicKey::default().export_auto(), Err(Error::InvalidKey));
+    assert_eq!(PrivateKey::default().export_auto(), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_key_pair_from_private() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let restored = KeyPair::from_private(kp.get_private(), &EES401EP1, &rand_ctx).unwrap();
+    assert_eq!(restored.get_private(), kp.get_private());
+    assert!(restored.get_public() != kp.get_public());
+
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, restored.get_public(), &EES401EP1, &rand_ctx).unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &restored, &EES401EP1).unwrap()[..], &msg[..]);
+
+    // Messages for the original public key need it
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+    assert!(ntru::decrypt(&encrypted, &restored, &EES401EP1).is_err());
+    let with_original = KeyPair::new(restored.get_private().clone(), kp.get_public().clone());
+    assert_eq!(&ntru::decrypt(&encrypted, &with_original, &EES401EP1).unwrap()[..], &msg[..]);
+}
+
+#[test]
+fn it_try_from_bytes() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+
+    let public = Vec::from(kp.get_public());
+    assert_eq!(&public[..], &kp.get_public().export(&EES443EP1)[..]);
+    assert_eq!(PublicKey::try_from(&public[..]).as_ref(), Ok(kp.get_public()));
+    assert_eq!(PublicKey::try_from(&public[..public.len() - 1]), Err(Error::InvalidKey));
+
+    let private = Vec::try_from(kp.get_private()).unwrap();
+    assert_eq!(&private[..], &kp.get_private().export(&EES443EP1)[..]);
+    assert_eq!(PrivateKey::try_from(&private[..]).as_ref(), Ok(kp.get_private()));
+    assert_eq!(PrivateKey::try_from(&private[1..]), Err(Error::InvalidKey));
+    assert_eq!(Vec::try_from(&PrivateKey::default()), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_stream_import_export() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES401EP2, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+        let mut stream = Vec::new();
+        kp.get_public().export_to(&mut stream).unwrap();
+        kp.get_private().export_to(&mut stream).unwrap();
+        stream.extend_from_slice(b"rest");
+        assert_eq!(&stream[..params.public_len() as usize], &kp.get_public().export(params)[..]);
+
+        let mut reader = &stream[..];
+        assert_eq!(&PublicKey::import_from(&mut reader).unwrap(), kp.get_public());
+        assert_eq!(&PrivateKey::import_from(&mut reader).unwrap(), kp.get_private());
+        assert_eq!(reader, b"rest");
+
+        let mut truncated = &stream[..params.public_len() as usize - 1];
+        assert_eq!(PublicKey::import_from(&mut truncated).unwrap_err().kind(),
+                   std::io::ErrorKind::UnexpectedEof);
+    }
+
+    let mut invalid = &[0u8, 0, 8, 0][..];
+    assert_eq!(PublicKey::import_from(&mut invalid).unwrap_err().kind(),
+               std::io::ErrorKind::InvalidData);
+    assert_eq!(PrivateKey::default().export_to(&mut Vec::new()).unwrap_err().kind(),
+               std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn it_canonical_encoding() {
+    // 1, 2 and 3 with 11 bits each, least significant bit first, whatever the target byte order
+    let packed = [0x01, 0x10, 0xc0, 0x00, 0x00];
+    let arr = IntPoly::new(&[1, 2, 3]).to_arr(&EES401EP1);
+    assert_eq!(&arr[..5], &packed[..]);
+    assert!(arr[5..].iter().all(|&b| b == 0));
+    assert_eq!(IntPoly::from_arr(&arr, 3, 2048), IntPoly::new(&[1, 2, 3]));
+
+    let exported = [0x00, 0x03, 0x08, 0x00, 0x01, 0x10, 0xc0, 0x00, 0x00];
+    let public = PublicKey::import(&exported).unwrap();
+    assert_eq!(public.get_q(), 2048);
+    assert_eq!(public.get_h().get_coeffs(), &[1, 2, 3]);
+    assert_eq!(&public.export_auto().unwrap()[..], &exported[..]);
+    assert_eq!(PublicKey::import(&exported[..3]), Err(Error::InvalidKey));
+    assert_eq!(PublicKey::import(&exported[..8]), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_public_key_hash() {
+    use std::collections::{HashMap, HashSet};
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp1 = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let kp2 = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+
+    let mut routes = HashMap::new();
+    let _ = routes.insert(kp1.get_public().clone(), "alice");
+    let _ = routes.insert(kp2.get_public().clone(), "bob");
+    let imported = PublicKey::try_from(&kp2.get_public().export(&EES401EP1)[..]).unwrap();
+    assert_eq!(routes.get(&imported), Some(&"bob"));
+
+    let keys: HashSet<PublicKey> = vec![kp1.get_public().clone(), kp1.get_public().clone(),
+                                        PublicKey::default()]
+        .into_iter()
+        .collect();
+    assert_eq!(keys.len(), 2);
+}
+
+#[test]
+fn it_boxed_keys() {
+    let seed = b"boxed keys seed";
+    let rand_ctx = ntru::rand::init_det(&ntru::rand::RNG_CTR_DRBG, seed).unwrap();
+    let kp = KeyPair::generate_boxed(&EES1087EP2, &rand_ctx).unwrap();
+    let rand_ctx = ntru::rand::init_det(&ntru::rand::RNG_CTR_DRBG, seed).unwrap();
+    assert_eq!(*kp, ntru::generate_key_pair(&EES1087EP2, &rand_ctx).unwrap());
+
+    let exported = kp.get_private().export(&EES1087EP2);
+    assert_eq!(PrivateKey::import_boxed(&exported[1..]).err(), Some(Error::InvalidKey));
+    assert_eq!(PrivateKey::import_boxed(&[]).err(), Some(Error::InvalidKey));
+    let private = PrivateKey::import_boxed(&exported).unwrap();
+    assert_eq!(&*private, kp.get_private());
+
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &EES1087EP2, &rand_ctx).unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &EES1087EP2).unwrap()[..], &msg[..]);
+
+    // Small stacks, such as the ones of embedded targets
+    let handle = std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(|| {
+            let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+            let kp = KeyPair::generate_boxed(&EES1087EP2, &rand_ctx).unwrap();
+            let encrypted = ntru::encrypt(b"msg", kp.get_public(), &EES1087EP2, &rand_ctx)
+                .unwrap();
+            ntru::decrypt(&encrypted, &kp, &EES1087EP2).unwrap()
+        })
+        .unwrap();
+    assert_eq!(&handle.join().unwrap()[..], b"msg");
+}
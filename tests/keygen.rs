@@ -0,0 +1,77 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::encparams::{DEFAULT_PARAMS_128_BITS, DEFAULT_PARAMS_256_BITS, EES401EP1};
+use ntru::keygen::KeyPairGenerator;
+use ntru::rand::{Rng, RNG_CTR_DRBG};
+use ntru::types::Error;
+
+#[test]
+fn it_key_pair_generator() {
+    let kp = KeyPairGenerator::new().generate_key_pair().unwrap();
+    assert_eq!(kp.get_params().unwrap(), DEFAULT_PARAMS_128_BITS);
+
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &DEFAULT_PARAMS_128_BITS,
+                                  &ntru::rand::init(&ntru::rand::RNG_DEFAULT).unwrap())
+        .unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &DEFAULT_PARAMS_128_BITS).unwrap()[..],
+               &msg[..]);
+}
+
+#[test]
+fn it_key_pair_generator_deterministic() {
+    let seed = b"key pair generator seed";
+    let generator = KeyPairGenerator::new().params(EES401EP1).deterministic_seed(seed);
+    let kp = generator.generate_key_pair().unwrap();
+    assert_eq!(kp, generator.clone().rng(Rng::CtrDrbg).generate_key_pair().unwrap());
+
+    let rand_ctx = ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap();
+    assert_eq!(kp, ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap());
+
+    let other = generator.deterministic_seed(b"another seed").generate_key_pair().unwrap();
+    assert!(kp != other);
+}
+
+#[test]
+fn it_key_pair_generator_multiple() {
+    let generator = KeyPairGenerator::new()
+        .params(DEFAULT_PARAMS_256_BITS)
+        .rng(Rng::Default)
+        .deterministic_seed(b"seed")
+        .num_public_keys(4);
+    let kp = generator.generate().unwrap();
+    assert_eq!(kp.get_publics().len(), 4);
+    assert_eq!(kp, generator.generate().unwrap());
+    assert_eq!(generator.generate_key_pair(), Err(Error::InvalidParam));
+
+    let msg = b"Hello from Rust!";
+    let rand_ctx = ntru::rand::init(&ntru::rand::RNG_DEFAULT).unwrap();
+    for pair in kp.key_pairs() {
+        let encrypted = ntru::encrypt(msg, pair.get_public(), &DEFAULT_PARAMS_256_BITS, &rand_ctx)
+            .unwrap();
+        assert_eq!(&kp.decrypt(&encrypted, &DEFAULT_PARAMS_256_BITS).unwrap()[..], &msg[..]);
+    }
+}
+
+#[test]
+fn it_key_pair_generator_invalid() {
+    assert_eq!(KeyPairGenerator::new().num_public_keys(0).generate(), Err(Error::InvalidParam));
+    let generator = KeyPairGenerator::new().rng(Rng::GetRandom).deterministic_seed(b"seed");
+    assert_eq!(generator.generate(), Err(Error::InvalidParam));
+    assert_eq!(KeyPairGenerator::new().deterministic_seed(b"").generate(),
+               Err(Error::InvalidParam));
+}
+
+#[test]
+fn it_rng() {
+    assert!(Rng::Default.is_ctr_drbg());
+    assert!(Rng::CtrDrbg.is_ctr_drbg());
+    assert!(!Rng::GetRandom.is_ctr_drbg());
+}
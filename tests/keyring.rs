@@ -0,0 +1,87 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{DEFAULT_PARAMS_128_BITS, DEFAULT_PARAMS_256_BITS, EES401EP1};
+use ntru::keyring::{KeyRing, PublicKeySet, KEY_ID_LEN};
+use ntru::types::Error;
+
+#[test]
+fn it_key_id() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp1 = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let kp2 = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+
+    let id1 = ntru::keyring::key_id(kp1.get_public(), &EES401EP1);
+    assert_eq!(id1, ntru::keyring::key_id(kp1.get_public(), &EES401EP1));
+    assert!(id1 != ntru::keyring::key_id(kp2.get_public(), &EES401EP1));
+
+    let hex = ntru::keyring::key_id_to_hex(&id1);
+    assert_eq!(hex.len(), 2 * KEY_ID_LEN);
+    assert_eq!(ntru::keyring::key_id_from_hex(&hex).unwrap(), id1);
+    assert_eq!(ntru::keyring::key_id_from_hex(&hex[2..]), Err(Error::InvalidEncoding));
+}
+
+#[test]
+fn it_key_ring() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let mut key_ring = KeyRing::new();
+    assert!(key_ring.is_empty());
+
+    let mut ids = Vec::new();
+    for params in &[DEFAULT_PARAMS_128_BITS, DEFAULT_PARAMS_256_BITS, EES401EP1] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let encrypted = ntru::keyring::encrypt_with_key_id(b"Key ring", kp.get_public(), params,
+                                                           &rand_ctx)
+            .unwrap();
+        assert_eq!(encrypted.len(), KEY_ID_LEN + params.enc_len() as usize);
+
+        let id = key_ring.add(kp.clone()).unwrap();
+        assert_eq!(id, ntru::keyring::key_id(kp.get_public(), params));
+        assert_eq!(key_ring.get(&id), Some(&kp));
+        ids.push((id, encrypted));
+    }
+    assert_eq!(key_ring.len(), 3);
+
+    for (_, encrypted) in &ids {
+        assert_eq!(&key_ring.decrypt(encrypted).unwrap()[..], b"Key ring");
+    }
+
+    let _ = key_ring.remove(&ids[0].0).unwrap();
+    assert_eq!(key_ring.decrypt(&ids[0].1), Err(Error::UnknownKey));
+    assert_eq!(key_ring.decrypt(&ids[0].1[..4]), Err(Error::InvalidEncoding));
+}
+
+#[test]
+fn it_public_key_set() {
+    let params = EES401EP1;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let mut set = PublicKeySet::generate(&params, &rand_ctx, 3).unwrap();
+    assert_eq!(set.len(), 3);
+
+    let id = {
+        let public = set.generate_public(&rand_ctx).unwrap();
+        ntru::keyring::key_id(public, &params)
+    };
+    assert_eq!(set.len(), 4);
+    assert_eq!(set.public_keys().count(), 4);
+
+    for public in set.public_keys() {
+        let id = ntru::keyring::key_id(public, &params);
+        assert_eq!(set.get(&id), Some(public));
+
+        let encrypted = ntru::encrypt(b"Key set", public, &params, &rand_ctx).unwrap();
+        let kp = set.key_pair(&id).unwrap();
+        assert_eq!(kp.get_public(), public);
+        assert_eq!(&ntru::decrypt(&encrypted, &kp, &params).unwrap()[..], b"Key set");
+    }
+
+    assert!(set.key_pair(&id).is_some());
+    assert!(set.key_pair(&[0u8; KEY_ID_LEN]).is_none());
+}
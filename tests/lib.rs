@@ -6,36 +6,44 @@
 #![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
     unused_qualifications, unused_results, variant_size_differences)]
 
-#[macro_use]
 extern crate ntru;
-extern crate crypto;
+extern crate sha1;
+extern crate sha2;
 extern crate rand;
 
-use crypto::digest::Digest;
-use crypto::sha1::Sha1;
+use sha1::{Digest, Sha1};
+use sha2::Sha512;
 
 use rand::Rng;
 
-use ntru::encparams::{EncParams, ALL_PARAM_SETS};
+use std::collections::HashMap;
+
+use ntru::encparams::{EncParams, HashAlgorithm, HashFunction, SecurityLevel, Profile, Sampler,
+                      ALL_PARAM_SETS, DEFAULT_PARAMS_112_BITS, DEFAULT_PARAMS_128_BITS,
+                      DEFAULT_PARAMS_192_BITS, DEFAULT_PARAMS_256_BITS, EES401EP2, EES439EP1,
+                      EES443EP1, EES449EP1, EES593EP1, EES613EP1, EES659EP1, EES1087EP2,
+                      EES1499EP1};
 use ntru::rand::{RNG_DEFAULT, RNG_CTR_DRBG};
-use ntru::types::{IntPoly, TernPoly, PrivateKey, PublicKey, KeyPair};
+use ntru::types::{IntPoly, TernPoly, PrivateKey, PublicKey, KeyPair, MultiKeyPair, Error,
+                  ErrorCategory};
 
 fn encrypt_poly(m: IntPoly, r: &TernPoly, h: &IntPoly, q: u16) -> IntPoly {
-    let (mut res, _) = h.mult_tern(r, q);
-    res = res + m;
+    let mut res = h.mult_tern(r, q).unwrap();
+    res += &m;
     res.mod_mask(q - 1);
     res
 }
 
 fn decrypt_poly(e: IntPoly, private: &PrivateKey, modulus: u16) -> IntPoly {
-    let (mut d, _) = if private.get_t().is_product() {
+    let mut d = if private.get_t().is_product() {
         e.mult_prod(private.get_t().get_poly_prod(), modulus - 1)
     } else {
         e.mult_tern(private.get_t().get_poly_tern(), modulus - 1)
-    };
+    }
+    .unwrap();
     d.mod_mask(modulus - 1);
     d.mult_fac(3);
-    d = d + e;
+    d += &e;
     d.mod_center(modulus);
     d.mod3();
     for i in 0..d.get_coeffs().len() {
@@ -55,11 +63,8 @@ fn gen_key_pair(seed: &str, params: &EncParams) -> KeyPair {
 }
 
 fn sha1(input: &[u8]) -> [u8; 20] {
-    let mut hasher = Sha1::new();
-    hasher.input(input);
-
     let mut digest = [0u8; 20];
-    hasher.result(&mut digest);
+    digest.copy_from_slice(&Sha1::digest(input));
     digest
 }
 
@@ -113,7 +118,7 @@ fn test_encr_decr_nondet(params: &EncParams) {
 
     // Create a key pair with multiple public keys (using ntru_gen_key_pair_multi)
     let (priv_multi1, pub_multi1) =
-        ntru::generate_multiple_key_pairs(params, &rand_ctx, num_pub_keys).unwrap();
+        ntru::generate_multiple_key_pairs(params, &rand_ctx, num_pub_keys).unwrap().into_parts();
 
     // Create a key pair with multiple public keys (using ntru::generate_public)
     let kp_multi2 = ntru::generate_key_pair(params, &rand_ctx).unwrap();
@@ -139,7 +144,7 @@ fn test_encr_decr_nondet(params: &EncParams) {
         }
 
         // Test multiple public keys
-        for (i, pub_key) in pub_multi1.into_iter().enumerate() {
+        for (i, pub_key) in pub_multi1.iter().enumerate() {
             let rand_value = rand::thread_rng().gen_range(1, 100);
             if rand_value % 100 != 0 {
                 continue;
@@ -179,7 +184,7 @@ fn test_encr_decr_det(params: &EncParams, digest_expected: &[u8]) {
     let kp = gen_key_pair("seed value for key generation", params);
     let pub_arr = kp.get_public().export(params);
 
-    let pub2 = PublicKey::import(&pub_arr);
+    let pub2 = PublicKey::import(&pub_arr).unwrap();
     assert_eq!(kp.get_public().get_h(), pub2.get_h());
 
     let max_len = params.max_msg_len();
@@ -287,3 +292,505 @@ fn it_encr_decr() {
         test_encr_decr_det(param, &digests_expected[i]);
     }
 }
+
+#[test]
+fn it_decrypt_invalid_length() {
+    let params = &DEFAULT_PARAMS_112_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+    let encrypted = ntru::encrypt(b"Hello from Rust!", kp.get_public(), params, &rand_ctx).unwrap();
+    let enc_len = params.enc_len() as usize;
+
+    assert_eq!(ntru::decrypt(&encrypted[..enc_len - 1], &kp, params),
+               Err(Error::InvalidCiphertextLength {
+                   expected: enc_len,
+                   actual: enc_len - 1,
+               }));
+    assert_eq!(ntru::decrypt(&[], &kp, params),
+               Err(Error::InvalidCiphertextLength {
+                   expected: enc_len,
+                   actual: 0,
+               }));
+
+    let mut longer = encrypted.to_vec();
+    longer.push(0);
+    assert_eq!(ntru::decrypt(&longer, &kp, params),
+               Err(Error::InvalidCiphertextLength {
+                   expected: enc_len,
+                   actual: enc_len + 1,
+               }));
+}
+
+#[test]
+fn it_const_lengths() {
+    const ENC_LEN: usize = EES613EP1.enc_len() as usize;
+    const MAX_MSG_LEN: usize = EES613EP1.max_msg_len() as usize;
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES613EP1, &rand_ctx).unwrap();
+    let msg = [1u8; MAX_MSG_LEN];
+    let mut buf = [0u8; ENC_LEN];
+    buf.copy_from_slice(&ntru::encrypt(&msg, kp.get_public(), &EES613EP1, &rand_ctx).unwrap());
+    assert_eq!(&ntru::decrypt(&buf, &kp, &EES613EP1).unwrap()[..], &msg[..]);
+}
+
+#[test]
+fn it_params_getters() {
+    let params = &EES1087EP2;
+    assert!(!params.is_product());
+    assert_eq!((params.get_df1(), params.get_df2(), params.get_df3()), (120, 0, 0));
+    assert_eq!((params.get_dg(), params.get_dm0(), params.get_c()), (362, 120, 13));
+    assert_eq!((params.get_min_calls_r(), params.get_min_calls_mask()), (25, 14));
+    assert!(params.hashes_seed());
+    assert_eq!(params.get_oid(), [0, 6, 3]);
+    assert_eq!((params.get_hlen(), params.get_pklen()), (32, 256));
+
+    let params = &EES401EP2;
+    assert!(params.is_product());
+    assert_eq!((params.get_df1(), params.get_df2(), params.get_df3()), (8, 8, 6));
+    assert_eq!((params.get_hlen(), params.get_pklen()), (20, 112));
+}
+
+#[test]
+fn it_params_display() {
+    assert_eq!(EES1087EP2.to_string(),
+               "EES1087EP2 (n = 1087, q = 2048, df = 120, dg = 362, dm0 = 120, SHA-256, 256 bits)");
+    assert_eq!(EES401EP2.to_string(),
+               "EES401EP2 (n = 401, q = 2048, df1 = 8, df2 = 8, df3 = 6, dg = 133, dm0 = 101, \
+                SHA-1, 112 bits)");
+
+    let table = format!("{:#?}", EES401EP2);
+    assert!(table.starts_with("EncParams {\n    name: \"EES401EP2\",\n"));
+    assert!(table.contains("min_calls_mask: 6,\n"));
+    assert!(table.contains("hash: Sha1,\n"));
+}
+
+#[test]
+fn it_params_eq_hash() {
+    let mut names = HashMap::new();
+    for params in ALL_PARAM_SETS.iter() {
+        assert!(names.insert(params, params.get_name()).is_none());
+    }
+    assert_eq!(names.len(), ALL_PARAM_SETS.len());
+    assert_eq!(names[&EES401EP2], "EES401EP2\0\0");
+    assert_eq!(names[&DEFAULT_PARAMS_112_BITS], names[&ALL_PARAM_SETS[4]]);
+
+    assert_eq!(EES1087EP2.get_hash_algorithm(), HashAlgorithm::Sha256);
+    assert_eq!(EES401EP2.get_hash_algorithm(), HashAlgorithm::Sha1);
+    assert_eq!(HashAlgorithm::Sha256.to_string(), "SHA-256");
+}
+
+#[test]
+fn it_params_select() {
+    let levels = [SecurityLevel::Bits112,
+                  SecurityLevel::Bits128,
+                  SecurityLevel::Bits192,
+                  SecurityLevel::Bits256];
+    let defaults = [DEFAULT_PARAMS_112_BITS,
+                    DEFAULT_PARAMS_128_BITS,
+                    DEFAULT_PARAMS_192_BITS,
+                    DEFAULT_PARAMS_256_BITS];
+    for (&level, default) in levels.iter().zip(defaults.iter()) {
+        assert!(EncParams::select(level, Profile::Balanced) == default);
+        for &profile in &[Profile::KeySize, Profile::Balanced, Profile::Speed] {
+            let params = EncParams::select(level, profile);
+            assert_eq!(params.get_db(), default.get_db());
+            assert!(!params.is_product());
+        }
+    }
+    assert!(EncParams::select(SecurityLevel::Bits128, Profile::KeySize) == &EES449EP1);
+    assert!(EncParams::select(SecurityLevel::Bits112, Profile::Speed) == &EES659EP1);
+    assert!(EncParams::select(SecurityLevel::Bits256, Profile::Speed) == &EES1499EP1);
+}
+
+#[test]
+fn it_params_registry() {
+    assert_eq!(EncParams::all().count(), ALL_PARAM_SETS.len());
+    assert!(EncParams::all().zip(ALL_PARAM_SETS.iter()).all(|(a, b)| a == b));
+
+    let recommended: Vec<&EncParams> = EncParams::recommended().collect();
+    assert!(recommended == [&DEFAULT_PARAMS_112_BITS, &DEFAULT_PARAMS_128_BITS,
+                            &DEFAULT_PARAMS_192_BITS, &DEFAULT_PARAMS_256_BITS]);
+    assert_eq!(EncParams::recommended().deprecated(true).count(), 0);
+
+    let deprecated: Vec<&EncParams> = EncParams::all().deprecated(true).collect();
+    assert!(deprecated == [&EES439EP1, &EES593EP1]);
+    assert!(EES439EP1.is_deprecated());
+    assert!(!EES443EP1.is_deprecated());
+
+    assert_eq!(EncParams::all().product_form(true).count(), 6);
+    assert_eq!(EncParams::all().product_form(false).count(), 12);
+    assert!(EncParams::all().security_bits(256).all(|params| params.get_db() == 256));
+    assert_eq!(EncParams::all().security_bits(256).count(), 4);
+    assert_eq!(EncParams::all().security_bits(192).deprecated(false).count(), 4);
+    assert_eq!(EncParams::all().security_bits(100).count(), 0);
+
+    // Without the deny-deprecated-params feature, every parameter set is allowed
+    if !cfg!(feature = "deny-deprecated-params") {
+        assert!(EncParams::all().all(|params| params.is_allowed()));
+    }
+}
+
+#[test]
+fn it_params_metadata() {
+    for params in ALL_PARAM_SETS.iter() {
+        assert_eq!(params.security_bits(), params.get_db());
+        assert_eq!(params.ciphertext_overhead() as usize,
+                   params.enc_len() as usize - params.max_msg_len() as usize);
+
+        let max_len = params.max_msg_len() as usize;
+        let ratio = params.expansion_ratio(max_len).unwrap();
+        assert!(ratio > 1.0);
+        assert!(params.expansion_ratio(1).unwrap() > ratio);
+        assert_eq!(params.expansion_ratio(max_len + 1), None);
+    }
+    assert_eq!(EES1087EP2.security_bits(), 256);
+    assert_eq!(EES401EP2.ciphertext_overhead(), 552 - 60);
+}
+
+#[test]
+fn it_params_sampler() {
+    assert_eq!(EES439EP1.sampler(), Some(Sampler::Ternary));
+    assert_eq!(DEFAULT_PARAMS_256_BITS.sampler(), Some(Sampler::Ternary));
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    if ntru::encparams::AVOID_HAMMING_WT_PATENT {
+        assert_eq!(EES401EP2.sampler(), None);
+        assert_eq!(ntru::generate_key_pair(&EES401EP2, &rand_ctx).unwrap_err(),
+                   Error::UnknownParamSet);
+    } else {
+        assert_eq!(EES401EP2.sampler(), Some(Sampler::ProductForm));
+        let kp = ntru::generate_key_pair(&EES401EP2, &rand_ctx).unwrap();
+        assert!(kp.get_private().get_t().is_product());
+    }
+}
+
+#[test]
+fn it_decryption_failure_rate_estimate() {
+    for params in ALL_PARAM_SETS.iter() {
+        let rate = params.decryption_failure_rate_estimate();
+        assert!(rate < 2f64.powi(-100), "{}: {}", params, rate);
+    }
+}
+
+#[test]
+fn it_with_hash_algorithm() {
+    let params = EES443EP1.with_hash_algorithm(HashAlgorithm::Sha256, "EES443H256", [255, 2, 9])
+        .unwrap();
+    assert_eq!(params.get_hash_algorithm(), HashAlgorithm::Sha256);
+    assert_eq!(params.get_hlen(), 32);
+    assert_eq!(params.get_oid(), [255, 2, 9]);
+    assert!(params != EES443EP1);
+
+    assert_eq!(EES443EP1.with_hash_algorithm(HashAlgorithm::Sha256, "", [255, 2, 9]),
+               Err(Error::InvalidParam));
+    assert_eq!(EES443EP1.with_hash_algorithm(HashAlgorithm::Sha256, "EES443EP1H256", [255, 2, 9]),
+               Err(Error::InvalidParam));
+    // The OID of a built-in parameter set
+    assert_eq!(EES443EP1.with_hash_algorithm(HashAlgorithm::Sha256,
+                                             "EES443H256",
+                                             EES443EP1.get_oid()),
+               Err(Error::InvalidParam));
+}
+
+struct Sha512Hash;
+
+impl HashFunction for Sha512Hash {
+    const OUTPUT_LEN: usize = 64;
+
+    fn digest(input: &[u8], output: &mut [u8]) {
+        output.copy_from_slice(&Sha512::digest(input));
+    }
+}
+
+struct Sha1Half;
+
+impl HashFunction for Sha1Half {
+    const OUTPUT_LEN: usize = 10;
+
+    fn digest(input: &[u8], output: &mut [u8]) {
+        output.copy_from_slice(&Sha1::digest(input)[..10]);
+    }
+}
+
+#[test]
+fn it_with_hash_function() {
+    let params = EES443EP1.with_hash_function::<Sha512Hash>("EES443H512", [255, 3, 0]).unwrap();
+    assert_eq!(params.get_hash_algorithm(), HashAlgorithm::Custom);
+    assert_eq!(params.get_hlen(), 64);
+    assert!(ntru::low_level::mgf(b"seed", &params).unwrap() !=
+            ntru::low_level::mgf(b"seed", &EES443EP1).unwrap());
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let msg = b"Hello from Rust!";
+    let encrypted = ntru::encrypt(msg, kp.get_public(), &params, &rand_ctx).unwrap();
+    let decrypted = ntru::decrypt(&encrypted, &kp, &params).unwrap();
+    assert_eq!(&msg[..], &decrypted[..]);
+    assert!(ntru::decrypt(&encrypted, &kp, &EES443EP1).is_err());
+
+    assert_eq!(EES443EP1.with_hash_function::<Sha1Half>("EES443H80", [255, 3, 1]),
+               Err(Error::InvalidParam));
+    assert_eq!(EES443EP1.with_hash_algorithm(HashAlgorithm::Custom, "EES443HC", [255, 3, 1]),
+               Err(Error::InvalidParam));
+}
+
+#[test]
+fn it_estimate_security() {
+    for params in ALL_PARAM_SETS.iter() {
+        let estimate = params.estimate_security();
+        assert!(estimate.block_size > 0 && estimate.block_size <= 2 * params.get_n());
+        assert!(estimate.classical_bits > estimate.quantum_bits);
+        assert!(estimate.classical_bits >= 100.0, "{}: {:?}", params, estimate);
+    }
+    assert!(EES1087EP2.estimate_security().classical_bits >
+            EES443EP1.estimate_security().classical_bits);
+}
+
+#[test]
+fn it_encrypt_too_long() {
+    let params = &DEFAULT_PARAMS_112_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+    let max_len = params.max_msg_len() as usize;
+
+    let msg = vec![0u8; max_len + 1];
+    assert_eq!(ntru::encrypt(&msg, kp.get_public(), params, &rand_ctx),
+               Err(Error::MessageTooLong));
+
+    // A length that would wrap around when truncated to 16 bits
+    let msg = vec![0u8; 70000];
+    assert_eq!(ntru::encrypt(&msg, kp.get_public(), params, &rand_ctx),
+               Err(Error::MessageTooLong));
+
+    assert!(ntru::encrypt(&msg[..max_len], kp.get_public(), params, &rand_ctx).is_ok());
+}
+
+#[test]
+fn it_encrypt_batch() {
+    let params = &DEFAULT_PARAMS_112_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+    // 8 messages of the same length share the 8-way hashes, the rest are hashed one by one
+    let mut msgs = vec![&b"Hello from Rust!"[..]; 8];
+    let rest: [&[u8]; 3] = [b"Hello", b"", b"from Rust!"];
+    msgs.extend_from_slice(&rest);
+    let encrypted = ntru::encrypt_batch(&msgs, kp.get_public(), params, &rand_ctx).unwrap();
+    assert_eq!(encrypted.len(), msgs.len());
+    for (enc, msg) in encrypted.iter().zip(msgs.iter()) {
+        assert_eq!(&ntru::decrypt(enc, &kp, params).unwrap()[..], *msg);
+    }
+    assert!(encrypted[0] != encrypted[1]);
+    assert_eq!(ntru::encrypt_batch(&[], kp.get_public(), params, &rand_ctx).unwrap().len(), 0);
+
+    let long = vec![0u8; params.max_msg_len() as usize + 1];
+    assert_eq!(ntru::encrypt_batch(&[b"Hello", &long], kp.get_public(), params, &rand_ctx),
+               Err(Error::MessageTooLong));
+}
+
+#[test]
+fn it_encrypt_with_blinding() {
+    let params = &DEFAULT_PARAMS_112_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+    let b1 = vec![0x5a; params.get_db() as usize / 8];
+    let b2 = vec![0xa5; params.get_db() as usize / 8];
+    let msg = b"Hello from Rust!";
+    let enc1 = ntru::encrypt_with_blinding(msg, kp.get_public(), params, &b1).unwrap();
+    let enc2 = ntru::encrypt_with_blinding(msg, kp.get_public(), params, &b1).unwrap();
+    let enc3 = ntru::encrypt_with_blinding(msg, kp.get_public(), params, &b2).unwrap();
+    assert_eq!(enc1, enc2);
+    assert!(enc1 != enc3);
+    assert_eq!(&ntru::decrypt(&enc1, &kp, params).unwrap()[..], &msg[..]);
+    assert_eq!(&ntru::decrypt(&enc3, &kp, params).unwrap()[..], &msg[..]);
+
+    assert_eq!(ntru::encrypt_with_blinding(msg, kp.get_public(), params, &b1[1..]),
+               Err(Error::InvalidParam));
+}
+
+#[test]
+fn it_multi_key_pair() {
+    let params = &EES443EP1;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    assert_eq!(ntru::generate_multiple_key_pairs(params, &rand_ctx, 0),
+               Err(Error::InvalidParam));
+
+    let kp = ntru::generate_multiple_key_pairs(params, &rand_ctx, 3).unwrap();
+    assert_eq!(kp.get_publics().len(), 3);
+    let key_pairs = kp.key_pairs();
+    assert_eq!(key_pairs.len(), 3);
+    for (i, key_pair) in key_pairs.iter().enumerate() {
+        assert_eq!(key_pair.get_private(), kp.get_private());
+        assert_eq!(key_pair.get_public(), &kp.get_publics()[i]);
+        assert_eq!(kp.get_key_pair(i).as_ref(), Some(key_pair));
+
+        let encrypted = ntru::encrypt(b"Hello", key_pair.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(&ntru::decrypt(&encrypted, key_pair, params).unwrap()[..], b"Hello");
+        assert_eq!(&kp.decrypt(&encrypted, params).unwrap()[..], b"Hello");
+    }
+    assert_eq!(kp.get_key_pair(3), None);
+
+    let (private, publics) = kp.clone().into_parts();
+    assert_eq!(MultiKeyPair::new(private.clone(), publics), Ok(kp));
+    assert_eq!(MultiKeyPair::new(private, Vec::new().into_boxed_slice()),
+               Err(Error::InvalidParam));
+}
+
+#[test]
+fn it_decrypt_any() {
+    let params = &EES443EP1;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let (private, publics) =
+        ntru::generate_multiple_key_pairs(params, &rand_ctx, 4).unwrap().into_parts();
+    let msg = b"Hello from Rust!";
+    for public in publics.iter() {
+        let encrypted = ntru::encrypt(msg, public, params, &rand_ctx).unwrap();
+        let decrypted = ntru::decrypt_any(&encrypted, &private, &publics, params).unwrap();
+        assert_eq!(&decrypted[..], &msg[..]);
+    }
+
+    let other = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+    let encrypted = ntru::encrypt(msg, other.get_public(), params, &rand_ctx).unwrap();
+    assert!(ntru::decrypt_any(&encrypted, &private, &publics, params).is_err());
+    assert_eq!(ntru::decrypt_any(&encrypted, &private, &[], params),
+               Err(Error::InvalidParam));
+    assert_eq!(ntru::decrypt_any(&encrypted[1..], &private, &publics, params),
+               Err(Error::InvalidCiphertextLength {
+                   expected: encrypted.len(),
+                   actual: encrypted.len() - 1,
+               }));
+}
+
+#[test]
+fn it_encrypt_deterministic() {
+    let params = &DEFAULT_PARAMS_112_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp1 = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+    let kp2 = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+    let msg = b"Hello from Rust!";
+    let enc1 = ntru::encrypt_deterministic(msg, kp1.get_public(), params).unwrap();
+    let enc2 = ntru::encrypt_deterministic(msg, kp1.get_public(), params).unwrap();
+    let enc3 = ntru::encrypt_deterministic(b"Hello from C!", kp1.get_public(), params).unwrap();
+    let enc4 = ntru::encrypt_deterministic(msg, kp2.get_public(), params).unwrap();
+    assert_eq!(enc1, enc2);
+    assert!(enc1 != enc3);
+    assert_eq!(&ntru::decrypt(&enc1, &kp1, params).unwrap()[..], &msg[..]);
+    assert_eq!(&ntru::decrypt(&enc3, &kp1, params).unwrap()[..], &b"Hello from C!"[..]);
+    assert_eq!(&ntru::decrypt(&enc4, &kp2, params).unwrap()[..], &msg[..]);
+    assert_eq!(&ntru::encrypt_deterministic(b"", kp1.get_public(), params).unwrap(),
+               &ntru::encrypt_deterministic(b"", kp1.get_public(), params).unwrap());
+
+    let msg = vec![0u8; params.max_msg_len() as usize + 1];
+    assert_eq!(ntru::encrypt_deterministic(&msg, kp1.get_public(), params),
+               Err(Error::MessageTooLong));
+}
+
+#[test]
+fn it_self_test() {
+    assert_eq!(ntru::self_test(), Ok(()));
+}
+
+#[test]
+fn it_check_library() {
+    assert_eq!(ntru::check_library(), Ok(()));
+    assert_eq!(ntru::system_libntru_version().is_some(), cfg!(feature = "system-libntru"));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn it_decrypt_batch() {
+    let params = &DEFAULT_PARAMS_112_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+    let msgs: [&[u8]; 3] = [b"Hello", b"", b"from Rust!"];
+    let encrypted: Vec<Box<[u8]>> = msgs.iter()
+        .map(|msg| ntru::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap())
+        .collect();
+    let mut encs: Vec<&[u8]> = encrypted.iter().map(|enc| &enc[..]).collect();
+    encs.push(&encrypted[0][1..]);
+
+    let decrypted = ntru::decrypt_batch(&encs, &kp, params);
+    assert_eq!(decrypted.len(), encs.len());
+    for (dec, msg) in decrypted.iter().zip(msgs.iter()) {
+        assert_eq!(&dec.as_ref().unwrap()[..], *msg);
+    }
+    assert_eq!(decrypted[3],
+               Err(Error::InvalidCiphertextLength {
+                   expected: params.enc_len() as usize,
+                   actual: params.enc_len() as usize - 1,
+               }));
+}
+
+#[test]
+fn it_decrypt_secret() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+
+    let msg = [42u8; 32];
+    let encrypted = ntru::encrypt(&msg, kp.get_public(), &EES443EP1, &rand_ctx).unwrap();
+    let decrypted = ntru::decrypt_secret(&encrypted, &kp, &EES443EP1).unwrap();
+    assert_eq!(&decrypted[..], &msg[..]);
+    assert_eq!(decrypted.as_ref(), &ntru::decrypt(&encrypted, &kp, &EES443EP1).unwrap()[..]);
+    assert_eq!(format!("{:?}", decrypted), "SecretVec(32 bytes)");
+    assert!(ntru::decrypt_secret(&encrypted[1..], &kp, &EES443EP1).is_err());
+}
+
+#[test]
+fn it_error_from_code() {
+    assert_eq!(Error::from(1u8), Error::OutOfMemory);
+    assert_eq!(Error::from(11u8), Error::InvalidKey);
+    assert_eq!(Error::from(12u8), Error::Unknown(12));
+    assert_eq!(Error::from(255u8), Error::Unknown(255));
+}
+
+#[test]
+fn it_error_codes() {
+    let errors = [Error::OutOfMemory, Error::Prng, Error::MessageTooLong, Error::InvalidMaxLength,
+                  Error::Md0Violation, Error::NoZeroPad, Error::InvalidEncoding,
+                  Error::NullArgument, Error::UnknownParamSet, Error::InvalidParam,
+                  Error::InvalidKey, Error::InvalidCiphertextLength { expected: 1, actual: 2 },
+                  Error::InvalidMac, Error::UnknownKey, Error::InvalidDegree,
+                  Error::InvalidWeight, Error::DeprecatedParamSet, Error::Unknown(42),
+                  Error::DecryptionFailed, Error::KeyMismatch];
+    for (i, e) in errors.iter().enumerate() {
+        assert!(errors[..i].iter().all(|other| other.code() != e.code()));
+        if e.code() < 256 {
+            assert_eq!(&Error::from(e.code() as u8), e);
+        }
+    }
+    assert_eq!(Error::InvalidKey.code(), 11);
+    assert_eq!(Error::InvalidMac.code(), 257);
+
+    assert_eq!(Error::InvalidKey.category(), ErrorCategory::Input);
+    assert_eq!(Error::Prng.category(), ErrorCategory::Environment);
+    assert_eq!(Error::Unknown(42).category(), ErrorCategory::Internal);
+
+    let io_error = std::io::Error::from(Error::InvalidMac);
+    assert_eq!(io_error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(io_error.into_inner().unwrap().to_string(), "InvalidMac");
+    assert_eq!(std::io::Error::from(Error::InvalidKey).kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(std::io::Error::from(Error::UnknownKey).kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn it_decryption_failed() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+    let other = ntru::generate_key_pair(&EES443EP1, &rand_ctx).unwrap();
+
+    let encrypted = ntru::encrypt(b"Hello", kp.get_public(), &EES443EP1, &rand_ctx).unwrap();
+    let mut modified = encrypted.to_vec();
+    modified[10] ^= 0x01;
+    for &(enc, kp) in &[(&encrypted[..], &other), (&modified[..], &kp)] {
+        let result = ntru::decrypt(enc, kp, &EES443EP1);
+        if cfg!(feature = "verbose-errors") {
+            assert!(result.is_err());
+        } else {
+            assert_eq!(result, Err(Error::DecryptionFailed));
+        }
+    }
+}
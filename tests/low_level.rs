@@ -0,0 +1,144 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+
+extern crate ntru;
+use ntru::encparams::{EncParams, EES439EP1, EES1087EP2, ALL_PARAM_SETS};
+use ntru::low_level::{self, BitString, IndexGenerator, mgf};
+use ntru::rand::{self, RNG_DEFAULT, RNG_CTR_DRBG};
+use ntru::types::{Error, PrivateKey};
+
+#[test]
+fn it_bit_string() {
+    let mut b = BitString::new();
+    assert_eq!(b.num_bits(), 0);
+    b.append(&[0b1010_0101, 0b0000_0011]);
+    assert_eq!(b.num_bits(), 16);
+    assert_eq!(b.leading(4), 0b0000);
+    assert_eq!(b.leading(10), 0b1110);
+
+    b.truncate(10);
+    assert_eq!(b.num_bits(), 6);
+    assert_eq!(b.as_bytes(), &[0b0010_0101]);
+    b.append_byte(0b1111_1111);
+    assert_eq!(b.num_bits(), 14);
+    assert_eq!(b.as_bytes(), &[0b1110_0101, 0b0011_1111]);
+
+    let t = b.trailing(3);
+    assert_eq!(t.num_bits(), 3);
+    assert_eq!(t.as_bytes(), &[0b101]);
+    assert_eq!(b.trailing(100), b);
+
+    b.truncate(100);
+    assert_eq!(b, BitString::new());
+}
+
+#[test]
+fn it_igf() {
+    for params in &ALL_PARAM_SETS {
+        let mut igf1 = IndexGenerator::new(b"seed", params).unwrap();
+        let mut igf2 = IndexGenerator::new(b"seed", params).unwrap();
+        let mut igf3 = IndexGenerator::new(b"other seed", params).unwrap();
+        let idx1: Vec<u16> = (0..1000).map(|_| igf1.next_index()).collect();
+        let idx2: Vec<u16> = (0..1000).map(|_| igf2.next_index()).collect();
+        let idx3: Vec<u16> = (0..1000).map(|_| igf3.next_index()).collect();
+        assert_eq!(idx1, idx2);
+        assert!(idx1 != idx3);
+        assert!(idx1.iter().all(|&i| i < params.get_n()));
+    }
+
+    assert_eq!(IndexGenerator::new(b"seed", &EncParams::default()).err(),
+               Some(Error::InvalidParam));
+}
+
+#[test]
+fn it_igf_tern_poly() {
+    let n = EES1087EP2.get_n();
+    let mut igf = IndexGenerator::new(b"seed", &EES1087EP2).unwrap();
+    let r = igf.tern_poly(120).unwrap();
+    assert_eq!(r.get_ones().len(), 120);
+    assert_eq!(r.get_neg_ones().len(), 120);
+
+    let mut used = vec![false; n as usize];
+    for &i in r.get_ones().iter().chain(r.get_neg_ones()) {
+        assert!(i < n);
+        assert!(!used[i as usize]);
+        used[i as usize] = true;
+    }
+
+    assert_eq!(igf.tern_poly(n / 2 + 1).err(), Some(Error::InvalidWeight));
+
+    let g = igf.tern_poly_with(120, 119).unwrap();
+    assert_eq!(g.get_ones().len(), 120);
+    assert_eq!(g.get_neg_ones().len(), 119);
+    assert_eq!(igf.tern_poly_with(n / 2, n / 2 + 2).err(), Some(Error::InvalidWeight));
+}
+
+#[test]
+fn it_mgf() {
+    for params in &ALL_PARAM_SETS {
+        let mask1 = mgf(b"seed", params).unwrap();
+        let mask2 = mgf(b"seed", params).unwrap();
+        let mask3 = mgf(b"other seed", params).unwrap();
+        assert_eq!(mask1, mask2);
+        assert!(mask1 != mask3);
+        assert_eq!(mask1.get_coeffs().len(), params.get_n() as usize);
+        assert!(mask1.get_coeffs().iter().all(|&c| (-1..=1).contains(&c)));
+    }
+
+    assert_eq!(mgf(b"", &EES439EP1).unwrap().get_coeffs().len(),
+               EES439EP1.get_n() as usize);
+    assert_eq!(mgf(b"seed", &EncParams::default()).err(), Some(Error::InvalidParam));
+}
+
+#[test]
+fn it_sves_matches_libntru() {
+    let rand_ctx = rand::init(&RNG_DEFAULT).unwrap();
+    let msg = b"Hello from Rust!";
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+        // On little-endian targets, ntru::encrypt() and ntru::decrypt() call libntru
+        let rand_ctx1 = rand::init_det(&RNG_CTR_DRBG, b"sves seed").unwrap();
+        let rand_ctx2 = rand::init_det(&RNG_CTR_DRBG, b"sves seed").unwrap();
+        let enc = ntru::encrypt(msg, kp.get_public(), params, &rand_ctx1).unwrap();
+        assert_eq!(low_level::encrypt(msg, kp.get_public(), params, &rand_ctx2).unwrap(), enc);
+        let dec = low_level::decrypt(&enc, kp.get_private(), kp.get_public(), params).unwrap();
+        assert_eq!(&dec[..], &msg[..]);
+
+        let enc = low_level::encrypt(b"", kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(&ntru::decrypt(&enc, &kp, params).unwrap()[..], b"");
+
+        let mut tampered = enc.to_vec();
+        tampered[0] ^= 1;
+        assert!(low_level::decrypt(&tampered, kp.get_private(), kp.get_public(), params).is_err());
+        assert_eq!(low_level::decrypt(&enc[1..], kp.get_private(), kp.get_public(), params).err(),
+                   Some(Error::InvalidCiphertextLength {
+                       expected: enc.len(),
+                       actual: enc.len() - 1,
+                   }));
+    }
+}
+
+#[test]
+fn it_private_key_matches_libntru() {
+    let rand_ctx = rand::init(&RNG_DEFAULT).unwrap();
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+
+        // On little-endian targets, PrivateKey::export() and import() call libntru
+        let exported = kp.get_private().export(params);
+        assert_eq!(low_level::export_private(kp.get_private(), params), exported);
+        assert_eq!(low_level::import_private(&exported, params).unwrap(),
+                   PrivateKey::import(&exported).unwrap());
+    }
+
+    let exported = low_level::export_private(&Default::default(), &EES439EP1);
+    assert_eq!(low_level::import_private(&exported, &EES439EP1).err(), Some(Error::InvalidKey));
+    assert_eq!(low_level::import_private(&[0; 3], &EES439EP1).err(), Some(Error::InvalidKey));
+}
@@ -0,0 +1,44 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "metrics")]
+
+extern crate ntru;
+
+use ntru::rand::{self, RNG_CTR_DRBG};
+use ntru::encparams::EES401EP1;
+use ntru::metrics;
+
+// The counters are global, so a single test checks them all
+#[test]
+fn it_metrics() {
+    metrics::reset();
+    assert_eq!(metrics::stats(), metrics::Stats::default());
+
+    let rand_ctx = rand::init_det(&RNG_CTR_DRBG, b"metrics test").unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    let msg = b"Hello from Rust!";
+    let enc = ntru::encrypt(msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+    assert_eq!(&ntru::decrypt(&enc, &kp, &EES401EP1).unwrap()[..], &msg[..]);
+    assert!(ntru::decrypt(&[0u8; 4], &kp, &EES401EP1).is_err());
+    let (_, invertible) = kp.get_private().get_t().invert(EES401EP1.get_q() - 1);
+    assert!(invertible);
+
+    let stats = metrics::stats();
+    assert_eq!(stats.key_generations, 1);
+    assert_eq!(stats.key_generation_failures, 0);
+    assert_eq!(stats.encryptions, 1);
+    assert_eq!(stats.encryption_failures, 0);
+    assert_eq!(stats.encryption_rounds.iter().sum::<u64>(), 1);
+    assert_eq!(stats.decryptions, 2);
+    assert_eq!(stats.decryption_failures, 1);
+    assert_eq!(stats.inversions, 1);
+    assert_eq!(stats.failed_inversions, 0);
+
+    metrics::reset();
+    assert_eq!(metrics::stats(), metrics::Stats::default());
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "nist-ntru")]
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+#[cfg(feature = "kat")]
+extern crate sha2;
+use ntru::rand::{RNG_DEFAULT, RNG_CTR_DRBG};
+use ntru::nistntru::{PublicKey, PrivateKey, NTRUHPS2048677, NTRUHRSS701, ALL_NIST_PARAM_SETS};
+use ntru::types::Error;
+
+#[test]
+fn it_lengths() {
+    assert_eq!(NTRUHPS2048677.public_len(), 930);
+    assert_eq!(NTRUHPS2048677.private_len(), 1234);
+    assert_eq!(NTRUHPS2048677.ciphertext_len(), 930);
+    assert_eq!(NTRUHPS2048677.shared_secret_len(), 32);
+}
+
+#[test]
+fn it_hrss_lengths() {
+    assert_eq!(NTRUHRSS701.public_len(), 1138);
+    assert_eq!(NTRUHRSS701.private_len(), 1450);
+    assert_eq!(NTRUHRSS701.ciphertext_len(), 1138);
+    assert!(NTRUHRSS701.is_hrss());
+    assert!(!NTRUHPS2048677.is_hrss());
+}
+
+#[test]
+fn it_encapsulate() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &ALL_NIST_PARAM_SETS {
+        let kp = ntru::nistntru::generate_key_pair(params, &rand_ctx).unwrap();
+        assert_eq!(kp.get_public().export().len(), params.public_len());
+        assert_eq!(kp.get_private().export().len(), params.private_len());
+
+        let (ciphertext, shared1) = ntru::nistntru::encapsulate(kp.get_public(), &rand_ctx)
+            .unwrap();
+        assert_eq!(ciphertext.len(), params.ciphertext_len());
+        let shared2 = ntru::nistntru::decapsulate(&ciphertext, kp.get_private()).unwrap();
+        assert_eq!(shared1, shared2);
+    }
+}
+
+#[test]
+fn it_implicit_rejection() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::nistntru::generate_key_pair(&NTRUHPS2048677, &rand_ctx).unwrap();
+    let (ciphertext, shared) = ntru::nistntru::encapsulate(kp.get_public(), &rand_ctx).unwrap();
+
+    let mut modified = ciphertext.to_vec();
+    modified[0] ^= 1;
+    let rejected1 = ntru::nistntru::decapsulate(&modified, kp.get_private()).unwrap();
+    let rejected2 = ntru::nistntru::decapsulate(&modified, kp.get_private()).unwrap();
+    assert!(rejected1 != shared);
+    assert_eq!(rejected1, rejected2);
+
+    assert_eq!(ntru::nistntru::decapsulate(&ciphertext[1..], kp.get_private()),
+               Err(Error::InvalidCiphertextLength {
+                   expected: NTRUHPS2048677.ciphertext_len(),
+                   actual: NTRUHPS2048677.ciphertext_len() - 1,
+               }));
+}
+
+#[test]
+fn it_import_export() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::nistntru::generate_key_pair(&NTRUHPS2048677, &rand_ctx).unwrap();
+
+    let public = PublicKey::import(kp.get_public().export(), &NTRUHPS2048677).unwrap();
+    let private = PrivateKey::import(kp.get_private().export(), &NTRUHPS2048677).unwrap();
+    assert_eq!(&public, kp.get_public());
+    assert_eq!(&private, kp.get_private());
+
+    assert_eq!(PublicKey::import(&[0u8; 10], &NTRUHPS2048677), Err(Error::InvalidKey));
+    assert_eq!(PrivateKey::import(&[0u8; 10], &NTRUHPS2048677), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_deterministic() {
+    let seed = b"ntruhps2048677 deterministic test";
+    let rand_ctx1 = ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap();
+    let rand_ctx2 = ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap();
+    let kp1 = ntru::nistntru::generate_key_pair(&NTRUHPS2048677, &rand_ctx1).unwrap();
+    let kp2 = ntru::nistntru::generate_key_pair(&NTRUHPS2048677, &rand_ctx2).unwrap();
+    assert_eq!(kp1, kp2);
+}
+
+#[cfg(feature = "kat")]
+#[test]
+fn it_nist_kat() {
+    use sha2::{Digest, Sha256};
+    use ntru::kat::{NistDrbg, NIST_ENTROPY_INPUT};
+
+    // The 100 records of the response files of PQCgenKAT_kem, compared by their SHA-256
+    let digests = [(&NTRUHPS2048677,
+                    "0e1d2eccfbc6e4f4d6f139b21de27417316202a5c113602d25704316aebb9303"),
+                   (&NTRUHRSS701,
+                    "1e7c8e02f7dc1a9796332d60d1b08995fff5dfe81f2ae7394ec2f4816dedf4b6")];
+    for &(params, digest) in &digests {
+        let mut master = NistDrbg::new(&NIST_ENTROPY_INPUT);
+        let mut rsp = format!("# {}\n\n", params.get_name());
+        for count in 0..100 {
+            let mut seed = [0u8; 48];
+            master.randombytes(&mut seed);
+            let rand_ctx = ntru::rand::init_source(NistDrbg::new(&seed)).unwrap();
+            let kp = ntru::nistntru::generate_key_pair(params, &rand_ctx).unwrap();
+            let (ct, ss) = ntru::nistntru::encapsulate(kp.get_public(), &rand_ctx).unwrap();
+            assert_eq!(ntru::nistntru::decapsulate(&ct, kp.get_private()).unwrap(), ss);
+            rsp.push_str(&format!("count = {}\nseed = {}\npk = {}\nsk = {}\nct = {}\nss = {}\n\n",
+                                  count,
+                                  hex(&seed),
+                                  hex(kp.get_public().export()),
+                                  hex(kp.get_private().export()),
+                                  hex(&ct),
+                                  hex(&ss)));
+        }
+        assert_eq!(format!("{:x}", Sha256::digest(rsp.as_bytes())), digest);
+    }
+}
+
+#[cfg(feature = "kat")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
@@ -0,0 +1,100 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "no-panic")]
+
+extern crate ntru;
+use std::convert::TryFrom;
+use ntru::encparams::{EES401EP1, EES401EP2};
+use ntru::rand::{RNG_DEFAULT, RNG_CTR_DRBG};
+use ntru::types::{IntPoly, PrivPoly, PrivateKey, PublicKey, TernPoly, Error};
+
+/// Malformed inputs: empty, truncated, with a header of huge or invalid `N` and `q`, and random
+fn malformed_inputs() -> Vec<Vec<u8>> {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let mut inputs = vec![vec![],
+                          vec![0x01],
+                          vec![0x01, 0x91, 0x08, 0x00],
+                          vec![0xFF, 0xFF, 0xFF, 0xFF, 0x01, 0x02],
+                          vec![0x01, 0x91, 0x08, 0x01, 0x00],
+                          vec![0x01, 0x91, 0x00, 0x00, 0x01]];
+    for &len in &[1, 5, 100, 556, 2000] {
+        inputs.push(ntru::rand::generate(len, &rand_ctx).unwrap().into_vec());
+    }
+    inputs
+}
+
+#[test]
+fn it_imports_do_not_panic() {
+    for input in malformed_inputs() {
+        assert!(PublicKey::try_from(&input[..]).is_err());
+        assert!(PublicKey::import(&input).is_err());
+        assert!(PrivateKey::try_from(&input[..]).is_err());
+        assert!(PrivateKey::import(&input).is_err());
+        assert!(PrivateKey::import_boxed(&input).is_err());
+        assert!(PublicKey::import_any(&input).is_err());
+        assert!(PrivateKey::import_any(&input).is_err());
+        assert!(PublicKey::import_versioned(&input).is_err());
+        assert!(PrivateKey::import_versioned(&input).is_err());
+        assert!(ntru::x509::public_from_spki(&input).is_err());
+        assert!(ntru::x509::private_from_pkcs8(&input).is_err());
+        assert!(ntru::x509::public_from_certificate(&input).is_err());
+        assert!(ntru::bouncycastle::import_public(&input, &EES401EP2).is_err() ||
+                input.len() == EES401EP2.enc_len() as usize);
+        assert!(ntru::bouncycastle::import_key_pair(&input, &EES401EP2).is_err());
+        assert!(PublicKey::import_from(&mut &input[..]).is_err());
+        assert!(PrivateKey::import_from(&mut &input[..]).is_err());
+
+        let text = String::from_utf8_lossy(&input);
+        assert!(PublicKey::from_hex(&text).is_err());
+        assert!(PublicKey::from_base64(&text).is_err());
+        assert!(PublicKey::from_bech32(&text).is_err());
+        assert!(PublicKey::from_base58(&text).is_err());
+    }
+}
+
+#[test]
+fn it_decrypt_does_not_panic() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES401EP1, &rand_ctx).unwrap();
+    for input in malformed_inputs() {
+        assert!(ntru::decrypt(&input, &kp, &EES401EP1).is_err());
+        assert!(ntru::cca::decapsulate(&input, &kp, &EES401EP1).is_ok() ||
+                input.len() != EES401EP1.enc_len() as usize);
+        #[cfg(feature = "etm")]
+        assert!(ntru::etm::decrypt(&input, &kp, &EES401EP1).is_err());
+    }
+    assert_eq!(ntru::rand::init_det(&RNG_CTR_DRBG, &[]).err(), Some(Error::InvalidParam));
+}
+
+#[test]
+fn it_try_variants() {
+    let mut poly = IntPoly::new(&[1, 2, 3]);
+    assert_eq!(poly.try_set_coeff(3, 1), Err(Error::InvalidDegree));
+    assert_eq!(poly.try_set_coeffs(&[1, 2, 3, 4]), Err(Error::InvalidDegree));
+    assert_eq!(poly.get_coeffs(), &[1, 2, 3]);
+    assert_eq!(poly.try_set_coeff(2, 5), Ok(()));
+    assert_eq!(poly.try_set_coeffs(&[7]), Ok(()));
+    assert_eq!(poly.get_coeffs(), &[7, 0, 0]);
+
+    assert_eq!(IntPoly::try_from_arr(&[], 401, 2048).err(), Some(Error::InvalidEncoding));
+    assert_eq!(IntPoly::try_from_arr(&[0; 16], 401, 2047).err(), Some(Error::InvalidParam));
+    assert_eq!(IntPoly::try_from_arr(&[0; 552], 401, 2048).unwrap().get_coeffs().len(), 401);
+
+    assert_eq!(TernPoly::try_new(1499, &[0; 500], &[1]).err(), Some(Error::InvalidWeight));
+    assert_eq!(TernPoly::try_new(5, &[0], &[5]).err(), Some(Error::InvalidWeight));
+    assert!(TernPoly::try_new(5, &[0], &[4]).is_ok());
+
+    let mut large = IntPoly::new(&[1; 2000]);
+    large.mod_mask(2048 - 1);
+    assert_eq!(IntPoly::try_new(large.get_coeffs()).err(), Some(Error::InvalidDegree));
+    assert_eq!(large.mult_int_16(&large, 2048 - 1).err(), Some(Error::InvalidDegree));
+
+    let tern = PrivPoly::new_with_tern_poly(TernPoly::new(5, &[0], &[1]));
+    assert!(tern.try_get_poly_tern().is_ok());
+    assert_eq!(tern.try_get_poly_prod().err(), Some(Error::InvalidParam));
+}
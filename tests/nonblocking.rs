@@ -0,0 +1,45 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "tokio")]
+
+extern crate ntru;
+extern crate tokio;
+
+use tokio::runtime::Builder;
+use ntru::rand::{RandGen, RNG_DEFAULT};
+use ntru::encparams::{EncParams, EES401EP1};
+use ntru::types::{KeyPair, PublicKey};
+use ntru::nonblocking::{generate_key_pair_async, encrypt_async, decrypt_async};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn it_send_sync() {
+    assert_send_sync::<EncParams>();
+    assert_send_sync::<RandGen>();
+    assert_send_sync::<PublicKey>();
+    assert_send_sync::<KeyPair>();
+}
+
+#[test]
+fn it_async() {
+    let runtime = Builder::new_current_thread().build().unwrap();
+    let kp = runtime.block_on(generate_key_pair_async(&EES401EP1, &RNG_DEFAULT)).unwrap();
+
+    let msg = b"Hello from Rust!";
+    let encrypted = runtime.block_on(encrypt_async(msg, kp.get_public(), &EES401EP1,
+                                                   &RNG_DEFAULT))
+        .unwrap();
+    let decrypted = runtime.block_on(decrypt_async(&encrypted, &kp, &EES401EP1)).unwrap();
+    assert_eq!(&msg[..], &decrypted[..]);
+
+    let spawned = runtime.spawn(decrypt_async(&encrypted, &kp, &EES401EP1));
+    assert_eq!(&runtime.block_on(spawned).unwrap().unwrap()[..], &msg[..]);
+
+    assert!(runtime.block_on(decrypt_async(&[0u8; 10], &kp, &EES401EP1)).is_err());
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "ntru-prime")]
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+#[cfg(feature = "kat")]
+extern crate sha2;
+use ntru::rand::{RNG_DEFAULT, RNG_CTR_DRBG};
+use ntru::ntruprime::{PublicKey, PrivateKey, SNTRUP761, ALL_PRIME_PARAM_SETS};
+use ntru::types::Error;
+
+#[test]
+fn it_lengths() {
+    assert_eq!(SNTRUP761.public_len(), 1158);
+    assert_eq!(SNTRUP761.private_len(), 1763);
+    assert_eq!(SNTRUP761.ciphertext_len(), 1039);
+    assert_eq!(SNTRUP761.shared_secret_len(), 32);
+}
+
+#[test]
+fn it_encapsulate() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &ALL_PRIME_PARAM_SETS {
+        let kp = ntru::ntruprime::generate_key_pair(params, &rand_ctx).unwrap();
+        assert_eq!(kp.get_public().export().len(), params.public_len());
+        assert_eq!(kp.get_private().export().len(), params.private_len());
+        assert_eq!(&kp.get_private().get_public(), kp.get_public());
+
+        let (ciphertext, shared1) = ntru::ntruprime::encapsulate(kp.get_public(), &rand_ctx)
+            .unwrap();
+        assert_eq!(ciphertext.len(), params.ciphertext_len());
+        let shared2 = ntru::ntruprime::decapsulate(&ciphertext, kp.get_private()).unwrap();
+        assert_eq!(shared1, shared2);
+    }
+}
+
+#[test]
+fn it_implicit_rejection() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx).unwrap();
+    let (ciphertext, shared) = ntru::ntruprime::encapsulate(kp.get_public(), &rand_ctx).unwrap();
+
+    let mut modified = ciphertext.to_vec();
+    modified[0] ^= 1;
+    let rejected1 = ntru::ntruprime::decapsulate(&modified, kp.get_private()).unwrap();
+    let rejected2 = ntru::ntruprime::decapsulate(&modified, kp.get_private()).unwrap();
+    assert!(rejected1 != shared);
+    assert_eq!(rejected1, rejected2);
+
+    assert_eq!(ntru::ntruprime::decapsulate(&ciphertext[1..], kp.get_private()),
+               Err(Error::InvalidCiphertextLength {
+                   expected: SNTRUP761.ciphertext_len(),
+                   actual: SNTRUP761.ciphertext_len() - 1,
+               }));
+}
+
+#[test]
+fn it_import_export() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx).unwrap();
+
+    let public = PublicKey::import(kp.get_public().export(), &SNTRUP761).unwrap();
+    let private = PrivateKey::import(kp.get_private().export(), &SNTRUP761).unwrap();
+    assert_eq!(&public, kp.get_public());
+    assert_eq!(&private, kp.get_private());
+
+    assert_eq!(PublicKey::import(&[0u8; 10], &SNTRUP761), Err(Error::InvalidKey));
+    assert_eq!(PrivateKey::import(&[0u8; 10], &SNTRUP761), Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_deterministic() {
+    let seed = b"sntrup761 deterministic test";
+    let rand_ctx1 = ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap();
+    let rand_ctx2 = ntru::rand::init_det(&RNG_CTR_DRBG, seed).unwrap();
+    let kp1 = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx1).unwrap();
+    let kp2 = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx2).unwrap();
+    assert_eq!(kp1, kp2);
+}
+
+#[cfg(feature = "kat")]
+#[test]
+fn it_nist_kat() {
+    use sha2::{Digest, Sha256};
+    use ntru::kat::{NistDrbg, NIST_ENTROPY_INPUT};
+
+    // The 100 records of the response file of PQCgenKAT_kem, compared by their SHA-256
+    let mut master = NistDrbg::new(&NIST_ENTROPY_INPUT);
+    let mut rsp = format!("# {}\n\n", SNTRUP761.get_name());
+    for count in 0..100 {
+        let mut seed = [0u8; 48];
+        master.randombytes(&mut seed);
+        let rand_ctx = ntru::rand::init_source(NistDrbg::new(&seed)).unwrap();
+        let kp = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx).unwrap();
+        let (ct, ss) = ntru::ntruprime::encapsulate(kp.get_public(), &rand_ctx).unwrap();
+        assert_eq!(ntru::ntruprime::decapsulate(&ct, kp.get_private()).unwrap(), ss);
+        rsp.push_str(&format!("count = {}\nseed = {}\npk = {}\nsk = {}\nct = {}\nss = {}\n\n",
+                              count,
+                              hex(&seed),
+                              hex(kp.get_public().export()),
+                              hex(kp.get_private().export()),
+                              hex(&ct),
+                              hex(&ss)));
+    }
+    assert_eq!(format!("{:x}", Sha256::digest(rsp.as_bytes())),
+               "88d9f5a108ff49078e0ad191c510e883558c131d8a825363b3327e610b22e93d");
+}
+
+#[cfg(feature = "kat")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
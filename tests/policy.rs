@@ -0,0 +1,44 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(feature = "deny-deprecated-params")]
+
+extern crate ntru;
+use ntru::encparams::{EncParams, EES439EP1, EES541EP1, EES593EP1, EES613EP1};
+use ntru::rand::RNG_DEFAULT;
+use ntru::types::Error;
+
+#[test]
+fn it_refuse_deprecated() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES439EP1, EES593EP1, EES541EP1] {
+        assert!(!params.is_allowed());
+        assert_eq!(ntru::generate_key_pair(params, &rand_ctx).unwrap_err(),
+                   Error::DeprecatedParamSet);
+    }
+
+    // A key pair of an allowed parameter set cannot be used with a refused one either
+    let kp = ntru::generate_key_pair(&EES613EP1, &rand_ctx).unwrap();
+    assert_eq!(ntru::encrypt(b"Hello", kp.get_public(), &EES439EP1, &rand_ctx).unwrap_err(),
+               Error::DeprecatedParamSet);
+    assert_eq!(ntru::generate_public(&EES439EP1, kp.get_private(), &rand_ctx).unwrap_err(),
+               Error::DeprecatedParamSet);
+    assert_eq!(ntru::encrypt_batch(&[b"Hello"], kp.get_public(), &EES541EP1, &rand_ctx)
+                   .unwrap_err(),
+               Error::DeprecatedParamSet);
+}
+
+#[test]
+fn it_allow_recommended() {
+    assert_eq!(EncParams::all().filter(|params| params.is_allowed()).count(), 12);
+    assert!(EncParams::recommended().skip(1).all(|params| params.is_allowed()));
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&EES613EP1, &rand_ctx).unwrap();
+    let encrypted = ntru::encrypt(b"Hello", kp.get_public(), &EES613EP1, &rand_ctx).unwrap();
+    assert_eq!(&ntru::decrypt(&encrypted, &kp, &EES613EP1).unwrap()[..], b"Hello");
+}
@@ -6,9 +6,9 @@
 #![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
     unused_qualifications, unused_results, variant_size_differences)]
 
-#[macro_use]
 extern crate ntru;
-use ntru::types::{MAX_DEGREE, MAX_ONES, IntPoly, TernPoly, ProdPoly, PrivPoly};
+use ntru::types::{MAX_DEGREE, MAX_ONES, KARATSUBA_MIN_N, IntPoly, TernPoly, ProdPoly, PrivPoly,
+                  CoeffDistribution, Error};
 use ntru::encparams::EES1087EP1;
 use ntru::rand::{RNG_DEFAULT, RandContext};
 
@@ -22,7 +22,8 @@ fn ntru_mult_int_nomod(a: &IntPoly, b: &IntPoly) -> IntPoly {
     for k in 0..n {
         let mut ck = 0i32;
         for i in 0..n {
-            ck += b.get_coeffs()[i] as i32 * a.get_coeffs()[((n + k - i) % n)] as i32;
+            ck = ck.wrapping_add(b.get_coeffs()[i] as i32 *
+                                 a.get_coeffs()[(n + k - i) % n] as i32);
         }
         coeffs.push(ck as i16);
     }
@@ -39,7 +40,7 @@ fn u8_arr_to_u16(arr: &[u8]) -> u16 {
 
 fn ntru_priv_to_int(a: &PrivPoly, modulus: u16) -> IntPoly {
     if a.is_product() {
-        a.get_poly_prod().to_int_poly(modulus)
+        a.get_poly_prod().to_int_poly(modulus).unwrap()
     } else {
         a.get_poly_tern().to_int_poly()
     }
@@ -50,7 +51,7 @@ fn rand_int(n: u16, pow2q: u16, rand_ctx: &RandContext) -> IntPoly {
     let shift = if pow2q < 16 {
         16 - pow2q
     } else {
-        u16::max_value() - pow2q + 16
+        u16::MAX - pow2q + 16
     };
 
     let mut coeffs = vec![0i16; n as usize];
@@ -66,7 +67,7 @@ fn verify_inverse(a: &PrivPoly, b: &IntPoly, modulus: u16) -> bool {
     let new_coeff = a_int.get_coeffs()[0] + 1;
     a_int.set_coeff(0, new_coeff);
 
-    let (mut c, _) = a_int.mult_int(b, modulus - 1);
+    let mut c = a_int.mult_int(b, modulus - 1).unwrap();
     c.mod_mask(modulus - 1);
     c.equals1()
 }
@@ -76,7 +77,7 @@ fn it_mult_int() {
     // Multiplication modulo q
     let a1 = IntPoly::new(&[-1, 1, 1, 0, -1, 0, 1, 0, 0, 1, -1]);
     let b1 = IntPoly::new(&[14, 11, 26, 24, 14, 16, 30, 7, 25, 6, 19]);
-    let (c1, _) = a1.mult_int(&b1, 32 - 1);
+    let c1 = a1.mult_int(&b1, 32 - 1).unwrap();
 
     let c1_exp = IntPoly::new(&[3, 25, -10, 21, 10, 7, 6, 7, 5, 29, -7]);
     assert!(c1_exp.equals_mod(&c1, 32));
@@ -85,7 +86,7 @@ fn it_mult_int() {
     let a2 = IntPoly::new(&[1278, 1451, 850, 1071, 942]);
     let b2 = IntPoly::new(&[571, 52, 1096, 1800, 662]);
 
-    let (c2, _) = a2.mult_int(&b2, 2048 - 1);
+    let c2 = a2.mult_int(&b2, 2048 - 1).unwrap();
     let mut c2_exp = ntru_mult_int_nomod(&a2, &b2);
     c2_exp.mod_mask(2048 - 1);
 
@@ -99,16 +100,31 @@ fn it_mult_int() {
         let mut n = u8_arr_to_u16(&n_arr);
         n = 100 + (n % (MAX_DEGREE - 100) as u16);
 
-        let a3 = IntPoly::rand(n, 11, &rand_ctx);
-        let b3 = IntPoly::rand(n, 11, &rand_ctx);
+        let a3 = IntPoly::rand(n, 11, &rand_ctx).unwrap();
+        let b3 = IntPoly::rand(n, 11, &rand_ctx).unwrap();
         let mut c3_exp = ntru_mult_int_nomod(&a3, &b3);
         c3_exp.mod_mask(2048 - 1);
 
-        let (c3, _) = a3.mult_int(&b3, 2048 - 1);
+        let c3 = a3.mult_int(&b3, 2048 - 1).unwrap();
         assert!(c3_exp.equals_mod(&c3, 2048));
     }
 }
 
+#[test]
+fn it_mult_int_karatsuba() {
+    for n in &[KARATSUBA_MIN_N as u16, 1087, 1171, 1499] {
+        let a_coeffs: Vec<i16> = (0..*n).map(|i| ((i as i32 * 7919) % 61 - 30) as i16).collect();
+        let b_coeffs: Vec<i16> = (0..*n).map(|i| ((i as i32 * 104729) % 64) as i16).collect();
+        let a = IntPoly::new(&a_coeffs);
+        let b = IntPoly::new(&b_coeffs);
+
+        let mut c_exp = ntru_mult_int_nomod(&a, &b);
+        c_exp.mod_mask(2048 - 1);
+        let c = a.mult_int(&b, 2048 - 1).unwrap();
+        assert!(c_exp.equals_mod(&c, 2048));
+    }
+}
+
 #[test]
 fn it_mult_tern() {
     let rng = RNG_DEFAULT;
@@ -118,8 +134,8 @@ fn it_mult_tern() {
     let b = rand_int(11, 5, &rand_ctx);
 
     let a_int = a.to_int_poly();
-    let (c_int, _) = a_int.mult_int(&b, 32 - 1);
-    let (c_tern, _) = b.mult_tern(&a, 32 - 1);
+    let c_int = a_int.mult_int(&b, 32 - 1).unwrap();
+    let c_tern = b.mult_tern(&a, 32 - 1).unwrap();
 
     assert!(c_tern.equals_mod(&c_int, 32));
 
@@ -143,7 +159,7 @@ fn it_mult_tern() {
         let a_int = a.to_int_poly();
 
         let c_int = ntru_mult_int_nomod(&a_int, &b);
-        let (c_tern, _) = b.mult_tern(&a, 2048 - 1);
+        let c_tern = b.mult_tern(&a, 2048 - 1).unwrap();
 
         assert!(c_tern.equals_mod(&c_int, 2048));
     }
@@ -160,10 +176,10 @@ fn it_mult_prod() {
     for _ in 0..10 {
         let a = ProdPoly::rand(853, 8, 8, 8, 9, &rand_ctx).unwrap();
         let b = rand_int(853, 1 << log_modulus, &rand_ctx);
-        let (c_prod, _) = b.mult_prod(&a, modulus - 1);
+        let c_prod = b.mult_prod(&a, modulus - 1).unwrap();
 
-        let a_int = a.to_int_poly(modulus);
-        let (c_int, _) = a_int.mult_int(&b, modulus - 1);
+        let a_int = a.to_int_poly(modulus).unwrap();
+        let c_int = a_int.mult_int(&b, modulus - 1).unwrap();
 
         assert!(c_prod.equals_mod(&c_int, log_modulus));
     }
@@ -222,3 +238,207 @@ fn it_arr() {
 
     assert_eq!(p1, p2);
 }
+
+#[test]
+fn it_add_sub() {
+    let a = IntPoly::new(&[1, -2, 3, 32767, 0]);
+    let b = IntPoly::new(&[4, 5, -6, 1, 7]);
+    let sum = IntPoly::new(&[5, 3, -3, -32768, 7]);
+    let diff = IntPoly::new(&[-3, -7, 9, 32766, -7]);
+
+    assert_eq!(&a + &b, sum);
+    assert_eq!(&a - &b, diff);
+    assert_eq!(a.clone() + &b, sum);
+    assert_eq!(a.clone() - b.clone(), diff);
+
+    let mut c = a.clone();
+    c += &b;
+    assert_eq!(c, sum);
+    c -= &b;
+    assert_eq!(c, a);
+}
+
+#[test]
+fn it_mult_variants() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+
+    for n in &[11u16, 401, 743, 1499] {
+        let a = TernPoly::rand(*n, n / 3, n / 3, &rand_ctx).unwrap();
+        let b_coeffs: Vec<i16> = (0..*n).map(|i| ((i as i32 * 7919) % 2048) as i16).collect();
+        let b = IntPoly::new(&b_coeffs);
+
+        let c_tern = b.mult_tern(&a, 2048 - 1).unwrap();
+        for c in &[b.mult_tern_32(&a, 2048 - 1),
+                   b.mult_tern_64(&a, 2048 - 1),
+                   b.mult_tern_native(&a, 2048 - 1)] {
+            assert!(c_tern.equals_mod(c.as_ref().unwrap(), 2048));
+        }
+        #[cfg(SSE3)]
+        assert!(c_tern.equals_mod(&b.mult_tern_sse(&a, 2048 - 1).unwrap(), 2048));
+
+        let a_int = a.to_int_poly();
+        let c_int = b.mult_int(&a_int, 2048 - 1).unwrap();
+        assert!(c_tern.equals_mod(&c_int, 2048));
+        for c in &[b.mult_int_16(&a_int, 2048 - 1),
+                   b.mult_int_64(&a_int, 2048 - 1),
+                   b.mult_int_karatsuba(&a_int, 2048 - 1)] {
+            assert!(c_int.equals_mod(c.as_ref().unwrap(), 2048));
+        }
+    }
+
+    let a = IntPoly::new(&[1, 2, 3]);
+    let b = IntPoly::new(&[1, 2]);
+    assert_eq!(a.mult_int(&b, 31), Err(Error::InvalidDegree));
+    assert_eq!(a.mult_int_karatsuba(&b, 31), Err(Error::InvalidDegree));
+    assert_eq!(a.mult_int_16(&b, 31), Err(Error::InvalidDegree));
+    let tern = TernPoly::new(2, &[0], &[1]);
+    assert_eq!(a.mult_tern(&tern, 31), Err(Error::InvalidDegree));
+    assert_eq!(a.mult_tern_native(&tern, 31), Err(Error::InvalidDegree));
+    let prod = ProdPoly::new(2, tern.clone(), tern.clone(), tern.clone());
+    assert_eq!(a.mult_prod(&prod, 31), Err(Error::InvalidDegree));
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+    assert_eq!(a.mult_priv(&PrivPoly::new_with_prod_poly(prod), 31), Err(Error::InvalidDegree));
+}
+
+#[test]
+fn it_inv_variants() {
+    let a1 = PrivPoly::new_with_tern_poly(TernPoly::new(11, &[1, 2, 6, 9], &[0, 3, 4, 10]));
+    let (b1, invertible) = a1.invert_native(32 - 1);
+    assert!(invertible);
+    assert!(verify_inverse(&a1, &b1, 32));
+
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let mut num_invertible = 0u16;
+    while num_invertible < 3 {
+        let a2 = PrivPoly::new_with_tern_poly(TernPoly::rand(853, 100, 100, &rand_ctx).unwrap());
+        let (b, invertible) = a2.invert(2048 - 1);
+
+        for &(ref b_var, invertible_var) in &[a2.invert_32(2048 - 1),
+                                              a2.invert_64(2048 - 1),
+                                              a2.invert_native(2048 - 1)] {
+            assert_eq!(invertible_var, invertible);
+            if invertible {
+                assert!(b.equals_mod(b_var, 2048));
+            }
+        }
+        if invertible {
+            num_invertible += 1;
+        }
+    }
+
+    let a3 = PrivPoly::new_with_tern_poly(TernPoly::new(11, &[3, 10], &[0, 6, 8]));
+    let (_, invertible) = a3.invert_native(32 - 1);
+    assert!(!invertible);
+}
+
+#[test]
+fn it_prod_to_int() {
+    let f1 = TernPoly::new(11, &[1, 4], &[2, 7]);
+    let f2 = TernPoly::new(11, &[0, 9], &[5]);
+    let f3 = TernPoly::new(11, &[3], &[6, 10]);
+    let a = ProdPoly::new(11, f1.clone(), f2.clone(), f3.clone());
+
+    let mut expected = ntru_mult_int_nomod(&f1.to_int_poly(), &f2.to_int_poly());
+    expected += &f3.to_int_poly();
+    assert!(a.to_int_poly(2048).unwrap().equals_mod(&expected, 2048));
+
+    assert_eq!(a.to_int_poly(0), Err(Error::InvalidParam));
+    assert_eq!(a.to_int_poly(2047), Err(Error::InvalidParam));
+    let short = ProdPoly::new(11, f1.clone(), f2.clone(), TernPoly::new(5, &[3], &[4]));
+    assert_eq!(short.to_int_poly(2048), Err(Error::InvalidDegree));
+    let out_of_range = ProdPoly::new(11, f1.clone(), f2.clone(), TernPoly::new(11, &[30], &[4]));
+    assert_eq!(out_of_range.to_int_poly(2048), Err(Error::InvalidWeight));
+
+    let b = IntPoly::new(&[14, 11, 26, 24, 14, 16, 30, 7, 25, 6, 19]);
+    let c_exp = b.mult_int(&a.to_int_poly(2048).unwrap(), 2048 - 1).unwrap();
+    for c in &[a.mult_int(&b, 2048 - 1), a.mult_int_native(&b, 2048 - 1)] {
+        assert!(c.as_ref().unwrap().equals_mod(&c_exp, 2048));
+    }
+
+    #[cfg(not(feature = "avoid-hamming-wt-patent"))]
+    {
+        let a_priv = PrivPoly::new_with_prod_poly(a);
+        let (inv, invertible) = a_priv.invert_native(2048 - 1);
+        if invertible {
+            assert!(verify_inverse(&a_priv, &inv, 2048));
+        }
+    }
+}
+
+#[test]
+fn it_rand_int() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+
+    let a = IntPoly::rand(743, 11, &rand_ctx).unwrap();
+    assert_eq!(a.get_coeffs().len(), 743);
+    assert!(a.get_coeffs().iter().all(|c| *c >= 0 && *c < 2048));
+    assert!(a.get_coeffs().iter().any(|c| *c != 0));
+
+    let b = IntPoly::rand_with(743, 11, CoeffDistribution::Centered, &rand_ctx).unwrap();
+    assert!(b.get_coeffs().iter().all(|c| *c >= -1024 && *c < 1024));
+    assert!(b.get_coeffs().iter().any(|c| *c < 0));
+
+    let distribution = CoeffDistribution::Ternary {
+        ones: 100,
+        neg_ones: 90,
+    };
+    let c = IntPoly::rand_with(743, 11, distribution, &rand_ctx).unwrap();
+    assert_eq!(c.get_coeffs().iter().filter(|c| **c == 1).count(), 100);
+    assert_eq!(c.get_coeffs().iter().filter(|c| **c == -1).count(), 90);
+    assert_eq!(c.get_coeffs().iter().filter(|c| **c == 0).count(), 743 - 190);
+
+    assert_eq!(IntPoly::rand(743, 0, &rand_ctx), Err(Error::InvalidParam));
+    assert_eq!(IntPoly::rand(743, 17, &rand_ctx), Err(Error::InvalidParam));
+}
+
+#[test]
+fn it_rand_errors() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+
+    assert_eq!(IntPoly::rand(u16::MAX, 11, &rand_ctx), Err(Error::InvalidDegree));
+    assert_eq!(IntPoly::rand(MAX_DEGREE as u16, 11, &rand_ctx), Err(Error::InvalidDegree));
+    assert_eq!(IntPoly::rand(0, 11, &rand_ctx), Err(Error::InvalidDegree));
+    assert_eq!(IntPoly::rand(743, 16, &rand_ctx), Err(Error::InvalidParam));
+    let centered = IntPoly::rand_with(743, 16, CoeffDistribution::Centered, &rand_ctx).unwrap();
+    assert_eq!(centered.get_coeffs().len(), 743);
+    let uniform = IntPoly::rand(743, 15, &rand_ctx).unwrap();
+    assert!(uniform.get_coeffs().iter().all(|c| *c >= 0));
+
+    assert_eq!(TernPoly::rand(0, 0, 0, &rand_ctx), Err(Error::InvalidDegree));
+    assert_eq!(TernPoly::rand(MAX_DEGREE as u16, 1, 1, &rand_ctx),
+               Err(Error::InvalidDegree));
+    assert_eq!(TernPoly::rand(1499, MAX_ONES as u16 + 1, 1, &rand_ctx),
+               Err(Error::InvalidWeight));
+    assert_eq!(TernPoly::rand(11, 6, 6, &rand_ctx), Err(Error::InvalidWeight));
+    assert!(TernPoly::rand(11, 6, 5, &rand_ctx).is_ok());
+
+    assert_eq!(ProdPoly::rand(853, 8, 8, 8, 9, &rand_ctx).map(|_| ()), Ok(()));
+    assert_eq!(ProdPoly::rand(853, 8, MAX_ONES as u16 + 1, 8, 9, &rand_ctx).map(|_| ()),
+               Err(Error::InvalidWeight));
+}
+
+#[test]
+fn it_large_poly() {
+    assert_eq!(IntPoly::try_new(&[0; MAX_DEGREE]), Err(Error::InvalidDegree));
+    assert!(IntPoly::try_new(&[0; MAX_DEGREE - 1]).is_ok());
+
+    let mut a = IntPoly::new(&[-1; 2000]);
+    a.mult_fac(3);
+    a.mod_center(2048);
+    a.mod3();
+    a.mod_mask(2048 - 1);
+    assert_eq!(a.get_coeffs(), &[0; 2000][..]);
+
+    let b = IntPoly::new(&[1; 2000]);
+    assert_eq!(b.mult_int_16(&b, 2048 - 1), Err(Error::InvalidDegree));
+    assert_eq!(b.mult_int_64(&b, 2048 - 1), Err(Error::InvalidDegree));
+}
+
+#[test]
+fn it_mod_center_mod3() {
+    let mut a = IntPoly::new(&[0, 1, 1023, 1024, 2047, 2048, -1, -1025]);
+    a.mod_center(2048);
+    assert_eq!(a.get_coeffs(), &[0, 1, 1023, -1024, -1, 0, -1, 1023]);
+    a.mod3();
+    assert_eq!(a.get_coeffs(), &[0, 1, 0, 2, 2, 0, 2, 0]);
+}
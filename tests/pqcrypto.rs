@@ -0,0 +1,83 @@
+#![cfg(all(feature = "ntru-prime", feature = "nist-ntru"))]
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::pqcrypto::{sntrup653, sntrup761, sntrup857, ntruhps2048677, ntruhps4096821,
+                     ntruhrss701};
+use ntru::ntruprime::{SNTRUP653, SNTRUP761, SNTRUP857};
+use ntru::nistntru::{NTRUHPS2048677, NTRUHPS4096821, NTRUHRSS701};
+use ntru::types::Error;
+
+#[test]
+fn it_lengths() {
+    assert_eq!(sntrup653::public_key_bytes(), SNTRUP653.public_len());
+    assert_eq!(sntrup653::secret_key_bytes(), SNTRUP653.private_len());
+    assert_eq!(sntrup653::ciphertext_bytes(), SNTRUP653.ciphertext_len());
+    assert_eq!(sntrup761::public_key_bytes(), SNTRUP761.public_len());
+    assert_eq!(sntrup761::secret_key_bytes(), SNTRUP761.private_len());
+    assert_eq!(sntrup761::ciphertext_bytes(), SNTRUP761.ciphertext_len());
+    assert_eq!(sntrup857::public_key_bytes(), SNTRUP857.public_len());
+    assert_eq!(sntrup857::secret_key_bytes(), SNTRUP857.private_len());
+    assert_eq!(sntrup857::ciphertext_bytes(), SNTRUP857.ciphertext_len());
+
+    assert_eq!(ntruhps2048677::public_key_bytes(), NTRUHPS2048677.public_len());
+    assert_eq!(ntruhps2048677::secret_key_bytes(), NTRUHPS2048677.private_len());
+    assert_eq!(ntruhps2048677::ciphertext_bytes(), NTRUHPS2048677.ciphertext_len());
+    assert_eq!(ntruhps4096821::public_key_bytes(), NTRUHPS4096821.public_len());
+    assert_eq!(ntruhps4096821::secret_key_bytes(), NTRUHPS4096821.private_len());
+    assert_eq!(ntruhps4096821::ciphertext_bytes(), NTRUHPS4096821.ciphertext_len());
+    assert_eq!(ntruhrss701::public_key_bytes(), NTRUHRSS701.public_len());
+    assert_eq!(ntruhrss701::secret_key_bytes(), NTRUHRSS701.private_len());
+    assert_eq!(ntruhrss701::ciphertext_bytes(), NTRUHRSS701.ciphertext_len());
+
+    assert_eq!(sntrup761::shared_secret_bytes(), 32);
+    assert_eq!(ntruhrss701::shared_secret_bytes(), 32);
+}
+
+#[test]
+fn it_kem() {
+    let (pk, sk) = sntrup761::keypair().unwrap();
+    let (ss1, ct) = sntrup761::encapsulate(&pk).unwrap();
+    assert_eq!(ss1, sntrup761::decapsulate(&ct, &sk).unwrap());
+
+    let (pk, sk) = ntruhps2048677::keypair().unwrap();
+    let (ss1, ct) = ntruhps2048677::encapsulate(&pk).unwrap();
+    assert_eq!(ss1, ntruhps2048677::decapsulate(&ct, &sk).unwrap());
+    assert_eq!(ss1.as_bytes().len(), ntruhps2048677::shared_secret_bytes());
+}
+
+#[test]
+fn it_from_bytes() {
+    let (pk, sk) = sntrup761::keypair().unwrap();
+    let (_, ct) = sntrup761::encapsulate(&pk).unwrap();
+
+    assert_eq!(sntrup761::PublicKey::from_bytes(pk.as_bytes()), Ok(pk.clone()));
+    assert_eq!(sntrup761::SecretKey::from_bytes(sk.as_bytes()), Ok(sk.clone()));
+    assert_eq!(sntrup761::Ciphertext::from_bytes(ct.as_bytes()), Ok(ct.clone()));
+
+    assert_eq!(sntrup761::PublicKey::from_bytes(&pk.as_bytes()[1..]),
+               Err(Error::InvalidKey));
+    assert_eq!(sntrup761::Ciphertext::from_bytes(&ct.as_bytes()[1..]),
+               Err(Error::InvalidCiphertextLength {
+                   expected: sntrup761::ciphertext_bytes(),
+                   actual: sntrup761::ciphertext_bytes() - 1,
+               }));
+    assert_eq!(sntrup761::SecretKey::from_bytes(&sk.as_bytes()[1..]),
+               Err(Error::InvalidKey));
+}
+
+#[test]
+fn it_debug_redacts_secrets() {
+    let (pk, sk) = sntrup761::keypair().unwrap();
+    let (ss, _) = sntrup761::encapsulate(&pk).unwrap();
+
+    assert_eq!(format!("{:?}", sk),
+               format!("SecretKey {{ len: {} }}", sntrup761::secret_key_bytes()));
+    assert_eq!(format!("{:?}", ss), "SharedSecret { len: 32 }");
+}
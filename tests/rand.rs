@@ -0,0 +1,197 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use std::rc::Rc;
+use std::thread;
+
+use ntru::encparams::EES439EP1;
+use ntru::rand::{RNG_DEFAULT, RNG_GETRANDOM, RNG_CTR_DRBG, RNG_COUNTING, RNG_REPLAY,
+                 RNG_RECORDING, RandomSource, DEFAULT_RESEED_INTERVAL};
+use ntru::types::Error;
+
+#[test]
+fn it_getrandom() {
+    let rand_ctx = ntru::rand::init(&RNG_GETRANDOM).unwrap();
+    let rand1 = ntru::rand::generate(100, &rand_ctx).unwrap();
+    let rand2 = ntru::rand::generate(100, &rand_ctx).unwrap();
+    assert_eq!(rand1.len(), 100);
+    assert!(rand1 != rand2);
+
+    let kp = ntru::generate_key_pair(&EES439EP1, &rand_ctx).unwrap();
+    let encrypted = ntru::encrypt(b"test", kp.get_public(), &EES439EP1, &rand_ctx).unwrap();
+    let decrypted = ntru::decrypt(&encrypted, &kp, &EES439EP1).unwrap();
+    assert_eq!(&decrypted[..], b"test");
+}
+
+#[test]
+fn it_default_rng() {
+    let rand_ctx1 = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let rand_ctx2 = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let rand1 = ntru::rand::generate(100, &rand_ctx1).unwrap();
+    let rand2 = ntru::rand::generate(100, &rand_ctx2).unwrap();
+    assert!(rand1 != rand2);
+}
+
+#[test]
+fn it_reseed() {
+    let mut rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    rand_ctx.reseed(b"additional input").unwrap();
+    rand_ctx.reseed(&[]).unwrap();
+    let _ = ntru::rand::generate(100, &rand_ctx).unwrap();
+
+    let mut rand_ctx = ntru::rand::init(&RNG_GETRANDOM).unwrap();
+    assert_eq!(rand_ctx.reseed(&[]), Err(Error::Prng));
+
+    // Reseeding breaks the determinism of a deterministic context
+    let mut rand_ctx1 = ntru::rand::init_det(&RNG_CTR_DRBG, b"seed").unwrap();
+    let rand_ctx2 = ntru::rand::init_det(&RNG_CTR_DRBG, b"seed").unwrap();
+    rand_ctx1.reseed(&[]).unwrap();
+    assert!(ntru::rand::generate(100, &rand_ctx1).unwrap() !=
+            ntru::rand::generate(100, &rand_ctx2).unwrap());
+}
+
+#[test]
+fn it_reseed_interval() {
+    let mut rand_ctx1 = ntru::rand::init_det(&RNG_CTR_DRBG, b"seed").unwrap();
+    let rand_ctx2 = ntru::rand::init_det(&RNG_CTR_DRBG, b"seed").unwrap();
+    assert_eq!(rand_ctx1.get_reseed_interval(), 0);
+    rand_ctx1.set_reseed_interval(3);
+    assert_eq!(rand_ctx1.get_reseed_interval(), 3);
+
+    // The first two calls happen before the interval is reached
+    for _ in 0..2 {
+        assert_eq!(ntru::rand::generate(32, &rand_ctx1).unwrap(),
+                   ntru::rand::generate(32, &rand_ctx2).unwrap());
+    }
+    assert!(ntru::rand::generate(32, &rand_ctx1).unwrap() !=
+            ntru::rand::generate(32, &rand_ctx2).unwrap());
+}
+
+#[test]
+fn it_personalization() {
+    let rand_ctx1 = ntru::rand::init_det_with(&RNG_CTR_DRBG, b"master seed", b"keygen").unwrap();
+    let rand_ctx2 = ntru::rand::init_det_with(&RNG_CTR_DRBG, b"master seed", b"keygen").unwrap();
+    let rand_ctx3 = ntru::rand::init_det_with(&RNG_CTR_DRBG, b"master seed", b"message").unwrap();
+    let rand1 = ntru::rand::generate(64, &rand_ctx1).unwrap();
+    let rand2 = ntru::rand::generate(64, &rand_ctx2).unwrap();
+    let rand3 = ntru::rand::generate(64, &rand_ctx3).unwrap();
+    assert_eq!(rand1, rand2);
+    assert!(rand1 != rand3);
+
+    assert!(ntru::rand::init_det_with(&RNG_GETRANDOM, b"master seed", b"keygen").is_err());
+}
+
+#[test]
+fn it_additional_input() {
+    let rand_ctx1 = ntru::rand::init_det(&RNG_CTR_DRBG, b"seed").unwrap();
+    let rand_ctx2 = ntru::rand::init_det(&RNG_CTR_DRBG, b"seed").unwrap();
+    let rand1 = ntru::rand::generate_with(64, b"input", &rand_ctx1).unwrap();
+    let rand2 = ntru::rand::generate_with(64, b"input", &rand_ctx2).unwrap();
+    assert_eq!(rand1, rand2);
+
+    let rand1 = ntru::rand::generate_with(64, b"input 1", &rand_ctx1).unwrap();
+    let rand2 = ntru::rand::generate_with(64, b"input 2", &rand_ctx2).unwrap();
+    assert!(rand1 != rand2);
+}
+
+#[test]
+fn it_counting_rng() {
+    let rand_ctx = ntru::rand::init(&RNG_COUNTING).unwrap();
+    let rand1 = ntru::rand::generate(200, &rand_ctx).unwrap();
+    let rand2 = ntru::rand::generate(100, &rand_ctx).unwrap();
+
+    for (i, b) in rand1.iter().chain(rand2.iter()).enumerate() {
+        assert_eq!(*b, (i % 256) as u8);
+    }
+
+    // A new context starts over
+    let rand_ctx = ntru::rand::init(&RNG_COUNTING).unwrap();
+    assert_eq!(&ntru::rand::generate(200, &rand_ctx).unwrap()[..], &rand1[..]);
+}
+
+#[test]
+fn it_record_replay() {
+    let params = &EES439EP1;
+    let kp = ntru::generate_key_pair(params, &ntru::rand::init(&RNG_DEFAULT).unwrap()).unwrap();
+
+    let rec_ctx = ntru::rand::init(&RNG_RECORDING).unwrap();
+    let encrypted = ntru::encrypt(b"replay me", kp.get_public(), params, &rec_ctx).unwrap();
+    let recording = rec_ctx.get_recording().unwrap();
+    assert!(!recording.is_empty());
+
+    let replay_ctx = ntru::rand::init_det(&RNG_REPLAY, &recording).unwrap();
+    let replayed = ntru::encrypt(b"replay me", kp.get_public(), params, &replay_ctx).unwrap();
+    assert_eq!(encrypted, replayed);
+
+    // The recorded stream is exhausted
+    assert_eq!(ntru::rand::generate(1, &replay_ctx), Err(Error::Prng));
+
+    assert!(ntru::rand::init(&RNG_DEFAULT).unwrap().get_recording().is_none());
+}
+
+/// A xorshift generator, good enough for testing the `RandomSource` plumbing
+struct XorShift(u64);
+
+impl RandomSource for XorShift {
+    fn fill(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        for b in buf.iter_mut() {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            *b = self.0 as u8;
+        }
+        Ok(())
+    }
+}
+
+/// A source that always fails
+struct Broken;
+
+impl RandomSource for Broken {
+    fn fill(&mut self, _buf: &mut [u8]) -> Result<(), Error> {
+        Err(Error::Prng)
+    }
+}
+
+#[test]
+fn it_random_source() {
+    let params = &EES439EP1;
+    let rand_ctx1 = ntru::rand::init_source(XorShift(0x1234_5678)).unwrap();
+    let rand_ctx2 = ntru::rand::init_source(XorShift(0x1234_5678)).unwrap();
+    let kp1 = ntru::generate_key_pair(params, &rand_ctx1).unwrap();
+    let kp2 = ntru::generate_key_pair(params, &rand_ctx2).unwrap();
+    assert_eq!(kp1, kp2);
+
+    let encrypted = ntru::encrypt(b"source", kp1.get_public(), params, &rand_ctx1).unwrap();
+    let decrypted = ntru::decrypt(&encrypted, &kp1, params).unwrap();
+    assert_eq!(&decrypted[..], b"source");
+
+    let rand_ctx = ntru::rand::init_source(Broken).unwrap();
+    assert_eq!(ntru::generate_key_pair(params, &rand_ctx), Err(Error::Prng));
+    assert_eq!(ntru::rand::generate(10, &rand_ctx), Err(Error::Prng));
+}
+
+#[test]
+fn it_default_context() {
+    let rand_ctx1 = ntru::rand::default_context().unwrap();
+    let rand_ctx2 = ntru::rand::default_context().unwrap();
+    assert!(Rc::ptr_eq(&rand_ctx1, &rand_ctx2));
+    assert_eq!(rand_ctx1.get_reseed_interval(), DEFAULT_RESEED_INTERVAL);
+
+    let kp = ntru::generate_key_pair(&EES439EP1, &rand_ctx1).unwrap();
+    let public = kp.get_public().clone();
+    let encrypted = thread::spawn(move || {
+            let rand_ctx = ntru::rand::default_context().unwrap();
+            ntru::encrypt(b"thread", &public, &EES439EP1, &rand_ctx).unwrap()
+        })
+        .join()
+        .unwrap();
+    let decrypted = ntru::decrypt(&encrypted, &kp, &EES439EP1).unwrap();
+    assert_eq!(&decrypted[..], b"thread");
+}
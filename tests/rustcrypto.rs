@@ -0,0 +1,97 @@
+#![cfg(all(feature = "rustcrypto-kem", feature = "ntru-prime", feature = "nist-ntru"))]
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+extern crate kem;
+extern crate rand_core;
+use std::convert::TryFrom;
+use kem::{Decapsulate, Encapsulate};
+use rand_core::{CryptoRng, RngCore, impls};
+use ntru::rand::RNG_DEFAULT;
+use ntru::ntruprime::{SNTRUP653, SNTRUP761};
+use ntru::nistntru::{NTRUHPS2048677, NTRUHRSS701};
+use ntru::types::Error;
+
+/// Test RNG backed by the default NTRU RNG
+struct TestRng(ntru::rand::RandContext);
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let bytes = ntru::rand::generate(dest.len() as u16, &self.0).unwrap();
+        dest.copy_from_slice(&bytes);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for TestRng {}
+
+/// Encapsulates and decapsulates with the generic traits
+fn round_trip<E, D>(public: &E, private: &D)
+    where E: Encapsulate<Box<[u8]>, Box<[u8]>>,
+          D: Decapsulate<Box<[u8]>, Box<[u8]>>
+{
+    let mut rng = TestRng(ntru::rand::init(&RNG_DEFAULT).unwrap());
+    let (ciphertext, shared1) = public.encapsulate(&mut rng).unwrap();
+    let shared2 = private.decapsulate(&ciphertext).unwrap();
+    assert_eq!(shared1, shared2);
+}
+
+#[test]
+fn it_ntru_prime_kem_traits() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::ntruprime::generate_key_pair(&SNTRUP761, &rand_ctx).unwrap();
+    round_trip(kp.get_public(), kp.get_private());
+    assert_eq!(kp.get_public().as_ref(), kp.get_public().export());
+}
+
+#[test]
+fn it_nist_ntru_kem_traits() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::nistntru::generate_key_pair(&NTRUHRSS701, &rand_ctx).unwrap();
+    round_trip(kp.get_public(), kp.get_private());
+    assert_eq!(kp.get_private().as_ref(), kp.get_private().export());
+}
+
+#[test]
+fn it_encoded_key_conversions() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+
+    let kp = ntru::ntruprime::generate_key_pair(&SNTRUP653, &rand_ctx).unwrap();
+    let public_bytes = Vec::from(kp.get_public());
+    let public = ntru::ntruprime::PublicKey::try_from(&public_bytes[..]).unwrap();
+    assert_eq!(&public, kp.get_public());
+    assert_eq!(public.get_params(), &SNTRUP653);
+    let private_bytes = Vec::from(kp.get_private());
+    let private = ntru::ntruprime::PrivateKey::try_from(&private_bytes[..]).unwrap();
+    assert_eq!(&private, kp.get_private());
+    assert_eq!(ntru::ntruprime::PublicKey::try_from(&private_bytes[..]),
+               Err(Error::InvalidKey));
+
+    let kp = ntru::nistntru::generate_key_pair(&NTRUHPS2048677, &rand_ctx).unwrap();
+    let public_bytes = Vec::from(kp.get_public());
+    let public = ntru::nistntru::PublicKey::try_from(&public_bytes[..]).unwrap();
+    assert_eq!(&public, kp.get_public());
+    assert_eq!(public.get_params(), &NTRUHPS2048677);
+    let private_bytes = Vec::from(kp.get_private());
+    let private = ntru::nistntru::PrivateKey::try_from(&private_bytes[..]).unwrap();
+    assert_eq!(&private, kp.get_private());
+    assert_eq!(ntru::nistntru::PrivateKey::try_from(&public_bytes[..]), Err(Error::InvalidKey));
+}
@@ -0,0 +1,63 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::context::NtruContext;
+use ntru::encparams::{EES401EP1, EES439EP1};
+use ntru::scheme::PublicKeyScheme;
+use ntru::types::{Error, KeyPair};
+
+/// A scheme for testing, where the ciphertext is the message XORed with the key
+struct XorScheme;
+
+impl PublicKeyScheme for XorScheme {
+    type PublicKey = u8;
+    type PrivateKey = u8;
+    type Ciphertext = Vec<u8>;
+    type Error = ();
+
+    fn keygen(&self) -> Result<(u8, u8), ()> {
+        Ok((0x5a, 0x5a))
+    }
+
+    fn encrypt(&self, msg: &[u8], public: &u8) -> Result<Vec<u8>, ()> {
+        Ok(msg.iter().map(|b| b ^ public).collect())
+    }
+
+    fn decrypt(&self, enc: &Vec<u8>, private: &u8) -> Result<Box<[u8]>, ()> {
+        Ok(enc.iter().map(|b| b ^ private).collect::<Vec<_>>().into_boxed_slice())
+    }
+}
+
+fn round_trip<S: PublicKeyScheme>(scheme: &S, msg: &[u8]) -> Result<Box<[u8]>, S::Error> {
+    let (public, private) = scheme.keygen()?;
+    let encrypted = scheme.encrypt(msg, &public)?;
+    assert!(encrypted.as_ref() != msg);
+    scheme.decrypt(&encrypted, &private)
+}
+
+#[test]
+fn it_scheme_round_trip() {
+    let msg = b"Hello from Rust!";
+    let ctx = NtruContext::with_default_rng(&EES401EP1).unwrap();
+    assert_eq!(&round_trip(&ctx, msg).unwrap()[..], &msg[..]);
+    assert_eq!(&round_trip(&EES439EP1, msg).unwrap()[..], &msg[..]);
+    assert_eq!(&round_trip(&XorScheme, msg).unwrap()[..], &msg[..]);
+}
+
+#[test]
+fn it_scheme_keys() {
+    let (public, kp): (_, KeyPair) = PublicKeyScheme::keygen(&EES401EP1).unwrap();
+    assert_eq!(&public, kp.get_public());
+    let encrypted = PublicKeyScheme::encrypt(&EES401EP1, b"message", &public).unwrap();
+    assert_eq!(encrypted.len(), EES401EP1.enc_len() as usize);
+
+    let ctx = NtruContext::with_default_rng(&EES439EP1).unwrap();
+    assert_eq!(PublicKeyScheme::decrypt(&ctx, &encrypted, &kp), Err(Error::InvalidKey));
+    assert_eq!(PublicKeyScheme::encrypt(&ctx, b"message", &public), Err(Error::InvalidKey));
+}
@@ -0,0 +1,38 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+#![cfg(all(feature = "secret-box", unix))]
+
+extern crate ntru;
+use ntru::encparams::{EES401EP1, EES1087EP2};
+use ntru::rand::RNG_DEFAULT;
+use ntru::secret_box::SecretKeyBox;
+use ntru::types::Error;
+
+#[test]
+fn it_secret_key_box() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &[EES401EP1, EES1087EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let public = kp.get_public().clone();
+        let secret_box = SecretKeyBox::new(kp).unwrap();
+        assert_eq!(secret_box.get_public(), &public);
+
+        let msg = b"Hello from Rust!";
+        let encrypted = ntru::encrypt(msg, &public, params, &rand_ctx).unwrap();
+        for _ in 0..2 {
+            let decrypted = secret_box.decrypt(&encrypted, params).unwrap();
+            assert_eq!(&msg[..], &decrypted[..]);
+        }
+        assert_eq!(secret_box.decrypt(&encrypted[1..], params),
+                   Err(Error::InvalidCiphertextLength {
+                       expected: encrypted.len(),
+                       actual: encrypted.len() - 1,
+                   }));
+        assert!(!format!("{:?}", secret_box).contains("private"));
+    }
+}
@@ -0,0 +1,75 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{DEFAULT_PARAMS_128_BITS, ALL_PARAM_SETS};
+use ntru::session::{EncryptSession, DecryptSession};
+use ntru::types::Error;
+
+#[test]
+fn it_encrypt_session() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let session = EncryptSession::new(kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(session.enc_len(), params.enc_len() as usize);
+        assert_eq!(session.max_msg_len(), params.max_msg_len() as usize);
+
+        let mut buffer = vec![0u8; session.enc_len()];
+        for i in 0..5u8 {
+            let msg = vec![i; i as usize * 10];
+            let encrypted = session.encrypt(&msg).unwrap();
+            assert_eq!(&ntru::decrypt(&encrypted, &kp, params).unwrap()[..], &msg[..]);
+
+            session.encrypt_into(&msg, &mut buffer).unwrap();
+            assert_eq!(&ntru::decrypt(&buffer, &kp, params).unwrap()[..], &msg[..]);
+        }
+    }
+}
+
+#[test]
+fn it_encrypt_session_errors() {
+    let params = DEFAULT_PARAMS_128_BITS;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    let session = EncryptSession::new(kp.get_public(), &params, &rand_ctx).unwrap();
+
+    let msg = vec![0u8; session.max_msg_len() + 1];
+    assert_eq!(session.encrypt(&msg), Err(Error::MessageTooLong));
+
+    let mut buffer = vec![0u8; session.enc_len() - 1];
+    assert_eq!(session.encrypt_into(b"Hello", &mut buffer),
+               Err(Error::InvalidCiphertextLength {
+                   expected: session.enc_len(),
+                   actual: session.enc_len() - 1,
+               }));
+}
+
+#[test]
+fn it_decrypt_session() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    for params in &ALL_PARAM_SETS {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let mut session = DecryptSession::new(&kp, params).unwrap();
+        assert_eq!(session.enc_len(), params.enc_len() as usize);
+
+        for i in 0..5u8 {
+            let msg = vec![i; 5 - i as usize];
+            let encrypted = ntru::encrypt(&msg, kp.get_public(), params, &rand_ctx).unwrap();
+            assert_eq!(session.decrypt(&encrypted).unwrap(), &msg[..]);
+        }
+
+        let encrypted = ntru::encrypt(b"Hello", kp.get_public(), params, &rand_ctx).unwrap();
+        assert_eq!(session.decrypt(&encrypted[1..]),
+                   Err(Error::InvalidCiphertextLength {
+                       expected: encrypted.len(),
+                       actual: encrypted.len() - 1,
+                   }));
+    }
+}
@@ -0,0 +1,50 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+#![cfg(feature = "proptest")]
+
+extern crate ntru;
+extern crate proptest;
+
+use proptest::prelude::*;
+use ntru::encparams::EES401EP1;
+use ntru::rand::RNG_DEFAULT;
+use ntru::strategies::{param_sets, key_pairs, params_and_key_pairs, plaintexts, ciphertexts};
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(8))]
+
+    #[test]
+    fn it_param_sets(params in param_sets()) {
+        prop_assert!(params.enc_len() > 0);
+        prop_assert!(params.max_msg_len() > 0);
+        prop_assert!(params.sampler().is_some());
+    }
+
+    #[test]
+    fn it_round_trip(kp in key_pairs(&EES401EP1), msg in plaintexts(&EES401EP1)) {
+        let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+        let enc = ntru::encrypt(&msg, kp.get_public(), &EES401EP1, &rand_ctx).unwrap();
+        prop_assert_eq!(ntru::decrypt(&enc, &kp, &EES401EP1).unwrap().to_vec(), msg);
+    }
+
+    #[test]
+    fn it_invalid_ciphertexts(kp in key_pairs(&EES401EP1), enc in ciphertexts(&EES401EP1)) {
+        prop_assert!(ntru::decrypt(&enc, &kp, &EES401EP1).is_err());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2))]
+
+    #[test]
+    fn it_params_and_key_pairs((params, kp) in params_and_key_pairs()) {
+        let pub_arr = kp.get_public().export(params);
+        prop_assert_eq!(pub_arr.len(), params.public_len() as usize);
+    }
+}
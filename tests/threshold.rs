@@ -0,0 +1,72 @@
+#![forbid(missing_docs, warnings)]
+#![deny(deprecated, improper_ctypes, non_shorthand_field_patterns, overflowing_literals,
+    plugin_as_library, private_no_mangle_fns, private_no_mangle_statics, stable_features,
+    unconditional_recursion, unknown_lints, unsafe_code, unused, unused_allocation,
+    unused_attributes, unused_comparisons, unused_features, unused_parens, while_true)]
+#![warn(trivial_casts, trivial_numeric_casts, unused, unused_extern_crates, unused_import_braces,
+    unused_qualifications, unused_results, variant_size_differences)]
+
+extern crate ntru;
+use ntru::rand::RNG_DEFAULT;
+use ntru::encparams::{EES401EP1, EES401EP2, EES439EP1};
+use ntru::threshold::{self, Share};
+use ntru::types::Error;
+
+#[test]
+fn it_threshold() {
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let msg = b"Escrowed message";
+    for params in &[EES401EP1, EES401EP2] {
+        let kp = ntru::generate_key_pair(params, &rand_ctx).unwrap();
+        let encrypted = ntru::encrypt(msg, kp.get_public(), params, &rand_ctx).unwrap();
+        let shares = threshold::split(kp.get_private(), params, 3, 5, &rand_ctx).unwrap();
+        assert_eq!(shares.len(), 5);
+        for (i, share) in shares.iter().enumerate() {
+            assert_eq!(share.get_index() as usize, i + 1);
+            assert_eq!(share.get_threshold(), 3);
+        }
+
+        let subsets: [&[usize]; 4] = [&[0, 1, 2], &[4, 2, 0], &[1, 3, 4], &[0, 1, 2, 3, 4]];
+        for subset in subsets.iter() {
+            let shares: Vec<Share> =
+                subset.iter().map(|&i| Share::import(&shares[i].export()).unwrap()).collect();
+            let private = threshold::combine(&shares, params).unwrap();
+            assert_eq!(private.export(params), kp.get_private().export(params));
+            let decrypted = threshold::decrypt(&encrypted, &shares, kp.get_public(), params)
+                .unwrap();
+            assert_eq!(&msg[..], &decrypted[..]);
+        }
+
+        assert_eq!(threshold::combine(&shares[..2], params).unwrap_err(), Error::InvalidParam);
+    }
+}
+
+#[test]
+fn it_threshold_invalid() {
+    let params = EES439EP1;
+    let rand_ctx = ntru::rand::init(&RNG_DEFAULT).unwrap();
+    let kp = ntru::generate_key_pair(&params, &rand_ctx).unwrap();
+    assert_eq!(threshold::split(kp.get_private(), &params, 0, 3, &rand_ctx).unwrap_err(),
+               Error::InvalidParam);
+    assert_eq!(threshold::split(kp.get_private(), &params, 4, 3, &rand_ctx).unwrap_err(),
+               Error::InvalidParam);
+
+    // A single share is the key itself
+    let shares = threshold::split(kp.get_private(), &params, 1, 1, &rand_ctx).unwrap();
+    assert_eq!(&shares[0].export()[2..], &kp.get_private().export(&params)[..]);
+
+    let shares = threshold::split(kp.get_private(), &params, 2, 3, &rand_ctx).unwrap();
+    let duplicate = [Share::import(&shares[0].export()).unwrap(),
+                     Share::import(&shares[0].export()).unwrap()];
+    assert_eq!(threshold::combine(&duplicate, &params).unwrap_err(), Error::InvalidParam);
+    assert_eq!(threshold::combine(&[], &params).unwrap_err(), Error::InvalidParam);
+
+    let mut modified = shares[1].export().to_vec();
+    modified[2] ^= 1;
+    let modified = [Share::import(&shares[0].export()).unwrap(), Share::import(&modified).unwrap()];
+    assert_eq!(threshold::combine(&modified, &params).unwrap_err(), Error::InvalidKey);
+
+    assert_eq!(Share::import(&[1, 2]).unwrap_err(), Error::InvalidEncoding);
+    assert_eq!(Share::import(&[0, 2, 3]).unwrap_err(), Error::InvalidEncoding);
+    assert_eq!(Share::import(&[1, 0, 3]).unwrap_err(), Error::InvalidEncoding);
+}